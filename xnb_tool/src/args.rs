@@ -1,4 +1,6 @@
 use clap::{Parser, Subcommand};
+use xnb_tool::export::texture::MipExport;
+use xnb_tool::import::texture::MipFilter;
 
 #[derive(Parser, Debug)]
 pub struct Args {
@@ -31,6 +33,94 @@ pub enum Subcommands {
         /// Compression level applied to the output [0 - 9]
         #[arg(short, long, default_value_t = 0, value_parser = compression_level_range)]
         compression_level: u8,
+
+        /// Export the remaining mip levels of textures
+        #[arg(long, value_enum, default_value_t = MipExport::None)]
+        mips: MipExport,
+
+        /// Also export textures as DDS with their original mip chain
+        #[arg(long)]
+        dds: bool,
+    },
+    /// Repack extracted JSON or MessagePack content back into XNB files
+    Repack {
+        /// File or directory to repack from
+        input: String,
+
+        /// Directory to write XNB files to
+        output: String,
+
+        /// Overwrite existing files
+        #[arg(short, long)]
+        overwrite: bool,
+
+        /// Compress output with LZX
+        #[arg(short, long)]
+        compress: bool,
+    },
+    /// Import PNG or DDS images as XNB textures and glTF or GLB files as XNB models
+    Import {
+        /// File or directory to import from
+        input: String,
+
+        /// Directory to write XNB files to
+        output: String,
+
+        /// Overwrite existing files
+        #[arg(short, long)]
+        overwrite: bool,
+
+        /// Compress output with LZX
+        #[arg(short, long)]
+        compress: bool,
+
+        /// Filter used to generate mip levels
+        #[arg(long, value_enum, default_value_t = MipFilter::Box)]
+        mips: MipFilter,
+    },
+    /// Assemble a character template with its model, equipment, lights and effects into one GLB
+    Character {
+        /// Character XNB or extracted character JSON or MessagePack file
+        input: String,
+
+        /// Content root that the character references are relative to
+        root: String,
+
+        /// GLB file to write
+        output: String,
+
+        /// Which of the character models to use
+        #[arg(short, long, default_value_t = 0)]
+        model: usize,
+
+        /// Overwrite existing files
+        #[arg(short, long)]
+        overwrite: bool,
+    },
+    /// Extract the entries of an XACT wave bank (.xwb) as WAV files
+    WaveBank {
+        /// Wave bank to extract from
+        input: String,
+
+        /// Directory to extract to, the entries are only listed when omitted
+        output: Option<String>,
+
+        /// Overwrite existing files
+        #[arg(short, long)]
+        overwrite: bool,
+    },
+    /// Report sound cues referenced by characters and items that no XACT sound bank (.xsb) defines
+    Cues {
+        /// Character or item XNB file or a directory containing them
+        input: String,
+
+        /// Directory containing the sound banks
+        sound_banks: String,
+    },
+    /// Check that XNB files are written back byte-for-byte identical after parsing
+    Verify {
+        /// File or directory to verify
+        input: String,
     },
 }
 
@@ -1,11 +1,11 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
 use anyhow::anyhow;
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use glam::Vec3;
 use serde::{Deserialize, Serialize};
 
-use crate::ext::MyReadBytesExt;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
 
 use super::{damage::Damage, element::Elements};
 
@@ -19,9 +19,9 @@ pub struct Ability {
 }
 
 impl Ability {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = reader.read_7bit_length_string()?;
-        let cooldown = reader.read_f32::<LittleEndian>()?;
+        let cooldown = reader.read_f32::<E>()?;
         let target = AbilityTarget::read(reader)?;
         let has_fuzzy_expression = reader.read_bool()?;
         let fuzzy_expression = if has_fuzzy_expression {
@@ -29,7 +29,7 @@ impl Ability {
         } else {
             None
         };
-        let num_animations = reader.read_i32::<LittleEndian>()?;
+        let num_animations = reader.read_i32::<E>()?;
         let mut animations = Vec::with_capacity(num_animations as usize);
         for _ in 0..num_animations {
             let animation = reader.read_7bit_length_string()?;
@@ -38,23 +38,23 @@ impl Ability {
 
         let kind = match kind.as_str() {
             "Jump" => {
-                let ability = JumpAbility::read(reader)?;
+                let ability = JumpAbility::read::<E>(reader)?;
                 AbilityKind::Jump(ability)
             }
             "Dash" => {
-                let ability = DashAbility::read(reader)?;
+                let ability = DashAbility::read::<E>(reader)?;
                 AbilityKind::Dash(ability)
             }
             "Block" => {
-                let ability = BlockAbility::read(reader)?;
+                let ability = BlockAbility::read::<E>(reader)?;
                 AbilityKind::Block(ability)
             }
             "Melee" => {
-                let ability = MeleeAbility::read(reader)?;
+                let ability = MeleeAbility::read::<E>(reader)?;
                 AbilityKind::Melee(ability)
             }
             "Ranged" => {
-                let ability = RangedAbility::read(reader)?;
+                let ability = RangedAbility::read::<E>(reader)?;
                 AbilityKind::Ranged(ability)
             }
             "ConfuseGrip" => {
@@ -66,15 +66,15 @@ impl Ability {
                 AbilityKind::DamageGrip(ability)
             }
             "ThrowGrip" => {
-                let ability = ThrowGripAbility::read(reader)?;
+                let ability = ThrowGripAbility::read::<E>(reader)?;
                 AbilityKind::ThrowGrip(ability)
             }
             "GripCharacterFromBehind" => {
-                let ability = GripCharacterFromBehindAbility::read(reader)?;
+                let ability = GripCharacterFromBehindAbility::read::<E>(reader)?;
                 AbilityKind::GripCharacterFromBehind(ability)
             }
             "PickUpCharacter" => {
-                let ability = PickUpCharacterAbility::read(reader)?;
+                let ability = PickUpCharacterAbility::read::<E>(reader)?;
                 AbilityKind::PickUpCharacter(ability)
             }
             "RemoveStatus" => {
@@ -82,11 +82,11 @@ impl Ability {
                 AbilityKind::RemoveStatus(ability)
             }
             "CastSpell" => {
-                let ability = CastSpellAbility::read(reader)?;
+                let ability = CastSpellAbility::read::<E>(reader)?;
                 AbilityKind::CastSpell(ability)
             }
             "SpecialAbilityAbility" => {
-                let ability = SpecialAbilityAbility::read(reader)?;
+                let ability = SpecialAbilityAbility::read::<E>(reader)?;
                 AbilityKind::SpecialAbility(ability)
             }
             v => {
@@ -102,6 +102,53 @@ impl Ability {
             animations,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        let kind = match &self.kind {
+            AbilityKind::Jump(..) => "Jump",
+            AbilityKind::Dash(..) => "Dash",
+            AbilityKind::Block(..) => "Block",
+            AbilityKind::Melee(..) => "Melee",
+            AbilityKind::Ranged(..) => "Ranged",
+            AbilityKind::ConfuseGrip(..) => "ConfuseGrip",
+            AbilityKind::DamageGrip(..) => "DamageGrip",
+            AbilityKind::ThrowGrip(..) => "ThrowGrip",
+            AbilityKind::GripCharacterFromBehind(..) => "GripCharacterFromBehind",
+            AbilityKind::PickUpCharacter(..) => "PickUpCharacter",
+            AbilityKind::RemoveStatus(..) => "RemoveStatus",
+            AbilityKind::CastSpell(..) => "CastSpell",
+            AbilityKind::SpecialAbility(..) => "SpecialAbilityAbility",
+        };
+
+        writer.write_7bit_length_string(kind)?;
+        writer.write_f32::<E>(self.cooldown)?;
+        self.target.write(writer)?;
+        writer.write_bool(self.fuzzy_expression.is_some())?;
+        if let Some(fuzzy_expression) = &self.fuzzy_expression {
+            writer.write_7bit_length_string(fuzzy_expression)?;
+        }
+        writer.write_i32::<E>(self.animations.len() as i32)?;
+        for animation in &self.animations {
+            writer.write_7bit_length_string(animation)?;
+        }
+
+        match &self.kind {
+            AbilityKind::Jump(ability) => ability.write::<E>(writer)?,
+            AbilityKind::Dash(ability) => ability.write::<E>(writer)?,
+            AbilityKind::Block(ability) => ability.write::<E>(writer)?,
+            AbilityKind::Melee(ability) => ability.write::<E>(writer)?,
+            AbilityKind::Ranged(ability) => ability.write::<E>(writer)?,
+            AbilityKind::ConfuseGrip(..) => {}
+            AbilityKind::DamageGrip(..) => {}
+            AbilityKind::ThrowGrip(ability) => ability.write::<E>(writer)?,
+            AbilityKind::GripCharacterFromBehind(ability) => ability.write::<E>(writer)?,
+            AbilityKind::PickUpCharacter(ability) => ability.write::<E>(writer)?,
+            AbilityKind::RemoveStatus(..) => {}
+            AbilityKind::CastSpell(ability) => ability.write::<E>(writer)?,
+            AbilityKind::SpecialAbility(ability) => ability.write::<E>(writer)?,
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -130,11 +177,11 @@ pub struct JumpAbility {
 }
 
 impl JumpAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let max_range = reader.read_f32::<LittleEndian>()?;
-        let min_range = reader.read_f32::<LittleEndian>()?;
-        let angle = reader.read_f32::<LittleEndian>()?;
-        let elevation = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let max_range = reader.read_f32::<E>()?;
+        let min_range = reader.read_f32::<E>()?;
+        let angle = reader.read_f32::<E>()?;
+        let elevation = reader.read_f32::<E>()?;
         Ok(JumpAbility {
             min_range,
             max_range,
@@ -142,6 +189,14 @@ impl JumpAbility {
             elevation,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.max_range)?;
+        writer.write_f32::<E>(self.min_range)?;
+        writer.write_f32::<E>(self.angle)?;
+        writer.write_f32::<E>(self.elevation)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -153,11 +208,11 @@ pub struct DashAbility {
 }
 
 impl DashAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let min_range = reader.read_f32::<LittleEndian>()?;
-        let max_range = reader.read_f32::<LittleEndian>()?;
-        let arc = reader.read_f32::<LittleEndian>()?;
-        let velocity = reader.read_vec3()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let min_range = reader.read_f32::<E>()?;
+        let max_range = reader.read_f32::<E>()?;
+        let arc = reader.read_f32::<E>()?;
+        let velocity = reader.read_vec3::<E>()?;
         Ok(DashAbility {
             min_range,
             max_range,
@@ -165,6 +220,14 @@ impl DashAbility {
             velocity,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.min_range)?;
+        writer.write_f32::<E>(self.max_range)?;
+        writer.write_f32::<E>(self.arc)?;
+        writer.write_vec3::<E>(self.velocity)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -174,11 +237,17 @@ pub struct BlockAbility {
 }
 
 impl BlockAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let arc = reader.read_f32::<LittleEndian>()?;
-        let shield = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let arc = reader.read_f32::<E>()?;
+        let shield = reader.read_i32::<E>()?;
         Ok(BlockAbility { arc, shield })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.arc)?;
+        writer.write_i32::<E>(self.shield)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -191,14 +260,14 @@ pub struct MeleeAbility {
 }
 
 impl MeleeAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let min_range = reader.read_f32::<LittleEndian>()?;
-        let max_range = reader.read_f32::<LittleEndian>()?;
-        let arc_angle = reader.read_f32::<LittleEndian>()?;
-        let num_weapon_slots = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let min_range = reader.read_f32::<E>()?;
+        let max_range = reader.read_f32::<E>()?;
+        let arc_angle = reader.read_f32::<E>()?;
+        let num_weapon_slots = reader.read_i32::<E>()?;
         let mut weapon_slots = Vec::with_capacity(num_weapon_slots as usize);
         for _ in 0..num_weapon_slots {
-            let weapon_slot = reader.read_i32::<LittleEndian>()?;
+            let weapon_slot = reader.read_i32::<E>()?;
             weapon_slots.push(weapon_slot);
         }
         let rotate = reader.read_bool()?;
@@ -210,6 +279,18 @@ impl MeleeAbility {
             rotate,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.min_range)?;
+        writer.write_f32::<E>(self.max_range)?;
+        writer.write_f32::<E>(self.arc_angle)?;
+        writer.write_i32::<E>(self.weapon_slots.len() as i32)?;
+        for weapon_slot in &self.weapon_slots {
+            writer.write_i32::<E>(*weapon_slot)?;
+        }
+        writer.write_bool(self.rotate)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -223,16 +304,16 @@ pub struct RangedAbility {
 }
 
 impl RangedAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let min_range = reader.read_f32::<LittleEndian>()?;
-        let max_range = reader.read_f32::<LittleEndian>()?;
-        let elevation = reader.read_f32::<LittleEndian>()?;
-        let arc = reader.read_f32::<LittleEndian>()?;
-        let accuracy = reader.read_f32::<LittleEndian>()?;
-        let num_weapon_slots = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let min_range = reader.read_f32::<E>()?;
+        let max_range = reader.read_f32::<E>()?;
+        let elevation = reader.read_f32::<E>()?;
+        let arc = reader.read_f32::<E>()?;
+        let accuracy = reader.read_f32::<E>()?;
+        let num_weapon_slots = reader.read_i32::<E>()?;
         let mut weapon_slots = Vec::with_capacity(num_weapon_slots as usize);
         for _ in 0..num_weapon_slots {
-            let weapon_slot = reader.read_i32::<LittleEndian>()?;
+            let weapon_slot = reader.read_i32::<E>()?;
             weapon_slots.push(weapon_slot);
         }
         Ok(RangedAbility {
@@ -244,6 +325,19 @@ impl RangedAbility {
             weapon_slots,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.min_range)?;
+        writer.write_f32::<E>(self.max_range)?;
+        writer.write_f32::<E>(self.elevation)?;
+        writer.write_f32::<E>(self.arc)?;
+        writer.write_f32::<E>(self.accuracy)?;
+        writer.write_i32::<E>(self.weapon_slots.len() as i32)?;
+        for weapon_slot in &self.weapon_slots {
+            writer.write_i32::<E>(*weapon_slot)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -261,14 +355,14 @@ pub struct ThrowGripAbility {
 }
 
 impl ThrowGripAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let max_range = reader.read_f32::<LittleEndian>()?;
-        let min_range = reader.read_f32::<LittleEndian>()?;
-        let elevation = reader.read_f32::<LittleEndian>()?;
-        let num_damages = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let max_range = reader.read_f32::<E>()?;
+        let min_range = reader.read_f32::<E>()?;
+        let elevation = reader.read_f32::<E>()?;
+        let num_damages = reader.read_i32::<E>()?;
         let mut damages = Vec::with_capacity(num_damages as usize);
         for _ in 0..num_damages {
-            let damage = Damage::read(reader)?;
+            let damage = Damage::read::<E>(reader)?;
             damages.push(damage);
         }
         Ok(ThrowGripAbility {
@@ -278,6 +372,17 @@ impl ThrowGripAbility {
             damages,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.max_range)?;
+        writer.write_f32::<E>(self.min_range)?;
+        writer.write_f32::<E>(self.elevation)?;
+        writer.write_i32::<E>(self.damages.len() as i32)?;
+        for damage in &self.damages {
+            damage.write::<E>(writer)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -289,11 +394,11 @@ pub struct GripCharacterFromBehindAbility {
 }
 
 impl GripCharacterFromBehindAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let max_range = reader.read_f32::<LittleEndian>()?;
-        let min_range = reader.read_f32::<LittleEndian>()?;
-        let angle = reader.read_f32::<LittleEndian>()?;
-        let max_weight = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let max_range = reader.read_f32::<E>()?;
+        let min_range = reader.read_f32::<E>()?;
+        let angle = reader.read_f32::<E>()?;
+        let max_weight = reader.read_f32::<E>()?;
         Ok(GripCharacterFromBehindAbility {
             min_range,
             max_range,
@@ -301,6 +406,14 @@ impl GripCharacterFromBehindAbility {
             max_weight,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.max_range)?;
+        writer.write_f32::<E>(self.min_range)?;
+        writer.write_f32::<E>(self.angle)?;
+        writer.write_f32::<E>(self.max_weight)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -313,11 +426,11 @@ pub struct PickUpCharacterAbility {
 }
 
 impl PickUpCharacterAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let max_range = reader.read_f32::<LittleEndian>()?;
-        let min_range = reader.read_f32::<LittleEndian>()?;
-        let angle = reader.read_f32::<LittleEndian>()?;
-        let max_weight = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let max_range = reader.read_f32::<E>()?;
+        let min_range = reader.read_f32::<E>()?;
+        let angle = reader.read_f32::<E>()?;
+        let max_weight = reader.read_f32::<E>()?;
         let drop_animation = reader.read_7bit_length_string()?;
         Ok(PickUpCharacterAbility {
             min_range,
@@ -327,6 +440,15 @@ impl PickUpCharacterAbility {
             drop_animation,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.max_range)?;
+        writer.write_f32::<E>(self.min_range)?;
+        writer.write_f32::<E>(self.angle)?;
+        writer.write_f32::<E>(self.max_weight)?;
+        writer.write_7bit_length_string(&self.drop_animation)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -344,17 +466,17 @@ pub struct CastSpellAbility {
 }
 
 impl CastSpellAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let min_range = reader.read_f32::<LittleEndian>()?;
-        let max_range = reader.read_f32::<LittleEndian>()?;
-        let angle = reader.read_f32::<LittleEndian>()?;
-        let chant_time = reader.read_f32::<LittleEndian>()?;
-        let power = reader.read_f32::<LittleEndian>()?;
-        let cast_kind = CastKind::read(reader)?;
-        let num_elements = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let min_range = reader.read_f32::<E>()?;
+        let max_range = reader.read_f32::<E>()?;
+        let angle = reader.read_f32::<E>()?;
+        let chant_time = reader.read_f32::<E>()?;
+        let power = reader.read_f32::<E>()?;
+        let cast_kind = CastKind::read::<E>(reader)?;
+        let num_elements = reader.read_i32::<E>()?;
         let mut elements = Vec::with_capacity(num_elements as usize);
         for _ in 0..num_elements {
-            let element = Elements::read(reader)?;
+            let element = Elements::read::<E>(reader)?;
             elements.push(element);
         }
         Ok(CastSpellAbility {
@@ -367,6 +489,20 @@ impl CastSpellAbility {
             elements,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.min_range)?;
+        writer.write_f32::<E>(self.max_range)?;
+        writer.write_f32::<E>(self.angle)?;
+        writer.write_f32::<E>(self.chant_time)?;
+        writer.write_f32::<E>(self.power)?;
+        self.cast_kind.write::<E>(writer)?;
+        writer.write_i32::<E>(self.elements.len() as i32)?;
+        for element in &self.elements {
+            element.write::<E>(writer)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -378,11 +514,11 @@ pub struct SpecialAbilityAbility {
 }
 
 impl SpecialAbilityAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let min_range = reader.read_f32::<LittleEndian>()?;
-        let max_range = reader.read_f32::<LittleEndian>()?;
-        let angle = reader.read_f32::<LittleEndian>()?;
-        let weapon_slot = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let min_range = reader.read_f32::<E>()?;
+        let max_range = reader.read_f32::<E>()?;
+        let angle = reader.read_f32::<E>()?;
+        let weapon_slot = reader.read_i32::<E>()?;
         Ok(SpecialAbilityAbility {
             min_range,
             max_range,
@@ -390,10 +526,18 @@ impl SpecialAbilityAbility {
             weapon_slot,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.min_range)?;
+        writer.write_f32::<E>(self.max_range)?;
+        writer.write_f32::<E>(self.angle)?;
+        writer.write_i32::<E>(self.weapon_slot)?;
+        Ok(())
+    }
 }
 
 #[repr(u8)]
-#[derive(strum::FromRepr, Serialize, Deserialize, Debug)]
+#[derive(strum::FromRepr, Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum AbilityTarget {
     User = 1,
     Enemy = 2,
@@ -407,10 +551,15 @@ impl AbilityTarget {
             AbilityTarget::from_repr(value).ok_or_else(|| anyhow!("unknown cast kind: {value}"))?;
         Ok(kind)
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u8(*self as u8)?;
+        Ok(())
+    }
 }
 
 #[repr(u8)]
-#[derive(strum::FromRepr, Serialize, Deserialize, Debug)]
+#[derive(strum::FromRepr, Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum CastKind {
     None,
     Force,
@@ -421,10 +570,15 @@ pub enum CastKind {
 }
 
 impl CastKind {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let value = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let value = reader.read_i32::<E>()?;
         let kind = CastKind::from_repr(value as u8)
             .ok_or_else(|| anyhow!("unknown cast kind: {value}"))?;
         Ok(kind)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(*self as i32)?;
+        Ok(())
+    }
 }
@@ -1,12 +1,12 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
 use anyhow::anyhow;
 use bitflags::bitflags;
-use byteorder::ReadBytesExt;
+use byteorder::{ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
 #[repr(u8)]
-#[derive(strum::FromRepr, Serialize, Deserialize, Debug)]
+#[derive(strum::FromRepr, Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum Order {
     None,
     Idle,
@@ -23,6 +23,11 @@ impl Order {
         let order = Order::from_repr(value).ok_or_else(|| anyhow!("unknown order: {value}"))?;
         Ok(order)
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u8(*self as u8)?;
+        Ok(())
+    }
 }
 
 bitflags! {
@@ -41,4 +46,9 @@ impl ReactionTriggers {
             .ok_or_else(|| anyhow!("unknown reaction triggers: {value}"))?;
         Ok(triggers)
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u8(self.bits())?;
+        Ok(())
+    }
 }
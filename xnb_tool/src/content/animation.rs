@@ -1,12 +1,12 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use anyhow::anyhow;
+use anyhow::{Context, anyhow};
 use bitflags::bitflags;
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use glam::Vec3;
 use serde::{Deserialize, Serialize};
 
-use crate::ext::MyReadBytesExt;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
 
 use super::{
     damage::Damage,
@@ -16,42 +16,51 @@ use super::{
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AnimationSet {
-    clips: Vec<AnimationClip>,
+    pub clips: Vec<AnimationClip>,
 }
 
 impl AnimationSet {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let num_clips = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let num_clips = reader.read_i32::<E>()?;
         let mut clips = Vec::with_capacity(num_clips as usize);
         for _ in 0..num_clips {
-            let clip = AnimationClip::read(reader)?;
+            let clip = AnimationClip::read::<E>(reader)?;
             clips.push(clip);
         }
         Ok(AnimationSet { clips })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.clips.len() as i32)?;
+        for (i, clip) in self.clips.iter().enumerate() {
+            clip.write::<E>(writer)
+                .with_context(|| format!("failed to write clip {i}"))?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AnimationClip {
-    kind: String,
-    key: String,
-    speed: f32,
-    blend_time: f32,
-    loops: bool,
-    actions: Vec<AnimationAction>,
+    pub kind: String,
+    pub key: String,
+    pub speed: f32,
+    pub blend_time: f32,
+    pub loops: bool,
+    pub actions: Vec<AnimationAction>,
 }
 
 impl AnimationClip {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = reader.read_7bit_length_string()?;
         let key = reader.read_7bit_length_string()?;
-        let speed = reader.read_f32::<LittleEndian>()?;
-        let blend_time = reader.read_f32::<LittleEndian>()?;
+        let speed = reader.read_f32::<E>()?;
+        let blend_time = reader.read_f32::<E>()?;
         let loops = reader.read_bool()?;
-        let num_actions = reader.read_i32::<LittleEndian>()?;
+        let num_actions = reader.read_i32::<E>()?;
         let mut actions = Vec::with_capacity(num_actions as usize);
         for _ in 0..num_actions {
-            let action = AnimationAction::read(reader)?;
+            let action = AnimationAction::read::<E>(reader)?;
             actions.push(action);
         }
         Ok(AnimationClip {
@@ -63,47 +72,62 @@ impl AnimationClip {
             actions,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.kind)?;
+        writer.write_7bit_length_string(&self.key)?;
+        writer.write_f32::<E>(self.speed)?;
+        writer.write_f32::<E>(self.blend_time)?;
+        writer.write_bool(self.loops)?;
+        writer.write_i32::<E>(self.actions.len() as i32)?;
+        for (i, action) in self.actions.iter().enumerate() {
+            action
+                .write::<E>(writer)
+                .with_context(|| format!("failed to write action {i}"))?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AnimationAction {
-    kind: AnimationActionKind,
-    start: f32,
-    end: f32,
+    pub kind: AnimationActionKind,
+    pub start: f32,
+    pub end: f32,
 }
 
 impl AnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = reader.read_7bit_length_string()?;
-        let start = reader.read_f32::<LittleEndian>()?;
-        let end = reader.read_f32::<LittleEndian>()?;
+        let start = reader.read_f32::<E>()?;
+        let end = reader.read_f32::<E>()?;
         let kind = match kind.as_str() {
             "Footstep" => {
                 let action = FootstepAnimationAction;
                 AnimationActionKind::Footstep(action)
             }
             "Move" => {
-                let action = MoveAnimationAction::read(reader)?;
+                let action = MoveAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::Move(action)
             }
             "Jump" => {
-                let action = JumpAnimationAction::read(reader)?;
+                let action = JumpAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::Jump(action)
             }
             "Crouch" => {
-                let action = CrouchAnimationAction::read(reader)?;
+                let action = CrouchAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::Crouch(action)
             }
             "Block" => {
-                let action = BlockAnimationAction::read(reader)?;
+                let action = BlockAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::Block(action)
             }
             "Grip" => {
-                let action = GripAnimationAction::read(reader)?;
+                let action = GripAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::Grip(action)
             }
             "DamageGrip" => {
-                let action = DamageGripAnimationAction::read(reader)?;
+                let action = DamageGripAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::DamageGrip(action)
             }
             "OverkillGrip" => {
@@ -119,15 +143,15 @@ impl AnimationAction {
                 AnimationActionKind::ReleaseGrip(action)
             }
             "BreakFree" => {
-                let action = BreakFreeAnimationAction::read(reader)?;
+                let action = BreakFreeAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::BreakFree(action)
             }
             "Gunfire" => {
-                let action = GunfireAnimationAction::read(reader)?;
+                let action = GunfireAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::Gunfire(action)
             }
             "DealDamage" => {
-                let action = DealDamageAnimationAction::read(reader)?;
+                let action = DealDamageAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::DealDamage(action)
             }
             "RemoveStatus" => {
@@ -135,7 +159,7 @@ impl AnimationAction {
                 AnimationActionKind::RemoveStatus(action)
             }
             "SpecialAbility" => {
-                let action = SpecialAbilityAnimationAction::read(reader)?;
+                let action = SpecialAbilityAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::SpecialAbility(action)
             }
             "CastSpell" => {
@@ -143,11 +167,11 @@ impl AnimationAction {
                 AnimationActionKind::CastSpell(action)
             }
             "SpawnMissile" => {
-                let action = SpawnMissileAnimationAction::read(reader)?;
+                let action = SpawnMissileAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::SpawnMissile(action)
             }
             "Tongue" => {
-                let action = TongueAnimationAction::read(reader)?;
+                let action = TongueAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::Tongue(action)
             }
             "Invisible" => {
@@ -155,7 +179,7 @@ impl AnimationAction {
                 AnimationActionKind::Invisible(action)
             }
             "Ethereal" => {
-                let action = EtherealAnimationAction::read(reader)?;
+                let action = EtherealAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::Ethereal(action)
             }
             "Immortal" => {
@@ -167,23 +191,23 @@ impl AnimationAction {
                 AnimationActionKind::Suicide(action)
             }
             "WeaponVisibility" => {
-                let action = WeaponVisibilityAnimationAction::read(reader)?;
+                let action = WeaponVisibilityAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::WeaponVisibility(action)
             }
             "DetachItem" => {
-                let action = DetachItemAnimationAction::read(reader)?;
+                let action = DetachItemAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::DetachItem(action)
             }
             "CameraShake" => {
-                let action = CameraShakeAnimationAction::read(reader)?;
+                let action = CameraShakeAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::CameraShake(action)
             }
             "PlaySound" => {
-                let action = PlaySoundAnimationAction::read(reader)?;
+                let action = PlaySoundAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::PlaySound(action)
             }
             "PlayEffect" => {
-                let action = PlayEffectAnimationAction::read(reader)?;
+                let action = PlayEffectAnimationAction::read::<E>(reader)?;
                 AnimationActionKind::PlayEffect(action)
             }
             v => {
@@ -192,6 +216,73 @@ impl AnimationAction {
         };
         Ok(AnimationAction { kind, start, end })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        let kind = match &self.kind {
+            AnimationActionKind::Footstep(..) => "Footstep",
+            AnimationActionKind::Move(..) => "Move",
+            AnimationActionKind::Jump(..) => "Jump",
+            AnimationActionKind::Crouch(..) => "Crouch",
+            AnimationActionKind::Block(..) => "Block",
+            AnimationActionKind::Grip(..) => "Grip",
+            AnimationActionKind::DamageGrip(..) => "DamageGrip",
+            AnimationActionKind::OverkillGrip(..) => "OverkillGrip",
+            AnimationActionKind::ThrowGrip(..) => "ThrowGrip",
+            AnimationActionKind::ReleaseGrip(..) => "ReleaseGrip",
+            AnimationActionKind::BreakFree(..) => "BreakFree",
+            AnimationActionKind::Gunfire(..) => "Gunfire",
+            AnimationActionKind::DealDamage(..) => "DealDamage",
+            AnimationActionKind::RemoveStatus(..) => "RemoveStatus",
+            AnimationActionKind::SpecialAbility(..) => "SpecialAbility",
+            AnimationActionKind::CastSpell(..) => "CastSpell",
+            AnimationActionKind::SpawnMissile(..) => "SpawnMissile",
+            AnimationActionKind::Tongue(..) => "Tongue",
+            AnimationActionKind::Invisible(..) => "Invisible",
+            AnimationActionKind::Ethereal(..) => "Ethereal",
+            AnimationActionKind::Immortal(..) => "Immortal",
+            AnimationActionKind::Suicide(..) => "Suicide",
+            AnimationActionKind::WeaponVisibility(..) => "WeaponVisibility",
+            AnimationActionKind::DetachItem(..) => "DetachItem",
+            AnimationActionKind::CameraShake(..) => "CameraShake",
+            AnimationActionKind::PlaySound(..) => "PlaySound",
+            AnimationActionKind::PlayEffect(..) => "PlayEffect",
+        };
+
+        writer.write_7bit_length_string(kind)?;
+        writer.write_f32::<E>(self.start)?;
+        writer.write_f32::<E>(self.end)?;
+
+        match &self.kind {
+            AnimationActionKind::Footstep(..) => {}
+            AnimationActionKind::Move(action) => action.write::<E>(writer)?,
+            AnimationActionKind::Jump(action) => action.write::<E>(writer)?,
+            AnimationActionKind::Crouch(action) => action.write::<E>(writer)?,
+            AnimationActionKind::Block(action) => action.write::<E>(writer)?,
+            AnimationActionKind::Grip(action) => action.write::<E>(writer)?,
+            AnimationActionKind::DamageGrip(action) => action.write::<E>(writer)?,
+            AnimationActionKind::OverkillGrip(..) => {}
+            AnimationActionKind::ThrowGrip(..) => {}
+            AnimationActionKind::ReleaseGrip(..) => {}
+            AnimationActionKind::BreakFree(action) => action.write::<E>(writer)?,
+            AnimationActionKind::Gunfire(action) => action.write::<E>(writer)?,
+            AnimationActionKind::DealDamage(action) => action.write::<E>(writer)?,
+            AnimationActionKind::RemoveStatus(action) => action.write(writer)?,
+            AnimationActionKind::SpecialAbility(action) => action.write::<E>(writer)?,
+            AnimationActionKind::CastSpell(action) => action.write(writer)?,
+            AnimationActionKind::SpawnMissile(action) => action.write::<E>(writer)?,
+            AnimationActionKind::Tongue(action) => action.write::<E>(writer)?,
+            AnimationActionKind::Invisible(action) => action.write(writer)?,
+            AnimationActionKind::Ethereal(action) => action.write::<E>(writer)?,
+            AnimationActionKind::Immortal(action) => action.write(writer)?,
+            AnimationActionKind::Suicide(action) => action.write(writer)?,
+            AnimationActionKind::WeaponVisibility(action) => action.write::<E>(writer)?,
+            AnimationActionKind::DetachItem(action) => action.write::<E>(writer)?,
+            AnimationActionKind::CameraShake(action) => action.write::<E>(writer)?,
+            AnimationActionKind::PlaySound(action) => action.write::<E>(writer)?,
+            AnimationActionKind::PlayEffect(action) => action.write::<E>(writer)?,
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -234,33 +325,38 @@ pub struct MoveAnimationAction {
 }
 
 impl MoveAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let velocity = reader.read_vec3()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let velocity = reader.read_vec3::<E>()?;
         Ok(MoveAnimationAction { velocity })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_vec3::<E>(self.velocity)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct JumpAnimationAction {
     pub elevation: f32,
-    pub min_range: f32,
-    pub max_range: f32,
+    pub min_range: Option<f32>,
+    pub max_range: Option<f32>,
 }
 
 impl JumpAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let elevation = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let elevation = reader.read_f32::<E>()?;
         let has_min_range = reader.read_bool()?;
         let min_range = if has_min_range {
-            reader.read_f32::<LittleEndian>()?
+            Some(reader.read_f32::<E>()?)
         } else {
-            0.0
+            None
         };
         let has_max_range = reader.read_bool()?;
         let max_range = if has_max_range {
-            reader.read_f32::<LittleEndian>()?
+            Some(reader.read_f32::<E>()?)
         } else {
-            0.0
+            None
         };
         Ok(JumpAnimationAction {
             elevation,
@@ -268,6 +364,19 @@ impl JumpAnimationAction {
             max_range,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.elevation)?;
+        writer.write_bool(self.min_range.is_some())?;
+        if let Some(min_range) = self.min_range {
+            writer.write_f32::<E>(min_range)?;
+        }
+        writer.write_bool(self.max_range.is_some())?;
+        if let Some(max_range) = self.max_range {
+            writer.write_f32::<E>(max_range)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -277,11 +386,17 @@ pub struct CrouchAnimationAction {
 }
 
 impl CrouchAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let radius = reader.read_f32::<LittleEndian>()?;
-        let length = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let radius = reader.read_f32::<E>()?;
+        let length = reader.read_f32::<E>()?;
         Ok(CrouchAnimationAction { radius, length })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.radius)?;
+        writer.write_f32::<E>(self.length)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -290,10 +405,15 @@ pub struct BlockAnimationAction {
 }
 
 impl BlockAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let weapon_slot = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let weapon_slot = reader.read_i32::<E>()?;
         Ok(BlockAnimationAction { weapon_slot })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.weapon_slot)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -307,10 +427,10 @@ pub struct GripAnimationAction {
 }
 
 impl GripAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = GripKind::read(reader)?;
-        let radius = reader.read_f32::<LittleEndian>()?;
-        let break_free_tolerance = reader.read_f32::<LittleEndian>()?;
+        let radius = reader.read_f32::<E>()?;
+        let break_free_tolerance = reader.read_f32::<E>()?;
         let bone_a = reader.read_7bit_length_string()?;
         let bone_b = reader.read_7bit_length_string()?;
         let finish_on_grip = reader.read_bool()?;
@@ -323,6 +443,16 @@ impl GripAnimationAction {
             finish_on_grip,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.kind.write(writer)?;
+        writer.write_f32::<E>(self.radius)?;
+        writer.write_f32::<E>(self.break_free_tolerance)?;
+        writer.write_7bit_length_string(&self.bone_a)?;
+        writer.write_7bit_length_string(&self.bone_b)?;
+        writer.write_bool(self.finish_on_grip)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -332,12 +462,12 @@ pub struct DamageGripAnimationAction {
 }
 
 impl DamageGripAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let damage_owner = reader.read_bool()?;
-        let num_damages = reader.read_i32::<LittleEndian>()?;
+        let num_damages = reader.read_i32::<E>()?;
         let mut damages = Vec::with_capacity(num_damages as usize);
         for _ in 0..num_damages {
-            let damage = Damage::read(reader)?;
+            let damage = Damage::read::<E>(reader)?;
             damages.push(damage);
         }
         Ok(DamageGripAnimationAction {
@@ -345,6 +475,15 @@ impl DamageGripAnimationAction {
             damages,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_bool(self.damage_owner)?;
+        writer.write_i32::<E>(self.damages.len() as i32)?;
+        for damage in &self.damages {
+            damage.write::<E>(writer)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -363,14 +502,20 @@ pub struct BreakFreeAnimationAction {
 }
 
 impl BreakFreeAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let magnitude = reader.read_f32::<LittleEndian>()?;
-        let weapon_slot = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let magnitude = reader.read_f32::<E>()?;
+        let weapon_slot = reader.read_i32::<E>()?;
         Ok(BreakFreeAnimationAction {
             weapon_slot,
             magnitude,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.magnitude)?;
+        writer.write_i32::<E>(self.weapon_slot)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -380,14 +525,20 @@ pub struct GunfireAnimationAction {
 }
 
 impl GunfireAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let weapon_slot = reader.read_i32::<LittleEndian>()?;
-        let accuracy = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let weapon_slot = reader.read_i32::<E>()?;
+        let accuracy = reader.read_f32::<E>()?;
         Ok(GunfireAnimationAction {
             weapon_slot,
             accuracy,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.weapon_slot)?;
+        writer.write_f32::<E>(self.accuracy)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -397,14 +548,20 @@ pub struct DealDamageAnimationAction {
 }
 
 impl DealDamageAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let weapon_slot = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let weapon_slot = reader.read_i32::<E>()?;
         let targets = AnimationTargets::read(reader)?;
         Ok(DealDamageAnimationAction {
             weapon_slot,
             targets,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.weapon_slot)?;
+        self.targets.write(writer)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -417,6 +574,11 @@ impl RemoveStatusAnimationAction {
         let status = reader.read_7bit_length_string()?;
         Ok(RemoveStatusAnimationAction { status })
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.status)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -426,10 +588,10 @@ pub struct SpecialAbilityAnimationAction {
 }
 
 impl SpecialAbilityAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let weapon_slot = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let weapon_slot = reader.read_i32::<E>()?;
         let ability = if weapon_slot < 0 {
-            let ability = SpecialAbility::read(reader)?;
+            let ability = SpecialAbility::read::<E>(reader)?;
             Some(ability)
         } else {
             None
@@ -439,6 +601,17 @@ impl SpecialAbilityAnimationAction {
             ability,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.weapon_slot)?;
+        if self.weapon_slot < 0 {
+            let Some(ability) = &self.ability else {
+                anyhow::bail!("expected special ability for negative weapon slot");
+            };
+            ability.write::<E>(writer)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -457,6 +630,17 @@ impl CastSpellAnimationAction {
         };
         Ok(CastSpellAnimationAction { from_staff, bone })
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_bool(self.from_staff)?;
+        if !self.from_staff {
+            let Some(bone) = &self.bone else {
+                anyhow::bail!("expected bone for spell not cast from staff");
+            };
+            writer.write_7bit_length_string(bone)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -467,9 +651,9 @@ pub struct SpawnMissileAnimationAction {
 }
 
 impl SpawnMissileAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let weapon_slot = reader.read_i32::<LittleEndian>()?;
-        let velocity = reader.read_vec3()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let weapon_slot = reader.read_i32::<E>()?;
+        let velocity = reader.read_vec3::<E>()?;
         let aligned = reader.read_bool()?;
         Ok(SpawnMissileAnimationAction {
             weapon_slot,
@@ -477,6 +661,13 @@ impl SpawnMissileAnimationAction {
             aligned,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.weapon_slot)?;
+        writer.write_vec3::<E>(self.velocity)?;
+        writer.write_bool(self.aligned)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -485,10 +676,15 @@ pub struct TongueAnimationAction {
 }
 
 impl TongueAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let max_length = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let max_length = reader.read_f32::<E>()?;
         Ok(TongueAnimationAction { max_length })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.max_length)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -501,6 +697,11 @@ impl InvisibleAnimationAction {
         let shimmer = reader.read_bool()?;
         Ok(InvisibleAnimationAction { shimmer })
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_bool(self.shimmer)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -511,16 +712,23 @@ pub struct EtherealAnimationAction {
 }
 
 impl EtherealAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let is_ethereal = reader.read_bool()?;
-        let alpha = reader.read_f32::<LittleEndian>()?;
-        let speed = reader.read_f32::<LittleEndian>()?;
+        let alpha = reader.read_f32::<E>()?;
+        let speed = reader.read_f32::<E>()?;
         Ok(EtherealAnimationAction {
             is_ethereal,
             alpha,
             speed,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_bool(self.is_ethereal)?;
+        writer.write_f32::<E>(self.alpha)?;
+        writer.write_f32::<E>(self.speed)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -533,6 +741,11 @@ impl ImmortalAnimationAction {
         let collide = reader.read_bool()?;
         Ok(ImmortalAnimationAction { collide })
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_bool(self.collide)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -545,6 +758,11 @@ impl SuicideAnimationAction {
         let overkill = reader.read_bool()?;
         Ok(SuicideAnimationAction { overkill })
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_bool(self.overkill)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -554,14 +772,20 @@ pub struct WeaponVisibilityAnimationAction {
 }
 
 impl WeaponVisibilityAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let weapon_slot = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let weapon_slot = reader.read_i32::<E>()?;
         let visible = reader.read_bool()?;
         Ok(WeaponVisibilityAnimationAction {
             weapon_slot,
             visible,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.weapon_slot)?;
+        writer.write_bool(self.visible)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -571,14 +795,20 @@ pub struct DetachItemAnimationAction {
 }
 
 impl DetachItemAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let weapon_slot = reader.read_i32::<LittleEndian>()?;
-        let velocity = reader.read_vec3()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let weapon_slot = reader.read_i32::<E>()?;
+        let velocity = reader.read_vec3::<E>()?;
         Ok(DetachItemAnimationAction {
             weapon_slot,
             velocity,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.weapon_slot)?;
+        writer.write_vec3::<E>(self.velocity)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -589,16 +819,23 @@ pub struct CameraShakeAnimationAction {
 }
 
 impl CameraShakeAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let unk = reader.read_7bit_length_string()?;
-        let duration = reader.read_f32::<LittleEndian>()?;
-        let magnitude = reader.read_f32::<LittleEndian>()?;
+        let duration = reader.read_f32::<E>()?;
+        let magnitude = reader.read_f32::<E>()?;
         Ok(CameraShakeAnimationAction {
             unk,
             duration,
             magnitude,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.unk)?;
+        writer.write_f32::<E>(self.duration)?;
+        writer.write_f32::<E>(self.magnitude)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -607,12 +844,18 @@ pub struct PlaySoundAnimationAction {
 }
 
 impl PlaySoundAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let cue = reader.read_7bit_length_string()?;
-        let bank = Bank::read(reader)?;
+        let bank = Bank::read::<E>(reader)?;
         let sound = Sound { cue, bank };
         Ok(PlaySoundAnimationAction { sound })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.sound.cue)?;
+        self.sound.bank.write::<E>(writer)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -624,11 +867,11 @@ pub struct PlayEffectAnimationAction {
 }
 
 impl PlayEffectAnimationAction {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let bone = reader.read_7bit_length_string()?;
         let attached = reader.read_bool()?;
         let effect = reader.read_7bit_length_string()?;
-        let value = reader.read_f32::<LittleEndian>()?;
+        let value = reader.read_f32::<E>()?;
         Ok(PlayEffectAnimationAction {
             bone,
             attached,
@@ -636,10 +879,18 @@ impl PlayEffectAnimationAction {
             value,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.bone)?;
+        writer.write_bool(self.attached)?;
+        writer.write_7bit_length_string(&self.effect)?;
+        writer.write_f32::<E>(self.value)?;
+        Ok(())
+    }
 }
 
 #[repr(u8)]
-#[derive(strum::FromRepr, Serialize, Deserialize, Debug)]
+#[derive(strum::FromRepr, Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum GripKind {
     Pickup,
     Ride,
@@ -653,6 +904,11 @@ impl GripKind {
             GripKind::from_repr(value).ok_or_else(|| anyhow!("unknown grip kind: {value}"))?;
         Ok(kind)
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u8(*self as u8)?;
+        Ok(())
+    }
 }
 
 bitflags! {
@@ -672,4 +928,9 @@ impl AnimationTargets {
             .ok_or_else(|| anyhow!("unknown animation targets: {value}"))?;
         Ok(targets)
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u8(self.bits())?;
+        Ok(())
+    }
 }
@@ -1,8 +1,8 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
 use anyhow::anyhow;
 use bitflags::bitflags;
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
 bitflags! {
@@ -22,10 +22,15 @@ bitflags! {
 }
 
 impl AttackProperties {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let value = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let value = reader.read_i32::<E>()?;
         let attack_properties = AttackProperties::from_bits(value as u16)
             .ok_or_else(|| anyhow!("unknown attack properties: {value}"))?;
         Ok(attack_properties)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.bits() as i32)?;
+        Ok(())
+    }
 }
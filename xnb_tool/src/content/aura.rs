@@ -1,11 +1,11 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
 use anyhow::anyhow;
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
 use crate::content::{color::Color, faction::Factions};
-use crate::ext::MyReadBytesExt;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
 
 use super::attack_property::AttackProperties;
 use super::element::Elements;
@@ -25,34 +25,37 @@ pub struct Aura {
 }
 
 impl Aura {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let target = AuraTarget::read(reader)?;
         let kind = reader.read_u8()?;
         let visual_category = VisualCategory::read(reader)?;
-        let color = Color::read(reader)?;
+        let color = Color::read::<E>(reader)?;
         let effect = reader.read_7bit_length_string()?;
-        let duration = reader.read_f32::<LittleEndian>()?;
-        let radius = reader.read_f32::<LittleEndian>()?;
+        let duration = reader.read_f32::<E>()?;
+        let radius = reader.read_f32::<E>()?;
         let types = reader.read_7bit_length_string()?;
-        let factions = Factions::read(reader)?;
+        let factions = Factions::read::<E>(reader)?;
 
         let kind = match kind {
             0 => {
-                let kind = BuffAura::read(reader)?;
+                let kind = BuffAura::read::<E>(reader)?;
                 AuraKind::Buff(kind)
             }
             1 => {
-                let kind = DeflectAura::read(reader)?;
+                let kind = DeflectAura::read::<E>(reader)?;
                 AuraKind::Deflect(kind)
             }
             2 => {
-                todo!("boost aura")
+                let kind = BoostAura::read::<E>(reader)?;
+                AuraKind::Boost(kind)
             }
             3 => {
-                todo!("life steal aura");
+                let kind = LifeStealAura::read::<E>(reader)?;
+                AuraKind::LifeSteal(kind)
             }
             4 => {
-                todo!("love aura");
+                let kind = LoveAura::read::<E>(reader)?;
+                AuraKind::Love(kind)
             }
             v => {
                 return Err(anyhow!("unknown aura kind: {v}"));
@@ -72,12 +75,44 @@ impl Aura {
         };
         Ok(aura)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        let kind = match &self.kind {
+            AuraKind::Buff(..) => 0,
+            AuraKind::Deflect(..) => 1,
+            AuraKind::Boost(..) => 2,
+            AuraKind::LifeSteal(..) => 3,
+            AuraKind::Love(..) => 4,
+        };
+
+        self.target.write(writer)?;
+        writer.write_u8(kind)?;
+        self.visual_category.write(writer)?;
+        self.color.write::<E>(writer)?;
+        writer.write_7bit_length_string(&self.effect)?;
+        writer.write_f32::<E>(self.duration)?;
+        writer.write_f32::<E>(self.radius)?;
+        writer.write_7bit_length_string(&self.types)?;
+        self.factions.write::<E>(writer)?;
+
+        match &self.kind {
+            AuraKind::Buff(kind) => kind.write::<E>(writer)?,
+            AuraKind::Deflect(kind) => kind.write::<E>(writer)?,
+            AuraKind::Boost(kind) => kind.write::<E>(writer)?,
+            AuraKind::LifeSteal(kind) => kind.write::<E>(writer)?,
+            AuraKind::Love(kind) => kind.write::<E>(writer)?,
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum AuraKind {
     Buff(BuffAura),
     Deflect(DeflectAura),
+    Boost(BoostAura),
+    LifeSteal(LifeStealAura),
+    Love(LoveAura),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -86,10 +121,15 @@ pub struct BuffAura {
 }
 
 impl BuffAura {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let buff = Buff::read(reader)?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let buff = Buff::read::<E>(reader)?;
         Ok(BuffAura { buff })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.buff.write::<E>(writer)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -98,10 +138,69 @@ pub struct DeflectAura {
 }
 
 impl DeflectAura {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let strength = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let strength = reader.read_f32::<E>()?;
         Ok(DeflectAura { strength })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.strength)?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BoostAura {
+    amount: f32,
+}
+
+impl BoostAura {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let amount = reader.read_f32::<E>()?;
+        Ok(BoostAura { amount })
+    }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.amount)?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LifeStealAura {
+    amount: f32,
+}
+
+impl LifeStealAura {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let amount = reader.read_f32::<E>()?;
+        Ok(LifeStealAura { amount })
+    }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.amount)?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LoveAura {
+    radius: f32,
+    ttl: f32,
+}
+
+impl LoveAura {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let radius = reader.read_f32::<E>()?;
+        let ttl = reader.read_f32::<E>()?;
+        Ok(LoveAura { radius, ttl })
+    }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.radius)?;
+        writer.write_f32::<E>(self.ttl)?;
+        Ok(())
+    }
 }
 
 #[repr(u8)]
@@ -126,6 +225,11 @@ impl AuraTarget {
             AuraTarget::from_repr(value).ok_or_else(|| anyhow!("unknown aura target: {value}"))?;
         Ok(target)
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u8(*self as u8)?;
+        Ok(())
+    }
 }
 
 #[repr(u8)]
@@ -144,6 +248,11 @@ impl VisualCategory {
             .ok_or_else(|| anyhow!("unknown visual category: {value}"))?;
         Ok(category)
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u8(*self as u8)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -156,24 +265,24 @@ pub struct Buff {
 }
 
 impl Buff {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = reader.read_u8()?;
         let visual_category = VisualCategory::read(reader)?;
-        let color = Color::read(reader)?;
-        let duration = reader.read_f32::<LittleEndian>()?;
+        let color = Color::read::<E>(reader)?;
+        let duration = reader.read_f32::<E>()?;
         let effect = reader.read_7bit_length_string()?;
 
         let kind = match kind {
             0 => {
-                let kind = BoostDamageBuff::read(reader)?;
+                let kind = BoostDamageBuff::read::<E>(reader)?;
                 BuffKind::BoostDamage(kind)
             }
             1 => {
-                let kind = DealDamageBuff::read(reader)?;
+                let kind = DealDamageBuff::read::<E>(reader)?;
                 BuffKind::DealDamage(kind)
             }
             2 => {
-                let kind = ResistanceBuff::read(reader)?;
+                let kind = ResistanceBuff::read::<E>(reader)?;
                 BuffKind::Resistance(kind)
             }
             3 => {
@@ -181,23 +290,23 @@ impl Buff {
                 BuffKind::Undying(kind)
             }
             4 => {
-                let kind = BoostBuff::read(reader)?;
+                let kind = BoostBuff::read::<E>(reader)?;
                 BuffKind::Boost(kind)
             }
             5 => {
-                let kind = ReduceAggroBuff::read(reader)?;
+                let kind = ReduceAggroBuff::read::<E>(reader)?;
                 BuffKind::ReduceAggro(kind)
             }
             6 => {
-                let kind = ModifyHitPointsBuff::read(reader)?;
+                let kind = ModifyHitPointsBuff::read::<E>(reader)?;
                 BuffKind::ModifyHitPoints(kind)
             }
             7 => {
-                let kind = ModifySpellDurationBuff::read(reader)?;
+                let kind = ModifySpellDurationBuff::read::<E>(reader)?;
                 BuffKind::ModifySpellDuration(kind)
             }
             8 => {
-                let kind = ModifySpellRangeBuff::read(reader)?;
+                let kind = ModifySpellRangeBuff::read::<E>(reader)?;
                 BuffKind::ModifySpellRange(kind)
             }
             v => {
@@ -214,6 +323,39 @@ impl Buff {
         };
         Ok(buff)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        let kind = match &self.kind {
+            BuffKind::BoostDamage(..) => 0,
+            BuffKind::DealDamage(..) => 1,
+            BuffKind::Resistance(..) => 2,
+            BuffKind::Undying(..) => 3,
+            BuffKind::Boost(..) => 4,
+            BuffKind::ReduceAggro(..) => 5,
+            BuffKind::ModifyHitPoints(..) => 6,
+            BuffKind::ModifySpellDuration(..) => 7,
+            BuffKind::ModifySpellRange(..) => 8,
+        };
+
+        writer.write_u8(kind)?;
+        self.visual_category.write(writer)?;
+        self.color.write::<E>(writer)?;
+        writer.write_f32::<E>(self.time)?;
+        writer.write_7bit_length_string(&self.effect)?;
+
+        match &self.kind {
+            BuffKind::BoostDamage(kind) => kind.write::<E>(writer)?,
+            BuffKind::DealDamage(kind) => kind.write::<E>(writer)?,
+            BuffKind::Resistance(kind) => kind.write::<E>(writer)?,
+            BuffKind::Undying(..) => {}
+            BuffKind::Boost(kind) => kind.write::<E>(writer)?,
+            BuffKind::ReduceAggro(kind) => kind.write::<E>(writer)?,
+            BuffKind::ModifyHitPoints(kind) => kind.write::<E>(writer)?,
+            BuffKind::ModifySpellDuration(kind) => kind.write::<E>(writer)?,
+            BuffKind::ModifySpellRange(kind) => kind.write::<E>(writer)?,
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -238,11 +380,11 @@ pub struct BoostDamageBuff {
 }
 
 impl BoostDamageBuff {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let attack_properties = AttackProperties::read(reader)?;
-        let elements = Elements::read(reader)?;
-        let amount = reader.read_f32::<LittleEndian>()?;
-        let magnitude = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let attack_properties = AttackProperties::read::<E>(reader)?;
+        let elements = Elements::read::<E>(reader)?;
+        let amount = reader.read_f32::<E>()?;
+        let magnitude = reader.read_f32::<E>()?;
 
         let buff = BoostDamageBuff {
             attack_properties,
@@ -252,6 +394,14 @@ impl BoostDamageBuff {
         };
         Ok(buff)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.attack_properties.write::<E>(writer)?;
+        self.elements.write::<E>(writer)?;
+        writer.write_f32::<E>(self.amount)?;
+        writer.write_f32::<E>(self.magnitude)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -263,11 +413,11 @@ pub struct DealDamageBuff {
 }
 
 impl DealDamageBuff {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let attack_properties = AttackProperties::read(reader)?;
-        let elements = Elements::read(reader)?;
-        let amount = reader.read_f32::<LittleEndian>()?;
-        let magnitude = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let attack_properties = AttackProperties::read::<E>(reader)?;
+        let elements = Elements::read::<E>(reader)?;
+        let amount = reader.read_f32::<E>()?;
+        let magnitude = reader.read_f32::<E>()?;
 
         let buff = DealDamageBuff {
             attack_properties,
@@ -277,6 +427,14 @@ impl DealDamageBuff {
         };
         Ok(buff)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.attack_properties.write::<E>(writer)?;
+        self.elements.write::<E>(writer)?;
+        writer.write_f32::<E>(self.amount)?;
+        writer.write_f32::<E>(self.magnitude)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -285,11 +443,16 @@ pub struct ResistanceBuff {
 }
 
 impl ResistanceBuff {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let resistance = Resistance::read(reader)?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let resistance = Resistance::read::<E>(reader)?;
         let buff = ResistanceBuff { resistance };
         Ok(buff)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.resistance.write::<E>(writer)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -301,11 +464,16 @@ pub struct BoostBuff {
 }
 
 impl BoostBuff {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let amount = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let amount = reader.read_f32::<E>()?;
         let buff = BoostBuff { amount };
         Ok(buff)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.amount)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -314,11 +482,16 @@ pub struct ReduceAggroBuff {
 }
 
 impl ReduceAggroBuff {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let amount = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let amount = reader.read_f32::<E>()?;
         let buff = ReduceAggroBuff { amount };
         Ok(buff)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.amount)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -328,9 +501,9 @@ pub struct ModifyHitPointsBuff {
 }
 
 impl ModifyHitPointsBuff {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let multiplier = reader.read_f32::<LittleEndian>()?;
-        let modifier = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let multiplier = reader.read_f32::<E>()?;
+        let modifier = reader.read_f32::<E>()?;
 
         let buff = ModifyHitPointsBuff {
             multiplier,
@@ -338,6 +511,12 @@ impl ModifyHitPointsBuff {
         };
         Ok(buff)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.multiplier)?;
+        writer.write_f32::<E>(self.modifier)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -347,9 +526,9 @@ pub struct ModifySpellDurationBuff {
 }
 
 impl ModifySpellDurationBuff {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let multiplier = reader.read_f32::<LittleEndian>()?;
-        let modifier = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let multiplier = reader.read_f32::<E>()?;
+        let modifier = reader.read_f32::<E>()?;
 
         let buff = ModifySpellDurationBuff {
             multiplier,
@@ -357,6 +536,12 @@ impl ModifySpellDurationBuff {
         };
         Ok(buff)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.multiplier)?;
+        writer.write_f32::<E>(self.modifier)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -366,9 +551,9 @@ pub struct ModifySpellRangeBuff {
 }
 
 impl ModifySpellRangeBuff {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let multiplier = reader.read_f32::<LittleEndian>()?;
-        let modifier = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let multiplier = reader.read_f32::<E>()?;
+        let modifier = reader.read_f32::<E>()?;
 
         let buff = ModifySpellRangeBuff {
             multiplier,
@@ -376,4 +561,10 @@ impl ModifySpellRangeBuff {
         };
         Ok(buff)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.multiplier)?;
+        writer.write_f32::<E>(self.modifier)?;
+        Ok(())
+    }
 }
@@ -1,11 +1,11 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use anyhow::anyhow;
-use byteorder::{LittleEndian, ReadBytesExt};
+use anyhow::{Context, anyhow};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use glam::Vec3;
 use serde::{Deserialize, Serialize};
 
-use crate::ext::MyReadBytesExt;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
 
 use super::{
     ability::Ability,
@@ -90,11 +90,11 @@ pub struct Character {
 }
 
 impl Character {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let name = reader.read_7bit_length_string()?;
         let locale_name = reader.read_7bit_length_string()?;
-        let factions = Factions::read(reader)?;
-        let blood = BloodKind::read(reader)?;
+        let factions = Factions::read::<E>(reader)?;
+        let blood = BloodKind::read::<E>(reader)?;
         let is_ethereal = reader.read_bool()?;
         let looks_ethereal = reader.read_bool()?;
         let fearless = reader.read_bool()?;
@@ -103,78 +103,78 @@ impl Character {
         let has_fairy = reader.read_bool()?;
         let can_see_invisible = reader.read_bool()?;
 
-        let num_sounds = reader.read_i32::<LittleEndian>()?;
+        let num_sounds = reader.read_i32::<E>()?;
         let mut sounds = Vec::with_capacity(num_sounds as usize);
         for _ in 0..num_sounds {
             let cue = reader.read_7bit_length_string()?;
-            let bank = Bank::read(reader)?;
+            let bank = Bank::read::<E>(reader)?;
             let sound = Sound { cue, bank };
             sounds.push(sound);
         }
 
-        let num_gibs = reader.read_i32::<LittleEndian>()?;
+        let num_gibs = reader.read_i32::<E>()?;
         let mut gibs = Vec::with_capacity(num_gibs as usize);
         for _ in 0..num_gibs {
-            let gib = Gib::read(reader)?;
+            let gib = Gib::read::<E>(reader)?;
             gibs.push(gib);
         }
 
-        let num_lights = reader.read_i32::<LittleEndian>()?;
+        let num_lights = reader.read_i32::<E>()?;
         let mut lights = Vec::with_capacity(num_lights as usize);
         for _ in 0..num_lights {
-            let light = BonedLight::read(reader)?;
+            let light = BonedLight::read::<E>(reader)?;
             lights.push(light);
         }
 
-        let max_hitpoints = reader.read_f32::<LittleEndian>()?;
-        let num_healthbars = reader.read_i32::<LittleEndian>()?;
+        let max_hitpoints = reader.read_f32::<E>()?;
+        let num_healthbars = reader.read_i32::<E>()?;
         let undying = reader.read_bool()?;
-        let undie_time = reader.read_f32::<LittleEndian>()?;
-        let undie_hitpoints = reader.read_f32::<LittleEndian>()?;
-        let pain_tolerance = reader.read_i32::<LittleEndian>()?;
-        let knockdown_tolerance = reader.read_f32::<LittleEndian>()?;
-        let score_value = reader.read_i32::<LittleEndian>()?;
+        let undie_time = reader.read_f32::<E>()?;
+        let undie_hitpoints = reader.read_f32::<E>()?;
+        let pain_tolerance = reader.read_i32::<E>()?;
+        let knockdown_tolerance = reader.read_f32::<E>()?;
+        let score_value = reader.read_i32::<E>()?;
 
         // modern only (?)
-        let xp_value = reader.read_i32::<LittleEndian>()?;
+        let xp_value = reader.read_i32::<E>()?;
         let reward_on_kill = reader.read_bool()?;
         let reward_on_overkill = reader.read_bool()?;
 
-        let regeneration = reader.read_i32::<LittleEndian>()?;
-        let max_panic = reader.read_f32::<LittleEndian>()?;
-        let zap_modifier = reader.read_f32::<LittleEndian>()?;
-        let length = reader.read_f32::<LittleEndian>()?;
-        let radius = reader.read_f32::<LittleEndian>()?;
-        let mass = reader.read_f32::<LittleEndian>()?;
-        let speed = reader.read_f32::<LittleEndian>()?;
-        let turn_speed = reader.read_f32::<LittleEndian>()?;
-        let bleed_rate = reader.read_f32::<LittleEndian>()?;
-        let stun_time = reader.read_f32::<LittleEndian>()?;
-
-        let summon_element_bank = Bank::read(reader)?;
+        let regeneration = reader.read_i32::<E>()?;
+        let max_panic = reader.read_f32::<E>()?;
+        let zap_modifier = reader.read_f32::<E>()?;
+        let length = reader.read_f32::<E>()?;
+        let radius = reader.read_f32::<E>()?;
+        let mass = reader.read_f32::<E>()?;
+        let speed = reader.read_f32::<E>()?;
+        let turn_speed = reader.read_f32::<E>()?;
+        let bleed_rate = reader.read_f32::<E>()?;
+        let stun_time = reader.read_f32::<E>()?;
+
+        let summon_element_bank = Bank::read::<E>(reader)?;
         let summon_element_cue = reader.read_7bit_length_string()?;
         let summon_element_sound = Sound {
             cue: summon_element_cue,
             bank: summon_element_bank,
         };
 
-        let num_resistances = reader.read_i32::<LittleEndian>()?;
+        let num_resistances = reader.read_i32::<E>()?;
         let mut resistances = Vec::with_capacity(num_resistances as usize);
         for _ in 0..num_resistances {
-            let resistance = Resistance::read(reader)?;
+            let resistance = Resistance::read::<E>(reader)?;
             resistances.push(resistance);
         }
 
-        let num_models = reader.read_i32::<LittleEndian>()?;
+        let num_models = reader.read_i32::<E>()?;
         let mut models = Vec::with_capacity(num_models as usize);
         for _ in 0..num_models {
-            let model = CharacterModel::read(reader)?;
+            let model = CharacterModel::read::<E>(reader)?;
             models.push(model);
         }
 
         let animation_skeleton = reader.read_7bit_length_string()?;
 
-        let num_effects = reader.read_i32::<LittleEndian>()?;
+        let num_effects = reader.read_i32::<E>()?;
         let mut effects = Vec::with_capacity(num_effects as usize);
         for _ in 0..num_effects {
             let effect = BonedEffect::read(reader)?;
@@ -183,65 +183,65 @@ impl Character {
 
         let mut animations = Vec::with_capacity(MAX_ANIMATION_SETS);
         for _ in 0..MAX_ANIMATION_SETS {
-            let set = AnimationSet::read(reader)?;
+            let set = AnimationSet::read::<E>(reader)?;
             animations.push(set);
         }
 
-        let num_equipment = reader.read_i32::<LittleEndian>()?;
+        let num_equipment = reader.read_i32::<E>()?;
         let mut equipment = Vec::with_capacity(num_equipment as usize);
         for _ in 0..num_equipment {
-            let attachment = Attachment::read(reader)?;
+            let attachment = Attachment::read::<E>(reader)?;
             equipment.push(attachment);
         }
 
-        let num_conditions = reader.read_i32::<LittleEndian>()?;
+        let num_conditions = reader.read_i32::<E>()?;
         let mut conditions = Vec::with_capacity(num_conditions as usize);
         for _ in 0..num_conditions {
-            let condition = EventConditions::read(reader)?;
+            let condition = EventConditions::read::<E>(reader)?;
             conditions.push(condition);
         }
 
-        let alert_radius = reader.read_f32::<LittleEndian>()?;
-        let group_chase = reader.read_f32::<LittleEndian>()?;
-        let group_separation = reader.read_f32::<LittleEndian>()?;
-        let group_cohesion = reader.read_f32::<LittleEndian>()?;
-        let group_alignment = reader.read_f32::<LittleEndian>()?;
-        let group_wander = reader.read_f32::<LittleEndian>()?;
-        let friendly_avoidance = reader.read_f32::<LittleEndian>()?;
-        let enemy_avoidance = reader.read_f32::<LittleEndian>()?;
-        let sight_avoidance = reader.read_f32::<LittleEndian>()?;
-        let danger_avoidance = reader.read_f32::<LittleEndian>()?;
-        let anger_weight = reader.read_f32::<LittleEndian>()?;
-        let distance_weight = reader.read_f32::<LittleEndian>()?;
-        let health_weight = reader.read_f32::<LittleEndian>()?;
+        let alert_radius = reader.read_f32::<E>()?;
+        let group_chase = reader.read_f32::<E>()?;
+        let group_separation = reader.read_f32::<E>()?;
+        let group_cohesion = reader.read_f32::<E>()?;
+        let group_alignment = reader.read_f32::<E>()?;
+        let group_wander = reader.read_f32::<E>()?;
+        let friendly_avoidance = reader.read_f32::<E>()?;
+        let enemy_avoidance = reader.read_f32::<E>()?;
+        let sight_avoidance = reader.read_f32::<E>()?;
+        let danger_avoidance = reader.read_f32::<E>()?;
+        let anger_weight = reader.read_f32::<E>()?;
+        let distance_weight = reader.read_f32::<E>()?;
+        let health_weight = reader.read_f32::<E>()?;
         let flocking = reader.read_bool()?;
-        let break_free_strength = reader.read_f32::<LittleEndian>()?;
+        let break_free_strength = reader.read_f32::<E>()?;
 
-        let num_abilities = reader.read_i32::<LittleEndian>()?;
+        let num_abilities = reader.read_i32::<E>()?;
         let mut abilities = Vec::with_capacity(num_abilities as usize);
         for _ in 0..num_abilities {
-            let ability = Ability::read(reader)?;
+            let ability = Ability::read::<E>(reader)?;
             abilities.push(ability);
         }
 
-        let num_movements = reader.read_i32::<LittleEndian>()?;
+        let num_movements = reader.read_i32::<E>()?;
         let mut movements = Vec::with_capacity(num_movements as usize);
         for _ in 0..num_movements {
-            let movement = Movement::read(reader)?;
+            let movement = Movement::read::<E>(reader)?;
             movements.push(movement);
         }
 
-        let num_buffs = reader.read_i32::<LittleEndian>()?;
+        let num_buffs = reader.read_i32::<E>()?;
         let mut buffs = Vec::with_capacity(num_buffs as usize);
         for _ in 0..num_buffs {
-            let buff = Buff::read(reader)?;
+            let buff = Buff::read::<E>(reader)?;
             buffs.push(buff);
         }
 
-        let num_auras = reader.read_i32::<LittleEndian>()?;
+        let num_auras = reader.read_i32::<E>()?;
         let mut auras = Vec::with_capacity(num_auras as usize);
         for _ in 0..num_auras {
-            let aura = Aura::read(reader)?;
+            let aura = Aura::read::<E>(reader)?;
             auras.push(aura);
         }
 
@@ -310,39 +310,190 @@ impl Character {
             auras,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.name)?;
+        writer.write_7bit_length_string(&self.locale_name)?;
+        self.factions.write::<E>(writer)?;
+        self.blood.write::<E>(writer)?;
+        writer.write_bool(self.is_ethereal)?;
+        writer.write_bool(self.looks_ethereal)?;
+        writer.write_bool(self.fearless)?;
+        writer.write_bool(self.uncharmable)?;
+        writer.write_bool(self.non_slippery)?;
+        writer.write_bool(self.has_fairy)?;
+        writer.write_bool(self.can_see_invisible)?;
+
+        writer.write_i32::<E>(self.sounds.len() as i32)?;
+        for sound in &self.sounds {
+            writer.write_7bit_length_string(&sound.cue)?;
+            sound.bank.write::<E>(writer)?;
+        }
+
+        writer.write_i32::<E>(self.gibs.len() as i32)?;
+        for gib in &self.gibs {
+            gib.write::<E>(writer)?;
+        }
+
+        writer.write_i32::<E>(self.lights.len() as i32)?;
+        for light in &self.lights {
+            light.write::<E>(writer)?;
+        }
+
+        writer.write_f32::<E>(self.max_hitpoints)?;
+        writer.write_i32::<E>(self.num_healthbars)?;
+        writer.write_bool(self.undying)?;
+        writer.write_f32::<E>(self.undie_time)?;
+        writer.write_f32::<E>(self.undie_hitpoints)?;
+        writer.write_i32::<E>(self.pain_tolerance)?;
+        writer.write_f32::<E>(self.knockdown_tolerance)?;
+        writer.write_i32::<E>(self.score_value)?;
+
+        writer.write_i32::<E>(self.xp_value)?;
+        writer.write_bool(self.reward_on_kill)?;
+        writer.write_bool(self.reward_on_overkill)?;
+
+        writer.write_i32::<E>(self.regeneration)?;
+        writer.write_f32::<E>(self.max_panic)?;
+        writer.write_f32::<E>(self.zap_modifier)?;
+        writer.write_f32::<E>(self.length)?;
+        writer.write_f32::<E>(self.radius)?;
+        writer.write_f32::<E>(self.mass)?;
+        writer.write_f32::<E>(self.speed)?;
+        writer.write_f32::<E>(self.turn_speed)?;
+        writer.write_f32::<E>(self.bleed_rate)?;
+        writer.write_f32::<E>(self.stun_time)?;
+
+        self.summon_element_sound.bank.write::<E>(writer)?;
+        writer.write_7bit_length_string(&self.summon_element_sound.cue)?;
+
+        writer.write_i32::<E>(self.resistances.len() as i32)?;
+        for resistance in &self.resistances {
+            resistance.write::<E>(writer)?;
+        }
+
+        writer.write_i32::<E>(self.models.len() as i32)?;
+        for (i, model) in self.models.iter().enumerate() {
+            model
+                .write::<E>(writer)
+                .with_context(|| format!("failed to write model {i}"))?;
+        }
+
+        writer.write_7bit_length_string(&self.animation_skeleton)?;
+
+        writer.write_i32::<E>(self.effects.len() as i32)?;
+        for effect in &self.effects {
+            effect.write(writer)?;
+        }
+
+        if self.animations.len() != MAX_ANIMATION_SETS {
+            anyhow::bail!(
+                "expected {MAX_ANIMATION_SETS} animation sets, found {}",
+                self.animations.len()
+            );
+        }
+        for (i, set) in self.animations.iter().enumerate() {
+            set.write::<E>(writer)
+                .with_context(|| format!("failed to write animation set {i}"))?;
+        }
+
+        writer.write_i32::<E>(self.equipment.len() as i32)?;
+        for (i, attachment) in self.equipment.iter().enumerate() {
+            attachment
+                .write::<E>(writer)
+                .with_context(|| format!("failed to write attachment {i}"))?;
+        }
+
+        writer.write_i32::<E>(self.conditions.len() as i32)?;
+        for (i, condition) in self.conditions.iter().enumerate() {
+            condition
+                .write::<E>(writer)
+                .with_context(|| format!("failed to write event conditions {i}"))?;
+        }
+
+        writer.write_f32::<E>(self.alert_radius)?;
+        writer.write_f32::<E>(self.group_chase)?;
+        writer.write_f32::<E>(self.group_separation)?;
+        writer.write_f32::<E>(self.group_cohesion)?;
+        writer.write_f32::<E>(self.group_alignment)?;
+        writer.write_f32::<E>(self.group_wander)?;
+        writer.write_f32::<E>(self.friendly_avoidance)?;
+        writer.write_f32::<E>(self.enemy_avoidance)?;
+        writer.write_f32::<E>(self.sight_avoidance)?;
+        writer.write_f32::<E>(self.danger_avoidance)?;
+        writer.write_f32::<E>(self.anger_weight)?;
+        writer.write_f32::<E>(self.distance_weight)?;
+        writer.write_f32::<E>(self.health_weight)?;
+        writer.write_bool(self.flocking)?;
+        writer.write_f32::<E>(self.break_free_strength)?;
+
+        writer.write_i32::<E>(self.abilities.len() as i32)?;
+        for (i, ability) in self.abilities.iter().enumerate() {
+            ability
+                .write::<E>(writer)
+                .with_context(|| format!("failed to write ability {i}"))?;
+        }
+
+        writer.write_i32::<E>(self.movements.len() as i32)?;
+        for (i, movement) in self.movements.iter().enumerate() {
+            movement
+                .write::<E>(writer)
+                .with_context(|| format!("failed to write movement {i}"))?;
+        }
+
+        writer.write_i32::<E>(self.buffs.len() as i32)?;
+        for (i, buff) in self.buffs.iter().enumerate() {
+            buff.write::<E>(writer)
+                .with_context(|| format!("failed to write buff {i}"))?;
+        }
+
+        writer.write_i32::<E>(self.auras.len() as i32)?;
+        for (i, aura) in self.auras.iter().enumerate() {
+            aura.write::<E>(writer)
+                .with_context(|| format!("failed to write aura {i}"))?;
+        }
+        Ok(())
+    }
 }
 
 use super::color::Color;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CharacterModel {
-    model: String,
-    scale: f32,
-    tint: Color,
+    pub model: String,
+    pub scale: f32,
+    pub tint: Color,
 }
 
 impl CharacterModel {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let model = reader.read_7bit_length_string()?;
-        let scale = reader.read_f32::<LittleEndian>()?;
-        let tint = Color::read(reader)?;
+        let scale = reader.read_f32::<E>()?;
+        let tint = Color::read::<E>(reader)?;
         Ok(CharacterModel { model, scale, tint })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.model)?;
+        writer.write_f32::<E>(self.scale)?;
+        self.tint.write::<E>(writer)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Attachment {
-    slot: i32,
-    bone: String,
-    rotation: Vec3,
-    item: String,
+    pub slot: i32,
+    pub bone: String,
+    pub rotation: Vec3,
+    pub item: String,
 }
 
 impl Attachment {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let slot = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let slot = reader.read_i32::<E>()?;
         let bone = reader.read_7bit_length_string()?;
-        let rotation = reader.read_vec3()?;
+        let rotation = reader.read_vec3::<E>()?;
         let item = reader.read_7bit_length_string()?;
 
         let attachment = Attachment {
@@ -353,6 +504,14 @@ impl Attachment {
         };
         Ok(attachment)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.slot)?;
+        writer.write_7bit_length_string(&self.bone)?;
+        writer.write_vec3::<E>(self.rotation)?;
+        writer.write_7bit_length_string(&self.item)?;
+        Ok(())
+    }
 }
 
 #[repr(u8)]
@@ -367,10 +526,83 @@ pub enum BloodKind {
 }
 
 impl BloodKind {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let value = reader.read_i32::<LittleEndian>()? as u32;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let value = reader.read_i32::<E>()? as u32;
         let kind = BloodKind::from_repr(value as u8)
             .ok_or_else(|| anyhow!("unknown blood kind: {value}"))?;
         Ok(kind)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(*self as i32)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::tests::assert_round_trip;
+
+    #[test]
+    fn character_round_trip() {
+        let mut json: serde_json::Value = serde_json::from_str(
+            r##"{"Character": {
+                "name": "test_wizard", "locale_name": "#wiz", "factions": "", "blood": "Regular",
+                "is_ethereal": false, "looks_ethereal": false, "fearless": false,
+                "uncharmable": false, "non_slippery": false, "has_fairy": false,
+                "can_see_invisible": false,
+                "sounds": [{"cue": "grunt", "bank": "Characters"}],
+                "gibs": [{"model": "Models\\Gibs\\arm", "mass": 2.0, "scale": 1.0}],
+                "lights": [{"bone": "spine", "light": {"radius": 5.0, "diffuse_color": {"r": 2.0,
+                "g": 0.5, "b": 0.25}, "ambient_color": {"r": 0.0, "g": 0.0, "b": 0.0},
+                "specular_amount": 1.0, "variation": "Flicker", "variation_amount": 0.3,
+                "variation_speed": 4.0}}], "max_hitpoints": 100.0, "num_healthbars": 1,
+                "undying": false, "undie_time": 0.0, "undie_hitpoints": 0.0, "pain_tolerance": 0,
+                "knockdown_tolerance": 0.0, "score_value": 0, "xp_value": 0,
+                "reward_on_kill": false, "reward_on_overkill": false, "regeneration": 0,
+                "max_panic": 1.0, "zap_modifier": 1.0, "length": 1.0, "radius": 1.0, "mass": 1.0,
+                "speed": 1.0, "turn_speed": 1.0, "bleed_rate": 1.0, "stun_time": 1.0,
+                "summon_element_sound": {"cue": "summon", "bank": "Spells"},
+                "resistances": [{"element": "Lightning", "multiplier": 2.0, "modifier": 0.0,
+                "status_immunity": false}],
+                "models": [{"model": "Models\\Characters\\skinned", "scale": 2.0, "tint": {"r": 1.0,
+                "g": 0.5, "b": 0.5}}, {"model": "Models\\Characters\\other", "scale": 1.0,
+                "tint": {"r": 1.0, "g": 1.0, "b": 1.0}}],
+                "animation_skeleton": "Models\\Characters\\skinned",
+                "effects": [{"bone": "hip", "effect": "fx_glow"}],
+                "animations": [{"clips": [{"kind": "idle", "key": "Wave", "speed": 1.5,
+                "blend_time": 0.2, "loops": true, "actions": [{"kind": {"Footstep": null},
+                "start": 0.25, "end": 0.5}, {"kind": {"Footstep": null}, "start": 0.75,
+                "end": 1.0}]}]}, {"clips": [{"kind": "attack", "key": "wave", "speed": 1.0,
+                "blend_time": 0.1, "loops": false, "actions": []}, {"kind": "hit", "key": "missing",
+                "speed": 1.0, "blend_time": 0.1, "loops": false, "actions": []}]}],
+                "equipment": [{"slot": 0, "bone": "spine", "rotation": [0.0, 90.0, 0.0],
+                "item": "Staff"}, {"slot": 1, "bone": "nope", "rotation": [0.0, 0.0, 0.0],
+                "item": "missing_item"}],
+                "conditions": [{"kind": "Death", "hitpoints": 0.0, "element": "", "threshold": 0.0,
+                "time": 0.0, "repeat": false, "events": [{"Light": {"light": {"radius": 5.0,
+                "diffuse_color": {"r": 2.0, "g": 0.5, "b": 0.25}, "ambient_color": {"r": 0.0,
+                "g": 0.0, "b": 0.0}, "specular_amount": 1.0, "variation": "Flicker",
+                "variation_amount": 0.3, "variation_speed": 4.0}}}]}], "alert_radius": 0.0,
+                "group_chase": 0.0, "group_separation": 0.0, "group_cohesion": 0.0,
+                "group_alignment": 0.0, "group_wander": 0.0, "friendly_avoidance": 0.0,
+                "enemy_avoidance": 0.0, "sight_avoidance": 0.0, "danger_avoidance": 0.0,
+                "anger_weight": 0.0, "distance_weight": 0.0, "health_weight": 0.0,
+                "flocking": false, "break_free_strength": 0.0,
+                "abilities": [{"kind": {"Jump": {"min_range": 1.0, "max_range": 5.0, "angle": 0.5,
+                "elevation": 1.0}}, "cooldown": 3.0, "target": "Enemy",
+                "fuzzy_expression": "distance > 2", "animations": ["jump"]}],
+                "movements": [{"properties": "JUMP | FLY", "animations": ["fly"]}],
+                "buffs": [{"kind": {"Resistance": {"resistance": {"element": "Cold",
+                "multiplier": 0.5, "modifier": 0.0, "status_immunity": true}}},
+                "visual_category": "Defensive", "color": {"r": 0.0, "g": 0.0, "b": 1.0},
+                "time": 5.0, "effect": "buff"}], "auras": []
+            }}"##,
+        )
+        .unwrap();
+        let sets = json["Character"]["animations"].as_array_mut().unwrap();
+        sets.resize(MAX_ANIMATION_SETS, serde_json::json!({"clips": []}));
+        assert_round_trip(&json.to_string());
+    }
 }
@@ -1,6 +1,6 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
@@ -11,10 +11,17 @@ pub struct Color {
 }
 
 impl Color {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let r = reader.read_f32::<LittleEndian>()?;
-        let g = reader.read_f32::<LittleEndian>()?;
-        let b = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let r = reader.read_f32::<E>()?;
+        let g = reader.read_f32::<E>()?;
+        let b = reader.read_f32::<E>()?;
         Ok(Color { r, g, b })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.r)?;
+        writer.write_f32::<E>(self.g)?;
+        writer.write_f32::<E>(self.b)?;
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
 use super::{attack_property::AttackProperties, element::Elements};
@@ -14,11 +14,11 @@ pub struct Damage {
 }
 
 impl Damage {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let attack_properties = AttackProperties::read(reader)?;
-        let elements = Elements::read(reader)?;
-        let amount = reader.read_f32::<LittleEndian>()?;
-        let magnitude = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let attack_properties = AttackProperties::read::<E>(reader)?;
+        let elements = Elements::read::<E>(reader)?;
+        let amount = reader.read_f32::<E>()?;
+        let magnitude = reader.read_f32::<E>()?;
         Ok(Damage {
             attack_properties,
             elements,
@@ -26,4 +26,12 @@ impl Damage {
             magnitude,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.attack_properties.write::<E>(writer)?;
+        self.elements.write::<E>(writer)?;
+        writer.write_f32::<E>(self.amount)?;
+        writer.write_f32::<E>(self.magnitude)?;
+        Ok(())
+    }
 }
@@ -1,11 +1,14 @@
-use std::{io::Read, vec};
+use std::io::{Read, Seek, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
-use crate::{ext::MyReadBytesExt, xnb::TypeReader};
+use crate::{
+    ext::{MyReadBytesExt, MyWriteBytesExt},
+    xnb::{TypeReader, Version},
+};
 
-use super::{Content, color::Color};
+use super::{Content, EXTERNAL_REFERENCE_READER_NAME, color::Color, write_type_id};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Effect {
@@ -13,12 +16,18 @@ pub struct Effect {
 }
 
 impl Effect {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let length = reader.read_u32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let length = reader.read_u32::<E>()?;
         let mut bytecode = vec![0; length as usize];
         reader.read_exact(&mut bytecode)?;
         Ok(Effect { bytecode })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u32::<E>(self.bytecode.len() as u32)?;
+        writer.write_all(&self.bytecode)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -33,13 +42,13 @@ pub struct BasicEffect {
 }
 
 impl BasicEffect {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let texture = reader.read_7bit_length_string()?;
-        let diffuse_color = Color::read(reader)?;
-        let emissive_color = Color::read(reader)?;
-        let specular_color = Color::read(reader)?;
-        let specular_power = reader.read_f32::<LittleEndian>()?;
-        let alpha = reader.read_f32::<LittleEndian>()?;
+        let diffuse_color = Color::read::<E>(reader)?;
+        let emissive_color = Color::read::<E>(reader)?;
+        let specular_color = Color::read::<E>(reader)?;
+        let specular_power = reader.read_f32::<E>()?;
+        let alpha = reader.read_f32::<E>()?;
         let vertex_color_enabled = reader.read_bool()?;
         Ok(BasicEffect {
             texture,
@@ -51,6 +60,17 @@ impl BasicEffect {
             vertex_color_enabled,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.texture)?;
+        self.diffuse_color.write::<E>(writer)?;
+        self.emissive_color.write::<E>(writer)?;
+        self.specular_color.write::<E>(writer)?;
+        writer.write_f32::<E>(self.specular_power)?;
+        writer.write_f32::<E>(self.alpha)?;
+        writer.write_bool(self.vertex_color_enabled)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -71,13 +91,17 @@ pub struct SkinnedModelBasicEffect {
 }
 
 impl SkinnedModelBasicEffect {
-    pub fn read(reader: &mut impl Read, type_readers: &[TypeReader]) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
         let method = reader.read_u8()?;
-        let emissive_amount = reader.read_f32::<LittleEndian>()?;
-        let diffuse_color = Color::read(reader)?;
-        let specular_amount = reader.read_f32::<LittleEndian>()?;
-        let specular_power = reader.read_f32::<LittleEndian>()?;
-        let alpha = reader.read_f32::<LittleEndian>()?;
+        let emissive_amount = reader.read_f32::<E>()?;
+        let diffuse_color = Color::read::<E>(reader)?;
+        let specular_amount = reader.read_f32::<E>()?;
+        let specular_power = reader.read_f32::<E>()?;
+        let alpha = reader.read_f32::<E>()?;
         let use_soft_light_blend = reader.read_bool()?;
         let map_0_diffuse_enabled = reader.read_bool()?;
         let map_1_diffuse_enabled = reader.read_bool()?;
@@ -86,13 +110,14 @@ impl SkinnedModelBasicEffect {
         let map_1_damage_enabled = reader.read_bool()?;
         let normal_map_enabled = reader.read_bool()?;
 
-        fn read_external_reference(
+        fn read_external_reference<E: ByteOrder>(
             enabled: bool,
-            reader: &mut impl Read,
+            reader: &mut (impl Read + Seek),
             type_readers: &[TypeReader],
+            version: Version,
         ) -> anyhow::Result<Option<String>> {
             let reference = if enabled {
-                let reference = Content::read(reader, type_readers)?;
+                let reference = Content::read::<E>(reader, type_readers, version)?;
                 let Content::ExternalReference(reference) = reference else {
                     anyhow::bail!("expected external reference");
                 };
@@ -107,12 +132,18 @@ impl SkinnedModelBasicEffect {
             Ok(reference)
         }
 
-        let map_0_diffuse = read_external_reference(map_0_diffuse_enabled, reader, type_readers)?;
-        let map_1_diffuse = read_external_reference(map_1_diffuse_enabled, reader, type_readers)?;
-        let material_map = read_external_reference(material_map_enabled, reader, type_readers)?;
-        let map_0_damage = read_external_reference(map_0_damage_enabled, reader, type_readers)?;
-        let map_1_damage = read_external_reference(map_1_damage_enabled, reader, type_readers)?;
-        let normal_map = read_external_reference(normal_map_enabled, reader, type_readers)?;
+        let map_0_diffuse =
+            read_external_reference::<E>(map_0_diffuse_enabled, reader, type_readers, version)?;
+        let map_1_diffuse =
+            read_external_reference::<E>(map_1_diffuse_enabled, reader, type_readers, version)?;
+        let material_map =
+            read_external_reference::<E>(material_map_enabled, reader, type_readers, version)?;
+        let map_0_damage =
+            read_external_reference::<E>(map_0_damage_enabled, reader, type_readers, version)?;
+        let map_1_damage =
+            read_external_reference::<E>(map_1_damage_enabled, reader, type_readers, version)?;
+        let normal_map =
+            read_external_reference::<E>(normal_map_enabled, reader, type_readers, version)?;
 
         Ok(SkinnedModelBasicEffect {
             method,
@@ -130,6 +161,48 @@ impl SkinnedModelBasicEffect {
             normal_map,
         })
     }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+    ) -> anyhow::Result<()> {
+        writer.write_u8(self.method)?;
+        writer.write_f32::<E>(self.emissive_amount)?;
+        self.diffuse_color.write::<E>(writer)?;
+        writer.write_f32::<E>(self.specular_amount)?;
+        writer.write_f32::<E>(self.specular_power)?;
+        writer.write_f32::<E>(self.alpha)?;
+        writer.write_bool(self.use_soft_light_blend)?;
+        writer.write_bool(self.map_0_diffuse.is_some())?;
+        writer.write_bool(self.map_1_diffuse.is_some())?;
+        writer.write_bool(self.material_map.is_some())?;
+        writer.write_bool(self.map_0_damage.is_some())?;
+        writer.write_bool(self.map_1_damage.is_some())?;
+        writer.write_bool(self.normal_map.is_some())?;
+
+        fn write_external_reference(
+            reference: &Option<String>,
+            writer: &mut impl Write,
+            type_readers: &[TypeReader],
+        ) -> anyhow::Result<()> {
+            if let Some(reference) = reference {
+                write_type_id(writer, type_readers, EXTERNAL_REFERENCE_READER_NAME)?;
+                writer.write_7bit_length_string(reference)?;
+            } else {
+                writer.write_u8(0)?;
+            }
+            Ok(())
+        }
+
+        write_external_reference(&self.map_0_diffuse, writer, type_readers)?;
+        write_external_reference(&self.map_1_diffuse, writer, type_readers)?;
+        write_external_reference(&self.material_map, writer, type_readers)?;
+        write_external_reference(&self.map_0_damage, writer, type_readers)?;
+        write_external_reference(&self.map_1_damage, writer, type_readers)?;
+        write_external_reference(&self.normal_map, writer, type_readers)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -141,8 +214,8 @@ pub struct AdditiveEffect {
 }
 
 impl AdditiveEffect {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let color_tint = Color::read(reader)?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let color_tint = Color::read::<E>(reader)?;
         let vertex_color_enabled = reader.read_bool()?;
         let texture_enabled = reader.read_bool()?;
         let texture = reader.read_7bit_length_string()?;
@@ -153,6 +226,14 @@ impl AdditiveEffect {
             texture,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.color_tint.write::<E>(writer)?;
+        writer.write_bool(self.vertex_color_enabled)?;
+        writer.write_bool(self.texture_enabled)?;
+        writer.write_7bit_length_string(&self.texture)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -167,16 +248,16 @@ pub struct RenderDeferredEffect {
 }
 
 impl RenderDeferredEffect {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let alpha = reader.read_f32::<LittleEndian>()?;
-        let sharpness = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let alpha = reader.read_f32::<E>()?;
+        let sharpness = reader.read_f32::<E>()?;
         let vertex_color_enabled = reader.read_bool()?;
         let use_material_texture_for_reflectiveness = reader.read_bool()?;
         let reflection_map = reader.read_7bit_length_string()?;
-        let material_0 = RenderDeferredEffectMaterial::read(reader)?;
+        let material_0 = RenderDeferredEffectMaterial::read::<E>(reader)?;
         let has_material_1 = reader.read_bool()?;
         let material_1 = if has_material_1 {
-            Some(RenderDeferredEffectMaterial::read(reader)?)
+            Some(RenderDeferredEffectMaterial::read::<E>(reader)?)
         } else {
             None
         };
@@ -190,6 +271,20 @@ impl RenderDeferredEffect {
             material_1,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.alpha)?;
+        writer.write_f32::<E>(self.sharpness)?;
+        writer.write_bool(self.vertex_color_enabled)?;
+        writer.write_bool(self.use_material_texture_for_reflectiveness)?;
+        writer.write_7bit_length_string(&self.reflection_map)?;
+        self.material_0.write::<E>(writer)?;
+        writer.write_bool(self.material_1.is_some())?;
+        if let Some(material_1) = &self.material_1 {
+            material_1.write::<E>(writer)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -208,15 +303,15 @@ pub struct RenderDeferredEffectMaterial {
 }
 
 impl RenderDeferredEffectMaterial {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let diffuse_texture_alpha_disabled = reader.read_bool()?;
         let alpha_mask_enabled = reader.read_bool()?;
-        let diffuse_color = Color::read(reader)?;
-        let spec_amount = reader.read_f32::<LittleEndian>()?;
-        let spec_power = reader.read_f32::<LittleEndian>()?;
-        let emissive_amount = reader.read_f32::<LittleEndian>()?;
-        let normal_power = reader.read_f32::<LittleEndian>()?;
-        let reflectiveness = reader.read_f32::<LittleEndian>()?;
+        let diffuse_color = Color::read::<E>(reader)?;
+        let spec_amount = reader.read_f32::<E>()?;
+        let spec_power = reader.read_f32::<E>()?;
+        let emissive_amount = reader.read_f32::<E>()?;
+        let normal_power = reader.read_f32::<E>()?;
+        let reflectiveness = reader.read_f32::<E>()?;
         let diffuse_texture = reader.read_7bit_length_string()?;
         let material_texture = reader.read_7bit_length_string()?;
         let normal_texture = reader.read_7bit_length_string()?;
@@ -234,6 +329,21 @@ impl RenderDeferredEffectMaterial {
             normal_texture,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_bool(self.diffuse_texture_alpha_disabled)?;
+        writer.write_bool(self.alpha_mask_enabled)?;
+        self.diffuse_color.write::<E>(writer)?;
+        writer.write_f32::<E>(self.spec_amount)?;
+        writer.write_f32::<E>(self.spec_power)?;
+        writer.write_f32::<E>(self.emissive_amount)?;
+        writer.write_f32::<E>(self.normal_power)?;
+        writer.write_f32::<E>(self.reflectiveness)?;
+        writer.write_7bit_length_string(&self.diffuse_texture)?;
+        writer.write_7bit_length_string(&self.material_texture)?;
+        writer.write_7bit_length_string(&self.normal_texture)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -251,12 +361,12 @@ pub struct SkinnedModelDeferredNormalMappedEffect {
 }
 
 impl SkinnedModelDeferredNormalMappedEffect {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let diffuse_color = Color::read(reader)?;
-        let specular_amount = reader.read_f32::<LittleEndian>()?;
-        let specular_power = reader.read_f32::<LittleEndian>()?;
-        let emissive_amount = reader.read_f32::<LittleEndian>()?;
-        let normal_power = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let diffuse_color = Color::read::<E>(reader)?;
+        let specular_amount = reader.read_f32::<E>()?;
+        let specular_power = reader.read_f32::<E>()?;
+        let emissive_amount = reader.read_f32::<E>()?;
+        let normal_power = reader.read_f32::<E>()?;
         let diffuse_texture = reader.read_7bit_length_string()?;
         let material_texture = reader.read_7bit_length_string()?;
         let damage_texture = reader.read_7bit_length_string()?;
@@ -275,12 +385,26 @@ impl SkinnedModelDeferredNormalMappedEffect {
             normal_damage_texture,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.diffuse_color.write::<E>(writer)?;
+        writer.write_f32::<E>(self.specular_amount)?;
+        writer.write_f32::<E>(self.specular_power)?;
+        writer.write_f32::<E>(self.emissive_amount)?;
+        writer.write_f32::<E>(self.normal_power)?;
+        writer.write_7bit_length_string(&self.diffuse_texture)?;
+        writer.write_7bit_length_string(&self.material_texture)?;
+        writer.write_7bit_length_string(&self.damage_texture)?;
+        writer.write_7bit_length_string(&self.normal_texture)?;
+        writer.write_7bit_length_string(&self.normal_damage_texture)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BonedEffect {
-    bone: String,
-    effect: String,
+    pub bone: String,
+    pub effect: String,
 }
 
 impl BonedEffect {
@@ -289,4 +413,58 @@ impl BonedEffect {
         let effect = reader.read_7bit_length_string()?;
         Ok(BonedEffect { bone, effect })
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.bone)?;
+        writer.write_7bit_length_string(&self.effect)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content::tests::assert_round_trip;
+
+    #[test]
+    fn effect_round_trip() {
+        assert_round_trip(r#"{"Effect": {"bytecode": [1, 9, 255, 254, 0, 3]}}"#);
+        assert_round_trip(
+            r#"{"BasicEffect": {"texture": "tex\\foo", "diffuse_color": {"r": 1.0, "g": 0.5,
+                "b": 0.25}, "emissive_color": {"r": 0.0, "g": 0.0, "b": 0.1}, "specular_color":
+                {"r": 0.2, "g": 0.2, "b": 0.2}, "specular_power": 16.0, "alpha": 0.75,
+                "vertex_color_enabled": true}}"#,
+        );
+        assert_round_trip(
+            r#"{"AdditiveEffect": {"color_tint": {"r": 2.0, "g": 1.0, "b": 0.0},
+                "vertex_color_enabled": false, "texture_enabled": true, "texture": "fx\\glow"}}"#,
+        );
+        assert_round_trip(
+            r#"{"RenderDeferredEffect": {"alpha": 1.0, "sharpness": 0.5,
+                "vertex_color_enabled": true, "use_material_texture_for_reflectiveness": false,
+                "reflection_map": "env", "material_0": {"diffuse_texture_alpha_disabled": true,
+                "alpha_mask_enabled": false, "diffuse_color": {"r": 1.0, "g": 1.0, "b": 1.0},
+                "spec_amount": 0.5, "spec_power": 20.0, "emissive_amount": 0.0,
+                "normal_power": 1.0, "reflectiveness": 0.25, "diffuse_texture": "grass",
+                "material_texture": "", "normal_texture": "grass_n"}, "material_1": {
+                "diffuse_texture_alpha_disabled": false, "alpha_mask_enabled": true,
+                "diffuse_color": {"r": 0.5, "g": 0.5, "b": 0.5}, "spec_amount": 0.0,
+                "spec_power": 1.0, "emissive_amount": 0.5, "normal_power": 0.0,
+                "reflectiveness": 0.0, "diffuse_texture": "dirt", "material_texture": "dirt_m",
+                "normal_texture": ""}}}"#,
+        );
+        assert_round_trip(
+            r#"{"SkinnedModelBasicEffect": {"method": 1, "emissive_amount": 0.5,
+                "diffuse_color": {"r": 1.0, "g": 0.0, "b": 0.0}, "specular_amount": 0.25,
+                "specular_power": 8.0, "alpha": 1.0, "use_soft_light_blend": true,
+                "map_0_diffuse": "skin", "map_1_diffuse": null, "map_0_damage": "skin_dmg",
+                "map_1_damage": null, "material_map": null, "normal_map": "skin_n"}}"#,
+        );
+        assert_round_trip(
+            r#"{"SkinnedModelDeferredNormalMappedEffect": {"diffuse_color": {"r": 1.0,
+                "g": 1.0, "b": 1.0}, "specular_amount": 0.5, "specular_power": 10.0,
+                "emissive_amount": 0.0, "normal_power": 1.0, "diffuse_texture": "body",
+                "material_texture": "body_m", "damage_texture": "body_d",
+                "normal_texture": "body_n", "normal_damage_texture": "body_dn"}}"#,
+        );
+    }
 }
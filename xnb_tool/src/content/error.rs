@@ -0,0 +1,76 @@
+use std::io;
+
+use thiserror::Error;
+
+/// error returned by [`Content::read`](super::Content::read), offsets are relative to the
+/// start of the decompressed xnb payload and point at the start of the content that failed
+#[derive(Error, Debug)]
+pub enum ContentError {
+    #[error("unknown type reader {name} at offset {offset:#x}")]
+    UnknownTypeReader { name: String, offset: u64 },
+    #[error("type id {id} at offset {offset:#x} does not match any of the {count} type readers")]
+    InvalidTypeId {
+        id: usize,
+        count: usize,
+        offset: u64,
+    },
+    #[error("{reader} does not support {variant} at offset {offset:#x}")]
+    UnsupportedVariant {
+        reader: String,
+        variant: String,
+        offset: u64,
+    },
+    #[error("{reader} ran out of data at offset {offset:#x}")]
+    TruncatedData { reader: String, offset: u64 },
+    #[error("{reader} failed at offset {offset:#x}")]
+    InvalidData {
+        reader: String,
+        offset: u64,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl ContentError {
+    /// classifies an error returned by the reader with the given name, `offset` is where the
+    /// content it was reading starts
+    pub(super) fn from_reader(reader: &str, offset: u64, error: anyhow::Error) -> Self {
+        // errors from nested content already know where they happened
+        let error = match error.downcast::<ContentError>() {
+            Ok(error) => return error,
+            Err(error) => error,
+        };
+
+        let reader = reader.to_string();
+        if let Some(Unsupported(variant)) = error.downcast_ref::<Unsupported>() {
+            return ContentError::UnsupportedVariant {
+                reader,
+                variant: variant.clone(),
+                offset,
+            };
+        }
+
+        let truncated = error.chain().any(|cause| {
+            cause
+                .downcast_ref::<io::Error>()
+                .is_some_and(|e| e.kind() == io::ErrorKind::UnexpectedEof)
+        });
+        if truncated {
+            return ContentError::TruncatedData { reader, offset };
+        }
+
+        ContentError::InvalidData {
+            reader,
+            offset,
+            source: error.into(),
+        }
+    }
+}
+
+/// returned by readers for variants that are known to exist but cannot be read yet,
+/// [`Content::read`](super::Content::read) reports it as [`ContentError::UnsupportedVariant`]
+#[derive(Error, Debug)]
+#[error("unsupported {0}")]
+pub struct Unsupported(pub String);
@@ -1,8 +1,8 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use anyhow::anyhow;
+use anyhow::{Context, anyhow};
 use bitflags::bitflags;
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use glam::Vec3;
 use serde::{Deserialize, Serialize};
 
@@ -13,7 +13,7 @@ use super::{
     light::Light,
     sound::Bank,
 };
-use crate::ext::MyReadBytesExt;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Event {
@@ -22,7 +22,11 @@ pub enum Event {
     Sound(SoundEvent),
     Effect(EffectEvent),
     Remove(RemoveEvent),
+    CameraShake(CameraShakeEvent),
+    Decal(DecalEvent),
+    Blast(BlastEvent),
     Spawn(SpawnEvent),
+    Overkill(OverkillEvent),
     SpawnGibs(SpawnGibsEvent),
     SpawnItem(SpawnItemEvent),
     SpawnMagick(SpawnMagickEvent),
@@ -30,22 +34,23 @@ pub enum Event {
     Light(LightEvent),
     CastMagick(CastMagickEvent),
     DamageOwner(DamageOwnerEvent),
+    Callback(CallbackEvent),
 }
 
 impl Event {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = reader.read_u8()?;
         match kind {
             0 => {
-                let event = DamageEvent::read(reader)?;
+                let event = DamageEvent::read::<E>(reader)?;
                 Ok(Event::Damage(event))
             }
             1 => {
-                let event = SplashEvent::read(reader)?;
+                let event = SplashEvent::read::<E>(reader)?;
                 Ok(Event::Splash(event))
             }
             2 => {
-                let event = SoundEvent::read(reader)?;
+                let event = SoundEvent::read::<E>(reader)?;
                 Ok(Event::Sound(event))
             }
             3 => {
@@ -53,27 +58,31 @@ impl Event {
                 Ok(Event::Effect(event))
             }
             4 => {
-                let event = RemoveEvent::read(reader)?;
+                let event = RemoveEvent::read::<E>(reader)?;
                 Ok(Event::Remove(event))
             }
             5 => {
-                todo!("camera shake event");
+                let event = CameraShakeEvent::read::<E>(reader)?;
+                Ok(Event::CameraShake(event))
             }
             6 => {
-                todo!("decal event");
+                let event = DecalEvent::read::<E>(reader)?;
+                Ok(Event::Decal(event))
             }
             7 => {
-                todo!("blast event (invalid?)");
+                let event = BlastEvent::read::<E>(reader)?;
+                Ok(Event::Blast(event))
             }
             8 => {
-                let event = SpawnEvent::read(reader)?;
+                let event = SpawnEvent::read::<E>(reader)?;
                 Ok(Event::Spawn(event))
             }
             9 => {
-                todo!("overkill event");
+                let event = OverkillEvent::read(reader)?;
+                Ok(Event::Overkill(event))
             }
             10 => {
-                let event = SpawnGibsEvent::read(reader)?;
+                let event = SpawnGibsEvent::read::<E>(reader)?;
                 Ok(Event::SpawnGibs(event))
             }
             11 => {
@@ -85,27 +94,106 @@ impl Event {
                 Ok(Event::SpawnMagick(event))
             }
             13 => {
-                let event = SpawnMissileEvent::read(reader)?;
+                let event = SpawnMissileEvent::read::<E>(reader)?;
                 Ok(Event::SpawnMissile(event))
             }
             14 => {
-                let event = LightEvent::read(reader)?;
+                let event = LightEvent::read::<E>(reader)?;
                 Ok(Event::Light(event))
             }
             15 => {
-                let event = CastMagickEvent::read(reader)?;
+                let event = CastMagickEvent::read::<E>(reader)?;
                 Ok(Event::CastMagick(event))
             }
             16 => {
-                let event = DamageOwnerEvent::read(reader)?;
+                let event = DamageOwnerEvent::read::<E>(reader)?;
                 Ok(Event::DamageOwner(event))
             }
             17 => {
-                todo!("callback event (invalid?)");
+                let event = CallbackEvent::read(reader)?;
+                Ok(Event::Callback(event))
             }
             _ => Err(anyhow!("unknown event kind: {kind}")),
         }
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        match self {
+            Event::Damage(event) => {
+                writer.write_u8(0)?;
+                event.write::<E>(writer)?;
+            }
+            Event::Splash(event) => {
+                writer.write_u8(1)?;
+                event.write::<E>(writer)?;
+            }
+            Event::Sound(event) => {
+                writer.write_u8(2)?;
+                event.write::<E>(writer)?;
+            }
+            Event::Effect(event) => {
+                writer.write_u8(3)?;
+                event.write(writer)?;
+            }
+            Event::Remove(event) => {
+                writer.write_u8(4)?;
+                event.write::<E>(writer)?;
+            }
+            Event::CameraShake(event) => {
+                writer.write_u8(5)?;
+                event.write::<E>(writer)?;
+            }
+            Event::Decal(event) => {
+                writer.write_u8(6)?;
+                event.write::<E>(writer)?;
+            }
+            Event::Blast(event) => {
+                writer.write_u8(7)?;
+                event.write::<E>(writer)?;
+            }
+            Event::Spawn(event) => {
+                writer.write_u8(8)?;
+                event.write::<E>(writer)?;
+            }
+            Event::Overkill(event) => {
+                writer.write_u8(9)?;
+                event.write(writer)?;
+            }
+            Event::SpawnGibs(event) => {
+                writer.write_u8(10)?;
+                event.write::<E>(writer)?;
+            }
+            Event::SpawnItem(event) => {
+                writer.write_u8(11)?;
+                event.write(writer)?;
+            }
+            Event::SpawnMagick(event) => {
+                writer.write_u8(12)?;
+                event.write(writer)?;
+            }
+            Event::SpawnMissile(event) => {
+                writer.write_u8(13)?;
+                event.write::<E>(writer)?;
+            }
+            Event::Light(event) => {
+                writer.write_u8(14)?;
+                event.write::<E>(writer)?;
+            }
+            Event::CastMagick(event) => {
+                writer.write_u8(15)?;
+                event.write::<E>(writer)?;
+            }
+            Event::DamageOwner(event) => {
+                writer.write_u8(16)?;
+                event.write::<E>(writer)?;
+            }
+            Event::Callback(event) => {
+                writer.write_u8(17)?;
+                event.write(writer)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -118,11 +206,11 @@ pub struct DamageEvent {
 }
 
 impl DamageEvent {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let attack_properties = AttackProperties::read(reader)?;
-        let elements = Elements::read(reader)?;
-        let amount = reader.read_f32::<LittleEndian>()?;
-        let magnitude = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let attack_properties = AttackProperties::read::<E>(reader)?;
+        let elements = Elements::read::<E>(reader)?;
+        let amount = reader.read_f32::<E>()?;
+        let magnitude = reader.read_f32::<E>()?;
         let velocity_based = reader.read_bool()?;
         Ok(DamageEvent {
             attack_properties,
@@ -132,6 +220,15 @@ impl DamageEvent {
             velocity_based,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.attack_properties.write::<E>(writer)?;
+        self.elements.write::<E>(writer)?;
+        writer.write_f32::<E>(self.amount)?;
+        writer.write_f32::<E>(self.magnitude)?;
+        writer.write_bool(self.velocity_based)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -144,12 +241,12 @@ pub struct SplashEvent {
 }
 
 impl SplashEvent {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let attack_properties = AttackProperties::read(reader)?;
-        let element = Elements::read(reader)?;
-        let amount = reader.read_i32::<LittleEndian>()?;
-        let magnitude = reader.read_f32::<LittleEndian>()?;
-        let radius = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let attack_properties = AttackProperties::read::<E>(reader)?;
+        let element = Elements::read::<E>(reader)?;
+        let amount = reader.read_i32::<E>()?;
+        let magnitude = reader.read_f32::<E>()?;
+        let radius = reader.read_f32::<E>()?;
         Ok(SplashEvent {
             attack_properties,
             elements: element,
@@ -158,21 +255,30 @@ impl SplashEvent {
             radius,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.attack_properties.write::<E>(writer)?;
+        self.elements.write::<E>(writer)?;
+        writer.write_i32::<E>(self.amount)?;
+        writer.write_f32::<E>(self.magnitude)?;
+        writer.write_f32::<E>(self.radius)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SoundEvent {
-    banks: Bank,
-    cue: String,
-    magnitude: f32,
-    stop_on_remove: bool,
+    pub banks: Bank,
+    pub cue: String,
+    pub magnitude: f32,
+    pub stop_on_remove: bool,
 }
 
 impl SoundEvent {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let bank = Bank::read(reader)?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let bank = Bank::read::<E>(reader)?;
         let cue = reader.read_7bit_length_string()?;
-        let magnitude = reader.read_f32::<LittleEndian>()?;
+        let magnitude = reader.read_f32::<E>()?;
         let stop_on_remove = reader.read_bool()?;
         Ok(SoundEvent {
             banks: bank,
@@ -181,6 +287,14 @@ impl SoundEvent {
             stop_on_remove,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.banks.write::<E>(writer)?;
+        writer.write_7bit_length_string(&self.cue)?;
+        writer.write_f32::<E>(self.magnitude)?;
+        writer.write_bool(self.stop_on_remove)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -201,6 +315,13 @@ impl EffectEvent {
             effect,
         })
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_bool(self.follow)?;
+        writer.write_bool(self.world_aligned)?;
+        writer.write_7bit_length_string(&self.effect)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -209,11 +330,97 @@ pub struct RemoveEvent {
 }
 
 impl RemoveEvent {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let bounces = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let bounces = reader.read_i32::<E>()?;
         let event = RemoveEvent { bounces };
         Ok(event)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.bounces)?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CameraShakeEvent {
+    duration: f32,
+    magnitude: f32,
+}
+
+impl CameraShakeEvent {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let duration = reader.read_f32::<E>()?;
+        let magnitude = reader.read_f32::<E>()?;
+        Ok(CameraShakeEvent {
+            duration,
+            magnitude,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.duration)?;
+        writer.write_f32::<E>(self.magnitude)?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DecalEvent {
+    decal: i32,
+    scale: f32,
+    ttl: f32,
+}
+
+impl DecalEvent {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let decal = reader.read_i32::<E>()?;
+        let scale = reader.read_f32::<E>()?;
+        let ttl = reader.read_f32::<E>()?;
+        Ok(DecalEvent { decal, scale, ttl })
+    }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.decal)?;
+        writer.write_f32::<E>(self.scale)?;
+        writer.write_f32::<E>(self.ttl)?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlastEvent {
+    attack_properties: AttackProperties,
+    elements: Elements,
+    amount: f32,
+    magnitude: f32,
+    radius: f32,
+}
+
+impl BlastEvent {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let attack_properties = AttackProperties::read::<E>(reader)?;
+        let elements = Elements::read::<E>(reader)?;
+        let amount = reader.read_f32::<E>()?;
+        let magnitude = reader.read_f32::<E>()?;
+        let radius = reader.read_f32::<E>()?;
+        Ok(BlastEvent {
+            attack_properties,
+            elements,
+            amount,
+            magnitude,
+            radius,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.attack_properties.write::<E>(writer)?;
+        self.elements.write::<E>(writer)?;
+        writer.write_f32::<E>(self.amount)?;
+        writer.write_f32::<E>(self.magnitude)?;
+        writer.write_f32::<E>(self.radius)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -230,16 +437,16 @@ pub struct SpawnEvent {
 }
 
 impl SpawnEvent {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = reader.read_7bit_length_string()?;
         let idle_animation = reader.read_7bit_length_string()?;
         let spawn_animation = reader.read_7bit_length_string()?;
-        let health = reader.read_f32::<LittleEndian>()?;
+        let health = reader.read_f32::<E>()?;
         let order = Order::read(reader)?;
         let react_to = ReactionTriggers::read(reader)?;
         let reaction = Order::read(reader)?;
-        let rotation = reader.read_f32::<LittleEndian>()?;
-        let offset = reader.read_vec3()?;
+        let rotation = reader.read_f32::<E>()?;
+        let offset = reader.read_vec3::<E>()?;
         Ok(SpawnEvent {
             kind,
             idle_animation,
@@ -252,6 +459,33 @@ impl SpawnEvent {
             offset,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.kind)?;
+        writer.write_7bit_length_string(&self.idle_animation)?;
+        writer.write_7bit_length_string(&self.spawn_animation)?;
+        writer.write_f32::<E>(self.health)?;
+        self.order.write(writer)?;
+        self.react_to.write(writer)?;
+        self.reaction.write(writer)?;
+        writer.write_f32::<E>(self.rotation)?;
+        writer.write_vec3::<E>(self.offset)?;
+        Ok(())
+    }
+}
+
+/// gibs the owner when it is overkilled, carries no data of its own
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OverkillEvent {}
+
+impl OverkillEvent {
+    pub fn read(_reader: &mut impl Read) -> anyhow::Result<Self> {
+        Ok(OverkillEvent {})
+    }
+
+    pub fn write(&self, _writer: &mut impl Write) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -261,14 +495,20 @@ pub struct SpawnGibsEvent {
 }
 
 impl SpawnGibsEvent {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let start_index = reader.read_i32::<LittleEndian>()?;
-        let end_index = reader.read_i32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let start_index = reader.read_i32::<E>()?;
+        let end_index = reader.read_i32::<E>()?;
         Ok(SpawnGibsEvent {
             start_index,
             end_index,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.start_index)?;
+        writer.write_i32::<E>(self.end_index)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -281,6 +521,11 @@ impl SpawnItemEvent {
         let item = reader.read_7bit_length_string()?;
         Ok(SpawnItemEvent { item })
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.item)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -293,6 +538,11 @@ impl SpawnMagickEvent {
         let magick = reader.read_7bit_length_string()?;
         Ok(SpawnMagickEvent { magick })
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.magick)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -303,9 +553,9 @@ pub struct SpawnMissileEvent {
 }
 
 impl SpawnMissileEvent {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = reader.read_7bit_length_string()?;
-        let velocity = reader.read_vec3()?;
+        let velocity = reader.read_vec3::<E>()?;
         let facing = reader.read_bool()?;
 
         Ok(SpawnMissileEvent {
@@ -314,18 +564,30 @@ impl SpawnMissileEvent {
             facing,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.kind)?;
+        writer.write_vec3::<E>(self.velocity)?;
+        writer.write_bool(self.facing)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct LightEvent {
-    light: Light,
+    pub light: Light,
 }
 
 impl LightEvent {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let light = Light::read(reader)?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let light = Light::read::<E>(reader)?;
         Ok(LightEvent { light })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.light.write::<E>(writer)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -335,16 +597,25 @@ pub struct CastMagickEvent {
 }
 
 impl CastMagickEvent {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = reader.read_7bit_length_string()?;
-        let num_elements = reader.read_i32::<LittleEndian>()? as usize;
+        let num_elements = reader.read_i32::<E>()? as usize;
         let mut elements = Vec::with_capacity(num_elements);
         for _ in 0..num_elements {
-            let element = Elements::read(reader)?;
+            let element = Elements::read::<E>(reader)?;
             elements.push(element);
         }
         Ok(CastMagickEvent { kind, elements })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.kind)?;
+        writer.write_i32::<E>(self.elements.len() as i32)?;
+        for element in &self.elements {
+            element.write::<E>(writer)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -357,11 +628,11 @@ pub struct DamageOwnerEvent {
 }
 
 impl DamageOwnerEvent {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let attack_properties = AttackProperties::read(reader)?;
-        let elements = Elements::read(reader)?;
-        let amount = reader.read_f32::<LittleEndian>()?;
-        let magnitude = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let attack_properties = AttackProperties::read::<E>(reader)?;
+        let elements = Elements::read::<E>(reader)?;
+        let amount = reader.read_f32::<E>()?;
+        let magnitude = reader.read_f32::<E>()?;
         let velocity_based = reader.read_bool()?;
         Ok(DamageOwnerEvent {
             attack_properties,
@@ -371,6 +642,29 @@ impl DamageOwnerEvent {
             velocity_based,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.attack_properties.write::<E>(writer)?;
+        self.elements.write::<E>(writer)?;
+        writer.write_f32::<E>(self.amount)?;
+        writer.write_f32::<E>(self.magnitude)?;
+        writer.write_bool(self.velocity_based)?;
+        Ok(())
+    }
+}
+
+/// the game attaches the actual callback at runtime, the content only stores the kind
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CallbackEvent {}
+
+impl CallbackEvent {
+    pub fn read(_reader: &mut impl Read) -> anyhow::Result<Self> {
+        Ok(CallbackEvent {})
+    }
+
+    pub fn write(&self, _writer: &mut impl Write) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
 
 bitflags! {
@@ -393,32 +687,37 @@ impl EventConditionKind {
             .ok_or_else(|| anyhow!("unknown event condition kind: {value}"))?;
         Ok(kind)
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u8(self.bits())?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EventConditions {
-    kind: EventConditionKind,
-    hitpoints: f32,
-    element: Elements,
-    threshold: f32,
-    time: f32,
-    repeat: bool,
-    events: Vec<Event>,
+    pub kind: EventConditionKind,
+    pub hitpoints: f32,
+    pub element: Elements,
+    pub threshold: f32,
+    pub time: f32,
+    pub repeat: bool,
+    pub events: Vec<Event>,
 }
 
 impl EventConditions {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = EventConditionKind::read(reader)?;
-        let hitpoints = reader.read_i32::<LittleEndian>()? as f32;
-        let element = Elements::read(reader)?;
-        let threshold = reader.read_f32::<LittleEndian>()?;
-        let time = reader.read_f32::<LittleEndian>()?;
+        let hitpoints = reader.read_i32::<E>()? as f32;
+        let element = Elements::read::<E>(reader)?;
+        let threshold = reader.read_f32::<E>()?;
+        let time = reader.read_f32::<E>()?;
         let repeat = reader.read_bool()?;
 
-        let num_events = reader.read_i32::<LittleEndian>()?;
+        let num_events = reader.read_i32::<E>()?;
         let mut events = Vec::with_capacity(num_events as usize);
         for _ in 0..num_events {
-            let event = Event::read(reader)?;
+            let event = Event::read::<E>(reader)?;
             events.push(event);
         }
 
@@ -432,4 +731,20 @@ impl EventConditions {
             events,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.kind.write(writer)?;
+        writer.write_i32::<E>(self.hitpoints as i32)?;
+        self.element.write::<E>(writer)?;
+        writer.write_f32::<E>(self.threshold)?;
+        writer.write_f32::<E>(self.time)?;
+        writer.write_bool(self.repeat)?;
+        writer.write_i32::<E>(self.events.len() as i32)?;
+        for (i, event) in self.events.iter().enumerate() {
+            event
+                .write::<E>(writer)
+                .with_context(|| format!("failed to write event {i}"))?;
+        }
+        Ok(())
+    }
 }
@@ -1,9 +1,9 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
-use crate::ext::MyReadBytesExt;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Gib {
@@ -13,10 +13,17 @@ pub struct Gib {
 }
 
 impl Gib {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let model = reader.read_7bit_length_string()?;
-        let mass = reader.read_f32::<LittleEndian>()?;
-        let scale = reader.read_f32::<LittleEndian>()?;
+        let mass = reader.read_f32::<E>()?;
+        let scale = reader.read_f32::<E>()?;
         Ok(Gib { model, mass, scale })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.model)?;
+        writer.write_f32::<E>(self.mass)?;
+        writer.write_f32::<E>(self.scale)?;
+        Ok(())
+    }
 }
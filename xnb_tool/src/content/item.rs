@@ -1,10 +1,10 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use anyhow::anyhow;
-use byteorder::{LittleEndian, ReadBytesExt};
+use anyhow::{Context, anyhow};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
-use crate::ext::MyReadBytesExt;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
 
 use super::{
     aura::Aura,
@@ -19,155 +19,155 @@ use super::{
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Item {
-    name: String,
-    locale_name: String,
-    locale_description: String,
-    sounds: Vec<Sound>,
-    pickupable: bool,
-    bound: bool,
-    block_value: i32,
-    weapon_class: WeaponClass,
-    cooldown_time: f32,
-    hide_model: bool,
-    hide_effect: bool,
-    pause_sounds: bool,
-    resistances: Vec<Resistance>,
-    passive_ability: PassiveAbility,
-    effects: Vec<String>,
-    lights: Vec<Light>,
-    special_ability: Option<SpecialAbilityWithCooldown>,
-    melee_range: f32,
-    melee_multi_hit: bool,
-    melee_conditions: Vec<EventConditions>,
-    ranged_range: f32,
-    facing: bool,
-    homing_strength: f32,
-    ranged_elevation: f32,
-    ranged_danger: f32,
-    gun_range: f32,
-    gun_clip: i32,
-    gun_rate: i32,
-    gun_accuracy: f32,
-    gun_sound_cue: String,
-    gun_muzzle_effect: String,
-    gun_shell_effect: String,
-    gun_tracer_velocity: f32,
-    gun_non_tracer: String,
-    gun_tracer: String,
-    gun_conditions: Vec<EventConditions>,
-    projectile_model: String,
-    ranged_conditions: Vec<EventConditions>,
-    scale: f32,
-    model: String,
-    auras: Vec<Aura>,
+    pub name: String,
+    pub locale_name: String,
+    pub locale_description: String,
+    pub sounds: Vec<Sound>,
+    pub pickupable: bool,
+    pub bound: bool,
+    pub block_value: i32,
+    pub weapon_class: WeaponClass,
+    pub cooldown_time: f32,
+    pub hide_model: bool,
+    pub hide_effect: bool,
+    pub pause_sounds: bool,
+    pub resistances: Vec<Resistance>,
+    pub passive_ability: PassiveAbility,
+    pub effects: Vec<String>,
+    pub lights: Vec<Light>,
+    pub special_ability: Option<SpecialAbilityWithCooldown>,
+    pub melee_range: f32,
+    pub melee_multi_hit: bool,
+    pub melee_conditions: Vec<EventConditions>,
+    pub ranged_range: f32,
+    pub facing: bool,
+    pub homing_strength: f32,
+    pub ranged_elevation: f32,
+    pub ranged_danger: f32,
+    pub gun_range: f32,
+    pub gun_clip: i32,
+    pub gun_rate: i32,
+    pub gun_accuracy: f32,
+    pub gun_sound_cue: String,
+    pub gun_muzzle_effect: String,
+    pub gun_shell_effect: String,
+    pub gun_tracer_velocity: f32,
+    pub gun_non_tracer: String,
+    pub gun_tracer: String,
+    pub gun_conditions: Vec<EventConditions>,
+    pub projectile_model: String,
+    pub ranged_conditions: Vec<EventConditions>,
+    pub scale: f32,
+    pub model: String,
+    pub auras: Vec<Aura>,
 }
 
 impl Item {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let name = reader.read_7bit_length_string()?;
         let locale_name = reader.read_7bit_length_string()?;
         let locale_description = reader.read_7bit_length_string()?;
 
-        let num_sounds = reader.read_i32::<LittleEndian>()?;
+        let num_sounds = reader.read_i32::<E>()?;
         let mut sounds = Vec::with_capacity(num_sounds as usize);
         for _ in 0..num_sounds {
             let cue = reader.read_7bit_length_string()?;
-            let bank = Bank::read(reader)?;
+            let bank = Bank::read::<E>(reader)?;
             let sound = Sound { cue, bank };
             sounds.push(sound);
         }
 
         let pickupable = reader.read_bool()?;
         let bound = reader.read_bool()?;
-        let block_value = reader.read_i32::<LittleEndian>()?;
+        let block_value = reader.read_i32::<E>()?;
         let weapon_class = reader.read_u8()?;
         let weapon_class = WeaponClass::from_repr(weapon_class)
             .ok_or_else(|| anyhow!("unknown weapon class: {weapon_class}"))?;
-        let cooldown_time = reader.read_f32::<LittleEndian>()?;
+        let cooldown_time = reader.read_f32::<E>()?;
         let hide_model = reader.read_bool()?;
         let hide_effect = reader.read_bool()?;
         let pause_sounds = reader.read_bool()?;
 
-        let num_resistances = reader.read_i32::<LittleEndian>()?;
+        let num_resistances = reader.read_i32::<E>()?;
         let mut resistances = Vec::with_capacity(num_resistances as usize);
         for _ in 0..num_resistances {
-            let resistance = Resistance::read(reader)?;
+            let resistance = Resistance::read::<E>(reader)?;
             resistances.push(resistance);
         }
 
-        let passive_ability = PassiveAbility::read(reader)?;
+        let passive_ability = PassiveAbility::read::<E>(reader)?;
 
-        let num_effects = reader.read_i32::<LittleEndian>()?;
+        let num_effects = reader.read_i32::<E>()?;
         let mut effects = Vec::with_capacity(num_effects as usize);
         for _ in 0..num_effects {
             let effect = reader.read_7bit_length_string()?;
             effects.push(effect);
         }
 
-        let num_lights = reader.read_i32::<LittleEndian>()?;
+        let num_lights = reader.read_i32::<E>()?;
         let mut lights = Vec::with_capacity(num_lights as usize);
         for _ in 0..num_lights {
-            let light = Light::read(reader)?;
+            let light = Light::read::<E>(reader)?;
             lights.push(light);
         }
 
         let has_special_ability = reader.read_bool()?;
         let special_ability = if has_special_ability {
-            let ability = SpecialAbilityWithCooldown::read(reader)?;
+            let ability = SpecialAbilityWithCooldown::read::<E>(reader)?;
             Some(ability)
         } else {
             None
         };
 
-        let melee_range = reader.read_f32::<LittleEndian>()?;
+        let melee_range = reader.read_f32::<E>()?;
         let melee_multi_hit = reader.read_bool()?;
-        let num_melee_conditions = reader.read_i32::<LittleEndian>()?;
+        let num_melee_conditions = reader.read_i32::<E>()?;
         let mut melee_conditions = Vec::with_capacity(num_melee_conditions as usize);
         for _ in 0..num_melee_conditions {
-            let condition = EventConditions::read(reader)?;
+            let condition = EventConditions::read::<E>(reader)?;
             melee_conditions.push(condition);
         }
 
-        let ranged_range = reader.read_f32::<LittleEndian>()?;
+        let ranged_range = reader.read_f32::<E>()?;
         let facing = reader.read_bool()?;
-        let homing_strength = reader.read_f32::<LittleEndian>()?;
-        let ranged_elevation = reader.read_f32::<LittleEndian>()?;
-        let ranged_danger = reader.read_f32::<LittleEndian>()?;
-
-        let gun_range = reader.read_f32::<LittleEndian>()?;
-        let gun_clip = reader.read_i32::<LittleEndian>()?;
-        let gun_rate = reader.read_i32::<LittleEndian>()?;
-        let gun_accuracy = reader.read_f32::<LittleEndian>()?;
+        let homing_strength = reader.read_f32::<E>()?;
+        let ranged_elevation = reader.read_f32::<E>()?;
+        let ranged_danger = reader.read_f32::<E>()?;
+
+        let gun_range = reader.read_f32::<E>()?;
+        let gun_clip = reader.read_i32::<E>()?;
+        let gun_rate = reader.read_i32::<E>()?;
+        let gun_accuracy = reader.read_f32::<E>()?;
         let gun_sound_cue = reader.read_7bit_length_string()?;
         let gun_muzzle_effect = reader.read_7bit_length_string()?;
         let gun_shell_effect = reader.read_7bit_length_string()?;
-        let gun_tracer_velocity = reader.read_f32::<LittleEndian>()?;
+        let gun_tracer_velocity = reader.read_f32::<E>()?;
         let gun_non_tracer = reader.read_7bit_length_string()?;
         let gun_tracer = reader.read_7bit_length_string()?;
 
-        let num_gun_conditions = reader.read_i32::<LittleEndian>()?;
+        let num_gun_conditions = reader.read_i32::<E>()?;
         let mut gun_conditions = Vec::with_capacity(num_gun_conditions as usize);
         for _ in 0..num_gun_conditions {
-            let condition = EventConditions::read(reader)?;
+            let condition = EventConditions::read::<E>(reader)?;
             gun_conditions.push(condition);
         }
 
         let projectile_model = reader.read_7bit_length_string()?;
 
-        let num_ranged_conditions = reader.read_i32::<LittleEndian>()?;
+        let num_ranged_conditions = reader.read_i32::<E>()?;
         let mut ranged_conditions = Vec::with_capacity(num_ranged_conditions as usize);
         for _ in 0..num_ranged_conditions {
-            let condition = EventConditions::read(reader)?;
+            let condition = EventConditions::read::<E>(reader)?;
             ranged_conditions.push(condition);
         }
 
-        let scale = reader.read_f32::<LittleEndian>()?;
+        let scale = reader.read_f32::<E>()?;
         let model = reader.read_7bit_length_string()?;
 
-        let num_auras = reader.read_i32::<LittleEndian>()?;
+        let num_auras = reader.read_i32::<E>()?;
         let mut auras = Vec::with_capacity(num_auras as usize);
         for _ in 0..num_auras {
-            let aura = Aura::read(reader)?;
+            let aura = Aura::read::<E>(reader)?;
             auras.push(aura);
         }
 
@@ -216,4 +216,141 @@ impl Item {
         };
         Ok(item)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.name)?;
+        writer.write_7bit_length_string(&self.locale_name)?;
+        writer.write_7bit_length_string(&self.locale_description)?;
+
+        writer.write_i32::<E>(self.sounds.len() as i32)?;
+        for sound in &self.sounds {
+            writer.write_7bit_length_string(&sound.cue)?;
+            sound.bank.write::<E>(writer)?;
+        }
+
+        writer.write_bool(self.pickupable)?;
+        writer.write_bool(self.bound)?;
+        writer.write_i32::<E>(self.block_value)?;
+        writer.write_u8(self.weapon_class as u8)?;
+        writer.write_f32::<E>(self.cooldown_time)?;
+        writer.write_bool(self.hide_model)?;
+        writer.write_bool(self.hide_effect)?;
+        writer.write_bool(self.pause_sounds)?;
+
+        writer.write_i32::<E>(self.resistances.len() as i32)?;
+        for resistance in &self.resistances {
+            resistance.write::<E>(writer)?;
+        }
+
+        self.passive_ability.write::<E>(writer)?;
+
+        writer.write_i32::<E>(self.effects.len() as i32)?;
+        for effect in &self.effects {
+            writer.write_7bit_length_string(effect)?;
+        }
+
+        writer.write_i32::<E>(self.lights.len() as i32)?;
+        for light in &self.lights {
+            light.write::<E>(writer)?;
+        }
+
+        writer.write_bool(self.special_ability.is_some())?;
+        if let Some(ability) = &self.special_ability {
+            ability.write::<E>(writer)?;
+        }
+
+        writer.write_f32::<E>(self.melee_range)?;
+        writer.write_bool(self.melee_multi_hit)?;
+        writer.write_i32::<E>(self.melee_conditions.len() as i32)?;
+        for (i, condition) in self.melee_conditions.iter().enumerate() {
+            condition
+                .write::<E>(writer)
+                .with_context(|| format!("failed to write melee conditions {i}"))?;
+        }
+
+        writer.write_f32::<E>(self.ranged_range)?;
+        writer.write_bool(self.facing)?;
+        writer.write_f32::<E>(self.homing_strength)?;
+        writer.write_f32::<E>(self.ranged_elevation)?;
+        writer.write_f32::<E>(self.ranged_danger)?;
+
+        writer.write_f32::<E>(self.gun_range)?;
+        writer.write_i32::<E>(self.gun_clip)?;
+        writer.write_i32::<E>(self.gun_rate)?;
+        writer.write_f32::<E>(self.gun_accuracy)?;
+        writer.write_7bit_length_string(&self.gun_sound_cue)?;
+        writer.write_7bit_length_string(&self.gun_muzzle_effect)?;
+        writer.write_7bit_length_string(&self.gun_shell_effect)?;
+        writer.write_f32::<E>(self.gun_tracer_velocity)?;
+        writer.write_7bit_length_string(&self.gun_non_tracer)?;
+        writer.write_7bit_length_string(&self.gun_tracer)?;
+
+        writer.write_i32::<E>(self.gun_conditions.len() as i32)?;
+        for (i, condition) in self.gun_conditions.iter().enumerate() {
+            condition
+                .write::<E>(writer)
+                .with_context(|| format!("failed to write gun conditions {i}"))?;
+        }
+
+        writer.write_7bit_length_string(&self.projectile_model)?;
+
+        writer.write_i32::<E>(self.ranged_conditions.len() as i32)?;
+        for (i, condition) in self.ranged_conditions.iter().enumerate() {
+            condition
+                .write::<E>(writer)
+                .with_context(|| format!("failed to write ranged conditions {i}"))?;
+        }
+
+        writer.write_f32::<E>(self.scale)?;
+        writer.write_7bit_length_string(&self.model)?;
+
+        writer.write_i32::<E>(self.auras.len() as i32)?;
+        for (i, aura) in self.auras.iter().enumerate() {
+            aura.write::<E>(writer)
+                .with_context(|| format!("failed to write aura {i}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content::tests::assert_round_trip;
+
+    #[test]
+    fn item_round_trip() {
+        assert_round_trip(
+            r##"{"Item": {
+                "name": "staff", "locale_name": "#item_staff", "locale_description": "#item_staff_d",
+                "sounds": [{"cue": "swing", "bank": "Weapons"}],
+                "pickupable": true, "bound": false, "block_value": 3, "weapon_class": "Default",
+                "cooldown_time": 1.5, "hide_model": false, "hide_effect": true, "pause_sounds": false,
+                "resistances": [{"element": "Fire", "multiplier": 0.5, "modifier": -1.0,
+                    "status_immunity": true}],
+                "passive_ability": {"kind": "Glow", "value": 2.0},
+                "effects": ["fx_glow"],
+                "lights": [{"radius": 5.0, "diffuse_color": {"r": 2.0, "g": 0.5, "b": 0.25},
+                    "ambient_color": {"r": 0.0, "g": 0.0, "b": 0.0}, "specular_amount": 1.0,
+                    "variation": "Flicker", "variation_amount": 0.3, "variation_speed": 4.0}],
+                "special_ability": {"ability": {"kind": "Haste", "animation": "cast",
+                    "hash": "haste", "elements": ["Water | Cold"]}, "cooldown": 30.0},
+                "melee_range": 2.0, "melee_multi_hit": true,
+                "melee_conditions": [{"kind": "Hit", "hitpoints": 0.0, "element": "",
+                    "threshold": 0.0, "time": 0.0, "repeat": false,
+                    "events": [{"Sound": {"banks": "Weapons", "cue": "hit", "magnitude": 1.0,
+                        "stop_on_remove": false}}]}],
+                "ranged_range": 10.0, "facing": true, "homing_strength": 0.5,
+                "ranged_elevation": 0.25, "ranged_danger": 1.0,
+                "gun_range": 0.0, "gun_clip": 6, "gun_rate": 2, "gun_accuracy": 0.75,
+                "gun_sound_cue": "bang", "gun_muzzle_effect": "muzzle", "gun_shell_effect": "",
+                "gun_tracer_velocity": 100.0, "gun_non_tracer": "", "gun_tracer": "tracer",
+                "gun_conditions": [], "projectile_model": "", "ranged_conditions": [],
+                "scale": 0.5, "model": "Models\\Items\\staff",
+                "auras": [{"kind": {"Deflect": {"strength": 2.0}}, "target": "Friendly",
+                    "visual_category": "Defensive", "color": {"r": 0.0, "g": 1.0, "b": 0.0},
+                    "effect": "aura", "duration": 10.0, "radius": 4.0, "types": "",
+                    "factions": "Wizard"}]
+            }}"##,
+        );
+    }
 }
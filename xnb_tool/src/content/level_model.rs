@@ -0,0 +1,974 @@
+//! levels as read by the game's `LevelModelReader`
+//!
+//! the render geometry, lights, triggers and locators follow the layout of the game's reader,
+//! liquids, force fields, animated parts and nav meshes have not been checked against game
+//! files yet. only the render geometry is saved and written back as parsed content, everything
+//! after it is kept as raw bytes and parsed into [`LevelSections`] for exporting
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use anyhow::{Context, anyhow};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
+use glam::{Mat4, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
+use crate::xnb::{TypeReader, Version};
+
+use super::color::Color;
+use super::light::LightVariation;
+use super::model::{IndexBuffer, Model, VertexBuffer, VertexDeclaration};
+use super::skinned_model::{SkinnedModelAnimationKeyframe, SkinnedModelPose};
+use super::{
+    BI_TREE_MODEL_READER_NAME, Content, INDEX_BUFFER_READER_NAME, VERTEX_BUFFER_READER_NAME,
+    VERTEX_DECL_READER_NAME, read_list, write_list, write_type_id,
+};
+
+/// number of collision channels every level stores
+const COLLISION_CHANNELS: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LevelModel {
+    pub geometry: BiTreeModel,
+    /// everything after the render geometry as stored in the xnb, written back unchanged
+    pub data: Vec<u8>,
+    /// `data` parsed for exporting, it is not saved since parts of the layout are unverified
+    #[serde(skip)]
+    pub sections: Option<LevelSections>,
+}
+
+impl LevelModel {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
+        let geometry = Content::read::<E>(reader, type_readers, version)?;
+        let Content::BiTreeModel(geometry) = geometry else {
+            anyhow::bail!("expected bi tree model");
+        };
+
+        // the sections have to be parsed to find where the level ends, the shared content
+        // follows right after
+        let start = reader.stream_position()?;
+        let sections = match LevelSections::read::<E>(reader, type_readers, version) {
+            Ok(sections) => Some(sections),
+            Err(e) => {
+                eprintln!(
+                    "WARNING: failed to parse level sections, keeping the rest of the xnb as raw \
+                     bytes: {e:#}"
+                );
+                None
+            }
+        };
+        let end = reader.stream_position()?;
+        reader.seek(SeekFrom::Start(start))?;
+        let mut data = Vec::new();
+        if sections.is_some() {
+            data.resize((end - start) as usize, 0);
+            reader.read_exact(&mut data)?;
+        } else {
+            // without the sections the end of the level is unknown, this swallows the shared
+            // content as well
+            reader.read_to_end(&mut data)?;
+        }
+
+        Ok(LevelModel {
+            geometry,
+            data,
+            sections,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<()> {
+        write_type_id(writer, type_readers, BI_TREE_MODEL_READER_NAME)?;
+        self.geometry.write::<E>(writer, type_readers, version)?;
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+
+    /// names of the type readers needed by the nested content, `data` refers to the type readers
+    /// of the xnb it was read from
+    pub fn nested_reader_names(&self) -> Vec<&'static str> {
+        let mut names = vec![BI_TREE_MODEL_READER_NAME];
+        names.extend(self.geometry.nested_reader_names());
+        names
+    }
+}
+
+/// everything a level stores after its render geometry
+#[derive(Debug)]
+pub struct LevelSections {
+    pub animated_parts: Vec<AnimatedLevelPart>,
+    pub lights: Vec<LevelLight>,
+    pub effects: Vec<LevelEffect>,
+    pub physics_entities: Vec<PhysicsEntity>,
+    pub liquids: Vec<Liquid>,
+    pub force_fields: Vec<ForceField>,
+    /// one optional mesh per collision channel
+    pub collision_meshes: Vec<Option<CollisionMesh>>,
+    pub camera_mesh: Option<CollisionMesh>,
+    pub trigger_areas: Vec<TriggerArea>,
+    pub locators: Vec<Locator>,
+    pub nav_mesh: NavMesh,
+}
+
+impl LevelSections {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
+        let animated_parts = read_list::<E, _, _>(reader, "animated part", |reader| {
+            AnimatedLevelPart::read::<E>(reader, type_readers, version)
+        })?;
+        let lights = read_list::<E, _, _>(reader, "light", LevelLight::read::<E>)?;
+        let effects = read_list::<E, _, _>(reader, "effect", LevelEffect::read::<E>)?;
+        let physics_entities =
+            read_list::<E, _, _>(reader, "physics entity", PhysicsEntity::read::<E>)?;
+        let liquids = read_list::<E, _, _>(reader, "liquid", |reader| {
+            Liquid::read::<E>(reader, type_readers, version)
+        })?;
+        let force_fields = read_list::<E, _, _>(reader, "force field", |reader| {
+            ForceField::read::<E>(reader, type_readers, version)
+        })?;
+
+        let mut collision_meshes = Vec::with_capacity(COLLISION_CHANNELS);
+        for i in 0..COLLISION_CHANNELS {
+            let mesh = read_optional(reader, CollisionMesh::read::<E>)
+                .with_context(|| format!("failed to read collision channel {i}"))?;
+            collision_meshes.push(mesh);
+        }
+        let camera_mesh = read_optional(reader, CollisionMesh::read::<E>)
+            .context("failed to read camera collision mesh")?;
+
+        let trigger_areas = read_list::<E, _, _>(reader, "trigger area", TriggerArea::read::<E>)?;
+        let locators = read_list::<E, _, _>(reader, "locator", Locator::read::<E>)?;
+        let nav_mesh = NavMesh::read::<E>(reader).context("failed to read nav mesh")?;
+
+        Ok(LevelSections {
+            animated_parts,
+            lights,
+            effects,
+            physics_entities,
+            liquids,
+            force_fields,
+            collision_meshes,
+            camera_mesh,
+            trigger_areas,
+            locators,
+            nav_mesh,
+        })
+    }
+}
+
+/// render geometry of a level, every tree is drawn with a single effect
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BiTreeModel {
+    pub trees: Vec<BiTree>,
+}
+
+impl BiTreeModel {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
+        let trees = read_list::<E, _, _>(reader, "bi tree", |reader| {
+            BiTree::read::<E>(reader, type_readers, version)
+        })?;
+        Ok(BiTreeModel { trees })
+    }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<()> {
+        write_list::<E, _, _>(writer, &self.trees, "bi tree", |writer, tree| {
+            tree.write::<E>(writer, type_readers, version)
+        })
+    }
+
+    /// names of the type readers needed by the nested content
+    pub fn nested_reader_names(&self) -> Vec<&'static str> {
+        self.trees
+            .iter()
+            .flat_map(|tree| tree.geometry.nested_reader_names())
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BiTree {
+    pub visible: bool,
+    pub cast_shadows: bool,
+    pub sway: f32,
+    pub entity_influence: f32,
+    pub ground_level: f32,
+    pub geometry: LevelGeometry,
+    pub root: BiTreeNode,
+}
+
+impl BiTree {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
+        let visible = reader.read_bool()?;
+        let cast_shadows = reader.read_bool()?;
+        let sway = reader.read_f32::<E>()?;
+        let entity_influence = reader.read_f32::<E>()?;
+        let ground_level = reader.read_f32::<E>()?;
+        let geometry = LevelGeometry::read::<E>(reader, type_readers, version)?;
+        let root = BiTreeNode::read::<E>(reader)?;
+        Ok(BiTree {
+            visible,
+            cast_shadows,
+            sway,
+            entity_influence,
+            ground_level,
+            geometry,
+            root,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<()> {
+        writer.write_bool(self.visible)?;
+        writer.write_bool(self.cast_shadows)?;
+        writer.write_f32::<E>(self.sway)?;
+        writer.write_f32::<E>(self.entity_influence)?;
+        writer.write_f32::<E>(self.ground_level)?;
+        self.geometry.write::<E>(writer, type_readers, version)?;
+        self.root.write::<E>(writer)?;
+        Ok(())
+    }
+}
+
+/// range of triangles used for culling, the children split the range of their parent
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BiTreeNode {
+    pub primitive_count: i32,
+    pub start_index: i32,
+    pub bounds: BoundingBox,
+    pub left: Option<Box<BiTreeNode>>,
+    pub right: Option<Box<BiTreeNode>>,
+}
+
+impl BiTreeNode {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let primitive_count = reader.read_i32::<E>()?;
+        let start_index = reader.read_i32::<E>()?;
+        let bounds = BoundingBox::read::<E>(reader)?;
+        let left = read_optional(reader, |reader| Ok(Box::new(Self::read::<E>(reader)?)))?;
+        let right = read_optional(reader, |reader| Ok(Box::new(Self::read::<E>(reader)?)))?;
+        Ok(BiTreeNode {
+            primitive_count,
+            start_index,
+            bounds,
+            left,
+            right,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.primitive_count)?;
+        writer.write_i32::<E>(self.start_index)?;
+        self.bounds.write::<E>(writer)?;
+        write_optional(writer, self.left.as_deref(), |writer, node| {
+            node.write::<E>(writer)
+        })?;
+        write_optional(writer, self.right.as_deref(), |writer, node| {
+            node.write::<E>(writer)
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct BoundingBox {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl BoundingBox {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let min = reader.read_vec3::<E>()?;
+        let max = reader.read_vec3::<E>()?;
+        Ok(BoundingBox { min, max })
+    }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_vec3::<E>(self.min)?;
+        writer.write_vec3::<E>(self.max)?;
+        Ok(())
+    }
+}
+
+/// vertices and indices drawn with a single effect, shared by trees, liquids and force fields
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LevelGeometry {
+    pub vertex_count: i32,
+    pub vertex_stride: i32,
+    pub vertex_decl: VertexDeclaration,
+    pub vertex_buffer: VertexBuffer,
+    pub index_buffer: IndexBuffer,
+    pub effect: Content,
+}
+
+impl LevelGeometry {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
+        let vertex_count = reader.read_i32::<E>()?;
+        let vertex_stride = reader.read_i32::<E>()?;
+
+        let vertex_decl = Content::read::<E>(reader, type_readers, version)?;
+        let Content::VertexDeclaration(vertex_decl) = vertex_decl else {
+            anyhow::bail!("expected vertex declaration");
+        };
+        let vertex_buffer = Content::read::<E>(reader, type_readers, version)?;
+        let Content::VertexBuffer(vertex_buffer) = vertex_buffer else {
+            anyhow::bail!("expected vertex buffer");
+        };
+        let index_buffer = Content::read::<E>(reader, type_readers, version)?;
+        let Content::IndexBuffer(index_buffer) = index_buffer else {
+            anyhow::bail!("expected index buffer");
+        };
+        let effect = Content::read::<E>(reader, type_readers, version)?;
+
+        Ok(LevelGeometry {
+            vertex_count,
+            vertex_stride,
+            vertex_decl,
+            vertex_buffer,
+            index_buffer,
+            effect,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.vertex_count)?;
+        writer.write_i32::<E>(self.vertex_stride)?;
+        write_type_id(writer, type_readers, VERTEX_DECL_READER_NAME)?;
+        self.vertex_decl.write::<E>(writer, version)?;
+        write_type_id(writer, type_readers, VERTEX_BUFFER_READER_NAME)?;
+        self.vertex_buffer.write::<E>(writer, version)?;
+        write_type_id(writer, type_readers, INDEX_BUFFER_READER_NAME)?;
+        self.index_buffer.write::<E>(writer)?;
+        self.effect
+            .write::<E>(writer, type_readers, version)
+            .context("failed to write effect")?;
+        Ok(())
+    }
+
+    /// number of triangles in the index buffer
+    pub fn primitive_count(&self) -> usize {
+        let index_size = if self.index_buffer.is_16_bit { 2 } else { 4 };
+        self.index_buffer.data.len() / index_size / 3
+    }
+
+    fn nested_reader_names(&self) -> Vec<&'static str> {
+        let mut names = vec![
+            VERTEX_DECL_READER_NAME,
+            VERTEX_BUFFER_READER_NAME,
+            INDEX_BUFFER_READER_NAME,
+        ];
+        names.extend(self.effect.reader_names());
+        names
+    }
+}
+
+/// part of the level that moves along a fixed animation, such as doors and bridges
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnimatedLevelPart {
+    pub name: String,
+    pub affect_shields: bool,
+    pub model: Model,
+    pub mesh_settings: Vec<MeshSettings>,
+    pub liquids: Vec<Liquid>,
+    pub locators: Vec<Locator>,
+    pub animation_duration: f32,
+    pub animation: Vec<SkinnedModelAnimationKeyframe>,
+    pub effects: Vec<LevelEffect>,
+    /// level lights that move with the part
+    pub lights: Vec<AttachedLight>,
+    pub collision_mesh: Option<CollisionMesh>,
+    pub nav_mesh: Option<NavMesh>,
+    pub children: Vec<AnimatedLevelPart>,
+}
+
+impl AnimatedLevelPart {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
+        let name = reader.read_7bit_length_string()?;
+        let affect_shields = reader.read_bool()?;
+        let model = Content::read::<E>(reader, type_readers, version)?;
+        let Content::Model(model) = model else {
+            anyhow::bail!("expected model for animated part {name}");
+        };
+        let mesh_settings = read_list::<E, _, _>(reader, "mesh setting", MeshSettings::read::<E>)?;
+        let liquids = read_list::<E, _, _>(reader, "liquid", |reader| {
+            Liquid::read::<E>(reader, type_readers, version)
+        })?;
+        let locators = read_list::<E, _, _>(reader, "locator", Locator::read::<E>)?;
+
+        let animation_duration = reader.read_f32::<E>()?;
+        let animation = read_list::<E, _, _>(reader, "keyframe", |reader| {
+            let time = reader.read_f32::<E>()?;
+            let translation = reader.read_vec3::<E>()?;
+            let orientation = reader.read_quat::<E>()?;
+            let scale = reader.read_vec3::<E>()?;
+            Ok(SkinnedModelAnimationKeyframe {
+                time,
+                pose: SkinnedModelPose {
+                    translation,
+                    orientation,
+                    scale,
+                },
+            })
+        })?;
+
+        let effects = read_list::<E, _, _>(reader, "effect", LevelEffect::read::<E>)?;
+        let lights = read_list::<E, _, _>(reader, "light", AttachedLight::read::<E>)?;
+        let collision_mesh = read_optional(reader, CollisionMesh::read::<E>)
+            .context("failed to read collision mesh")?;
+        let nav_mesh =
+            read_optional(reader, NavMesh::read::<E>).context("failed to read nav mesh")?;
+        let children = read_list::<E, _, _>(reader, "child part", |reader| {
+            Self::read::<E>(reader, type_readers, version)
+        })?;
+
+        Ok(AnimatedLevelPart {
+            name,
+            affect_shields,
+            model,
+            mesh_settings,
+            liquids,
+            locators,
+            animation_duration,
+            animation,
+            effects,
+            lights,
+            collision_mesh,
+            nav_mesh,
+            children,
+        })
+    }
+
+    /// this part followed by all of its children, depth first
+    pub fn descendants(&self) -> Vec<&AnimatedLevelPart> {
+        let mut parts = vec![self];
+        for child in &self.children {
+            parts.extend(child.descendants());
+        }
+        parts
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MeshSettings {
+    pub mesh: String,
+    pub visible: bool,
+    pub cast_shadows: bool,
+}
+
+impl MeshSettings {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let mesh = reader.read_7bit_length_string()?;
+        let visible = reader.read_bool()?;
+        let cast_shadows = reader.read_bool()?;
+        Ok(MeshSettings {
+            mesh,
+            visible,
+            cast_shadows,
+        })
+    }
+}
+
+#[repr(u8)]
+#[derive(strum::FromRepr, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelLightKind {
+    Point = 0,
+    Directional,
+    Spot,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LevelLight {
+    pub name: String,
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub kind: LevelLightKind,
+    pub variation: LightVariation,
+    pub reach: f32,
+    pub use_attenuation: bool,
+    pub cutoff_angle: f32,
+    pub sharpness: f32,
+    pub diffuse_color: Color,
+    pub ambient_color: Color,
+    pub specular_amount: f32,
+    pub variation_speed: f32,
+    pub variation_amount: f32,
+    pub shadow_map_size: i32,
+    pub cast_shadows: bool,
+}
+
+impl LevelLight {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let name = reader.read_7bit_length_string()?;
+        let position = reader.read_vec3::<E>()?;
+        let direction = reader.read_vec3::<E>()?;
+        let kind = reader.read_i32::<E>()?;
+        let kind = LevelLightKind::from_repr(kind as u8)
+            .ok_or_else(|| anyhow!("unknown light kind: {kind}"))?;
+        let variation = reader.read_i32::<E>()?;
+        let variation = LightVariation::from_repr(variation as u8)
+            .ok_or_else(|| anyhow!("unknown light variation kind: {variation}"))?;
+        let reach = reader.read_f32::<E>()?;
+        let use_attenuation = reader.read_bool()?;
+        let cutoff_angle = reader.read_f32::<E>()?;
+        let sharpness = reader.read_f32::<E>()?;
+        let diffuse_color = Color::read::<E>(reader)?;
+        let ambient_color = Color::read::<E>(reader)?;
+        let specular_amount = reader.read_f32::<E>()?;
+        let variation_speed = reader.read_f32::<E>()?;
+        let variation_amount = reader.read_f32::<E>()?;
+        let shadow_map_size = reader.read_i32::<E>()?;
+        let cast_shadows = reader.read_bool()?;
+        Ok(LevelLight {
+            name,
+            position,
+            direction,
+            kind,
+            variation,
+            reach,
+            use_attenuation,
+            cutoff_angle,
+            sharpness,
+            diffuse_color,
+            ambient_color,
+            specular_amount,
+            variation_speed,
+            variation_amount,
+            shadow_map_size,
+            cast_shadows,
+        })
+    }
+}
+
+/// level light referenced by name, placed relative to the animated part it belongs to
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AttachedLight {
+    pub name: String,
+    pub transform: Mat4,
+}
+
+impl AttachedLight {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let name = reader.read_7bit_length_string()?;
+        let transform = reader.read_mat4::<E>()?;
+        Ok(AttachedLight { name, transform })
+    }
+}
+
+/// visual effect placed in the level
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LevelEffect {
+    pub id: String,
+    pub position: Vec3,
+    pub forward: Vec3,
+    pub range: f32,
+    pub effect: String,
+}
+
+impl LevelEffect {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let id = reader.read_7bit_length_string()?;
+        let position = reader.read_vec3::<E>()?;
+        let forward = reader.read_vec3::<E>()?;
+        let range = reader.read_f32::<E>()?;
+        let effect = reader.read_7bit_length_string()?;
+        Ok(LevelEffect {
+            id,
+            position,
+            forward,
+            range,
+            effect,
+        })
+    }
+}
+
+/// physics object such as a crate or barrel, spawned from a template
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PhysicsEntity {
+    pub transform: Mat4,
+    pub template: String,
+}
+
+impl PhysicsEntity {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let transform = reader.read_mat4::<E>()?;
+        let template = reader.read_7bit_length_string()?;
+        Ok(PhysicsEntity {
+            transform,
+            template,
+        })
+    }
+}
+
+/// water or lava surface
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Liquid {
+    pub geometry: LevelGeometry,
+    pub freezable: bool,
+    pub auto_freeze: bool,
+}
+
+impl Liquid {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
+        let geometry = LevelGeometry::read::<E>(reader, type_readers, version)?;
+        let freezable = reader.read_bool()?;
+        let auto_freeze = reader.read_bool()?;
+        Ok(Liquid {
+            geometry,
+            freezable,
+            auto_freeze,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ForceField {
+    pub color: Color,
+    pub width: f32,
+    pub alpha_power: f32,
+    pub alpha_falloff_power: f32,
+    pub max_radius: f32,
+    pub ripple_distortion: f32,
+    pub map_distortion: f32,
+    pub vertex_color_enabled: bool,
+    pub displacement_map: String,
+    pub ttl: f32,
+    pub geometry: LevelGeometry,
+}
+
+impl ForceField {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
+        let color = Color::read::<E>(reader)?;
+        let width = reader.read_f32::<E>()?;
+        let alpha_power = reader.read_f32::<E>()?;
+        let alpha_falloff_power = reader.read_f32::<E>()?;
+        let max_radius = reader.read_f32::<E>()?;
+        let ripple_distortion = reader.read_f32::<E>()?;
+        let map_distortion = reader.read_f32::<E>()?;
+        let vertex_color_enabled = reader.read_bool()?;
+        let displacement_map = reader.read_7bit_length_string()?;
+        let ttl = reader.read_f32::<E>()?;
+        let geometry = LevelGeometry::read::<E>(reader, type_readers, version)?;
+        Ok(ForceField {
+            color,
+            width,
+            alpha_power,
+            alpha_falloff_power,
+            max_radius,
+            ripple_distortion,
+            map_distortion,
+            vertex_color_enabled,
+            displacement_map,
+            ttl,
+            geometry,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CollisionMesh {
+    pub vertices: Vec<Vec3>,
+    pub triangles: Vec<[i32; 3]>,
+}
+
+impl CollisionMesh {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let vertices =
+            read_list::<E, _, _>(reader, "vertex", |reader| Ok(reader.read_vec3::<E>()?))?;
+        let triangles = read_list::<E, _, _>(reader, "triangle", |reader| {
+            let a = reader.read_i32::<E>()?;
+            let b = reader.read_i32::<E>()?;
+            let c = reader.read_i32::<E>()?;
+            Ok([a, b, c])
+        })?;
+        Ok(CollisionMesh {
+            vertices,
+            triangles,
+        })
+    }
+}
+
+/// box shaped area that runs level scripts when entered
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TriggerArea {
+    pub name: String,
+    pub position: Vec3,
+    pub side_lengths: Vec3,
+    pub orientation: Quat,
+}
+
+impl TriggerArea {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let name = reader.read_7bit_length_string()?;
+        let position = reader.read_vec3::<E>()?;
+        let side_lengths = reader.read_vec3::<E>()?;
+        let orientation = reader.read_quat::<E>()?;
+        Ok(TriggerArea {
+            name,
+            position,
+            side_lengths,
+            orientation,
+        })
+    }
+}
+
+/// named point used by scripts, for example to spawn characters
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Locator {
+    pub name: String,
+    pub transform: Mat4,
+    pub radius: f32,
+}
+
+impl Locator {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let name = reader.read_7bit_length_string()?;
+        let transform = reader.read_mat4::<E>()?;
+        let radius = reader.read_f32::<E>()?;
+        Ok(Locator {
+            name,
+            transform,
+            radius,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NavMesh {
+    pub vertices: Vec<Vec3>,
+    pub triangles: Vec<NavTriangle>,
+}
+
+impl NavMesh {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let num_vertices = reader.read_u16::<E>()?;
+        let mut vertices = Vec::with_capacity(num_vertices as usize);
+        for _ in 0..num_vertices {
+            vertices.push(reader.read_vec3::<E>()?);
+        }
+        let num_triangles = reader.read_u16::<E>()?;
+        let mut triangles = Vec::with_capacity(num_triangles as usize);
+        for _ in 0..num_triangles {
+            triangles.push(NavTriangle::read::<E>(reader)?);
+        }
+        Ok(NavMesh {
+            vertices,
+            triangles,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NavTriangle {
+    pub vertices: [u16; 3],
+    /// neighbouring triangles across each edge, `u16::MAX` if there is none
+    pub neighbours: [u16; 3],
+    pub costs: [f32; 3],
+    pub properties: u8,
+}
+
+impl NavTriangle {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let mut vertices = [0; 3];
+        for vertex in &mut vertices {
+            *vertex = reader.read_u16::<E>()?;
+        }
+        let mut neighbours = [0; 3];
+        for neighbour in &mut neighbours {
+            *neighbour = reader.read_u16::<E>()?;
+        }
+        let mut costs = [0.0; 3];
+        for cost in &mut costs {
+            *cost = reader.read_f32::<E>()?;
+        }
+        let properties = reader.read_u8()?;
+        Ok(NavTriangle {
+            vertices,
+            neighbours,
+            costs,
+            properties,
+        })
+    }
+}
+
+/// reads a bool followed by the item if it was set
+fn read_optional<R: Read, T>(
+    reader: &mut R,
+    read: impl FnOnce(&mut R) -> anyhow::Result<T>,
+) -> anyhow::Result<Option<T>> {
+    if reader.read_bool()? {
+        Ok(Some(read(reader)?))
+    } else {
+        Ok(None)
+    }
+}
+
+fn write_optional<W: Write, T>(
+    writer: &mut W,
+    item: Option<&T>,
+    write: impl FnOnce(&mut W, &T) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    writer.write_bool(item.is_some())?;
+    if let Some(item) = item {
+        write(writer, item)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use byteorder::LittleEndian;
+
+    use super::*;
+    use crate::export::model::material::TextureLoader;
+    use crate::xnb::{Platform, XnbContent};
+
+    /// payload of a level with no geometry, one trigger area and a string as shared content.
+    /// `sections` is written after the geometry in place of the real sections
+    fn level_payload(sections: &[u8]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.write_7bit_encoded_i32(3).unwrap();
+        for name in [
+            "Magicka.ContentReaders.LevelModelReader",
+            BI_TREE_MODEL_READER_NAME,
+            "Microsoft.Xna.Framework.Content.StringReader",
+        ] {
+            payload.write_7bit_length_string(name).unwrap();
+            payload.write_i32::<LittleEndian>(0).unwrap();
+        }
+        // one shared content
+        payload.write_7bit_encoded_i32(1).unwrap();
+
+        payload.write_7bit_encoded_i32(1).unwrap();
+        payload.write_7bit_encoded_i32(2).unwrap();
+        payload.write_i32::<LittleEndian>(0).unwrap();
+        payload.extend_from_slice(sections);
+
+        payload.write_7bit_encoded_i32(3).unwrap();
+        payload.write_7bit_length_string("shared").unwrap();
+        payload
+    }
+
+    fn sections() -> Vec<u8> {
+        let mut sections = Vec::new();
+        // animated parts, lights, effects, physics entities, liquids and force fields
+        for _ in 0..6 {
+            sections.write_i32::<LittleEndian>(0).unwrap();
+        }
+        // collision channels and the camera mesh
+        sections.extend_from_slice(&[0; COLLISION_CHANNELS + 1]);
+        sections.write_i32::<LittleEndian>(1).unwrap();
+        sections.write_7bit_length_string("trigger").unwrap();
+        sections
+            .write_vec3::<LittleEndian>(Vec3::new(1.0, 2.0, 3.0))
+            .unwrap();
+        sections.write_vec3::<LittleEndian>(Vec3::ONE).unwrap();
+        sections.write_quat::<LittleEndian>(Quat::IDENTITY).unwrap();
+        // locators and the nav mesh
+        sections.write_i32::<LittleEndian>(0).unwrap();
+        sections.write_u16::<LittleEndian>(0).unwrap();
+        sections.write_u16::<LittleEndian>(0).unwrap();
+        sections
+    }
+
+    fn parse(payload: &[u8]) -> XnbContent {
+        XnbContent::parse(&mut Cursor::new(payload), Platform::Windows, Version::XNA31).unwrap()
+    }
+
+    fn write(content: &XnbContent) -> Vec<u8> {
+        let mut written = Vec::new();
+        content.write(&mut written).unwrap();
+        written
+    }
+
+    #[test]
+    fn level_round_trip() {
+        let payload = level_payload(&sections());
+        let content = parse(&payload);
+        let Content::LevelModel(level) = &content.primary_content else {
+            panic!("expected a level");
+        };
+        let sections = level.sections.as_ref().unwrap();
+        assert_eq!(sections.trigger_areas.len(), 1);
+        assert_eq!(sections.trigger_areas[0].name, "trigger");
+        assert_eq!(sections.trigger_areas[0].position, Vec3::new(1.0, 2.0, 3.0));
+        assert!(matches!(&content.shared_content[..], [Content::String(s)] if s == "shared"));
+        assert_eq!(content.unparsed_shared_content, 0);
+        assert_eq!(write(&content), payload);
+
+        let glb = level
+            .to_glb(&content.shared_content, &TextureLoader::default())
+            .unwrap();
+        let glb = gltf::Gltf::from_slice(&glb).unwrap();
+        let trigger = glb.nodes().find(|node| node.name() == Some("trigger"));
+        let (translation, _, _) = trigger.unwrap().transform().decomposed();
+        assert_eq!(translation, [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn unparsable_sections_are_kept_as_raw_bytes() {
+        // a negative light count stops the sections from parsing
+        let mut sections = sections();
+        sections[4..8].copy_from_slice(&(-1i32).to_le_bytes());
+        let payload = level_payload(&sections);
+
+        let content = parse(&payload);
+        let Content::LevelModel(level) = &content.primary_content else {
+            panic!("expected a level");
+        };
+        assert!(level.sections.is_none());
+        assert!(content.shared_content.is_empty());
+        assert_eq!(content.unparsed_shared_content, 1);
+        assert_eq!(write(&content), payload);
+        assert!(level.to_glb(&[], &TextureLoader::default()).is_err());
+    }
+}
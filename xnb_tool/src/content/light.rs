@@ -1,11 +1,11 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
 use anyhow::anyhow;
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
 use super::color::Color;
-use crate::ext::MyReadBytesExt;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
 
 #[repr(u8)]
 #[derive(strum::FromRepr, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,26 +19,26 @@ pub enum LightVariation {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Light {
-    radius: f32,
-    diffuse_color: Color,
-    ambient_color: Color,
-    specular_amount: f32,
-    variation: LightVariation,
-    variation_amount: f32,
-    variation_speed: f32,
+    pub radius: f32,
+    pub diffuse_color: Color,
+    pub ambient_color: Color,
+    pub specular_amount: f32,
+    pub variation: LightVariation,
+    pub variation_amount: f32,
+    pub variation_speed: f32,
 }
 
 impl Light {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let radius = reader.read_f32::<LittleEndian>()?;
-        let diffuse_color = Color::read(reader)?;
-        let ambient_color = Color::read(reader)?;
-        let specular_amount = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let radius = reader.read_f32::<E>()?;
+        let diffuse_color = Color::read::<E>(reader)?;
+        let ambient_color = Color::read::<E>(reader)?;
+        let specular_amount = reader.read_f32::<E>()?;
         let variation_kind = reader.read_u8()?;
         let variation_kind = LightVariation::from_repr(variation_kind)
             .ok_or_else(|| anyhow!("unknown light variation kind: {variation_kind}"))?;
-        let variation_amount = reader.read_f32::<LittleEndian>()?;
-        let variation_speed = reader.read_f32::<LittleEndian>()?;
+        let variation_amount = reader.read_f32::<E>()?;
+        let variation_speed = reader.read_f32::<E>()?;
 
         let light = Light {
             radius,
@@ -51,18 +51,35 @@ impl Light {
         };
         Ok(light)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.radius)?;
+        self.diffuse_color.write::<E>(writer)?;
+        self.ambient_color.write::<E>(writer)?;
+        writer.write_f32::<E>(self.specular_amount)?;
+        writer.write_u8(self.variation as u8)?;
+        writer.write_f32::<E>(self.variation_amount)?;
+        writer.write_f32::<E>(self.variation_speed)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BonedLight {
-    bone: String,
-    light: Light,
+    pub bone: String,
+    pub light: Light,
 }
 
 impl BonedLight {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let bone = reader.read_7bit_length_string()?;
-        let light = Light::read(reader)?;
+        let light = Light::read::<E>(reader)?;
         Ok(BonedLight { bone, light })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.bone)?;
+        self.light.write::<E>(writer)?;
+        Ok(())
+    }
 }
@@ -1,18 +1,26 @@
-use std::io::Read;
+use std::io::{Read, Seek, Write};
+
+use anyhow::{Context, anyhow};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 
 use character::Character;
 use effect::{
     AdditiveEffect, BasicEffect, Effect, RenderDeferredEffect, SkinnedModelBasicEffect,
     SkinnedModelDeferredNormalMappedEffect,
 };
+use error::ContentError;
 use item::Item;
-use model::{IndexBuffer, Model, VertexBuffer, VertexDeclaration};
+use level_model::{BiTreeModel, LevelModel};
+use model::{IndexBuffer, Model, VertexBuffer, VertexDeclaration, Xna40Model};
 use serde::{Deserialize, Serialize};
 use skinned_model::{SkinnedModel, SkinnedModelAnimationClip, SkinnedModelBone};
+use song::Song;
+use sound_effect::SoundEffect;
+use sprite_font::SpriteFont;
 use texture::Texture2D;
 
-use crate::ext::MyReadBytesExt;
-use crate::xnb::TypeReader;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
+use crate::xnb::{TypeReader, Version};
 
 pub mod ability;
 pub mod ai;
@@ -24,23 +32,29 @@ pub mod color;
 pub mod damage;
 pub mod effect;
 pub mod element;
+pub mod error;
 pub mod event;
 pub mod faction;
 pub mod gib;
 pub mod item;
+pub mod level_model;
 pub mod light;
 pub mod model;
 pub mod movement;
 pub mod passive_ability;
 pub mod resistance;
 pub mod skinned_model;
+pub mod song;
 pub mod sound;
+pub mod sound_effect;
 pub mod special_ability;
+pub mod sprite_font;
 pub mod texture;
 pub mod weapon_class;
 
 const ITEM_READER_NAME: &str = "Magicka.ContentReaders.ItemReader";
 const CHARACTER_READER_NAME: &str = "Magicka.ContentReaders.CharacterTemplateReader";
+const LEVEL_MODEL_READER_NAME: &str = "Magicka.ContentReaders.LevelModelReader";
 
 const STRING_READER_NAME: &str = "Microsoft.Xna.Framework.Content.StringReader";
 const EXTERNAL_REFERENCE_READER_NAME: &str =
@@ -52,6 +66,20 @@ const VERTEX_BUFFER_READER_NAME: &str = "Microsoft.Xna.Framework.Content.VertexB
 const INDEX_BUFFER_READER_NAME: &str = "Microsoft.Xna.Framework.Content.IndexBufferReader";
 const EFFECT_READER_NAME: &str = "Microsoft.Xna.Framework.Content.EffectReader";
 const BASIC_EFFECT_READER_NAME: &str = "Microsoft.Xna.Framework.Content.BasicEffectReader";
+const SPRITE_FONT_READER_NAME: &str = "Microsoft.Xna.Framework.Content.SpriteFontReader";
+const RECTANGLE_READER_NAME: &str = "Microsoft.Xna.Framework.Content.RectangleReader";
+const CHAR_READER_NAME: &str = "Microsoft.Xna.Framework.Content.CharReader";
+const VECTOR3_READER_NAME: &str = "Microsoft.Xna.Framework.Content.Vector3Reader";
+const INT32_READER_NAME: &str = "Microsoft.Xna.Framework.Content.Int32Reader";
+const SOUND_EFFECT_READER_NAME: &str = "Microsoft.Xna.Framework.Content.SoundEffectReader";
+const SONG_READER_NAME: &str = "Microsoft.Xna.Framework.Content.SongReader";
+// generic readers are only compared up to the first comma, which cuts them off inside the
+// assembly qualified name of their element type
+const RECTANGLE_LIST_READER_NAME: &str =
+    "Microsoft.Xna.Framework.Content.ListReader`1[[Microsoft.Xna.Framework.Rectangle";
+const CHAR_LIST_READER_NAME: &str = "Microsoft.Xna.Framework.Content.ListReader`1[[System.Char";
+const VECTOR3_LIST_READER_NAME: &str =
+    "Microsoft.Xna.Framework.Content.ListReader`1[[Microsoft.Xna.Framework.Vector3";
 
 const SKINNED_MODEL_READER_NAME: &str = "XNAnimation.Pipeline.SkinnedModelReader";
 const SKINNED_MODEL_BONE_READER_NAME: &str = "XNAnimation.Pipeline.SkinnedModelBoneReader";
@@ -63,16 +91,50 @@ const ADDITIVE_EFFECT_READER_NAME: &str = "PolygonHead.Pipeline.AdditiveEffectRe
 const RENDER_DEFERRED_EFFECT_READER_NAME: &str = "PolygonHead.Pipeline.RenderDeferredEffectReader";
 const SKINNED_MODEL_DEFERRED_NORMAL_MAPPED_EFFECT_READER_NAME: &str =
     "PolygonHead.Pipeline.SkinnedModelDeferredNormalMappedEffectReader";
+const BI_TREE_MODEL_READER_NAME: &str = "PolygonHead.Pipeline.BiTreeModelReader";
+
+/// every kind name extract can write, see [`Content::kind_name`]
+pub const KIND_NAMES: &[&str] = &[
+    "string",
+    "externalreference",
+    "item",
+    "character",
+    "levelmodel",
+    "bitreemodel",
+    "texture2d",
+    "spritefont",
+    "soundeffect",
+    "song",
+    "model",
+    "skinnedmodel",
+    "skinnedmodelbone",
+    "skinnedmodelanimationclip",
+    "vertexdecl",
+    "vertexbuffer",
+    "indexbuffer",
+    "effect",
+    "basiceffect",
+    "additiveeffect",
+    "renderdeferredeffect",
+    "skinnedmodelbasiceffect",
+    "skinnedmodeldeferrednormalmappedeffect",
+];
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Content {
     Null,
     Item(Item),
     Character(Character),
+    LevelModel(LevelModel),
+    BiTreeModel(BiTreeModel),
     String(String),
     ExternalReference(String),
     Texture2D(Texture2D),
+    SpriteFont(SpriteFont),
+    SoundEffect(SoundEffect),
+    Song(Song),
     Model(Model),
+    Xna40Model(Xna40Model),
     SkinnedModel(SkinnedModel),
     SkinnedModelBone(SkinnedModelBone),
     SkinnedModelAnimationClip(SkinnedModelAnimationClip),
@@ -88,90 +150,394 @@ pub enum Content {
 }
 
 impl Content {
-    pub fn read(reader: &mut impl Read, type_readers: &[TypeReader]) -> anyhow::Result<Self> {
-        let type_id = reader.read_7bit_encoded_i32()? as usize;
+    /// the suffix extract puts before the serialized extension, `None` for null content
+    pub fn kind_name(&self) -> Option<&'static str> {
+        match self {
+            Content::Null => None,
+            Content::String(..) => Some("string"),
+            Content::ExternalReference(..) => Some("externalreference"),
+            Content::Item(..) => Some("item"),
+            Content::Character(..) => Some("character"),
+            Content::LevelModel(..) => Some("levelmodel"),
+            Content::BiTreeModel(..) => Some("bitreemodel"),
+            Content::Texture2D(..) => Some("texture2d"),
+            Content::SpriteFont(..) => Some("spritefont"),
+            Content::SoundEffect(..) => Some("soundeffect"),
+            Content::Song(..) => Some("song"),
+            Content::Model(..) | Content::Xna40Model(..) => Some("model"),
+            Content::SkinnedModel(..) => Some("skinnedmodel"),
+            Content::SkinnedModelBone(..) => Some("skinnedmodelbone"),
+            Content::SkinnedModelAnimationClip(..) => Some("skinnedmodelanimationclip"),
+            Content::VertexDeclaration(..) => Some("vertexdecl"),
+            Content::VertexBuffer(..) => Some("vertexbuffer"),
+            Content::IndexBuffer(..) => Some("indexbuffer"),
+            Content::Effect(..) => Some("effect"),
+            Content::BasicEffect(..) => Some("basiceffect"),
+            Content::AdditiveEffect(..) => Some("additiveeffect"),
+            Content::RenderDeferredEffect(..) => Some("renderdeferredeffect"),
+            Content::SkinnedModelBasicEffect(..) => Some("skinnedmodelbasiceffect"),
+            Content::SkinnedModelDeferredNormalMappedEffect(..) => {
+                Some("skinnedmodeldeferrednormalmappedeffect")
+            }
+        }
+    }
+
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> Result<Self, ContentError> {
+        let offset = reader.stream_position()?;
+        let type_id = reader
+            .read_7bit_encoded_i32()
+            .map_err(|e| ContentError::from_reader("content type id", offset, e.into()))?
+            as usize;
         if type_id == 0 {
             return Ok(Content::Null);
         }
-        let type_reader = &type_readers[type_id - 1];
+        let type_reader =
+            type_readers
+                .get(type_id - 1)
+                .ok_or_else(|| ContentError::InvalidTypeId {
+                    id: type_id,
+                    count: type_readers.len(),
+                    offset,
+                })?;
 
         let name = type_reader.name.split(",").next().unwrap();
+        // errors point at the start of the content rather than wherever the reader gave up
+        let content_offset = reader.stream_position()?;
+        Self::read_as::<E>(reader, name, type_readers, version)
+            .map_err(|e| ContentError::from_reader(name, content_offset, e))
+    }
+
+    fn read_as<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        name: &str,
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
         match name {
             STRING_READER_NAME => {
                 let string = reader.read_7bit_length_string()?;
-                return Ok(Content::String(string));
+                Ok(Content::String(string))
             }
             EXTERNAL_REFERENCE_READER_NAME => {
                 let path = reader.read_7bit_length_string()?;
-                return Ok(Content::ExternalReference(path));
+                Ok(Content::ExternalReference(path))
             }
             ITEM_READER_NAME => {
-                let item = Item::read(reader)?;
-                return Ok(Content::Item(item));
+                let item = Item::read::<E>(reader)?;
+                Ok(Content::Item(item))
             }
             CHARACTER_READER_NAME => {
-                let character = Character::read(reader)?;
-                return Ok(Content::Character(character));
+                let character = Character::read::<E>(reader)?;
+                Ok(Content::Character(character))
+            }
+            LEVEL_MODEL_READER_NAME => {
+                let level = LevelModel::read::<E>(reader, type_readers, version)?;
+                Ok(Content::LevelModel(level))
+            }
+            BI_TREE_MODEL_READER_NAME => {
+                let model = BiTreeModel::read::<E>(reader, type_readers, version)?;
+                Ok(Content::BiTreeModel(model))
             }
             TEXTURE_2D_READER_NAME => {
-                let texture = Texture2D::read(reader)?;
-                return Ok(Content::Texture2D(texture));
+                let texture = Texture2D::read::<E>(reader, version)?;
+                Ok(Content::Texture2D(texture))
+            }
+            SPRITE_FONT_READER_NAME => {
+                let font = SpriteFont::read::<E>(reader, type_readers, version)?;
+                Ok(Content::SpriteFont(font))
             }
-            MODEL_READER_NAME => {
-                let model = Model::read(reader, type_readers)?;
-                return Ok(Content::Model(model));
+            SOUND_EFFECT_READER_NAME => {
+                let sound_effect = SoundEffect::read::<E>(reader)?;
+                Ok(Content::SoundEffect(sound_effect))
             }
+            SONG_READER_NAME => {
+                let song = Song::read::<E>(reader, type_readers)?;
+                Ok(Content::Song(song))
+            }
+            MODEL_READER_NAME => match version {
+                Version::XNA31 => {
+                    let model = Model::read::<E>(reader, type_readers)?;
+                    Ok(Content::Model(model))
+                }
+                Version::XNA40 => {
+                    let model = Xna40Model::read::<E>(reader, type_readers)?;
+                    Ok(Content::Xna40Model(model))
+                }
+            },
             SKINNED_MODEL_READER_NAME => {
-                let model = SkinnedModel::read(reader, type_readers)?;
-                return Ok(Content::SkinnedModel(model));
+                let model = SkinnedModel::read::<E>(reader, type_readers, version)?;
+                Ok(Content::SkinnedModel(model))
             }
             SKINNED_MODEL_BONE_READER_NAME => {
-                let bone = SkinnedModelBone::read(reader)?;
-                return Ok(Content::SkinnedModelBone(bone));
+                let bone = SkinnedModelBone::read::<E>(reader)?;
+                Ok(Content::SkinnedModelBone(bone))
             }
             SKINNED_MODEL_ANIMATION_CLIP_READER_NAME => {
-                let clip = SkinnedModelAnimationClip::read(reader)?;
-                return Ok(Content::SkinnedModelAnimationClip(clip));
+                let clip = SkinnedModelAnimationClip::read::<E>(reader)?;
+                Ok(Content::SkinnedModelAnimationClip(clip))
             }
             VERTEX_DECL_READER_NAME => {
-                let decl = VertexDeclaration::read(reader)?;
-                return Ok(Content::VertexDeclaration(decl));
+                let decl = VertexDeclaration::read::<E>(reader, version)?;
+                Ok(Content::VertexDeclaration(decl))
             }
             VERTEX_BUFFER_READER_NAME => {
-                let buffer = VertexBuffer::read(reader)?;
-                return Ok(Content::VertexBuffer(buffer));
+                let buffer = VertexBuffer::read::<E>(reader, version)?;
+                Ok(Content::VertexBuffer(buffer))
             }
             INDEX_BUFFER_READER_NAME => {
-                let buffer = IndexBuffer::read(reader)?;
-                return Ok(Content::IndexBuffer(buffer));
+                let buffer = IndexBuffer::read::<E>(reader)?;
+                Ok(Content::IndexBuffer(buffer))
             }
             EFFECT_READER_NAME => {
-                let effect = Effect::read(reader)?;
-                return Ok(Content::Effect(effect));
+                let effect = Effect::read::<E>(reader)?;
+                Ok(Content::Effect(effect))
             }
             BASIC_EFFECT_READER_NAME => {
-                let effect = BasicEffect::read(reader)?;
-                return Ok(Content::BasicEffect(effect));
+                let effect = BasicEffect::read::<E>(reader)?;
+                Ok(Content::BasicEffect(effect))
             }
             RENDER_DEFERRED_EFFECT_READER_NAME => {
-                let effect = RenderDeferredEffect::read(reader)?;
-                return Ok(Content::RenderDeferredEffect(effect));
+                let effect = RenderDeferredEffect::read::<E>(reader)?;
+                Ok(Content::RenderDeferredEffect(effect))
             }
             ADDITIVE_EFFECT_READER_NAME => {
-                let effect = AdditiveEffect::read(reader)?;
-                return Ok(Content::AdditiveEffect(effect));
+                let effect = AdditiveEffect::read::<E>(reader)?;
+                Ok(Content::AdditiveEffect(effect))
             }
             SKINNED_MODEL_BASIC_EFFECT_READER_NAME => {
-                let effect = SkinnedModelBasicEffect::read(reader, type_readers)?;
-                return Ok(Content::SkinnedModelBasicEffect(effect));
+                let effect = SkinnedModelBasicEffect::read::<E>(reader, type_readers, version)?;
+                Ok(Content::SkinnedModelBasicEffect(effect))
             }
             SKINNED_MODEL_DEFERRED_NORMAL_MAPPED_EFFECT_READER_NAME => {
-                let effect = SkinnedModelDeferredNormalMappedEffect::read(reader)?;
-                return Ok(Content::SkinnedModelDeferredNormalMappedEffect(effect));
+                let effect = SkinnedModelDeferredNormalMappedEffect::read::<E>(reader)?;
+                Ok(Content::SkinnedModelDeferredNormalMappedEffect(effect))
+            }
+            _ => Err(ContentError::UnknownTypeReader {
+                name: name.to_string(),
+                offset: reader.stream_position()?,
+            }
+            .into()),
+        }
+    }
+
+    /// name of the type reader used to read this content, or `None` for null content
+    pub fn reader_name(&self) -> Option<&'static str> {
+        let name = match self {
+            Content::Null => return None,
+            Content::Item(..) => ITEM_READER_NAME,
+            Content::Character(..) => CHARACTER_READER_NAME,
+            Content::LevelModel(..) => LEVEL_MODEL_READER_NAME,
+            Content::BiTreeModel(..) => BI_TREE_MODEL_READER_NAME,
+            Content::String(..) => STRING_READER_NAME,
+            Content::ExternalReference(..) => EXTERNAL_REFERENCE_READER_NAME,
+            Content::Texture2D(..) => TEXTURE_2D_READER_NAME,
+            Content::SpriteFont(..) => SPRITE_FONT_READER_NAME,
+            Content::SoundEffect(..) => SOUND_EFFECT_READER_NAME,
+            Content::Song(..) => SONG_READER_NAME,
+            Content::Model(..) | Content::Xna40Model(..) => MODEL_READER_NAME,
+            Content::SkinnedModel(..) => SKINNED_MODEL_READER_NAME,
+            Content::SkinnedModelBone(..) => SKINNED_MODEL_BONE_READER_NAME,
+            Content::SkinnedModelAnimationClip(..) => SKINNED_MODEL_ANIMATION_CLIP_READER_NAME,
+            Content::VertexDeclaration(..) => VERTEX_DECL_READER_NAME,
+            Content::VertexBuffer(..) => VERTEX_BUFFER_READER_NAME,
+            Content::IndexBuffer(..) => INDEX_BUFFER_READER_NAME,
+            Content::Effect(..) => EFFECT_READER_NAME,
+            Content::BasicEffect(..) => BASIC_EFFECT_READER_NAME,
+            Content::AdditiveEffect(..) => ADDITIVE_EFFECT_READER_NAME,
+            Content::RenderDeferredEffect(..) => RENDER_DEFERRED_EFFECT_READER_NAME,
+            Content::SkinnedModelBasicEffect(..) => SKINNED_MODEL_BASIC_EFFECT_READER_NAME,
+            Content::SkinnedModelDeferredNormalMappedEffect(..) => {
+                SKINNED_MODEL_DEFERRED_NORMAL_MAPPED_EFFECT_READER_NAME
+            }
+        };
+        Some(name)
+    }
+
+    /// names of every type reader needed to write this content, including the readers of nested
+    /// content such as the strings and buffers inside a model
+    pub fn reader_names(&self) -> Vec<&'static str> {
+        const MODEL_NESTED_READER_NAMES: [&str; 4] = [
+            STRING_READER_NAME,
+            VERTEX_DECL_READER_NAME,
+            VERTEX_BUFFER_READER_NAME,
+            INDEX_BUFFER_READER_NAME,
+        ];
+        let nested: Vec<&'static str> = match self {
+            Content::Model(..) => MODEL_NESTED_READER_NAMES.to_vec(),
+            Content::Xna40Model(..) => vec![STRING_READER_NAME],
+            Content::SkinnedModel(..) => vec![
+                MODEL_READER_NAME,
+                STRING_READER_NAME,
+                VERTEX_DECL_READER_NAME,
+                VERTEX_BUFFER_READER_NAME,
+                INDEX_BUFFER_READER_NAME,
+            ],
+            Content::SkinnedModelBasicEffect(..) => vec![EXTERNAL_REFERENCE_READER_NAME],
+            Content::SpriteFont(..) => vec![
+                TEXTURE_2D_READER_NAME,
+                RECTANGLE_LIST_READER_NAME,
+                RECTANGLE_READER_NAME,
+                CHAR_LIST_READER_NAME,
+                CHAR_READER_NAME,
+                VECTOR3_LIST_READER_NAME,
+                VECTOR3_READER_NAME,
+            ],
+            Content::Song(..) => vec![INT32_READER_NAME],
+            Content::LevelModel(level) => level.nested_reader_names(),
+            Content::BiTreeModel(model) => model.nested_reader_names(),
+            _ => Vec::new(),
+        };
+        self.reader_name().into_iter().chain(nested).collect()
+    }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<()> {
+        let Some(name) = self.reader_name() else {
+            writer.write_7bit_encoded_i32(0)?;
+            return Ok(());
+        };
+        write_type_id(writer, type_readers, name)?;
+
+        match self {
+            Content::Null => unreachable!(),
+            Content::String(string) => writer.write_7bit_length_string(string)?,
+            Content::ExternalReference(path) => writer.write_7bit_length_string(path)?,
+            Content::Item(item) => item.write::<E>(writer)?,
+            Content::Character(character) => character.write::<E>(writer)?,
+            Content::LevelModel(level) => level.write::<E>(writer, type_readers, version)?,
+            Content::BiTreeModel(model) => model.write::<E>(writer, type_readers, version)?,
+            Content::Texture2D(texture) => texture.write::<E>(writer, version)?,
+            Content::SpriteFont(font) => font.write::<E>(writer, type_readers, version)?,
+            Content::SoundEffect(sound_effect) => sound_effect.write::<E>(writer)?,
+            Content::Song(song) => song.write::<E>(writer, type_readers)?,
+            Content::Model(model) => {
+                if version != Version::XNA31 {
+                    anyhow::bail!("XNA 3.1 model cannot be written as {version:?}");
+                }
+                model.write::<E>(writer, type_readers)?
             }
-            _ => {
-                anyhow::bail!("unknown type reader: {}", type_reader.name);
+            Content::Xna40Model(model) => {
+                if version != Version::XNA40 {
+                    anyhow::bail!("XNA 4.0 model cannot be written as {version:?}");
+                }
+                model.write::<E>(writer, type_readers)?
             }
+            Content::SkinnedModel(model) => model.write::<E>(writer, type_readers, version)?,
+            Content::SkinnedModelBone(bone) => bone.write::<E>(writer)?,
+            Content::SkinnedModelAnimationClip(clip) => clip.write::<E>(writer)?,
+            Content::VertexDeclaration(decl) => decl.write::<E>(writer, version)?,
+            Content::VertexBuffer(buffer) => buffer.write::<E>(writer, version)?,
+            Content::IndexBuffer(buffer) => buffer.write::<E>(writer)?,
+            Content::Effect(effect) => effect.write::<E>(writer)?,
+            Content::BasicEffect(effect) => effect.write::<E>(writer)?,
+            Content::RenderDeferredEffect(effect) => effect.write::<E>(writer)?,
+            Content::AdditiveEffect(effect) => effect.write::<E>(writer)?,
+            Content::SkinnedModelBasicEffect(effect) => effect.write::<E>(writer, type_readers)?,
+            Content::SkinnedModelDeferredNormalMappedEffect(effect) => effect.write::<E>(writer)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// writes the 7-bit encoded id of the type reader with the given name, as expected by `Content::read`
+fn write_type_id(
+    writer: &mut impl Write,
+    type_readers: &[TypeReader],
+    name: &str,
+) -> anyhow::Result<()> {
+    let index = type_readers
+        .iter()
+        .position(|type_reader| type_reader.name.split(",").next().unwrap() == name)
+        .ok_or_else(|| anyhow!("missing type reader: {name}"))?;
+    writer.write_7bit_encoded_i32(index as i32 + 1)?;
+    Ok(())
+}
+
+/// reads a 7-bit encoded type id and checks that it refers to the type reader with the given
+/// name, for nested content that the game always reads with the same reader such as lists
+fn read_type_id(
+    reader: &mut impl Read,
+    type_readers: &[TypeReader],
+    name: &str,
+) -> anyhow::Result<()> {
+    let id = reader.read_7bit_encoded_i32()? as usize;
+    let found = id
+        .checked_sub(1)
+        .and_then(|index| type_readers.get(index))
+        .map(|type_reader| type_reader.name.split(",").next().unwrap());
+    if found != Some(name) {
+        anyhow::bail!("expected {name}, found {}", found.unwrap_or("null content"));
+    }
+    Ok(())
+}
+
+/// reads an i32 count followed by that many items, errors mention the index of the failed item
+fn read_list<E: ByteOrder, R: Read, T>(
+    reader: &mut R,
+    what: &str,
+    mut read: impl FnMut(&mut R) -> anyhow::Result<T>,
+) -> anyhow::Result<Vec<T>> {
+    let count = reader
+        .read_i32::<E>()
+        .with_context(|| format!("failed to read {what} count"))?;
+    let count = usize::try_from(count).map_err(|_| anyhow!("invalid {what} count: {count}"))?;
+    let mut items = Vec::with_capacity(count.min(1024));
+    for i in 0..count {
+        let item = read(reader).with_context(|| format!("failed to read {what} {i}"))?;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+fn write_list<E: ByteOrder, W: Write, T>(
+    writer: &mut W,
+    items: &[T],
+    what: &str,
+    mut write: impl FnMut(&mut W, &T) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    writer.write_i32::<E>(items.len() as i32)?;
+    for (i, item) in items.iter().enumerate() {
+        write(writer, item).with_context(|| format!("failed to write {what} {i}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::xnb::{Platform, XnbContent};
+
+    /// writes the content in both byte orders, reads it back and checks that the read content is
+    /// the same and writes the same bytes again
+    pub(crate) fn assert_round_trip(json: &str) {
+        let primary_content: Content = serde_json::from_str(json).unwrap();
+        let mut content = XnbContent::new(primary_content);
+        for platform in [Platform::Windows, Platform::Xbox360] {
+            content.platform = platform;
+            let mut written = Vec::new();
+            content.write(&mut written).unwrap();
+
+            let read = XnbContent::parse(&mut Cursor::new(&written), platform, content.version)
+                .unwrap_or_else(|e| panic!("failed to read {platform:?} content: {e:#}"));
+            assert_eq!(
+                serde_json::to_value(&read.primary_content).unwrap(),
+                serde_json::to_value(&content.primary_content).unwrap(),
+                "{platform:?} content changed"
+            );
+            let mut rewritten = Vec::new();
+            read.write(&mut rewritten).unwrap();
+            assert_eq!(rewritten, written, "{platform:?} bytes changed");
         }
     }
 }
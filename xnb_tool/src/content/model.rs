@@ -1,14 +1,18 @@
-use std::io::Read;
+use std::io::{Read, Seek, Write};
 
-use anyhow::anyhow;
-use byteorder::{LittleEndian, ReadBytesExt};
-use glam::{Mat4, Vec3};
+use anyhow::{Context, anyhow};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
+use glam::{Mat4, Vec3, Vec4};
+use half::f16;
 use serde::{Deserialize, Serialize};
 
-use crate::ext::MyReadBytesExt;
-use crate::xnb::TypeReader;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
+use crate::xnb::{TypeReader, Version};
 
-use super::Content;
+use super::{
+    Content, INDEX_BUFFER_READER_NAME, STRING_READER_NAME, VERTEX_BUFFER_READER_NAME,
+    VERTEX_DECL_READER_NAME, write_type_id,
+};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Model {
@@ -21,20 +25,23 @@ pub struct Model {
 }
 
 impl Model {
-    pub fn read(reader: &mut impl Read, type_readers: &[TypeReader]) -> anyhow::Result<Self> {
-        let num_bones = reader.read_u32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+    ) -> anyhow::Result<Self> {
+        let num_bones = reader.read_u32::<E>()?;
         let mut bones = Vec::with_capacity(num_bones as usize);
         for _ in 0..num_bones {
-            let bone = Bone::read(reader, type_readers)?;
+            let bone = Bone::read::<E>(reader, type_readers)?;
             bones.push(bone);
         }
         let mut bones_hierarchy = Vec::with_capacity(num_bones as usize);
         for _ in 0..num_bones {
-            let parent_ref = read_bone_ref(reader, num_bones)?;
-            let num_children = reader.read_u32::<LittleEndian>()? as usize;
+            let parent_ref = read_bone_ref::<E>(reader, num_bones)?;
+            let num_children = reader.read_u32::<E>()? as usize;
             let mut children_refs = Vec::with_capacity(num_children);
             for _ in 0..num_children {
-                let child_ref = read_bone_ref(reader, num_bones)?;
+                let child_ref = read_bone_ref::<E>(reader, num_bones)?;
                 children_refs.push(child_ref);
             }
             bones_hierarchy.push(BoneHierarchy {
@@ -43,24 +50,24 @@ impl Model {
             });
         }
 
-        let num_vertex_decls = reader.read_u32::<LittleEndian>()?;
+        let num_vertex_decls = reader.read_u32::<E>()?;
         let mut vertex_decls = Vec::with_capacity(num_vertex_decls as usize);
         for _ in 0..num_vertex_decls {
-            let content = Content::read(reader, type_readers)?;
+            let content = Content::read::<E>(reader, type_readers, Version::XNA31)?;
             let Content::VertexDeclaration(decl) = content else {
                 anyhow::bail!("expected vertex declaration");
             };
             vertex_decls.push(decl);
         }
 
-        let num_meshes = reader.read_u32::<LittleEndian>()?;
+        let num_meshes = reader.read_u32::<E>()?;
         let mut meshes = Vec::with_capacity(num_meshes as usize);
         for _ in 0..num_meshes {
-            let mesh = Mesh::read(reader, type_readers)?;
+            let mesh = Mesh::read::<E>(reader, type_readers)?;
             meshes.push(mesh);
         }
 
-        let root_bone_ref = read_bone_ref(reader, num_bones)?;
+        let root_bone_ref = read_bone_ref::<E>(reader, num_bones)?;
         let tag = reader.read_u8()?;
 
         Ok(Model {
@@ -72,6 +79,49 @@ impl Model {
             tag,
         })
     }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+    ) -> anyhow::Result<()> {
+        let num_bones = self.bones.len() as u32;
+        writer.write_u32::<E>(num_bones)?;
+        for (i, bone) in self.bones.iter().enumerate() {
+            bone.write::<E>(writer, type_readers)
+                .with_context(|| format!("failed to write bone {i}"))?;
+        }
+        if self.bones_hierarchy.len() != self.bones.len() {
+            anyhow::bail!(
+                "expected {} bone hierarchy entries, found {}",
+                self.bones.len(),
+                self.bones_hierarchy.len()
+            );
+        }
+        for relation in &self.bones_hierarchy {
+            write_bone_ref::<E>(writer, relation.parent_ref, num_bones)?;
+            writer.write_u32::<E>(relation.children_refs.len() as u32)?;
+            for child_ref in &relation.children_refs {
+                write_bone_ref::<E>(writer, *child_ref, num_bones)?;
+            }
+        }
+
+        writer.write_u32::<E>(self.vertex_decls.len() as u32)?;
+        for decl in &self.vertex_decls {
+            write_type_id(writer, type_readers, VERTEX_DECL_READER_NAME)?;
+            decl.write::<E>(writer, Version::XNA31)?;
+        }
+
+        writer.write_u32::<E>(self.meshes.len() as u32)?;
+        for (i, mesh) in self.meshes.iter().enumerate() {
+            mesh.write::<E>(writer, type_readers)
+                .with_context(|| format!("failed to write mesh {i}"))?;
+        }
+
+        write_bone_ref::<E>(writer, self.root_bone_ref, num_bones)?;
+        writer.write_u8(self.tag)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -81,14 +131,28 @@ pub struct Bone {
 }
 
 impl Bone {
-    pub fn read(reader: &mut impl Read, type_readers: &[TypeReader]) -> anyhow::Result<Self> {
-        let name = Content::read(reader, type_readers)?;
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+    ) -> anyhow::Result<Self> {
+        let name = Content::read::<E>(reader, type_readers, Version::XNA31)?;
         let Content::String(name) = name else {
             anyhow::bail!("expected bone name to be a string");
         };
-        let transform = reader.read_mat4()?;
+        let transform = reader.read_mat4::<E>()?;
         Ok(Bone { name, transform })
     }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+    ) -> anyhow::Result<()> {
+        write_type_id(writer, type_readers, STRING_READER_NAME)?;
+        writer.write_7bit_length_string(&self.name)?;
+        writer.write_mat4::<E>(self.transform)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -97,15 +161,27 @@ pub struct BoneHierarchy {
     pub children_refs: Vec<u32>,
 }
 
-fn read_bone_ref(reader: &mut impl Read, num_bones: u32) -> std::io::Result<u32> {
+fn read_bone_ref<E: ByteOrder>(reader: &mut impl Read, num_bones: u32) -> std::io::Result<u32> {
     let bone_ref = if num_bones <= 255 {
         reader.read_u8()? as u32
     } else {
-        reader.read_u32::<LittleEndian>()?
+        reader.read_u32::<E>()?
     };
     Ok(bone_ref)
 }
 
+fn write_bone_ref<E: ByteOrder>(
+    writer: &mut impl Write,
+    bone_ref: u32,
+    num_bones: u32,
+) -> std::io::Result<()> {
+    if num_bones <= 255 {
+        writer.write_u8(bone_ref as u8)
+    } else {
+        writer.write_u32::<E>(bone_ref)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Mesh {
     pub name: String,
@@ -118,31 +194,34 @@ pub struct Mesh {
 }
 
 impl Mesh {
-    pub fn read(reader: &mut impl Read, type_readers: &[TypeReader]) -> anyhow::Result<Self> {
-        let name = Content::read(reader, type_readers)?;
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+    ) -> anyhow::Result<Self> {
+        let name = Content::read::<E>(reader, type_readers, Version::XNA31)?;
         let Content::String(name) = name else {
             anyhow::bail!("expected bone name to be a string");
         };
 
-        let parent_bone_ref = read_bone_ref(reader, 0)?;
-        let bounds = BoundingSphere::read(reader)?;
+        let parent_bone_ref = read_bone_ref::<E>(reader, 0)?;
+        let bounds = BoundingSphere::read::<E>(reader)?;
 
-        let vertex_buffer = Content::read(reader, type_readers)?;
+        let vertex_buffer = Content::read::<E>(reader, type_readers, Version::XNA31)?;
         let Content::VertexBuffer(vertex_buffer) = vertex_buffer else {
             anyhow::bail!("expected vertex buffer");
         };
 
-        let index_buffer = Content::read(reader, type_readers)?;
+        let index_buffer = Content::read::<E>(reader, type_readers, Version::XNA31)?;
         let Content::IndexBuffer(index_buffer) = index_buffer else {
             anyhow::bail!("expected index buffer");
         };
 
         let tag = reader.read_u8()?;
 
-        let num_parts = reader.read_u32::<LittleEndian>()? as usize;
+        let num_parts = reader.read_u32::<E>()? as usize;
         let mut parts = Vec::with_capacity(num_parts);
         for _ in 0..num_parts {
-            let part = MeshPart::read(reader)?;
+            let part = MeshPart::read::<E>(reader)?;
             parts.push(part);
         }
 
@@ -156,6 +235,33 @@ impl Mesh {
             tag,
         })
     }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+    ) -> anyhow::Result<()> {
+        write_type_id(writer, type_readers, STRING_READER_NAME)?;
+        writer.write_7bit_length_string(&self.name)?;
+
+        write_bone_ref::<E>(writer, self.parent_bone_ref, 0)?;
+        self.bounds.write::<E>(writer)?;
+
+        write_type_id(writer, type_readers, VERTEX_BUFFER_READER_NAME)?;
+        self.vertex_buffer.write::<E>(writer, Version::XNA31)?;
+
+        write_type_id(writer, type_readers, INDEX_BUFFER_READER_NAME)?;
+        self.index_buffer.write::<E>(writer)?;
+
+        writer.write_u8(self.tag)?;
+
+        writer.write_u32::<E>(self.parts.len() as u32)?;
+        for (i, part) in self.parts.iter().enumerate() {
+            part.write::<E>(writer)
+                .with_context(|| format!("failed to write mesh part {i}"))?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -171,13 +277,13 @@ pub struct MeshPart {
 }
 
 impl MeshPart {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let stream_offset = reader.read_u32::<LittleEndian>()?;
-        let base_vertex = reader.read_u32::<LittleEndian>()?;
-        let vertex_count = reader.read_u32::<LittleEndian>()?;
-        let start_index = reader.read_u32::<LittleEndian>()?;
-        let primitive_count = reader.read_u32::<LittleEndian>()?;
-        let vertex_decl_index = reader.read_u32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let stream_offset = reader.read_u32::<E>()?;
+        let base_vertex = reader.read_u32::<E>()?;
+        let vertex_count = reader.read_u32::<E>()?;
+        let start_index = reader.read_u32::<E>()?;
+        let primitive_count = reader.read_u32::<E>()?;
+        let vertex_decl_index = reader.read_u32::<E>()?;
         let tag = reader.read_u8()?;
         let shared_content_material_idx = reader.read_7bit_encoded_i32()?;
         Ok(MeshPart {
@@ -191,6 +297,18 @@ impl MeshPart {
             shared_content_material_idx,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u32::<E>(self.stream_offset)?;
+        writer.write_u32::<E>(self.base_vertex)?;
+        writer.write_u32::<E>(self.vertex_count)?;
+        writer.write_u32::<E>(self.start_index)?;
+        writer.write_u32::<E>(self.primitive_count)?;
+        writer.write_u32::<E>(self.vertex_decl_index)?;
+        writer.write_u8(self.tag)?;
+        writer.write_7bit_encoded_i32(self.shared_content_material_idx)?;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -200,30 +318,70 @@ pub struct BoundingSphere {
 }
 
 impl BoundingSphere {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let center = reader.read_vec3()?;
-        let radius = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let center = reader.read_vec3::<E>()?;
+        let radius = reader.read_f32::<E>()?;
         Ok(BoundingSphere { center, radius })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_vec3::<E>(self.center)?;
+        writer.write_f32::<E>(self.radius)?;
+        Ok(())
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct VertexDeclaration {
     pub elements: Vec<VertexElement>,
+    /// explicit vertex stride, only stored by XNA 4.0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vertex_stride: Option<u32>,
 }
 
 impl VertexDeclaration {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let num_elements = reader.read_u32::<LittleEndian>()? as usize;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read, version: Version) -> anyhow::Result<Self> {
+        let vertex_stride = match version {
+            Version::XNA31 => None,
+            Version::XNA40 => Some(reader.read_u32::<E>()?),
+        };
+        let num_elements = reader.read_u32::<E>()? as usize;
         let mut elements = Vec::with_capacity(num_elements);
         for _ in 0..num_elements {
-            let element = VertexElement::read(reader)?;
+            let element = match version {
+                Version::XNA31 => VertexElement::read::<E>(reader)?,
+                Version::XNA40 => VertexElement::read_xna40::<E>(reader)?,
+            };
             elements.push(element);
         }
-        Ok(VertexDeclaration { elements })
+        Ok(VertexDeclaration {
+            elements,
+            vertex_stride,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        version: Version,
+    ) -> anyhow::Result<()> {
+        if version == Version::XNA40 {
+            writer.write_u32::<E>(self.stride() as u32)?;
+        }
+        writer.write_u32::<E>(self.elements.len() as u32)?;
+        for element in &self.elements {
+            match version {
+                Version::XNA31 => element.write::<E>(writer)?,
+                Version::XNA40 => element.write_xna40::<E>(writer)?,
+            }
+        }
+        Ok(())
     }
 
     pub fn stride(&self) -> usize {
+        if let Some(vertex_stride) = self.vertex_stride {
+            return vertex_stride as usize;
+        }
         let mut end = 0;
         for el in &self.elements {
             let size = el.format.size();
@@ -233,7 +391,7 @@ impl VertexDeclaration {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct VertexElement {
     pub stream: u16,
     pub offset: u16,
@@ -244,9 +402,9 @@ pub struct VertexElement {
 }
 
 impl VertexElement {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let stream = reader.read_u16::<LittleEndian>()?;
-        let offset = reader.read_u16::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let stream = reader.read_u16::<E>()?;
+        let offset = reader.read_u16::<E>()?;
         let format = ElementFormat::read(reader)?;
         let method = ElementMethod::read(reader)?;
         let usage = ElementUsage::read(reader)?;
@@ -260,10 +418,96 @@ impl VertexElement {
             usage_index,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u16::<E>(self.stream)?;
+        writer.write_u16::<E>(self.offset)?;
+        writer.write_u8(self.format as u8)?;
+        writer.write_u8(self.method as u8)?;
+        writer.write_u8(self.usage as u8)?;
+        writer.write_u8(self.usage_index)?;
+        Ok(())
+    }
+
+    /// XNA 4.0 elements have no stream or method and use their own enum numbering
+    pub fn read_xna40<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let offset = reader.read_u32::<E>()?;
+        let format = reader.read_i32::<E>()?;
+        let usage = reader.read_i32::<E>()?;
+        let usage_index = reader.read_u32::<E>()?;
+
+        let format = *XNA40_ELEMENT_FORMATS
+            .get(format as usize)
+            .ok_or_else(|| anyhow!("unknown XNA 4.0 element format: {format}"))?;
+        let usage = *XNA40_ELEMENT_USAGES
+            .get(usage as usize)
+            .ok_or_else(|| anyhow!("unknown XNA 4.0 element usage: {usage}"))?;
+
+        Ok(VertexElement {
+            stream: 0,
+            offset: offset.try_into().context("element offset out of range")?,
+            format,
+            method: ElementMethod::Default,
+            usage,
+            usage_index: usage_index
+                .try_into()
+                .context("element usage index out of range")?,
+        })
+    }
+
+    pub fn write_xna40<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        let format = XNA40_ELEMENT_FORMATS
+            .iter()
+            .position(|format| *format == self.format)
+            .ok_or_else(|| anyhow!("element format {:?} has no XNA 4.0 equivalent", self.format))?;
+        let usage = XNA40_ELEMENT_USAGES
+            .iter()
+            .position(|usage| *usage == self.usage)
+            .unwrap();
+
+        writer.write_u32::<E>(self.offset as u32)?;
+        writer.write_i32::<E>(format as i32)?;
+        writer.write_i32::<E>(usage as i32)?;
+        writer.write_u32::<E>(self.usage_index as u32)?;
+        Ok(())
+    }
 }
 
+/// element formats indexed by their XNA 4.0 value
+const XNA40_ELEMENT_FORMATS: [ElementFormat; 12] = [
+    ElementFormat::Single,
+    ElementFormat::Vector2,
+    ElementFormat::Vector3,
+    ElementFormat::Vector4,
+    ElementFormat::Color,
+    ElementFormat::Byte4,
+    ElementFormat::Short2,
+    ElementFormat::Short4,
+    ElementFormat::NormalizedShort2,
+    ElementFormat::NormalizedShort4,
+    ElementFormat::HalfVector2,
+    ElementFormat::HalfVector4,
+];
+
+/// element usages indexed by their XNA 4.0 value
+const XNA40_ELEMENT_USAGES: [ElementUsage; 13] = [
+    ElementUsage::Position,
+    ElementUsage::Color,
+    ElementUsage::TextureCoordinate,
+    ElementUsage::Normal,
+    ElementUsage::Binormal,
+    ElementUsage::Tangent,
+    ElementUsage::BlendIndices,
+    ElementUsage::BlendWeight,
+    ElementUsage::Depth,
+    ElementUsage::Fog,
+    ElementUsage::PointSize,
+    ElementUsage::Sample,
+    ElementUsage::TessellateFactor,
+];
+
 #[repr(u8)]
-#[derive(strum::FromRepr, Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(strum::FromRepr, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ElementFormat {
     Single,
     Vector2,
@@ -287,7 +531,7 @@ pub enum ElementFormat {
 impl ElementFormat {
     pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
         let value = reader.read_u8()?;
-        let format = ElementFormat::from_repr(value as u8)
+        let format = ElementFormat::from_repr(value)
             .ok_or_else(|| anyhow!("unknown element format: {value}"))?;
         Ok(format)
     }
@@ -300,13 +544,210 @@ impl ElementFormat {
             ElementFormat::Vector4 => 16,
             ElementFormat::Color => 4,
             ElementFormat::Byte4 => 4,
-            other => unimplemented!("element format size: {other:?}"),
+            ElementFormat::Short2 => 4,
+            ElementFormat::Short4 => 8,
+            ElementFormat::RGBA32 => 4,
+            ElementFormat::NormalizedShort2 => 4,
+            ElementFormat::NormalizedShort4 => 8,
+            ElementFormat::RGB32 => 4,
+            ElementFormat::RGBA64 => 8,
+            ElementFormat::UInt40 => 4,
+            ElementFormat::Normalized40 => 4,
+            ElementFormat::HalfVector2 => 4,
+            ElementFormat::HalfVector4 => 8,
+        }
+    }
+
+    /// size of the individual values making up the element, packed formats count as one value
+    pub fn component_size(&self) -> usize {
+        match self {
+            ElementFormat::Single
+            | ElementFormat::Vector2
+            | ElementFormat::Vector3
+            | ElementFormat::Vector4 => 4,
+            ElementFormat::Color
+            | ElementFormat::Byte4
+            | ElementFormat::RGBA32
+            | ElementFormat::UInt40
+            | ElementFormat::Normalized40 => 4,
+            ElementFormat::Short2
+            | ElementFormat::Short4
+            | ElementFormat::NormalizedShort2
+            | ElementFormat::NormalizedShort4
+            | ElementFormat::RGB32
+            | ElementFormat::RGBA64
+            | ElementFormat::HalfVector2
+            | ElementFormat::HalfVector4 => 2,
+        }
+    }
+
+    /// number of values decoded from the element
+    pub fn components(&self) -> usize {
+        match self {
+            ElementFormat::Single => 1,
+            ElementFormat::Vector2
+            | ElementFormat::Short2
+            | ElementFormat::NormalizedShort2
+            | ElementFormat::RGB32
+            | ElementFormat::HalfVector2 => 2,
+            ElementFormat::Vector3 | ElementFormat::UInt40 | ElementFormat::Normalized40 => 3,
+            ElementFormat::Vector4
+            | ElementFormat::Color
+            | ElementFormat::Byte4
+            | ElementFormat::Short4
+            | ElementFormat::RGBA32
+            | ElementFormat::NormalizedShort4
+            | ElementFormat::RGBA64
+            | ElementFormat::HalfVector4 => 4,
+        }
+    }
+
+    /// decodes a single element into floats, normalized formats are mapped to [0, 1] or [-1, 1]
+    /// and unused components are left as zero
+    pub fn decode<E: ByteOrder>(&self, bytes: &[u8]) -> Vec4 {
+        let mut values = [0.0; 4];
+        let count = self.components();
+        match self {
+            ElementFormat::Single
+            | ElementFormat::Vector2
+            | ElementFormat::Vector3
+            | ElementFormat::Vector4 => {
+                for (i, value) in values.iter_mut().take(count).enumerate() {
+                    *value = E::read_f32(&bytes[i * 4..]);
+                }
+            }
+            ElementFormat::Color => {
+                // packed as argb
+                let packed = E::read_u32(bytes);
+                for (i, shift) in [16, 8, 0, 24].into_iter().enumerate() {
+                    values[i] = ((packed >> shift) & 0xff) as f32 / 255.0;
+                }
+            }
+            ElementFormat::Byte4 | ElementFormat::RGBA32 => {
+                let packed = E::read_u32(bytes);
+                let scale = if *self == ElementFormat::RGBA32 {
+                    255.0
+                } else {
+                    1.0
+                };
+                for (i, value) in values.iter_mut().enumerate() {
+                    *value = ((packed >> (i * 8)) & 0xff) as f32 / scale;
+                }
+            }
+            ElementFormat::Short2 | ElementFormat::Short4 => {
+                for (i, value) in values.iter_mut().take(count).enumerate() {
+                    *value = E::read_i16(&bytes[i * 2..]) as f32;
+                }
+            }
+            ElementFormat::NormalizedShort2 | ElementFormat::NormalizedShort4 => {
+                for (i, value) in values.iter_mut().take(count).enumerate() {
+                    *value = (E::read_i16(&bytes[i * 2..]) as f32 / 32767.0).max(-1.0);
+                }
+            }
+            ElementFormat::RGB32 | ElementFormat::RGBA64 => {
+                for (i, value) in values.iter_mut().take(count).enumerate() {
+                    *value = E::read_u16(&bytes[i * 2..]) as f32 / 65535.0;
+                }
+            }
+            ElementFormat::UInt40 => {
+                let packed = E::read_u32(bytes);
+                for (i, value) in values.iter_mut().take(count).enumerate() {
+                    *value = ((packed >> (i * 10)) & 0x3ff) as f32;
+                }
+            }
+            ElementFormat::Normalized40 => {
+                let packed = E::read_u32(bytes);
+                for (i, value) in values.iter_mut().take(count).enumerate() {
+                    // sign extend the 10 bit value
+                    let signed = ((packed >> (i * 10)) << 22) as i32 >> 22;
+                    *value = (signed as f32 / 511.0).max(-1.0);
+                }
+            }
+            ElementFormat::HalfVector2 | ElementFormat::HalfVector4 => {
+                for (i, value) in values.iter_mut().take(count).enumerate() {
+                    *value = f16::from_bits(E::read_u16(&bytes[i * 2..])).to_f32();
+                }
+            }
+        }
+        Vec4::from_array(values)
+    }
+
+    /// encodes a single element, the inverse of [`ElementFormat::decode`]. values outside the
+    /// range of the format are clamped
+    pub fn encode<E: ByteOrder>(&self, value: Vec4, bytes: &mut [u8]) {
+        let count = self.components();
+        let values = &value.to_array()[..count];
+        let unorm = |v: f32, max: f32| (v.clamp(0.0, 1.0) * max).round() as u32;
+        let snorm = |v: f32, max: f32| (v.clamp(-1.0, 1.0) * max).round() as i32;
+        match self {
+            ElementFormat::Single
+            | ElementFormat::Vector2
+            | ElementFormat::Vector3
+            | ElementFormat::Vector4 => {
+                for (i, value) in values.iter().enumerate() {
+                    E::write_f32(&mut bytes[i * 4..], *value);
+                }
+            }
+            ElementFormat::Color => {
+                // packed as argb
+                let mut packed = 0;
+                for (value, shift) in values.iter().zip([16, 8, 0, 24]) {
+                    packed |= unorm(*value, 255.0) << shift;
+                }
+                E::write_u32(bytes, packed);
+            }
+            ElementFormat::Byte4 | ElementFormat::RGBA32 => {
+                let mut packed = 0;
+                for (i, value) in values.iter().enumerate() {
+                    let byte = if *self == ElementFormat::RGBA32 {
+                        unorm(*value, 255.0)
+                    } else {
+                        value.round().clamp(0.0, 255.0) as u32
+                    };
+                    packed |= byte << (i * 8);
+                }
+                E::write_u32(bytes, packed);
+            }
+            ElementFormat::Short2 | ElementFormat::Short4 => {
+                for (i, value) in values.iter().enumerate() {
+                    E::write_i16(&mut bytes[i * 2..], value.round() as i16);
+                }
+            }
+            ElementFormat::NormalizedShort2 | ElementFormat::NormalizedShort4 => {
+                for (i, value) in values.iter().enumerate() {
+                    E::write_i16(&mut bytes[i * 2..], snorm(*value, 32767.0) as i16);
+                }
+            }
+            ElementFormat::RGB32 | ElementFormat::RGBA64 => {
+                for (i, value) in values.iter().enumerate() {
+                    E::write_u16(&mut bytes[i * 2..], unorm(*value, 65535.0) as u16);
+                }
+            }
+            ElementFormat::UInt40 => {
+                let mut packed = 0;
+                for (i, value) in values.iter().enumerate() {
+                    packed |= (value.round().clamp(0.0, 1023.0) as u32) << (i * 10);
+                }
+                E::write_u32(bytes, packed);
+            }
+            ElementFormat::Normalized40 => {
+                let mut packed = 0;
+                for (i, value) in values.iter().enumerate() {
+                    packed |= ((snorm(*value, 511.0) as u32) & 0x3ff) << (i * 10);
+                }
+                E::write_u32(bytes, packed);
+            }
+            ElementFormat::HalfVector2 | ElementFormat::HalfVector4 => {
+                for (i, value) in values.iter().enumerate() {
+                    E::write_u16(&mut bytes[i * 2..], f16::from_f32(*value).to_bits());
+                }
+            }
         }
     }
 }
 
 #[repr(u8)]
-#[derive(strum::FromRepr, Serialize, Deserialize, Debug)]
+#[derive(strum::FromRepr, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ElementMethod {
     Default,
     UV = 4,
@@ -317,7 +758,7 @@ pub enum ElementMethod {
 impl ElementMethod {
     pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
         let value = reader.read_u8()?;
-        let method = ElementMethod::from_repr(value as u8)
+        let method = ElementMethod::from_repr(value)
             .ok_or_else(|| anyhow!("unknown element method: {value}"))?;
         Ok(method)
     }
@@ -344,7 +785,7 @@ pub enum ElementUsage {
 impl ElementUsage {
     pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
         let value = reader.read_u8()?;
-        let usage = ElementUsage::from_repr(value as u8)
+        let usage = ElementUsage::from_repr(value)
             .ok_or_else(|| anyhow!("unknown element usage: {value}"))?;
         Ok(usage)
     }
@@ -352,15 +793,60 @@ impl ElementUsage {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VertexBuffer {
+    /// declaration embedded in XNA 4.0 vertex buffers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declaration: Option<VertexDeclaration>,
     pub data: Vec<u8>,
 }
 
 impl VertexBuffer {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let size = reader.read_u32::<LittleEndian>()? as usize;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read, version: Version) -> anyhow::Result<Self> {
+        let (declaration, size) = match version {
+            Version::XNA31 => {
+                let size = reader.read_u32::<E>()? as usize;
+                (None, size)
+            }
+            Version::XNA40 => {
+                let declaration = VertexDeclaration::read::<E>(reader, version)?;
+                let vertex_count = reader.read_u32::<E>()? as usize;
+                let size = vertex_count * declaration.stride();
+                (Some(declaration), size)
+            }
+        };
         let mut data = vec![0; size];
         reader.read_exact(&mut data)?;
-        Ok(VertexBuffer { data })
+        Ok(VertexBuffer { declaration, data })
+    }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        version: Version,
+    ) -> anyhow::Result<()> {
+        match (version, &self.declaration) {
+            (Version::XNA31, None) => {
+                writer.write_u32::<E>(self.data.len() as u32)?;
+            }
+            (Version::XNA40, Some(declaration)) => {
+                declaration.write::<E>(writer, version)?;
+                let stride = declaration.stride();
+                if stride == 0 || !self.data.len().is_multiple_of(stride) {
+                    anyhow::bail!(
+                        "vertex buffer size {} is not a multiple of the vertex stride {stride}",
+                        self.data.len()
+                    );
+                }
+                writer.write_u32::<E>((self.data.len() / stride) as u32)?;
+            }
+            (Version::XNA31, Some(..)) => {
+                anyhow::bail!("XNA 3.1 vertex buffers cannot embed a declaration")
+            }
+            (Version::XNA40, None) => {
+                anyhow::bail!("XNA 4.0 vertex buffers require an embedded declaration")
+            }
+        }
+        writer.write_all(&self.data)?;
+        Ok(())
     }
 }
 
@@ -371,11 +857,395 @@ pub struct IndexBuffer {
 }
 
 impl IndexBuffer {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let is_16_bit = reader.read_bool()?;
-        let size = reader.read_u32::<LittleEndian>()? as usize;
+        let size = reader.read_u32::<E>()? as usize;
         let mut data = vec![0; size];
         reader.read_exact(&mut data)?;
         Ok(IndexBuffer { is_16_bit, data })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_bool(self.is_16_bit)?;
+        writer.write_u32::<E>(self.data.len() as u32)?;
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+}
+
+/// XNA 4.0 model, meshes no longer own their buffers, every part references a
+/// vertex buffer, index buffer and effect in the shared content instead
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Xna40Model {
+    pub bones: Vec<Bone>,
+    pub bones_hierarchy: Vec<BoneHierarchy>,
+    pub meshes: Vec<Xna40Mesh>,
+    pub root_bone_ref: u32,
+    pub tag: u8,
+}
+
+impl Xna40Model {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+    ) -> anyhow::Result<Self> {
+        let num_bones = reader.read_u32::<E>()?;
+        let mut bones = Vec::with_capacity(num_bones as usize);
+        for _ in 0..num_bones {
+            let name = Content::read::<E>(reader, type_readers, Version::XNA40)?;
+            let Content::String(name) = name else {
+                anyhow::bail!("expected bone name to be a string");
+            };
+            let transform = reader.read_mat4::<E>()?;
+            bones.push(Bone { name, transform });
+        }
+        let mut bones_hierarchy = Vec::with_capacity(num_bones as usize);
+        for _ in 0..num_bones {
+            let parent_ref = read_bone_ref::<E>(reader, num_bones)?;
+            let num_children = reader.read_u32::<E>()? as usize;
+            let mut children_refs = Vec::with_capacity(num_children);
+            for _ in 0..num_children {
+                let child_ref = read_bone_ref::<E>(reader, num_bones)?;
+                children_refs.push(child_ref);
+            }
+            bones_hierarchy.push(BoneHierarchy {
+                parent_ref,
+                children_refs,
+            });
+        }
+
+        let num_meshes = reader.read_u32::<E>()?;
+        let mut meshes = Vec::with_capacity(num_meshes as usize);
+        for _ in 0..num_meshes {
+            let mesh = Xna40Mesh::read::<E>(reader, type_readers, num_bones)?;
+            meshes.push(mesh);
+        }
+
+        let root_bone_ref = read_bone_ref::<E>(reader, num_bones)?;
+        let tag = reader.read_u8()?;
+
+        Ok(Xna40Model {
+            bones,
+            bones_hierarchy,
+            meshes,
+            root_bone_ref,
+            tag,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+    ) -> anyhow::Result<()> {
+        let num_bones = self.bones.len() as u32;
+        writer.write_u32::<E>(num_bones)?;
+        for bone in &self.bones {
+            write_type_id(writer, type_readers, STRING_READER_NAME)?;
+            writer.write_7bit_length_string(&bone.name)?;
+            writer.write_mat4::<E>(bone.transform)?;
+        }
+        if self.bones_hierarchy.len() != self.bones.len() {
+            anyhow::bail!(
+                "expected {} bone hierarchy entries, found {}",
+                self.bones.len(),
+                self.bones_hierarchy.len()
+            );
+        }
+        for relation in &self.bones_hierarchy {
+            write_bone_ref::<E>(writer, relation.parent_ref, num_bones)?;
+            writer.write_u32::<E>(relation.children_refs.len() as u32)?;
+            for child_ref in &relation.children_refs {
+                write_bone_ref::<E>(writer, *child_ref, num_bones)?;
+            }
+        }
+
+        writer.write_u32::<E>(self.meshes.len() as u32)?;
+        for (i, mesh) in self.meshes.iter().enumerate() {
+            mesh.write::<E>(writer, type_readers, num_bones)
+                .with_context(|| format!("failed to write mesh {i}"))?;
+        }
+
+        write_bone_ref::<E>(writer, self.root_bone_ref, num_bones)?;
+        writer.write_u8(self.tag)?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Xna40Mesh {
+    pub name: String,
+    pub parent_bone_ref: u32,
+    pub bounds: BoundingSphere,
+    pub tag: u8,
+    pub parts: Vec<Xna40MeshPart>,
+}
+
+impl Xna40Mesh {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        num_bones: u32,
+    ) -> anyhow::Result<Self> {
+        let name = Content::read::<E>(reader, type_readers, Version::XNA40)?;
+        let Content::String(name) = name else {
+            anyhow::bail!("expected mesh name to be a string");
+        };
+
+        let parent_bone_ref = read_bone_ref::<E>(reader, num_bones)?;
+        let bounds = BoundingSphere::read::<E>(reader)?;
+        let tag = reader.read_u8()?;
+
+        let num_parts = reader.read_u32::<E>()? as usize;
+        let mut parts = Vec::with_capacity(num_parts);
+        for _ in 0..num_parts {
+            let part = Xna40MeshPart::read::<E>(reader)?;
+            parts.push(part);
+        }
+
+        Ok(Xna40Mesh {
+            name,
+            parent_bone_ref,
+            bounds,
+            tag,
+            parts,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+        num_bones: u32,
+    ) -> anyhow::Result<()> {
+        write_type_id(writer, type_readers, STRING_READER_NAME)?;
+        writer.write_7bit_length_string(&self.name)?;
+
+        write_bone_ref::<E>(writer, self.parent_bone_ref, num_bones)?;
+        self.bounds.write::<E>(writer)?;
+        writer.write_u8(self.tag)?;
+
+        writer.write_u32::<E>(self.parts.len() as u32)?;
+        for (i, part) in self.parts.iter().enumerate() {
+            part.write::<E>(writer)
+                .with_context(|| format!("failed to write mesh part {i}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Xna40MeshPart {
+    pub vertex_offset: u32,
+    pub vertex_count: u32,
+    pub start_index: u32,
+    pub primitive_count: u32,
+    pub tag: u8,
+    pub shared_content_vertex_buffer_idx: i32,
+    pub shared_content_index_buffer_idx: i32,
+    pub shared_content_material_idx: i32,
+}
+
+impl Xna40MeshPart {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let vertex_offset = reader.read_u32::<E>()?;
+        let vertex_count = reader.read_u32::<E>()?;
+        let start_index = reader.read_u32::<E>()?;
+        let primitive_count = reader.read_u32::<E>()?;
+        let tag = reader.read_u8()?;
+        let shared_content_vertex_buffer_idx = reader.read_7bit_encoded_i32()?;
+        let shared_content_index_buffer_idx = reader.read_7bit_encoded_i32()?;
+        let shared_content_material_idx = reader.read_7bit_encoded_i32()?;
+        Ok(Xna40MeshPart {
+            vertex_offset,
+            vertex_count,
+            start_index,
+            primitive_count,
+            tag,
+            shared_content_vertex_buffer_idx,
+            shared_content_index_buffer_idx,
+            shared_content_material_idx,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u32::<E>(self.vertex_offset)?;
+        writer.write_u32::<E>(self.vertex_count)?;
+        writer.write_u32::<E>(self.start_index)?;
+        writer.write_u32::<E>(self.primitive_count)?;
+        writer.write_u8(self.tag)?;
+        writer.write_7bit_encoded_i32(self.shared_content_vertex_buffer_idx)?;
+        writer.write_7bit_encoded_i32(self.shared_content_index_buffer_idx)?;
+        writer.write_7bit_encoded_i32(self.shared_content_material_idx)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::{BigEndian, LittleEndian};
+
+    use super::*;
+    use crate::content::tests::assert_round_trip;
+
+    const ALL_FORMATS: [ElementFormat; 17] = [
+        ElementFormat::Single,
+        ElementFormat::Vector2,
+        ElementFormat::Vector3,
+        ElementFormat::Vector4,
+        ElementFormat::Color,
+        ElementFormat::Byte4,
+        ElementFormat::Short2,
+        ElementFormat::Short4,
+        ElementFormat::RGBA32,
+        ElementFormat::NormalizedShort2,
+        ElementFormat::NormalizedShort4,
+        ElementFormat::RGB32,
+        ElementFormat::RGBA64,
+        ElementFormat::UInt40,
+        ElementFormat::Normalized40,
+        ElementFormat::HalfVector2,
+        ElementFormat::HalfVector4,
+    ];
+
+    fn encoded<E: ByteOrder>(format: ElementFormat, value: Vec4) -> Vec<u8> {
+        let mut bytes = vec![0; format.size()];
+        format.encode::<E>(value, &mut bytes);
+        bytes
+    }
+
+    /// a value every format can hold, with the unused components zeroed
+    fn sample(format: ElementFormat) -> Vec4 {
+        let value = match format {
+            ElementFormat::Byte4 | ElementFormat::UInt40 => Vec4::new(3.0, 200.0, 17.0, 1.0),
+            ElementFormat::Short2 | ElementFormat::Short4 => Vec4::new(-300.0, 12.0, 0.0, 7.0),
+            ElementFormat::Color
+            | ElementFormat::RGBA32
+            | ElementFormat::RGB32
+            | ElementFormat::RGBA64 => Vec4::new(0.2, 0.4, 0.6, 1.0),
+            _ => Vec4::new(-0.5, 0.25, 0.75, -1.0),
+        };
+        let mut values = value.to_array();
+        values[format.components()..].fill(0.0);
+        Vec4::from_array(values)
+    }
+
+    #[test]
+    fn element_formats_round_trip() {
+        for format in ALL_FORMATS {
+            // the step between two encoded values
+            let tolerance = match format {
+                ElementFormat::Color | ElementFormat::RGBA32 => 1.0 / 255.0,
+                ElementFormat::NormalizedShort2 | ElementFormat::NormalizedShort4 => 1.0 / 32767.0,
+                ElementFormat::RGB32 | ElementFormat::RGBA64 => 1.0 / 65535.0,
+                ElementFormat::Normalized40 => 1.0 / 511.0,
+                ElementFormat::HalfVector2 | ElementFormat::HalfVector4 => 1.0 / 1024.0,
+                _ => 0.0,
+            };
+            let value = sample(format);
+            let little = encoded::<LittleEndian>(format, value);
+            let big = encoded::<BigEndian>(format, value);
+            for decoded in [
+                format.decode::<LittleEndian>(&little),
+                format.decode::<BigEndian>(&big),
+            ] {
+                assert!(
+                    (decoded - value).abs().max_element() <= tolerance,
+                    "{format:?}: {value} decoded as {decoded}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn normalized_101010_is_sign_extended() {
+        let format = ElementFormat::Normalized40;
+        // x = -1 in ten bits, y = the smallest value, z = the largest value
+        let packed: u32 = 0x3ff | (0x200 << 10) | (0x1ff << 20);
+        let decoded = format.decode::<LittleEndian>(&packed.to_le_bytes());
+        assert_eq!(decoded, Vec4::new(-1.0 / 511.0, -1.0, 1.0, 0.0));
+
+        let bytes = encoded::<LittleEndian>(format, Vec4::new(-1.0, 0.0, 1.0, 0.0));
+        assert_eq!(
+            u32::from_le_bytes(bytes.try_into().unwrap()),
+            0x201 | (0x1ff << 20)
+        );
+    }
+
+    #[test]
+    fn normalized_shorts_are_scaled() {
+        let bytes = [i16::MIN.to_le_bytes(), i16::MAX.to_le_bytes()].concat();
+        let decoded = ElementFormat::NormalizedShort2.decode::<LittleEndian>(&bytes);
+        assert_eq!(decoded, Vec4::new(-1.0, 1.0, 0.0, 0.0));
+        let bytes = encoded::<LittleEndian>(ElementFormat::NormalizedShort2, Vec4::splat(0.5));
+        assert_eq!(
+            bytes,
+            [16384i16.to_le_bytes(), 16384i16.to_le_bytes()].concat()
+        );
+
+        // the unsigned two component format
+        let bytes = [0u16.to_le_bytes(), u16::MAX.to_le_bytes()].concat();
+        let decoded = ElementFormat::RGB32.decode::<LittleEndian>(&bytes);
+        assert_eq!(decoded, Vec4::new(0.0, 1.0, 0.0, 0.0));
+        let bytes = encoded::<LittleEndian>(ElementFormat::RGB32, Vec4::new(0.5, 2.0, 0.0, 0.0));
+        assert_eq!(
+            bytes,
+            [32768u16.to_le_bytes(), u16::MAX.to_le_bytes()].concat()
+        );
+    }
+
+    #[test]
+    fn half_vectors_use_half_floats() {
+        let value = Vec4::new(1.0, -2.5, 0.5, 65504.0);
+        let bytes = encoded::<BigEndian>(ElementFormat::HalfVector4, value);
+        assert_eq!(bytes, [0x3c, 0x00, 0xc1, 0x00, 0x38, 0x00, 0x7b, 0xff]);
+        assert_eq!(
+            ElementFormat::HalfVector4.decode::<BigEndian>(&bytes),
+            value
+        );
+        let bytes = encoded::<LittleEndian>(ElementFormat::HalfVector2, value);
+        assert_eq!(bytes, [0x00, 0x3c, 0x00, 0xc1]);
+        assert_eq!(
+            ElementFormat::HalfVector2.decode::<LittleEndian>(&bytes),
+            Vec4::new(1.0, -2.5, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn model_round_trip() {
+        assert_round_trip(
+            r#"{"Model": {
+                "bones": [
+                    {"name": "root", "transform": [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1]},
+                    {"name": "arm", "transform": [0, 1, 0, 0, -1, 0, 0, 0, 0, 0, 1, 0, 2, 3, 4, 1]}
+                ],
+                "bones_hierarchy": [
+                    {"parent_ref": 0, "children_refs": [2]},
+                    {"parent_ref": 1, "children_refs": []}
+                ],
+                "vertex_decls": [{"elements": [
+                    {"stream": 0, "offset": 0, "format": "Vector3", "method": "Default",
+                        "usage": "Position", "usage_index": 0},
+                    {"stream": 0, "offset": 12, "format": "Vector2", "method": "Default",
+                        "usage": "TextureCoordinate", "usage_index": 0}
+                ]}],
+                "meshes": [{
+                    "name": "tri", "parent_bone_ref": 2,
+                    "bounds": {"center": [0, 0.5, 0], "radius": 1},
+                    "vertex_buffer": {"data": [
+                        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                        0, 0, 128, 63, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 128, 63, 0, 0, 0, 0,
+                        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 128, 63, 0, 0, 0, 0,
+                        0, 0, 128, 63
+                    ]},
+                    "index_buffer": {"is_16_bit": true, "data": [0, 0, 1, 0, 2, 0]},
+                    "parts": [{"stream_offset": 0, "base_vertex": 0, "vertex_count": 3,
+                        "start_index": 0, "primitive_count": 1, "vertex_decl_index": 0,
+                        "tag": 0, "shared_content_material_idx": 1}],
+                    "tag": 0
+                }],
+                "root_bone_ref": 1, "tag": 0
+            }}"#,
+        );
+    }
 }
@@ -1,11 +1,11 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
 use anyhow::anyhow;
 use bitflags::bitflags;
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
-use crate::ext::MyReadBytesExt;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Movement {
@@ -14,9 +14,9 @@ pub struct Movement {
 }
 
 impl Movement {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let properties = MovementProperties::read(reader)?;
-        let num_animations = reader.read_i32::<LittleEndian>()?;
+        let num_animations = reader.read_i32::<E>()?;
         let mut animations = Vec::with_capacity(num_animations as usize);
         for _ in 0..num_animations {
             let animation = reader.read_7bit_length_string()?;
@@ -27,6 +27,15 @@ impl Movement {
             animations,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.properties.write(writer)?;
+        writer.write_i32::<E>(self.animations.len() as i32)?;
+        for animation in &self.animations {
+            writer.write_7bit_length_string(animation)?;
+        }
+        Ok(())
+    }
 }
 
 bitflags! {
@@ -48,4 +57,9 @@ impl MovementProperties {
             .ok_or_else(|| anyhow!("unknown movement properties: {value}"))?;
         Ok(properties)
     }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u8(self.bits())?;
+        Ok(())
+    }
 }
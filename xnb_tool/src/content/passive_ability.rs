@@ -1,7 +1,7 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
 use anyhow::anyhow;
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
 #[repr(u8)]
@@ -33,13 +33,19 @@ pub struct PassiveAbility {
 }
 
 impl PassiveAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = reader.read_u8()?;
         let kind = PassiveAbilityKind::from_repr(kind)
             .ok_or_else(|| anyhow!("unknown passive ability kind: {kind}"))?;
-        let value = reader.read_f32::<LittleEndian>()?;
+        let value = reader.read_f32::<E>()?;
 
         let ability = PassiveAbility { kind, value };
         Ok(ability)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u8(self.kind as u8)?;
+        writer.write_f32::<E>(self.value)?;
+        Ok(())
+    }
 }
@@ -1,10 +1,10 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
 use super::element::Elements;
-use crate::ext::MyReadBytesExt;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct Resistance {
@@ -24,10 +24,10 @@ impl Resistance {
         }
     }
 
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let element = Elements::read(reader)?;
-        let multiplier = reader.read_f32::<LittleEndian>()?;
-        let modifier = reader.read_f32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let element = Elements::read::<E>(reader)?;
+        let multiplier = reader.read_f32::<E>()?;
+        let modifier = reader.read_f32::<E>()?;
         let status_immunity = reader.read_bool()?;
 
         let resistance = Resistance {
@@ -38,4 +38,12 @@ impl Resistance {
         };
         Ok(resistance)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.element.write::<E>(writer)?;
+        writer.write_f32::<E>(self.multiplier)?;
+        writer.write_f32::<E>(self.modifier)?;
+        writer.write_bool(self.status_immunity)?;
+        Ok(())
+    }
 }
@@ -1,16 +1,16 @@
-use std::collections::HashMap;
-use std::io::Read;
+use std::io::{Read, Seek, Write};
 
 use anyhow::anyhow;
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use glam::{Mat4, Quat, Vec3};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-use crate::ext::MyReadBytesExt;
-use crate::xnb::TypeReader;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
+use crate::xnb::{TypeReader, Version};
 
-use super::Content;
 use super::model::Model;
+use super::{Content, MODEL_READER_NAME, write_type_id};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SkinnedModel {
@@ -20,20 +20,24 @@ pub struct SkinnedModel {
 }
 
 impl SkinnedModel {
-    pub fn read(reader: &mut impl Read, type_readers: &[TypeReader]) -> anyhow::Result<Self> {
-        let model = Content::read(reader, type_readers)?;
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
+        let model = Content::read::<E>(reader, type_readers, version)?;
         let Content::Model(model) = model else {
             return Err(anyhow!("expected model"));
         };
 
-        let num_bones = reader.read_i32::<LittleEndian>()?;
+        let num_bones = reader.read_i32::<E>()?;
         let mut shared_bone_refs = Vec::with_capacity(num_bones as usize);
         for _ in 0..num_bones {
             let bone_ref = reader.read_7bit_encoded_i32()?;
             shared_bone_refs.push(bone_ref as usize);
         }
 
-        let num_animations = reader.read_i32::<LittleEndian>()?;
+        let num_animations = reader.read_i32::<E>()?;
         let mut shared_animation_refs = Vec::with_capacity(num_animations as usize);
         for _ in 0..num_animations {
             let animation_ref = reader.read_7bit_encoded_i32()?;
@@ -46,6 +50,30 @@ impl SkinnedModel {
             shared_animation_refs,
         })
     }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<()> {
+        if version != Version::XNA31 {
+            anyhow::bail!("XNA 3.1 model cannot be written as {version:?}");
+        }
+        write_type_id(writer, type_readers, MODEL_READER_NAME)?;
+        self.model.write::<E>(writer, type_readers)?;
+
+        writer.write_i32::<E>(self.shared_bone_refs.len() as i32)?;
+        for bone_ref in &self.shared_bone_refs {
+            writer.write_7bit_encoded_i32(*bone_ref as i32)?;
+        }
+
+        writer.write_i32::<E>(self.shared_animation_refs.len() as i32)?;
+        for animation_ref in &self.shared_animation_refs {
+            writer.write_7bit_encoded_i32(*animation_ref as i32)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -61,15 +89,15 @@ pub struct SkinnedModelBone {
 }
 
 impl SkinnedModelBone {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let index = reader.read_u16::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let index = reader.read_u16::<E>()?;
         let name = reader.read_7bit_length_string()?;
-        let translation = reader.read_vec3()?;
-        let orientation = reader.read_quat()?;
-        let scale = reader.read_vec3()?;
-        let inverse_bind_pose_transform = reader.read_mat4()?;
+        let translation = reader.read_vec3::<E>()?;
+        let orientation = reader.read_quat::<E>()?;
+        let scale = reader.read_vec3::<E>()?;
+        let inverse_bind_pose_transform = reader.read_mat4::<E>()?;
         let shared_parent_ref = reader.read_7bit_encoded_i32()? as usize;
-        let num_children = reader.read_i32::<LittleEndian>()?;
+        let num_children = reader.read_i32::<E>()?;
         let mut shared_child_refs = Vec::with_capacity(num_children as usize);
         for _ in 0..num_children {
             let child_ref = reader.read_7bit_encoded_i32()?;
@@ -87,30 +115,45 @@ impl SkinnedModelBone {
             shared_child_refs,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u16::<E>(self.index)?;
+        writer.write_7bit_length_string(&self.name)?;
+        writer.write_vec3::<E>(self.translation)?;
+        writer.write_quat::<E>(self.orientation)?;
+        writer.write_vec3::<E>(self.scale)?;
+        writer.write_mat4::<E>(self.inverse_bind_pose_transform)?;
+        writer.write_7bit_encoded_i32(self.shared_parent_ref as i32)?;
+        writer.write_i32::<E>(self.shared_child_refs.len() as i32)?;
+        for child_ref in &self.shared_child_refs {
+            writer.write_7bit_encoded_i32(*child_ref as i32)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SkinnedModelAnimationClip {
     pub name: String,
     pub duration: f32,
-    pub channels: HashMap<String, Vec<SkinnedModelAnimationKeyframe>>,
+    pub channels: IndexMap<String, Vec<SkinnedModelAnimationKeyframe>>,
 }
 
 impl SkinnedModelAnimationClip {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let name = reader.read_7bit_length_string()?;
-        let duration = reader.read_f32::<LittleEndian>()?;
-        let num_channels = reader.read_i32::<LittleEndian>()?;
-        let mut channels = HashMap::with_capacity(num_channels as usize);
+        let duration = reader.read_f32::<E>()?;
+        let num_channels = reader.read_i32::<E>()?;
+        let mut channels = IndexMap::with_capacity(num_channels as usize);
         for _ in 0..num_channels {
             let channel_name = reader.read_7bit_length_string()?;
-            let num_frames = reader.read_i32::<LittleEndian>()?;
+            let num_frames = reader.read_i32::<E>()?;
             let mut frames = Vec::with_capacity(num_frames as usize);
             for _ in 0..num_frames {
-                let time = reader.read_f32::<LittleEndian>()?;
-                let translation = reader.read_vec3()?;
-                let orientation = reader.read_quat()?;
-                let scale = reader.read_vec3()?;
+                let time = reader.read_f32::<E>()?;
+                let translation = reader.read_vec3::<E>()?;
+                let orientation = reader.read_quat::<E>()?;
+                let scale = reader.read_vec3::<E>()?;
                 let pose = SkinnedModelPose {
                     translation,
                     orientation,
@@ -128,6 +171,23 @@ impl SkinnedModelAnimationClip {
             channels,
         })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.name)?;
+        writer.write_f32::<E>(self.duration)?;
+        writer.write_i32::<E>(self.channels.len() as i32)?;
+        for (channel_name, frames) in &self.channels {
+            writer.write_7bit_length_string(channel_name)?;
+            writer.write_i32::<E>(frames.len() as i32)?;
+            for frame in frames {
+                writer.write_f32::<E>(frame.time)?;
+                writer.write_vec3::<E>(frame.pose.translation)?;
+                writer.write_quat::<E>(frame.pose.orientation)?;
+                writer.write_vec3::<E>(frame.pose.scale)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
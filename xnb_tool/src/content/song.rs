@@ -0,0 +1,44 @@
+use std::io::{Read, Write};
+
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
+use serde::{Deserialize, Serialize};
+
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
+use crate::xnb::TypeReader;
+
+use super::{INT32_READER_NAME, read_type_id, write_type_id};
+
+/// songs only reference a music file that is streamed next to the xnb
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Song {
+    /// relative to the xnb
+    pub file_name: String,
+    /// in milliseconds
+    pub duration: i32,
+}
+
+impl Song {
+    pub fn read<E: ByteOrder>(
+        reader: &mut impl Read,
+        type_readers: &[TypeReader],
+    ) -> anyhow::Result<Self> {
+        let file_name = reader.read_7bit_length_string()?;
+        read_type_id(reader, type_readers, INT32_READER_NAME)?;
+        let duration = reader.read_i32::<E>()?;
+        Ok(Song {
+            file_name,
+            duration,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+    ) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.file_name)?;
+        write_type_id(writer, type_readers, INT32_READER_NAME)?;
+        writer.write_i32::<E>(self.duration)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use std::io::{Read, Write};
+
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SoundEffect {
+    pub format: WaveFormatEx,
+    pub data: Vec<u8>,
+    /// in samples
+    pub loop_start: i32,
+    /// in samples, zero when the sound does not loop
+    pub loop_length: i32,
+    /// in milliseconds
+    pub duration: i32,
+}
+
+impl SoundEffect {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let format_size = reader.read_u32::<E>()?;
+        let mut format = vec![0; format_size as usize];
+        reader.read_exact(&mut format)?;
+        let format = WaveFormatEx::read(&mut format.as_slice())?;
+
+        let data_size = reader.read_u32::<E>()?;
+        let mut data = vec![0; data_size as usize];
+        reader.read_exact(&mut data)?;
+
+        let loop_start = reader.read_i32::<E>()?;
+        let loop_length = reader.read_i32::<E>()?;
+        let duration = reader.read_i32::<E>()?;
+
+        Ok(SoundEffect {
+            format,
+            data,
+            loop_start,
+            loop_length,
+            duration,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        let mut format = Vec::new();
+        self.format.write(&mut format)?;
+        writer.write_u32::<E>(format.len() as u32)?;
+        writer.write_all(&format)?;
+
+        writer.write_u32::<E>(self.data.len() as u32)?;
+        writer.write_all(&self.data)?;
+
+        writer.write_i32::<E>(self.loop_start)?;
+        writer.write_i32::<E>(self.loop_length)?;
+        writer.write_i32::<E>(self.duration)?;
+        Ok(())
+    }
+}
+
+/// the format is stored as the raw bytes of a windows WAVEFORMATEX, which is always little-endian
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WaveFormatEx {
+    pub format_tag: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub average_bytes_per_second: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+    /// codec specific data following the size field, `None` when the format has no size field
+    pub extra: Option<Vec<u8>>,
+}
+
+impl WaveFormatEx {
+    pub const PCM: u16 = 1;
+    pub const ADPCM: u16 = 2;
+
+    pub fn read(reader: &mut &[u8]) -> anyhow::Result<Self> {
+        let format_tag = reader.read_u16::<LittleEndian>()?;
+        let channels = reader.read_u16::<LittleEndian>()?;
+        let sample_rate = reader.read_u32::<LittleEndian>()?;
+        let average_bytes_per_second = reader.read_u32::<LittleEndian>()?;
+        let block_align = reader.read_u16::<LittleEndian>()?;
+        let bits_per_sample = reader.read_u16::<LittleEndian>()?;
+        // plain WAVEFORMAT structures end here
+        let extra = if reader.is_empty() {
+            None
+        } else {
+            let _extra_size = reader.read_u16::<LittleEndian>()?;
+            Some(reader.to_vec())
+        };
+        Ok(WaveFormatEx {
+            format_tag,
+            channels,
+            sample_rate,
+            average_bytes_per_second,
+            block_align,
+            bits_per_sample,
+            extra,
+        })
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_u16::<LittleEndian>(self.format_tag)?;
+        writer.write_u16::<LittleEndian>(self.channels)?;
+        writer.write_u32::<LittleEndian>(self.sample_rate)?;
+        writer.write_u32::<LittleEndian>(self.average_bytes_per_second)?;
+        writer.write_u16::<LittleEndian>(self.block_align)?;
+        writer.write_u16::<LittleEndian>(self.bits_per_sample)?;
+        if let Some(extra) = &self.extra {
+            writer.write_u16::<LittleEndian>(extra.len() as u16)?;
+            writer.write_all(extra)?;
+        }
+        Ok(())
+    }
+}
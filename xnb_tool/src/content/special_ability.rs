@@ -1,10 +1,10 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
 use super::element::Elements;
-use crate::ext::MyReadBytesExt;
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SpecialAbility {
@@ -15,14 +15,14 @@ pub struct SpecialAbility {
 }
 
 impl SpecialAbility {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
         let kind = reader.read_7bit_length_string()?;
         let animation = reader.read_7bit_length_string()?;
         let hash = reader.read_7bit_length_string()?;
-        let num_elements = reader.read_i32::<LittleEndian>()?;
+        let num_elements = reader.read_i32::<E>()?;
         let mut elements = Vec::with_capacity(num_elements as usize);
         for _ in 0..num_elements {
-            let element = Elements::read(reader)?;
+            let element = Elements::read::<E>(reader)?;
             elements.push(element);
         }
 
@@ -34,6 +34,17 @@ impl SpecialAbility {
         };
         Ok(ability)
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_length_string(&self.kind)?;
+        writer.write_7bit_length_string(&self.animation)?;
+        writer.write_7bit_length_string(&self.hash)?;
+        writer.write_i32::<E>(self.elements.len() as i32)?;
+        for element in &self.elements {
+            element.write::<E>(writer)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -43,9 +54,15 @@ pub struct SpecialAbilityWithCooldown {
 }
 
 impl SpecialAbilityWithCooldown {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let cooldown = reader.read_f32::<LittleEndian>()?;
-        let ability = SpecialAbility::read(reader)?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let cooldown = reader.read_f32::<E>()?;
+        let ability = SpecialAbility::read::<E>(reader)?;
         Ok(SpecialAbilityWithCooldown { ability, cooldown })
     }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_f32::<E>(self.cooldown)?;
+        self.ability.write::<E>(writer)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,250 @@
+use std::io::{Read, Seek, Write};
+
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::ext::{MyReadBytesExt, MyWriteBytesExt};
+use crate::xnb::{TypeReader, Version};
+
+use super::texture::Texture2D;
+use super::{
+    CHAR_LIST_READER_NAME, Content, RECTANGLE_LIST_READER_NAME, TEXTURE_2D_READER_NAME,
+    VECTOR3_LIST_READER_NAME, read_list, read_type_id, write_list, write_type_id,
+};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpriteFont {
+    pub texture: Texture2D,
+    /// where each glyph is in the texture
+    pub glyphs: Vec<Rectangle>,
+    /// offset of each glyph inside its cell, the size is the size of the cell
+    pub cropping: Vec<Rectangle>,
+    /// character of each glyph
+    pub characters: Vec<char>,
+    pub line_spacing: i32,
+    /// extra space between characters
+    pub spacing: f32,
+    /// left bearing, width and right bearing of each glyph
+    pub kerning: Vec<Vec3>,
+    /// drawn in place of characters that are not in the font
+    pub default_character: Option<char>,
+}
+
+impl SpriteFont {
+    pub fn read<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<Self> {
+        let texture = Content::read::<E>(reader, type_readers, version)?;
+        let Content::Texture2D(texture) = texture else {
+            anyhow::bail!("expected sprite font texture");
+        };
+
+        read_type_id(reader, type_readers, RECTANGLE_LIST_READER_NAME)?;
+        let glyphs = read_list::<E, _, _>(reader, "glyph", Rectangle::read::<E>)?;
+        read_type_id(reader, type_readers, RECTANGLE_LIST_READER_NAME)?;
+        let cropping = read_list::<E, _, _>(reader, "cropping", Rectangle::read::<E>)?;
+        read_type_id(reader, type_readers, CHAR_LIST_READER_NAME)?;
+        let characters =
+            read_list::<E, _, _>(reader, "character", |reader| Ok(reader.read_utf8_char()?))?;
+
+        let line_spacing = reader.read_i32::<E>()?;
+        let spacing = reader.read_f32::<E>()?;
+
+        read_type_id(reader, type_readers, VECTOR3_LIST_READER_NAME)?;
+        let kerning =
+            read_list::<E, _, _>(reader, "kerning", |reader| Ok(reader.read_vec3::<E>()?))?;
+
+        let default_character = if reader.read_bool()? {
+            Some(reader.read_utf8_char()?)
+        } else {
+            None
+        };
+
+        if glyphs.len() != characters.len()
+            || cropping.len() != characters.len()
+            || kerning.len() != characters.len()
+        {
+            anyhow::bail!(
+                "sprite font has {} characters but {} glyphs, {} cropping rectangles and {} kerning entries",
+                characters.len(),
+                glyphs.len(),
+                cropping.len(),
+                kerning.len()
+            );
+        }
+
+        Ok(SpriteFont {
+            texture,
+            glyphs,
+            cropping,
+            characters,
+            line_spacing,
+            spacing,
+            kerning,
+            default_character,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        type_readers: &[TypeReader],
+        version: Version,
+    ) -> anyhow::Result<()> {
+        write_type_id(writer, type_readers, TEXTURE_2D_READER_NAME)?;
+        self.texture.write::<E>(writer, version)?;
+
+        write_type_id(writer, type_readers, RECTANGLE_LIST_READER_NAME)?;
+        write_list::<E, _, _>(writer, &self.glyphs, "glyph", |writer, rect| {
+            rect.write::<E>(writer)
+        })?;
+        write_type_id(writer, type_readers, RECTANGLE_LIST_READER_NAME)?;
+        write_list::<E, _, _>(writer, &self.cropping, "cropping", |writer, rect| {
+            rect.write::<E>(writer)
+        })?;
+        write_type_id(writer, type_readers, CHAR_LIST_READER_NAME)?;
+        write_list::<E, _, _>(writer, &self.characters, "character", |writer, c| {
+            Ok(writer.write_utf8_char(*c)?)
+        })?;
+
+        writer.write_i32::<E>(self.line_spacing)?;
+        writer.write_f32::<E>(self.spacing)?;
+
+        write_type_id(writer, type_readers, VECTOR3_LIST_READER_NAME)?;
+        write_list::<E, _, _>(writer, &self.kerning, "kerning", |writer, v| {
+            Ok(writer.write_vec3::<E>(*v)?)
+        })?;
+
+        writer.write_bool(self.default_character.is_some())?;
+        if let Some(c) = self.default_character {
+            writer.write_utf8_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Rectangle {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rectangle {
+    pub fn read<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let x = reader.read_i32::<E>()?;
+        let y = reader.read_i32::<E>()?;
+        let width = reader.read_i32::<E>()?;
+        let height = reader.read_i32::<E>()?;
+        Ok(Rectangle {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    pub fn write<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_i32::<E>(self.x)?;
+        writer.write_i32::<E>(self.y)?;
+        writer.write_i32::<E>(self.width)?;
+        writer.write_i32::<E>(self.height)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use byteorder::LittleEndian;
+
+    use super::*;
+    use crate::xnb::{Platform, XnbContent};
+
+    /// xnb payload of a font with `A` and `é` on a 2x1 texture and `?` as default character
+    fn font_payload(glyph_count: i32) -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.write_7bit_encoded_i32(5).unwrap();
+        for name in [
+            "Microsoft.Xna.Framework.Content.SpriteFontReader",
+            "Microsoft.Xna.Framework.Content.Texture2DReader",
+            "Microsoft.Xna.Framework.Content.ListReader`1[[Microsoft.Xna.Framework.Rectangle, Microsoft.Xna.Framework]]",
+            "Microsoft.Xna.Framework.Content.ListReader`1[[System.Char, mscorlib]]",
+            "Microsoft.Xna.Framework.Content.ListReader`1[[Microsoft.Xna.Framework.Vector3, Microsoft.Xna.Framework]]",
+        ] {
+            payload.write_7bit_length_string(name).unwrap();
+            payload.write_i32::<LittleEndian>(0).unwrap();
+        }
+        payload.write_7bit_encoded_i32(0).unwrap();
+
+        let i32s = |payload: &mut Vec<u8>, values: &[i32]| {
+            for value in values {
+                payload.write_i32::<LittleEndian>(*value).unwrap();
+            }
+        };
+        payload.push(1);
+        // color texture, 2x1, one mip of 8 bytes
+        payload.push(2);
+        i32s(&mut payload, &[1, 2, 1, 1, 8]);
+        payload.extend_from_slice(&[255, 255, 255, 255, 0, 0, 0, 0]);
+
+        payload.push(3);
+        let glyphs = [0, 0, 1, 1, 1, 0, 1, 1];
+        i32s(&mut payload, &[glyph_count]);
+        i32s(&mut payload, &glyphs[..glyph_count as usize * 4]);
+        payload.push(3);
+        i32s(&mut payload, &[2, 0, 2, 1, 8, 1, 3, 1, 8]);
+        payload.push(4);
+        i32s(&mut payload, &[2]);
+        payload.extend_from_slice("Aé".as_bytes());
+        i32s(&mut payload, &[10]);
+        payload.write_f32::<LittleEndian>(1.0).unwrap();
+        payload.push(5);
+        i32s(&mut payload, &[2]);
+        for value in [0.5, 1.0, 0.5, 0.0, 1.0, 1.0] {
+            payload.write_f32::<LittleEndian>(value).unwrap();
+        }
+        payload.push(1);
+        payload.push(b'?');
+        payload
+    }
+
+    fn parse(payload: &[u8]) -> anyhow::Result<XnbContent> {
+        XnbContent::parse(&mut Cursor::new(payload), Platform::Windows, Version::XNA31)
+    }
+
+    #[test]
+    fn sprite_font_from_bytes() {
+        let payload = font_payload(2);
+        let content = parse(&payload).unwrap();
+        let Content::SpriteFont(font) = &content.primary_content else {
+            panic!("expected a sprite font");
+        };
+        assert_eq!((font.texture.width, font.texture.height), (2, 1));
+        assert_eq!(font.characters, ['A', 'é']);
+        assert_eq!(font.glyphs[1].x, 1);
+        assert_eq!(font.cropping[1].y, 3);
+        assert_eq!(font.line_spacing, 10);
+        assert_eq!(font.spacing, 1.0);
+        assert_eq!(font.kerning[0], Vec3::new(0.5, 1.0, 0.5));
+        assert_eq!(font.default_character, Some('?'));
+
+        let mut written = Vec::new();
+        content.write(&mut written).unwrap();
+        assert_eq!(written, payload);
+    }
+
+    #[test]
+    fn sprite_font_counts_must_match() {
+        let error = parse(&font_payload(1)).unwrap_err();
+        assert!(
+            format!("{error:#}").contains("2 characters but 1 glyphs"),
+            "{error:#}"
+        );
+    }
+}
@@ -1,25 +1,61 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use anyhow::anyhow;
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
+use crate::xnb::Version;
+
+/// (XNA 4.0, XNA 3.1) surface format pairs, `format` always holds the 3.1 value.
+/// 4.0 `Color` is stored as R8G8B8A8 which is 3.1 `Rgba32`, not 3.1 `Color`.
+const XNA40_SURFACE_FORMATS: [(u32, SurfaceFormat); 19] = [
+    (0, SurfaceFormat::Rgba32),
+    (1, SurfaceFormat::Bgr565),
+    (2, SurfaceFormat::Bgra5551),
+    (3, SurfaceFormat::Bgra4444),
+    (4, SurfaceFormat::Dxt1),
+    (5, SurfaceFormat::Dxt3),
+    (6, SurfaceFormat::Dxt5),
+    (7, SurfaceFormat::NormalizedByte2),
+    (8, SurfaceFormat::NormalizedByte4),
+    (9, SurfaceFormat::Rgba1010102),
+    (10, SurfaceFormat::Rg32),
+    (11, SurfaceFormat::Rgba64),
+    (12, SurfaceFormat::Alpha8),
+    (13, SurfaceFormat::Single),
+    (14, SurfaceFormat::Vector2),
+    (15, SurfaceFormat::Vector4),
+    (16, SurfaceFormat::HalfSingle),
+    (17, SurfaceFormat::HalfVector2),
+    (18, SurfaceFormat::HalfVector4),
+];
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Texture2D {
-    pub format: u32,
+    pub format: SurfaceFormat,
     pub width: u32,
     pub height: u32,
     pub mips: Vec<Vec<u8>>,
 }
 
 impl Texture2D {
-    pub fn read(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let format = reader.read_u32::<LittleEndian>()?;
-        let width = reader.read_u32::<LittleEndian>()?;
-        let height = reader.read_u32::<LittleEndian>()?;
-        let mip_count = reader.read_u32::<LittleEndian>()?;
+    pub fn read<E: ByteOrder>(reader: &mut impl Read, version: Version) -> anyhow::Result<Self> {
+        let format = reader.read_u32::<E>()?;
+        let format = match version {
+            Version::XNA31 => SurfaceFormat::from_repr(format)
+                .ok_or_else(|| anyhow!("unknown surface format: {format}"))?,
+            Version::XNA40 => XNA40_SURFACE_FORMATS
+                .iter()
+                .find(|(xna40, _)| *xna40 == format)
+                .map(|(_, xna31)| *xna31)
+                .ok_or_else(|| anyhow!("unknown XNA 4.0 surface format: {format}"))?,
+        };
+        let width = reader.read_u32::<E>()?;
+        let height = reader.read_u32::<E>()?;
+        let mip_count = reader.read_u32::<E>()?;
         let mut mips = Vec::with_capacity(mip_count as usize);
         for _ in 0..mip_count {
-            let size = reader.read_u32::<LittleEndian>()?;
+            let size = reader.read_u32::<E>()?;
             let mut mip = vec![0u8; size as usize];
             reader.read_exact(&mut mip)?;
             mips.push(mip);
@@ -31,4 +67,182 @@ impl Texture2D {
             mips,
         })
     }
+
+    pub fn write<E: ByteOrder>(
+        &self,
+        writer: &mut impl Write,
+        version: Version,
+    ) -> anyhow::Result<()> {
+        let format = match version {
+            Version::XNA31 => self.format as u32,
+            Version::XNA40 => XNA40_SURFACE_FORMATS
+                .iter()
+                .find(|(_, xna31)| *xna31 == self.format)
+                .map(|(xna40, _)| *xna40)
+                .ok_or_else(|| {
+                    anyhow!("surface format {:?} has no XNA 4.0 equivalent", self.format)
+                })?,
+        };
+        writer.write_u32::<E>(format)?;
+        writer.write_u32::<E>(self.width)?;
+        writer.write_u32::<E>(self.height)?;
+        writer.write_u32::<E>(self.mips.len() as u32)?;
+        for mip in &self.mips {
+            writer.write_u32::<E>(mip.len() as u32)?;
+            writer.write_all(mip)?;
+        }
+        Ok(())
+    }
+}
+
+/// XNA 3.1 surface formats
+#[repr(u32)]
+#[derive(strum::FromRepr, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceFormat {
+    Color = 1,
+    Bgr32,
+    Bgra1010102,
+    Rgba32,
+    Rgb32,
+    Rgba1010102,
+    Rg32,
+    Rgba64,
+    Bgr565,
+    Bgra5551,
+    Bgr555,
+    Bgra4444,
+    Bgr444,
+    Bgra2338,
+    Alpha8,
+    Bgr233,
+    Bgr24,
+    NormalizedByte2,
+    NormalizedByte4,
+    NormalizedShort2,
+    NormalizedShort4,
+    Single,
+    Vector2,
+    Vector4,
+    HalfSingle,
+    HalfVector2,
+    HalfVector4,
+    Dxt1,
+    Dxt2,
+    Dxt3,
+    Dxt4,
+    Dxt5,
+    Luminance8,
+    Luminance16,
+    LuminanceAlpha8,
+    LuminanceAlpha16,
+    Palette8,
+    PaletteAlpha16,
+    NormalizedLuminance16,
+    NormalizedLuminance32,
+    NormalizedAlpha1010102,
+    NormalizedByte2Computed,
+    VideoYuYv,
+    VideoUyVy,
+    VideoGrGb,
+    VideoRgBg,
+    Multi2Bgra32,
+    Depth24Stencil8,
+    Depth24Stencil8Single,
+    Depth24Stencil4,
+    Depth24,
+    Depth32,
+    Depth16 = 54,
+    Depth15Stencil1 = 56,
+}
+
+impl SurfaceFormat {
+    /// width and height of a block, 4 for dxt formats and 1 for everything else
+    pub fn block_size(&self) -> u32 {
+        match self {
+            SurfaceFormat::Dxt1
+            | SurfaceFormat::Dxt2
+            | SurfaceFormat::Dxt3
+            | SurfaceFormat::Dxt4
+            | SurfaceFormat::Dxt5 => 4,
+            _ => 1,
+        }
+    }
+
+    /// bytes per block
+    pub fn block_bytes(&self) -> usize {
+        match self {
+            SurfaceFormat::Alpha8
+            | SurfaceFormat::Bgr233
+            | SurfaceFormat::Luminance8
+            | SurfaceFormat::LuminanceAlpha8
+            | SurfaceFormat::Palette8 => 1,
+            SurfaceFormat::Bgr565
+            | SurfaceFormat::Bgra5551
+            | SurfaceFormat::Bgr555
+            | SurfaceFormat::Bgra4444
+            | SurfaceFormat::Bgr444
+            | SurfaceFormat::Bgra2338
+            | SurfaceFormat::NormalizedByte2
+            | SurfaceFormat::NormalizedByte2Computed
+            | SurfaceFormat::HalfSingle
+            | SurfaceFormat::Luminance16
+            | SurfaceFormat::LuminanceAlpha16
+            | SurfaceFormat::PaletteAlpha16
+            | SurfaceFormat::NormalizedLuminance16
+            | SurfaceFormat::VideoYuYv
+            | SurfaceFormat::VideoUyVy
+            | SurfaceFormat::VideoGrGb
+            | SurfaceFormat::VideoRgBg
+            | SurfaceFormat::Depth16
+            | SurfaceFormat::Depth15Stencil1 => 2,
+            SurfaceFormat::Bgr24 => 3,
+            SurfaceFormat::Color
+            | SurfaceFormat::Bgr32
+            | SurfaceFormat::Bgra1010102
+            | SurfaceFormat::Rgba32
+            | SurfaceFormat::Rgb32
+            | SurfaceFormat::Rgba1010102
+            | SurfaceFormat::Rg32
+            | SurfaceFormat::NormalizedByte4
+            | SurfaceFormat::NormalizedShort2
+            | SurfaceFormat::Single
+            | SurfaceFormat::HalfVector2
+            | SurfaceFormat::NormalizedLuminance32
+            | SurfaceFormat::NormalizedAlpha1010102
+            | SurfaceFormat::Depth24Stencil8
+            | SurfaceFormat::Depth24Stencil8Single
+            | SurfaceFormat::Depth24Stencil4
+            | SurfaceFormat::Depth24
+            | SurfaceFormat::Depth32 => 4,
+            SurfaceFormat::Rgba64
+            | SurfaceFormat::NormalizedShort4
+            | SurfaceFormat::Vector2
+            | SurfaceFormat::HalfVector4
+            | SurfaceFormat::Multi2Bgra32
+            | SurfaceFormat::Dxt1 => 8,
+            SurfaceFormat::Vector4
+            | SurfaceFormat::Dxt2
+            | SurfaceFormat::Dxt3
+            | SurfaceFormat::Dxt4
+            | SurfaceFormat::Dxt5 => 16,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content::tests::assert_round_trip;
+
+    #[test]
+    fn texture_round_trip() {
+        assert_round_trip(
+            r#"{"Texture2D": {"format": "Color", "width": 2, "height": 2, "mips": [
+                [255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255, 255, 255, 128],
+                [64, 64, 64, 255]]}}"#,
+        );
+        assert_round_trip(
+            r#"{"Texture2D": {"format": "Dxt1", "width": 4, "height": 4, "mips": [
+                [0, 248, 224, 7, 0, 85, 170, 255]]}}"#,
+        );
+    }
 }
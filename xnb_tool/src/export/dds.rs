@@ -0,0 +1,244 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+
+use crate::content::texture::{SurfaceFormat, Texture2D};
+
+pub(crate) const DDS_MAGIC: u32 = 0x2053_4444;
+pub(crate) const DDS_HEADER_SIZE: u32 = 124;
+const DDS_PIXEL_FORMAT_SIZE: u32 = 32;
+
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PITCH: u32 = 0x8;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+pub(crate) const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x400000;
+
+pub(crate) const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_ALPHA: u32 = 0x2;
+pub(crate) const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDPF_LUMINANCE: u32 = 0x20000;
+
+const DX10_RESOURCE_DIMENSION_TEXTURE2D: u32 = 3;
+
+/// how the pixel format of a surface format is described in a dds header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DdsPixelFormat {
+    /// legacy four character code, or the numeric D3DFORMAT for formats without one
+    FourCc(u32),
+    Masks {
+        flags: u32,
+        bits: u32,
+        masks: [u32; 4],
+    },
+    /// DXGI_FORMAT written to the DX10 extension header
+    Dxgi(u32),
+}
+
+impl DdsPixelFormat {
+    pub(crate) fn new(format: SurfaceFormat) -> Self {
+        use DdsPixelFormat::{Dxgi, FourCc, Masks};
+
+        let rgb = |bits, r, g, b| Masks {
+            flags: DDPF_RGB,
+            bits,
+            masks: [r, g, b, 0],
+        };
+        let rgba = |bits, r, g, b, a| Masks {
+            flags: DDPF_RGB | DDPF_ALPHAPIXELS,
+            bits,
+            masks: [r, g, b, a],
+        };
+
+        match format {
+            SurfaceFormat::Color => rgba(32, 0xff0000, 0xff00, 0xff, 0xff000000),
+            SurfaceFormat::Bgr32 => rgb(32, 0xff0000, 0xff00, 0xff),
+            SurfaceFormat::Bgra1010102 => rgba(32, 0x3ff00000, 0xffc00, 0x3ff, 0xc0000000),
+            SurfaceFormat::Rgba32 => rgba(32, 0xff, 0xff00, 0xff0000, 0xff000000),
+            SurfaceFormat::Rgb32 => rgb(32, 0xff, 0xff00, 0xff0000),
+            SurfaceFormat::Rgba1010102 => Dxgi(24),
+            SurfaceFormat::Rg32 => Dxgi(35),
+            SurfaceFormat::Rgba64 => Dxgi(11),
+            SurfaceFormat::Bgr565 => rgb(16, 0xf800, 0x7e0, 0x1f),
+            SurfaceFormat::Bgra5551 => rgba(16, 0x7c00, 0x3e0, 0x1f, 0x8000),
+            SurfaceFormat::Bgr555 => rgb(16, 0x7c00, 0x3e0, 0x1f),
+            SurfaceFormat::Bgra4444 => rgba(16, 0xf00, 0xf0, 0xf, 0xf000),
+            SurfaceFormat::Bgr444 => rgb(16, 0xf00, 0xf0, 0xf),
+            SurfaceFormat::Bgra2338 => rgba(16, 0xe0, 0x1c, 0x3, 0xff00),
+            SurfaceFormat::Alpha8 => Masks {
+                flags: DDPF_ALPHA,
+                bits: 8,
+                masks: [0, 0, 0, 0xff],
+            },
+            SurfaceFormat::Bgr233 => rgb(8, 0xe0, 0x1c, 0x3),
+            SurfaceFormat::Bgr24 => rgb(24, 0xff0000, 0xff00, 0xff),
+            SurfaceFormat::NormalizedByte2 => Dxgi(51),
+            SurfaceFormat::NormalizedByte4 => Dxgi(31),
+            SurfaceFormat::NormalizedShort2 => Dxgi(37),
+            SurfaceFormat::NormalizedShort4 => Dxgi(13),
+            SurfaceFormat::Single => Dxgi(41),
+            SurfaceFormat::Vector2 => Dxgi(16),
+            SurfaceFormat::Vector4 => Dxgi(2),
+            SurfaceFormat::HalfSingle => Dxgi(54),
+            SurfaceFormat::HalfVector2 => Dxgi(34),
+            SurfaceFormat::HalfVector4 => Dxgi(10),
+            SurfaceFormat::Dxt1 => FourCc(four_cc(b"DXT1")),
+            SurfaceFormat::Dxt2 => FourCc(four_cc(b"DXT2")),
+            SurfaceFormat::Dxt3 => FourCc(four_cc(b"DXT3")),
+            SurfaceFormat::Dxt4 => FourCc(four_cc(b"DXT4")),
+            SurfaceFormat::Dxt5 => FourCc(four_cc(b"DXT5")),
+            SurfaceFormat::Luminance8 => Masks {
+                flags: DDPF_LUMINANCE,
+                bits: 8,
+                masks: [0xff, 0, 0, 0],
+            },
+            SurfaceFormat::Luminance16 => Masks {
+                flags: DDPF_LUMINANCE,
+                bits: 16,
+                masks: [0xffff, 0, 0, 0],
+            },
+            SurfaceFormat::LuminanceAlpha8 => Masks {
+                flags: DDPF_LUMINANCE | DDPF_ALPHAPIXELS,
+                bits: 8,
+                masks: [0xf, 0, 0, 0xf0],
+            },
+            SurfaceFormat::LuminanceAlpha16 => Masks {
+                flags: DDPF_LUMINANCE | DDPF_ALPHAPIXELS,
+                bits: 16,
+                masks: [0xff, 0, 0, 0xff00],
+            },
+            SurfaceFormat::VideoYuYv => FourCc(four_cc(b"YUY2")),
+            SurfaceFormat::VideoUyVy => FourCc(four_cc(b"UYVY")),
+            SurfaceFormat::VideoGrGb => FourCc(four_cc(b"GRGB")),
+            SurfaceFormat::VideoRgBg => FourCc(four_cc(b"RGBG")),
+            SurfaceFormat::Multi2Bgra32 => FourCc(four_cc(b"MET1")),
+            // D3DFORMAT values of formats that have no four character code
+            SurfaceFormat::Palette8 => FourCc(41),
+            SurfaceFormat::PaletteAlpha16 => FourCc(40),
+            SurfaceFormat::NormalizedLuminance16 => FourCc(61),
+            SurfaceFormat::NormalizedLuminance32 => FourCc(62),
+            SurfaceFormat::NormalizedAlpha1010102 => FourCc(67),
+            SurfaceFormat::NormalizedByte2Computed => FourCc(117),
+            SurfaceFormat::Depth24Stencil8 => FourCc(75),
+            SurfaceFormat::Depth24Stencil8Single => FourCc(83),
+            SurfaceFormat::Depth24Stencil4 => FourCc(79),
+            SurfaceFormat::Depth24 => FourCc(77),
+            SurfaceFormat::Depth32 => FourCc(71),
+            SurfaceFormat::Depth16 => FourCc(80),
+            SurfaceFormat::Depth15Stencil1 => FourCc(73),
+        }
+    }
+}
+
+impl Texture2D {
+    /// writes the texture as dds, the mip chain is copied as is so compressed blocks are not
+    /// decoded and encoded again
+    pub fn to_dds(&self) -> anyhow::Result<Vec<u8>> {
+        let pixel_format = DdsPixelFormat::new(self.format);
+        let compressed = self.format.block_size() > 1;
+        let mip_count = self.mips.len() as u32;
+
+        let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT;
+        flags |= if compressed {
+            DDSD_LINEARSIZE
+        } else {
+            DDSD_PITCH
+        };
+        let mut caps = DDSCAPS_TEXTURE;
+        if mip_count > 1 {
+            flags |= DDSD_MIPMAPCOUNT;
+            caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+        }
+
+        let mut dds = Vec::new();
+        dds.write_u32::<LittleEndian>(DDS_MAGIC)?;
+        dds.write_u32::<LittleEndian>(DDS_HEADER_SIZE)?;
+        dds.write_u32::<LittleEndian>(flags)?;
+        dds.write_u32::<LittleEndian>(self.height)?;
+        dds.write_u32::<LittleEndian>(self.width)?;
+        dds.write_u32::<LittleEndian>(self.pitch_or_linear_size())?;
+        dds.write_u32::<LittleEndian>(0)?; // depth
+        dds.write_u32::<LittleEndian>(mip_count)?;
+        for _ in 0..11 {
+            dds.write_u32::<LittleEndian>(0)?; // reserved
+        }
+
+        dds.write_u32::<LittleEndian>(DDS_PIXEL_FORMAT_SIZE)?;
+        let (pf_flags, four_cc_value, bits, masks) = match pixel_format {
+            DdsPixelFormat::FourCc(value) => (DDPF_FOURCC, value, 0, [0; 4]),
+            DdsPixelFormat::Masks { flags, bits, masks } => (flags, 0, bits, masks),
+            DdsPixelFormat::Dxgi(_) => (DDPF_FOURCC, four_cc(b"DX10"), 0, [0; 4]),
+        };
+        dds.write_u32::<LittleEndian>(pf_flags)?;
+        dds.write_u32::<LittleEndian>(four_cc_value)?;
+        dds.write_u32::<LittleEndian>(bits)?;
+        for mask in masks {
+            dds.write_u32::<LittleEndian>(mask)?;
+        }
+
+        dds.write_u32::<LittleEndian>(caps)?;
+        for _ in 0..4 {
+            dds.write_u32::<LittleEndian>(0)?; // caps2, caps3, caps4, reserved
+        }
+
+        if let DdsPixelFormat::Dxgi(dxgi_format) = pixel_format {
+            dds.write_u32::<LittleEndian>(dxgi_format)?;
+            dds.write_u32::<LittleEndian>(DX10_RESOURCE_DIMENSION_TEXTURE2D)?;
+            dds.write_u32::<LittleEndian>(0)?; // misc flags
+            dds.write_u32::<LittleEndian>(1)?; // array size
+            dds.write_u32::<LittleEndian>(0)?; // alpha mode
+        }
+
+        for (level, mip) in self.mips.iter().enumerate() {
+            let expected = self.mip_data_size(level);
+            if mip.len() < expected {
+                anyhow::bail!(
+                    "mip level {level} has {} bytes but {:?} needs {expected}",
+                    mip.len(),
+                    self.format
+                );
+            }
+            dds.extend_from_slice(&mip[..expected]);
+        }
+
+        Ok(dds)
+    }
+
+    /// bytes per row for uncompressed formats, or the size of the top level for compressed ones
+    fn pitch_or_linear_size(&self) -> u32 {
+        let size = self.mip_data_size(0) as u32;
+        if self.format.block_size() > 1 {
+            size
+        } else {
+            size / self.height.max(1)
+        }
+    }
+
+    /// size of the given mip level as stored in a dds file
+    pub(crate) fn mip_data_size(&self, level: usize) -> usize {
+        let (width, height) = self.mip_size(level);
+        let block_size = self.format.block_size();
+        let mut blocks_wide = width.div_ceil(block_size) as usize;
+        if matches!(
+            self.format,
+            SurfaceFormat::VideoYuYv
+                | SurfaceFormat::VideoUyVy
+                | SurfaceFormat::VideoGrGb
+                | SurfaceFormat::VideoRgBg
+        ) {
+            // two pixels share four bytes, so rows are padded to an even width
+            blocks_wide = blocks_wide.next_multiple_of(2);
+        }
+        let blocks_high = height.div_ceil(block_size) as usize;
+        blocks_wide * blocks_high * self.format.block_bytes()
+    }
+}
+
+pub(crate) fn four_cc(code: &[u8; 4]) -> u32 {
+    u32::from_le_bytes(*code)
+}
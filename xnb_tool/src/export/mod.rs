@@ -1,2 +1,7 @@
+pub mod dds;
 pub mod model;
+pub mod sound_effect;
+pub mod sprite_font;
 pub mod texture;
+pub mod wav;
+pub mod xbox;
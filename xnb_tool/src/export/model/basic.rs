@@ -3,23 +3,28 @@ use gltf::json::{Root, Scene};
 use crate::content::{Content, model::Model};
 
 use super::{
-    build_bones, build_buffer, build_glb_bytes, build_materials, build_mesh_parts,
+    build_bones, build_buffer, build_glb_bytes, build_mesh_parts,
+    material::{TextureLoader, build_materials},
     transformed_model::TransformedModel,
 };
 
 impl Model {
-    pub fn to_glb(&self, shared_content: &[Content]) -> anyhow::Result<Vec<u8>> {
+    pub fn to_glb(
+        &self,
+        shared_content: &[Content],
+        textures: &TextureLoader,
+    ) -> anyhow::Result<Vec<u8>> {
         let mut root = Root::default();
 
-        let transformed_model = TransformedModel::from(self);
+        let transformed_model = TransformedModel::try_from(self)?;
 
-        let buffer = build_buffer(&mut root, &transformed_model, shared_content);
+        let mut buffer = build_buffer(&mut root, &transformed_model, shared_content);
 
-        let materials = build_materials(&mut root, shared_content);
+        let materials = build_materials(&mut root, &mut buffer, shared_content, textures);
 
         let (root_bone_node, bone_nodes) = build_bones(&mut root, self)?;
 
-        for (mesh_idx, mesh) in self.meshes.iter().enumerate() {
+        for (mesh_idx, mesh) in transformed_model.meshes.iter().enumerate() {
             build_mesh_parts(
                 &mut root,
                 &buffer,
@@ -0,0 +1,498 @@
+use std::{
+    cell::OnceCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, anyhow};
+use glam::{EulerRot, Quat, Vec3};
+use gltf::json::{Index, Node, Root, Scene, scene::UnitQuaternion};
+use serde::Serialize;
+use serde_json::value::RawValue;
+use walkdir::WalkDir;
+
+use crate::{
+    content::{
+        Content,
+        animation::{AnimationAction, AnimationSet},
+        character::{Character, CharacterModel},
+        color::Color,
+        event::{Event, EventConditions},
+        item::Item,
+    },
+    xnb::XnbContent,
+};
+
+use super::{
+    FullBuffer, add_child, build_bones, build_glb_bytes, build_mesh_parts,
+    light::{LightAnchor, build_light},
+    material::{EffectMaterial, TextureLoader, build_materials},
+    skinned::{build_animations, build_skeleton, build_skin},
+    transformed_model::TransformedModel,
+};
+
+/// loads the content that character templates reference. references are relative to the content
+/// root and may point at xnb files or at content saved by extract
+#[derive(Debug)]
+pub struct ContentLoader {
+    root: PathBuf,
+    items: OnceCell<HashMap<String, Vec<PathBuf>>>,
+}
+
+impl ContentLoader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        ContentLoader {
+            root: root.into(),
+            items: OnceCell::new(),
+        }
+    }
+
+    /// loads the referenced content along with the path it was found at, `kind` is the
+    /// extension that extract gives this kind of content. returns `None` if it does not exist
+    pub fn load(
+        &self,
+        reference: &str,
+        kind: &str,
+    ) -> anyhow::Result<Option<(XnbContent, PathBuf)>> {
+        let reference = reference.replace('\\', "/");
+        let candidates = [
+            format!("{reference}.xnb"),
+            format!("{reference}.{kind}.json"),
+            format!("{reference}.{kind}.msgpack"),
+        ];
+        let Some(path) = candidates
+            .iter()
+            .map(|file| self.root.join(file))
+            .find(|path| path.is_file())
+        else {
+            return Ok(None);
+        };
+
+        let content = XnbContent::open(&path)
+            .with_context(|| format!("failed to load {}", path.display()))?;
+        Ok(Some((content, path)))
+    }
+
+    /// items are referenced by name instead of by path, so every file under the root with a
+    /// matching name is tried until one of them turns out to be an item
+    pub fn load_item(&self, name: &str) -> anyhow::Result<Option<Item>> {
+        let items = self.items.get_or_init(|| index_items(&self.root));
+        for path in items.get(&name.to_lowercase()).into_iter().flatten() {
+            let content = XnbContent::open(path)
+                .with_context(|| format!("failed to load {}", path.display()))?;
+            if let Content::Item(item) = content.primary_content {
+                return Ok(Some(item));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// maps lowercase file names without extension to the files that could contain an item
+fn index_items(root: &Path) -> HashMap<String, Vec<PathBuf>> {
+    let mut items: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
+        let Some(file_name) = entry.file_name().to_str() else {
+            continue;
+        };
+        let file_name = file_name.to_lowercase();
+        let Some(name) = [".xnb", ".item.json", ".item.msgpack"]
+            .iter()
+            .find_map(|extension| file_name.strip_suffix(extension))
+        else {
+            continue;
+        };
+        items
+            .entry(name.to_string())
+            .or_default()
+            .push(entry.into_path());
+    }
+    items
+}
+
+impl Character {
+    /// assembles one of the character models with its equipment, lights and effects, all
+    /// animated by the animation skeleton. the game picks a random model when the character
+    /// spawns, `variant` selects which one is exported
+    pub fn to_glb(&self, variant: usize, content: &ContentLoader) -> anyhow::Result<Vec<u8>> {
+        let character_model = self.models.get(variant).ok_or_else(|| {
+            anyhow!(
+                "model {variant} does not exist, the character has {} models",
+                self.models.len()
+            )
+        })?;
+
+        let mut root = Root::default();
+        let mut buffer = FullBuffer::new(&mut root);
+
+        // skeleton
+        let (skeleton_content, _) = content
+            .load(&self.animation_skeleton, "skinnedmodel")?
+            .ok_or_else(|| anyhow!("animation skeleton {} not found", self.animation_skeleton))?;
+        let Content::SkinnedModel(skeleton) = &skeleton_content.primary_content else {
+            anyhow::bail!(
+                "animation skeleton {} is not a skinned model",
+                self.animation_skeleton
+            );
+        };
+        let (skeleton_node, joint_nodes) =
+            build_skeleton(&mut root, skeleton, &skeleton_content.shared_content)?;
+        buffer.append_animations(&skeleton_content.shared_content);
+        build_animations(
+            &mut root,
+            &buffer,
+            skeleton,
+            &skeleton_content.shared_content,
+            &joint_nodes,
+        )?;
+        add_animation_actions(
+            &mut root,
+            &skeleton_content.shared_content,
+            &self.animations,
+        );
+
+        let skeleton = Skeleton {
+            node: skeleton_node,
+            joints: joint_nodes,
+        };
+
+        let mesh_nodes =
+            build_character_model(&mut root, &mut buffer, content, character_model, &skeleton)?;
+
+        // equipment
+        for attachment in &self.equipment {
+            if attachment.item.is_empty() {
+                continue;
+            }
+            let Some(item) = content.load_item(&attachment.item)? else {
+                eprintln!("WARNING: item {} not found", attachment.item);
+                continue;
+            };
+
+            let bone_node = skeleton.find_bone(&root, &attachment.bone);
+            // attachment rotations are in degrees
+            let rotation = Quat::from_euler(
+                EulerRot::YXZ,
+                attachment.rotation.y.to_radians(),
+                attachment.rotation.x.to_radians(),
+                attachment.rotation.z.to_radians(),
+            );
+            let item_node = root.push(Node {
+                name: Some(item.name.clone()),
+                rotation: Some(UnitQuaternion(rotation.to_array())),
+                scale: Some(Vec3::splat(item.scale).into()),
+                ..Default::default()
+            });
+            add_child(&mut root, bone_node, item_node);
+
+            if !item.model.is_empty() {
+                match build_item_model(&mut root, &mut buffer, content, &item.model) {
+                    Ok(Some(model_node)) => add_child(&mut root, item_node, model_node),
+                    Ok(None) => eprintln!("WARNING: item model {} not found", item.model),
+                    Err(e) => eprintln!("WARNING: failed to load item model {}: {e:#}", item.model),
+                }
+            }
+
+            for light in &item.lights {
+                let light_node = build_light(&mut root, light, None, LightAnchor::Item);
+                add_child(&mut root, item_node, light_node);
+            }
+            let conditions = item
+                .melee_conditions
+                .iter()
+                .chain(&item.gun_conditions)
+                .chain(&item.ranged_conditions);
+            add_event_lights(&mut root, item_node, LightAnchor::Item, conditions);
+        }
+
+        // lights and effects
+        for boned_light in &self.lights {
+            let bone_node = skeleton.find_bone(&root, &boned_light.bone);
+            let light_node = build_light(&mut root, &boned_light.light, None, LightAnchor::Bone);
+            add_child(&mut root, bone_node, light_node);
+        }
+        add_event_lights(
+            &mut root,
+            skeleton.node,
+            LightAnchor::Character,
+            &self.conditions,
+        );
+        for boned_effect in &self.effects {
+            let bone_node = skeleton.find_bone(&root, &boned_effect.bone);
+            let effect_node = root.push(Node {
+                name: Some(boned_effect.effect.clone()),
+                ..Default::default()
+            });
+            add_child(&mut root, bone_node, effect_node);
+        }
+
+        // skinned meshes ignore their own transform, so the scale goes above the skeleton
+        let character_node = root.push(Node {
+            name: Some(self.name.clone()),
+            scale: Some(Vec3::splat(character_model.scale).into()),
+            children: Some(vec![skeleton.node]),
+            ..Default::default()
+        });
+
+        let mut scene_nodes = mesh_nodes;
+        scene_nodes.push(character_node);
+        let scene = root.push(Scene {
+            nodes: scene_nodes,
+            name: None,
+            extensions: Default::default(),
+            extras: Default::default(),
+        });
+        root.scene = Some(scene);
+
+        buffer.update_length(&mut root);
+        let json_string = serde_json::to_string(&root)?;
+
+        let glb = build_glb_bytes(json_string, buffer.data)?;
+        Ok(glb)
+    }
+}
+
+/// the bones of the animation skeleton that everything else is attached to
+struct Skeleton {
+    node: Index<Node>,
+    joints: Vec<Index<Node>>,
+}
+
+impl Skeleton {
+    /// finds the bone with the given name, falls back to the root bone with a warning
+    fn find_bone(&self, root: &Root, name: &str) -> Index<Node> {
+        self.joints
+            .iter()
+            .copied()
+            .find(|index| {
+                root.nodes[index.value()]
+                    .name
+                    .as_deref()
+                    .is_some_and(|bone| bone.eq_ignore_ascii_case(name))
+            })
+            .unwrap_or_else(|| {
+                eprintln!("WARNING: bone {name} not found in the animation skeleton");
+                self.node
+            })
+    }
+}
+
+/// builds the meshes of a character model skinned to the animation skeleton, returns the mesh
+/// nodes
+fn build_character_model(
+    root: &mut Root,
+    buffer: &mut FullBuffer,
+    content: &ContentLoader,
+    character_model: &CharacterModel,
+    skeleton: &Skeleton,
+) -> anyhow::Result<Vec<Index<Node>>> {
+    let (model_content, model_path) = content
+        .load(&character_model.model, "skinnedmodel")?
+        .ok_or_else(|| anyhow!("model {} not found", character_model.model))?;
+    let Content::SkinnedModel(model) = &model_content.primary_content else {
+        anyhow::bail!("model {} is not a skinned model", character_model.model);
+    };
+    let shared_content = &model_content.shared_content;
+
+    let transformed_model = TransformedModel::try_from(&model.model)?;
+    buffer.append_meshes(&transformed_model);
+    buffer.append_inverse_bind_matrices(shared_content);
+
+    let textures = TextureLoader::new(model_path.parent().unwrap_or(Path::new(".")));
+    let materials = build_materials(root, buffer, shared_content, &textures);
+    apply_tint(root, &materials, character_model.tint);
+
+    let mut mesh_nodes = Vec::new();
+    let mut part_nodes = Vec::new();
+    for (mesh_idx, mesh) in transformed_model.meshes.iter().enumerate() {
+        let (mesh_node, mesh_part_nodes) = build_mesh_parts(
+            root,
+            buffer,
+            &transformed_model,
+            mesh,
+            mesh_idx,
+            &materials,
+            None,
+        );
+        mesh_nodes.push(mesh_node);
+        part_nodes.extend_from_slice(&mesh_part_nodes);
+    }
+
+    // the joints follow the bones of the model so they line up with its inverse bind matrices
+    let joints = shared_content
+        .iter()
+        .filter_map(|content| match content {
+            Content::SkinnedModelBone(bone) => Some(bone),
+            _ => None,
+        })
+        .map(|bone| skeleton.find_bone(root, &bone.name))
+        .collect();
+    let skin = build_skin(root, buffer, skeleton.node, joints);
+    for index in &part_nodes {
+        root.nodes[index.value()].skin = Some(skin);
+    }
+
+    Ok(mesh_nodes)
+}
+
+/// builds the bones and meshes of an item model, returns its root bone
+fn build_item_model(
+    root: &mut Root,
+    buffer: &mut FullBuffer,
+    content: &ContentLoader,
+    reference: &str,
+) -> anyhow::Result<Option<Index<Node>>> {
+    let Some((model_content, model_path)) = content.load(reference, "model")? else {
+        return Ok(None);
+    };
+    let Content::Model(model) = &model_content.primary_content else {
+        anyhow::bail!("{} is not a model", model_path.display());
+    };
+
+    let transformed_model = TransformedModel::try_from(model)?;
+    buffer.append_meshes(&transformed_model);
+
+    let textures = TextureLoader::new(model_path.parent().unwrap_or(Path::new(".")));
+    let materials = build_materials(root, buffer, &model_content.shared_content, &textures);
+
+    let (root_bone_node, bone_nodes) = build_bones(root, model)?;
+    for (mesh_idx, mesh) in transformed_model.meshes.iter().enumerate() {
+        build_mesh_parts(
+            root,
+            buffer,
+            &transformed_model,
+            mesh,
+            mesh_idx,
+            &materials,
+            Some(&bone_nodes),
+        );
+    }
+
+    Ok(Some(root_bone_node))
+}
+
+#[derive(Serialize)]
+struct AnimationExtras<'a> {
+    clips: Vec<ClipUsage<'a>>,
+}
+
+/// how an animation set plays a skeleton clip
+#[derive(Serialize)]
+struct ClipUsage<'a> {
+    set: usize,
+    kind: &'a str,
+    speed: f32,
+    blend_time: f32,
+    loops: bool,
+    actions: Vec<ActionMarker<'a>>,
+}
+
+#[derive(Serialize)]
+struct ActionMarker<'a> {
+    #[serde(flatten)]
+    action: &'a AnimationAction,
+    /// seconds into the clip, left out when the skeleton has no clip to take the duration from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_time: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_time: Option<f32>,
+}
+
+/// animation sets refer to the clips of the skeleton by key. every set that plays a clip is
+/// added to the extras of its animation with the action timeline, action times are fractions of
+/// the clip duration so the times in seconds are added next to them
+fn add_animation_actions(root: &mut Root, shared_content: &[Content], sets: &[AnimationSet]) {
+    let mut usages: HashMap<usize, Vec<ClipUsage>> = HashMap::new();
+    for (set_index, set) in sets.iter().enumerate() {
+        for clip in &set.clips {
+            let animation = root.animations.iter().position(|animation| {
+                animation
+                    .name
+                    .as_deref()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(&clip.key))
+            });
+            let Some(animation) = animation else {
+                eprintln!(
+                    "WARNING: animation {} of set {set_index} not found in the animation skeleton",
+                    clip.key
+                );
+                continue;
+            };
+
+            let duration = shared_content.iter().find_map(|content| match content {
+                Content::SkinnedModelAnimationClip(skeleton_clip)
+                    if skeleton_clip.name.eq_ignore_ascii_case(&clip.key) =>
+                {
+                    Some(skeleton_clip.duration)
+                }
+                _ => None,
+            });
+            if duration.is_none() {
+                eprintln!(
+                    "WARNING: no clip duration for animation {} of set {set_index}, action times \
+                     are left as fractions",
+                    clip.key
+                );
+            }
+            let actions = clip
+                .actions
+                .iter()
+                .map(|action| ActionMarker {
+                    action,
+                    start_time: duration.map(|duration| action.start * duration),
+                    end_time: duration.map(|duration| action.end * duration),
+                })
+                .collect();
+
+            usages.entry(animation).or_default().push(ClipUsage {
+                set: set_index,
+                kind: &clip.kind,
+                speed: clip.speed,
+                blend_time: clip.blend_time,
+                loops: clip.loops,
+                actions,
+            });
+        }
+    }
+
+    for (animation, usages) in usages {
+        let json = serde_json::to_string(&AnimationExtras { clips: usages }).unwrap();
+        root.animations[animation].extras = Some(RawValue::from_string(json).unwrap());
+    }
+}
+
+/// adds the lights that events turn on to the entity they belong to, events carry no bone so
+/// the lights sit at the origin of the entity
+fn add_event_lights<'a>(
+    root: &mut Root,
+    parent: Index<Node>,
+    anchor: LightAnchor,
+    conditions: impl IntoIterator<Item = &'a EventConditions>,
+) {
+    for conditions in conditions {
+        for event in &conditions.events {
+            if let Event::Light(event) = event {
+                let light_node = build_light(root, &event.light, Some(conditions.kind), anchor);
+                add_child(root, parent, light_node);
+            }
+        }
+    }
+}
+
+/// multiplies the base color of the materials with the tint of the character model
+fn apply_tint(root: &mut Root, materials: &[Option<EffectMaterial>], tint: Color) {
+    let materials = materials
+        .iter()
+        .flatten()
+        .flat_map(|material| [Some(material.material), material.damaged])
+        .flatten();
+    for material in materials {
+        let factor = &mut root.materials[material.value()]
+            .pbr_metallic_roughness
+            .base_color_factor
+            .0;
+        factor[0] *= tint.r.clamp(0.0, 1.0);
+        factor[1] *= tint.g.clamp(0.0, 1.0);
+        factor[2] *= tint.b.clamp(0.0, 1.0);
+    }
+}
@@ -0,0 +1,358 @@
+use anyhow::Context;
+use glam::{Quat, Vec3};
+use gltf::json::{Index, Node, Root, Scene, scene::UnitQuaternion};
+use serde::Serialize;
+use serde_json::value::RawValue;
+
+use crate::content::{
+    Content,
+    level_model::{AnimatedLevelPart, BiTree, LevelEffect, LevelGeometry, LevelModel, Locator},
+    model::{BoundingSphere, Mesh, MeshPart, Model},
+};
+
+use super::{
+    FullBuffer, add_child, build_bones, build_glb_bytes, build_mesh_parts,
+    light::build_level_light,
+    material::{EffectMaterial, TextureLoader, build_materials},
+    transformed_model::TransformedModel,
+};
+
+/// settings of a bi tree kept in the extras of its node
+#[derive(Serialize)]
+struct TreeExtras {
+    visible: bool,
+    cast_shadows: bool,
+    sway: f32,
+    entity_influence: f32,
+    ground_level: f32,
+}
+
+impl From<&BiTree> for TreeExtras {
+    fn from(tree: &BiTree) -> Self {
+        TreeExtras {
+            visible: tree.visible,
+            cast_shadows: tree.cast_shadows,
+            sway: tree.sway,
+            entity_influence: tree.entity_influence,
+            ground_level: tree.ground_level,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct LocatorExtras {
+    radius: f32,
+}
+
+impl LevelModel {
+    /// builds one scene with the render geometry, animated parts and lights of the level. effects,
+    /// physics entities, trigger areas and locators become empty nodes named after them, trigger
+    /// areas are scaled to their side lengths so they can be used as unit boxes
+    pub fn to_glb(
+        &self,
+        shared_content: &[Content],
+        textures: &TextureLoader,
+    ) -> anyhow::Result<Vec<u8>> {
+        let Some(sections) = &self.sections else {
+            anyhow::bail!(
+                "level sections are only available for levels read from an xnb whose sections \
+                 could be parsed"
+            );
+        };
+
+        let mut root = Root::default();
+        let mut buffer = FullBuffer::new(&mut root);
+        let mut scene_nodes = Vec::new();
+
+        // geometry
+        let trees = self
+            .geometry
+            .trees
+            .iter()
+            .enumerate()
+            .map(|(i, tree)| (format!("tree_{i}"), &tree.geometry));
+        let liquids = sections
+            .liquids
+            .iter()
+            .enumerate()
+            .map(|(i, liquid)| (format!("liquid_{i}"), &liquid.geometry));
+        let force_fields = sections
+            .force_fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| (format!("force_field_{i}"), &field.geometry));
+        let pieces: Vec<_> = trees.chain(liquids).chain(force_fields).collect();
+        let geometry_nodes = build_geometry(&mut root, &mut buffer, &pieces, textures)
+            .context("failed to build level geometry")?;
+        for (tree, node) in self.geometry.trees.iter().zip(&geometry_nodes) {
+            root.nodes[node.value()].extras = Some(to_raw(&TreeExtras::from(tree)));
+        }
+        scene_nodes.push(group(&mut root, "geometry", geometry_nodes));
+
+        // animated parts use the effects in the shared content
+        if !sections.animated_parts.is_empty() {
+            let materials = build_materials(&mut root, &mut buffer, shared_content, textures);
+            let mut part_nodes = Vec::new();
+            for part in &sections.animated_parts {
+                let node = build_animated_part(&mut root, &mut buffer, part, &materials, textures)
+                    .with_context(|| format!("failed to build animated part {}", part.name))?;
+                part_nodes.push(node);
+            }
+            scene_nodes.push(group(&mut root, "animated_parts", part_nodes));
+        }
+
+        let light_nodes = sections
+            .lights
+            .iter()
+            .map(|light| {
+                let node = build_level_light(&mut root, light);
+                let node_mut = &mut root.nodes[node.value()];
+                node_mut.translation = Some(light.position.into());
+                node_mut.rotation = facing(light.direction);
+                node
+            })
+            .collect();
+        scene_nodes.push(group(&mut root, "lights", light_nodes));
+
+        let effect_nodes = sections
+            .effects
+            .iter()
+            .map(|effect| build_effect(&mut root, effect))
+            .collect();
+        scene_nodes.push(group(&mut root, "effects", effect_nodes));
+
+        let entity_nodes = sections
+            .physics_entities
+            .iter()
+            .map(|entity| {
+                root.push(Node {
+                    name: Some(entity.template.clone()),
+                    matrix: Some(entity.transform.transpose().to_cols_array()),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        scene_nodes.push(group(&mut root, "physics_entities", entity_nodes));
+
+        let trigger_nodes = sections
+            .trigger_areas
+            .iter()
+            .map(|area| {
+                root.push(Node {
+                    name: Some(area.name.clone()),
+                    translation: Some(area.position.into()),
+                    rotation: Some(UnitQuaternion(area.orientation.to_array())),
+                    scale: Some(area.side_lengths.into()),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        scene_nodes.push(group(&mut root, "trigger_areas", trigger_nodes));
+
+        let locator_nodes = sections
+            .locators
+            .iter()
+            .map(|locator| build_locator(&mut root, locator))
+            .collect();
+        scene_nodes.push(group(&mut root, "locators", locator_nodes));
+
+        let scene = root.push(Scene {
+            nodes: scene_nodes,
+            name: None,
+            extensions: Default::default(),
+            extras: Default::default(),
+        });
+        root.scene = Some(scene);
+
+        buffer.update_length(&mut root);
+        let json_string = serde_json::to_string(&root)?;
+
+        let glb = build_glb_bytes(json_string, buffer.data)?;
+        Ok(glb)
+    }
+}
+
+/// builds a mesh for every piece of geometry, the pieces are wrapped in a model so the regular
+/// model export can be reused. returns one node per piece
+fn build_geometry(
+    root: &mut Root,
+    buffer: &mut FullBuffer,
+    pieces: &[(String, &LevelGeometry)],
+    textures: &TextureLoader,
+) -> anyhow::Result<Vec<Index<Node>>> {
+    let materials = build_materials(
+        root,
+        buffer,
+        pieces.iter().map(|(_, geometry)| &geometry.effect),
+        textures,
+    );
+
+    let meshes = pieces
+        .iter()
+        .enumerate()
+        .map(|(i, (name, geometry))| Mesh {
+            name: name.clone(),
+            parent_bone_ref: 0,
+            bounds: BoundingSphere {
+                center: Vec3::ZERO,
+                radius: 0.0,
+            },
+            vertex_buffer: geometry.vertex_buffer.clone(),
+            index_buffer: geometry.index_buffer.clone(),
+            parts: vec![MeshPart {
+                stream_offset: 0,
+                base_vertex: 0,
+                vertex_count: geometry.vertex_count as u32,
+                start_index: 0,
+                primitive_count: geometry.primitive_count() as u32,
+                vertex_decl_index: i as u32,
+                tag: 0,
+                // effects without a material are exported untextured
+                shared_content_material_idx: if materials[i].is_some() {
+                    i as i32 + 1
+                } else {
+                    0
+                },
+            }],
+            tag: 0,
+        })
+        .collect();
+    let model = Model {
+        bones: Vec::new(),
+        bones_hierarchy: Vec::new(),
+        vertex_decls: pieces
+            .iter()
+            .map(|(_, geometry)| geometry.vertex_decl.clone())
+            .collect(),
+        meshes,
+        root_bone_ref: 0,
+        tag: 0,
+    };
+
+    let transformed_model = TransformedModel::try_from(&model)?;
+    buffer.append_meshes(&transformed_model);
+
+    let nodes = transformed_model
+        .meshes
+        .iter()
+        .enumerate()
+        .map(|(mesh_idx, mesh)| {
+            let (node, _) = build_mesh_parts(
+                root,
+                buffer,
+                &transformed_model,
+                mesh,
+                mesh_idx,
+                &materials,
+                None,
+            );
+            root.nodes[node.value()].name = Some(mesh.name.clone());
+            node
+        })
+        .collect();
+    Ok(nodes)
+}
+
+/// builds the model of the part in its rest pose along with everything attached to it
+fn build_animated_part(
+    root: &mut Root,
+    buffer: &mut FullBuffer,
+    part: &AnimatedLevelPart,
+    materials: &[Option<EffectMaterial>],
+    textures: &TextureLoader,
+) -> anyhow::Result<Index<Node>> {
+    let transformed_model = TransformedModel::try_from(&part.model)?;
+    buffer.append_meshes(&transformed_model);
+    let (root_bone_node, bone_nodes) = build_bones(root, &part.model)?;
+    for (mesh_idx, mesh) in transformed_model.meshes.iter().enumerate() {
+        build_mesh_parts(
+            root,
+            buffer,
+            &transformed_model,
+            mesh,
+            mesh_idx,
+            materials,
+            Some(&bone_nodes),
+        );
+    }
+
+    let part_node = root.push(Node {
+        name: Some(part.name.clone()),
+        children: Some(vec![root_bone_node]),
+        ..Default::default()
+    });
+
+    let liquids: Vec<_> = part
+        .liquids
+        .iter()
+        .enumerate()
+        .map(|(i, liquid)| (format!("liquid_{i}"), &liquid.geometry))
+        .collect();
+    for node in build_geometry(root, buffer, &liquids, textures)? {
+        add_child(root, part_node, node);
+    }
+    for light in &part.lights {
+        let node = root.push(Node {
+            name: Some(light.name.clone()),
+            matrix: Some(light.transform.transpose().to_cols_array()),
+            ..Default::default()
+        });
+        add_child(root, part_node, node);
+    }
+    for effect in &part.effects {
+        let node = build_effect(root, effect);
+        add_child(root, part_node, node);
+    }
+    for locator in &part.locators {
+        let node = build_locator(root, locator);
+        add_child(root, part_node, node);
+    }
+    for child in &part.children {
+        let node = build_animated_part(root, buffer, child, materials, textures)
+            .with_context(|| format!("failed to build animated part {}", child.name))?;
+        add_child(root, part_node, node);
+    }
+
+    Ok(part_node)
+}
+
+fn build_effect(root: &mut Root, effect: &LevelEffect) -> Index<Node> {
+    root.push(Node {
+        name: Some(effect.id.clone()),
+        translation: Some(effect.position.into()),
+        rotation: facing(effect.forward),
+        extras: Some(to_raw(effect)),
+        ..Default::default()
+    })
+}
+
+fn build_locator(root: &mut Root, locator: &Locator) -> Index<Node> {
+    root.push(Node {
+        name: Some(locator.name.clone()),
+        matrix: Some(locator.transform.transpose().to_cols_array()),
+        extras: Some(to_raw(&LocatorExtras {
+            radius: locator.radius,
+        })),
+        ..Default::default()
+    })
+}
+
+/// rotation that points the -z axis of a node along `direction`, as gltf lights and cameras
+/// expect
+fn facing(direction: Vec3) -> Option<UnitQuaternion> {
+    let direction = direction.normalize_or_zero();
+    (direction != Vec3::ZERO)
+        .then(|| UnitQuaternion(Quat::from_rotation_arc(Vec3::NEG_Z, direction).to_array()))
+}
+
+fn group(root: &mut Root, name: &str, children: Vec<Index<Node>>) -> Index<Node> {
+    root.push(Node {
+        name: Some(name.to_string()),
+        children: (!children.is_empty()).then_some(children),
+        ..Default::default()
+    })
+}
+
+fn to_raw(value: &impl Serialize) -> Box<RawValue> {
+    RawValue::from_string(serde_json::to_string(value).unwrap()).unwrap()
+}
@@ -0,0 +1,146 @@
+use std::f32::consts::FRAC_PI_2;
+
+use gltf::json::{
+    Index, Node, Root,
+    extensions::{
+        self,
+        scene::khr_lights_punctual::{self, KhrLightsPunctual, Type},
+    },
+    validation::Checked,
+};
+use serde::Serialize;
+use serde_json::value::RawValue;
+
+use crate::content::{
+    event::EventConditionKind,
+    level_model::{LevelLight, LevelLightKind},
+    light::Light,
+};
+
+const KHR_LIGHTS_PUNCTUAL: &str = "KHR_lights_punctual";
+
+/// the full light is kept in the extras so engines can reproduce the variation
+#[derive(Serialize)]
+struct LightExtras<'a> {
+    #[serde(flatten)]
+    light: &'a Light,
+    /// lights spawned by events only turn on when the condition is met
+    #[serde(skip_serializing_if = "Option::is_none")]
+    condition: Option<EventConditionKind>,
+    anchor: LightAnchor,
+}
+
+/// what the light node is attached to. only character lights name a bone, item lights and the
+/// lights events spawn have no position of their own and sit at the origin of their owner
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum LightAnchor {
+    Bone,
+    Item,
+    Character,
+}
+
+/// adds the light as a `KHR_lights_punctual` point light and returns the node that holds it
+pub(super) fn build_light(
+    root: &mut Root,
+    light: &Light,
+    condition: Option<EventConditionKind>,
+    anchor: LightAnchor,
+) -> Index<Node> {
+    // gltf colors are in 0..1, brighter xna colors are moved into the intensity
+    let color = light.diffuse_color;
+    let intensity = color.r.max(color.g).max(color.b).max(1.0);
+
+    let extras = LightExtras {
+        light,
+        condition,
+        anchor,
+    };
+    let json = serde_json::to_string(&extras).unwrap();
+
+    add_light(
+        root,
+        "light",
+        khr_lights_punctual::Light {
+            color: [
+                (color.r / intensity).max(0.0),
+                (color.g / intensity).max(0.0),
+                (color.b / intensity).max(0.0),
+            ],
+            extensions: None,
+            extras: Some(RawValue::from_string(json).unwrap()),
+            intensity,
+            name: None,
+            range: (light.radius > 0.0).then_some(light.radius),
+            spot: None,
+            type_: Checked::Valid(Type::Point),
+        },
+    )
+}
+
+/// adds a level light and returns the node that holds it, the node still has to be placed at
+/// the position and direction of the light
+pub(super) fn build_level_light(root: &mut Root, light: &LevelLight) -> Index<Node> {
+    let color = light.diffuse_color;
+    let intensity = color.r.max(color.g).max(color.b).max(1.0);
+
+    let (type_, spot) = match light.kind {
+        LevelLightKind::Point => (Type::Point, None),
+        LevelLightKind::Directional => (Type::Directional, None),
+        LevelLightKind::Spot => (
+            Type::Spot,
+            Some(khr_lights_punctual::Spot {
+                inner_cone_angle: 0.0,
+                outer_cone_angle: light.cutoff_angle.clamp(0.0, FRAC_PI_2),
+            }),
+        ),
+    };
+
+    let json = serde_json::to_string(light).unwrap();
+    add_light(
+        root,
+        &light.name,
+        khr_lights_punctual::Light {
+            color: [
+                (color.r / intensity).max(0.0),
+                (color.g / intensity).max(0.0),
+                (color.b / intensity).max(0.0),
+            ],
+            extensions: None,
+            extras: Some(RawValue::from_string(json).unwrap()),
+            intensity,
+            name: None,
+            range: (light.kind != LevelLightKind::Directional && light.reach > 0.0)
+                .then_some(light.reach),
+            spot,
+            type_: Checked::Valid(type_),
+        },
+    )
+}
+
+fn add_light(root: &mut Root, name: &str, light: khr_lights_punctual::Light) -> Index<Node> {
+    let lights = &mut root
+        .extensions
+        .get_or_insert_default()
+        .khr_lights_punctual
+        .get_or_insert_default()
+        .lights;
+    let index = Index::new(lights.len() as u32);
+    lights.push(light);
+
+    if !root
+        .extensions_used
+        .iter()
+        .any(|extension| extension == KHR_LIGHTS_PUNCTUAL)
+    {
+        root.extensions_used.push(KHR_LIGHTS_PUNCTUAL.to_string());
+    }
+
+    root.push(Node {
+        name: Some(name.to_string()),
+        extensions: Some(extensions::scene::Node {
+            khr_lights_punctual: Some(KhrLightsPunctual { light: index }),
+        }),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,420 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use gltf::json::{
+    Image, Index, Material, Root, Texture,
+    buffer::View,
+    extensions::{
+        mesh::{self, Mapping},
+        scene::khr_materials_variants::Variant,
+    },
+    image::MimeType,
+    material::{
+        AlphaCutoff, AlphaMode, EmissiveFactor, NormalTexture, PbrBaseColorFactor,
+        PbrMetallicRoughness, StrengthFactor,
+    },
+    texture::Info,
+    validation::{Checked, USize64},
+};
+use serde_json::value::RawValue;
+
+use crate::{
+    content::{Content, color::Color},
+    xnb::Xnb,
+};
+
+use super::FullBuffer;
+
+/// loads the textures that effects reference. references are relative to the directory of the
+/// xnb that contains the effect, so the loader needs to know where that xnb came from
+#[derive(Debug, Default, Clone)]
+pub struct TextureLoader {
+    directory: Option<PathBuf>,
+}
+
+impl TextureLoader {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        TextureLoader {
+            directory: Some(directory.into()),
+        }
+    }
+
+    /// finds the referenced texture xnb and encodes it as png, returns `None` if it does not exist
+    pub fn load_png(&self, reference: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let Some(path) = self.resolve(reference) else {
+            return Ok(None);
+        };
+
+        if path.extension().is_some_and(|extension| extension == "png") {
+            let png = std::fs::read(&path).context("failed to read texture")?;
+            return Ok(Some(png));
+        }
+
+        let file = File::open(&path).context("failed to open texture")?;
+        let xnb = Xnb::parse(&mut BufReader::new(file)).context("failed to parse xnb header")?;
+        let mut content = xnb.content()?;
+        content
+            .convert_from_xbox360()
+            .context("failed to convert xbox 360 texture")?;
+        let Content::Texture2D(texture) = &content.primary_content else {
+            anyhow::bail!("{} is not a texture", path.display());
+        };
+        let png = texture.to_png().context("failed to encode png")?;
+        Ok(Some(png))
+    }
+
+    /// looks for the reference next to the model first, then in every parent directory since
+    /// some references are relative to the content root instead. extracted content only has the
+    /// png that was saved next to the texture json
+    fn resolve(&self, reference: &str) -> Option<PathBuf> {
+        let directory = self.directory.as_deref()?;
+        let reference = reference.replace('\\', "/");
+        let candidates = [
+            format!("{reference}.xnb"),
+            format!("{reference}.texture2d.png"),
+        ];
+        directory
+            .ancestors()
+            .flat_map(|directory| candidates.iter().map(|file| directory.join(file)))
+            .find(|path| path.is_file())
+            .map(normalize)
+    }
+}
+
+fn normalize(path: PathBuf) -> PathBuf {
+    path.canonicalize().unwrap_or(path)
+}
+
+/// pbr properties shared by all effects
+struct MaterialDesc<'a> {
+    base_color: Color,
+    alpha: f32,
+    diffuse: Option<&'a str>,
+    normal: Option<&'a str>,
+    /// textures of the damaged look, the pristine ones are used where these are missing
+    damage_diffuse: Option<&'a str>,
+    damage_normal: Option<&'a str>,
+    normal_scale: f32,
+    /// emissive color, multiplied with the diffuse texture if the effect has one
+    emissive: Color,
+    specular_power: f32,
+    alpha_mode: AlphaMode,
+}
+
+impl<'a> MaterialDesc<'a> {
+    fn new(content: &'a Content) -> Option<Self> {
+        let texture = |path: &'a str| (!path.is_empty()).then_some(path);
+        let desc = match content {
+            Content::BasicEffect(effect) => MaterialDesc {
+                base_color: effect.diffuse_color,
+                alpha: effect.alpha,
+                diffuse: texture(&effect.texture),
+                normal: None,
+                damage_diffuse: None,
+                damage_normal: None,
+                normal_scale: 1.0,
+                emissive: effect.emissive_color,
+                specular_power: effect.specular_power,
+                alpha_mode: blend_if_translucent(effect.alpha),
+            },
+            Content::AdditiveEffect(effect) => MaterialDesc {
+                base_color: effect.color_tint,
+                alpha: 1.0,
+                diffuse: effect
+                    .texture_enabled
+                    .then(|| texture(&effect.texture))
+                    .flatten(),
+                normal: None,
+                damage_diffuse: None,
+                damage_normal: None,
+                normal_scale: 1.0,
+                emissive: effect.color_tint,
+                specular_power: 0.0,
+                alpha_mode: AlphaMode::Blend,
+            },
+            Content::RenderDeferredEffect(effect) => {
+                let material = &effect.material_0;
+                let alpha_mode = if material.alpha_mask_enabled {
+                    AlphaMode::Mask
+                } else if material.diffuse_texture_alpha_disabled {
+                    AlphaMode::Opaque
+                } else {
+                    blend_if_translucent(effect.alpha)
+                };
+                MaterialDesc {
+                    base_color: material.diffuse_color,
+                    alpha: effect.alpha,
+                    diffuse: texture(&material.diffuse_texture),
+                    normal: texture(&material.normal_texture),
+                    damage_diffuse: None,
+                    damage_normal: None,
+                    normal_scale: material.normal_power,
+                    emissive: emissive_amount(material.emissive_amount),
+                    specular_power: material.spec_power,
+                    alpha_mode,
+                }
+            }
+            Content::SkinnedModelBasicEffect(effect) => MaterialDesc {
+                base_color: effect.diffuse_color,
+                alpha: effect.alpha,
+                diffuse: effect.map_0_diffuse.as_deref().and_then(texture),
+                normal: effect.normal_map.as_deref().and_then(texture),
+                damage_diffuse: effect
+                    .map_0_damage
+                    .as_deref()
+                    .and_then(texture)
+                    .or_else(|| effect.map_1_damage.as_deref().and_then(texture)),
+                damage_normal: None,
+                normal_scale: 1.0,
+                emissive: emissive_amount(effect.emissive_amount),
+                specular_power: effect.specular_power,
+                alpha_mode: blend_if_translucent(effect.alpha),
+            },
+            Content::SkinnedModelDeferredNormalMappedEffect(effect) => MaterialDesc {
+                base_color: effect.diffuse_color,
+                alpha: 1.0,
+                diffuse: texture(&effect.diffuse_texture),
+                normal: texture(&effect.normal_texture),
+                damage_diffuse: texture(&effect.damage_texture),
+                damage_normal: texture(&effect.normal_damage_texture),
+                normal_scale: effect.normal_power,
+                emissive: emissive_amount(effect.emissive_amount),
+                specular_power: effect.specular_power,
+                alpha_mode: AlphaMode::Opaque,
+            },
+            _ => return None,
+        };
+        Some(desc)
+    }
+}
+
+/// effects with an emissive amount glow in the color of their diffuse texture
+fn emissive_amount(amount: f32) -> Color {
+    let amount = amount.clamp(0.0, 1.0);
+    Color {
+        r: amount,
+        g: amount,
+        b: amount,
+    }
+}
+
+fn blend_if_translucent(alpha: f32) -> AlphaMode {
+    if alpha < 1.0 {
+        AlphaMode::Blend
+    } else {
+        AlphaMode::Opaque
+    }
+}
+
+/// the material of an effect, along with its damaged look if the effect has damage textures
+#[derive(Debug, Clone, Copy)]
+pub(super) struct EffectMaterial {
+    pub material: Index<Material>,
+    pub damaged: Option<Index<Material>>,
+}
+
+impl EffectMaterial {
+    /// maps the damaged material to the "damaged" `KHR_materials_variants` variant
+    pub fn variants(&self, root: &mut Root) -> Option<mesh::KhrMaterialsVariants> {
+        let damaged = self.damaged?;
+        let variants = &mut root
+            .extensions
+            .get_or_insert_default()
+            .khr_materials_variants
+            .get_or_insert_default()
+            .variants;
+        let variant = match variants.iter().position(|variant| variant.name == DAMAGED) {
+            Some(variant) => variant,
+            None => {
+                variants.push(Variant {
+                    name: DAMAGED.to_string(),
+                });
+                variants.len() - 1
+            }
+        };
+
+        if !root
+            .extensions_used
+            .iter()
+            .any(|extension| extension == KHR_MATERIALS_VARIANTS)
+        {
+            root.extensions_used
+                .push(KHR_MATERIALS_VARIANTS.to_string());
+        }
+
+        Some(mesh::KhrMaterialsVariants {
+            mappings: vec![Mapping {
+                material: damaged.value() as u32,
+                variants: vec![variant as u32],
+            }],
+        })
+    }
+}
+
+const KHR_MATERIALS_VARIANTS: &str = "KHR_materials_variants";
+const DAMAGED: &str = "damaged";
+
+/// maps every effect in the shared content to a pbr material, the referenced textures are
+/// embedded in the binary buffer and the effect itself is kept in the extras
+pub(super) fn build_materials<'a>(
+    root: &mut Root,
+    buffer: &mut FullBuffer,
+    shared_content: impl IntoIterator<Item = &'a Content>,
+    textures: &TextureLoader,
+) -> Vec<Option<EffectMaterial>> {
+    let mut embedded = HashMap::new();
+    let materials = shared_content
+        .into_iter()
+        .map(|content| {
+            let desc = MaterialDesc::new(content)?;
+            let mut embed = |root: &mut Root, reference: Option<&str>| {
+                let reference = reference?;
+                *embedded
+                    .entry(reference.to_string())
+                    .or_insert_with(|| embed_texture(root, buffer, textures, reference))
+            };
+            let diffuse = embed(root, desc.diffuse);
+            let normal = embed(root, desc.normal);
+
+            let json = serde_json::to_string(content).unwrap();
+            let material = build_material(root, &desc, diffuse, normal, &json);
+
+            let damaged =
+                (desc.damage_diffuse.is_some() || desc.damage_normal.is_some()).then(|| {
+                    let damage_diffuse = embed(root, desc.damage_diffuse).or(diffuse);
+                    let damage_normal = embed(root, desc.damage_normal).or(normal);
+                    build_material(root, &desc, damage_diffuse, damage_normal, &json)
+                });
+
+            Some(EffectMaterial { material, damaged })
+        })
+        .collect();
+
+    root.buffers[buffer.index.value()].byte_length = USize64(buffer.data.len() as u64);
+    materials
+}
+
+fn build_material(
+    root: &mut Root,
+    desc: &MaterialDesc,
+    diffuse: Option<Index<Texture>>,
+    normal: Option<Index<Texture>>,
+    json: &str,
+) -> Index<Material> {
+    let base_color_texture = diffuse.map(texture_info);
+    let emissive_factor = [
+        desc.emissive.r.clamp(0.0, 1.0),
+        desc.emissive.g.clamp(0.0, 1.0),
+        desc.emissive.b.clamp(0.0, 1.0),
+    ];
+    let emissive_texture = emissive_factor
+        .iter()
+        .any(|v| *v > 0.0)
+        .then_some(diffuse)
+        .flatten()
+        .map(texture_info);
+    let normal_texture = normal.map(|index| NormalTexture {
+        index,
+        scale: desc.normal_scale,
+        tex_coord: 0,
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+
+    root.push(Material {
+        pbr_metallic_roughness: PbrMetallicRoughness {
+            base_color_factor: PbrBaseColorFactor([
+                desc.base_color.r.clamp(0.0, 1.0),
+                desc.base_color.g.clamp(0.0, 1.0),
+                desc.base_color.b.clamp(0.0, 1.0),
+                desc.alpha.clamp(0.0, 1.0),
+            ]),
+            base_color_texture,
+            metallic_factor: StrengthFactor(0.0),
+            roughness_factor: StrengthFactor(roughness(desc.specular_power)),
+            ..Default::default()
+        },
+        normal_texture,
+        emissive_texture,
+        emissive_factor: EmissiveFactor(emissive_factor),
+        alpha_cutoff: (desc.alpha_mode == AlphaMode::Mask).then_some(AlphaCutoff(0.5)),
+        alpha_mode: Checked::Valid(desc.alpha_mode),
+        extras: Some(RawValue::from_string(json.to_string()).unwrap()),
+        ..Default::default()
+    })
+}
+
+/// appends the referenced texture to the buffer as png, missing or broken textures are skipped
+/// with a warning so the model can still be exported
+fn embed_texture(
+    root: &mut Root,
+    buffer: &mut FullBuffer,
+    textures: &TextureLoader,
+    reference: &str,
+) -> Option<Index<Texture>> {
+    let png = match textures.load_png(reference) {
+        Ok(Some(png)) => png,
+        Ok(None) => {
+            eprintln!("WARNING: texture {reference} not found");
+            return None;
+        }
+        Err(e) => {
+            eprintln!("WARNING: failed to load texture {reference}: {e:#}");
+            return None;
+        }
+    };
+
+    let offset = buffer.data.len();
+    buffer.data.extend_from_slice(&png);
+    while !buffer.data.len().is_multiple_of(4) {
+        buffer.data.push(0);
+    }
+
+    let view = root.push(View {
+        buffer: buffer.index,
+        byte_length: USize64(png.len() as u64),
+        byte_offset: Some(USize64(offset as u64)),
+        byte_stride: None,
+        target: None,
+        name: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+    let name = Path::new(&reference.replace('\\', "/"))
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned());
+    let image = root.push(Image {
+        buffer_view: Some(view),
+        mime_type: Some(MimeType("image/png".to_string())),
+        name: name.clone(),
+        uri: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+    Some(root.push(Texture {
+        name,
+        sampler: None,
+        source: image,
+        extensions: Default::default(),
+        extras: Default::default(),
+    }))
+}
+
+fn texture_info(index: Index<Texture>) -> Info {
+    Info {
+        index,
+        tex_coord: 0,
+        extensions: Default::default(),
+        extras: Default::default(),
+    }
+}
+
+/// rough conversion from a blinn-phong exponent to pbr roughness
+fn roughness(specular_power: f32) -> f32 {
+    (2.0 / (specular_power.max(0.0) + 2.0)).sqrt()
+}
@@ -9,14 +9,14 @@ use gltf::{
     Glb, Semantic,
     binary::Header,
     json::{
-        self, Accessor, Buffer, Index, Material, Node, Root,
+        self, Accessor, Buffer, Index, Node, Root,
         accessor::{ComponentType, GenericComponentType, Type},
         buffer::{Stride, Target, View},
         mesh::Primitive,
         validation::{Checked, USize64},
     },
 };
-use serde_json::value::RawValue;
+use material::EffectMaterial;
 use transformed_model::{TransformedModel, TransformedVertexDeclaration};
 
 use crate::content::{
@@ -25,8 +25,13 @@ use crate::content::{
 };
 
 pub mod basic;
+pub mod character;
+pub mod level;
+mod light;
+pub mod material;
 pub mod skinned;
 pub mod transformed_model;
+pub mod xna40;
 
 fn pad_to_multiple_of_four(n: usize) -> usize {
     (n + 3) & !3
@@ -35,7 +40,7 @@ fn pad_to_multiple_of_four(n: usize) -> usize {
 fn build_glb_bytes(json: String, mut bin: Vec<u8>) -> anyhow::Result<Vec<u8>> {
     let json_padded_len = pad_to_multiple_of_four(json.len());
 
-    while bin.len() % 4 != 0 {
+    while !bin.len().is_multiple_of(4) {
         bin.push(0);
     }
 
@@ -55,27 +60,11 @@ fn build_glb_bytes(json: String, mut bin: Vec<u8>) -> anyhow::Result<Vec<u8>> {
     Ok(bytes)
 }
 
-fn build_materials(root: &mut Root, shared_content: &[Content]) -> Vec<Option<Index<Material>>> {
-    let materials: Vec<Option<Index<Material>>> = shared_content
-        .iter()
-        .map(|content| match content {
-            Content::RenderDeferredEffect(..)
-            | Content::AdditiveEffect(..)
-            | Content::BasicEffect(..)
-            | Content::SkinnedModelBasicEffect(..)
-            | Content::SkinnedModelDeferredNormalMappedEffect(..) => {
-                let json = serde_json::to_string(content).unwrap();
-                let material = root.push(Material {
-                    extras: Some(RawValue::from_string(json).unwrap()),
-                    ..Default::default()
-                });
-                Some(material)
-            }
-            _ => None,
-        })
-        .collect();
-
-    materials
+fn add_child(root: &mut Root, parent: Index<Node>, child: Index<Node>) {
+    root.nodes[parent.value()]
+        .children
+        .get_or_insert_default()
+        .push(child);
 }
 
 fn build_bones(root: &mut Root, model: &Model) -> anyhow::Result<(Index<Node>, Vec<Index<Node>>)> {
@@ -137,131 +126,168 @@ fn build_buffer(
     model: &TransformedModel,
     shared_content: &[Content],
 ) -> FullBuffer {
-    let mut buffer_data = Vec::new();
-    let mut vertex_offsets = Vec::new();
-    let mut index_offsets = Vec::new();
+    let mut buffer = FullBuffer::new(root);
+    buffer.append_meshes(model);
+    buffer.append_inverse_bind_matrices(shared_content);
+    buffer.append_animations(shared_content);
+    buffer.update_length(root);
+    buffer
+}
 
-    for mesh in &model.meshes {
-        vertex_offsets.push(buffer_data.len());
-        buffer_data.extend_from_slice(&mesh.vertex_buffer.data);
+impl FullBuffer {
+    /// pushes an empty buffer, data is added with the `append_*` functions so several models
+    /// can share the single binary chunk of a glb
+    fn new(root: &mut Root) -> Self {
+        let index = root.push(Buffer {
+            byte_length: USize64(0),
+            name: None,
+            uri: None,
+            extensions: Default::default(),
+            extras: Default::default(),
+        });
+
+        FullBuffer {
+            index,
+            data: Vec::new(),
+            vertex_offsets: Vec::new(),
+            index_offsets: Vec::new(),
+            inverse_bind_matrices: OffsetCount {
+                offset: 0,
+                count: 0,
+            },
+            animation_timestamp_offsets: HashMap::new(),
+            animation_translation_offsets: HashMap::new(),
+            animation_orientation_offsets: HashMap::new(),
+            animation_scale_offsets: HashMap::new(),
+        }
+    }
 
-        index_offsets.push(buffer_data.len());
-        let reversed_indices = reverse_winding(&mesh.index_buffer);
-        buffer_data.extend_from_slice(&reversed_indices.data);
+    fn pad(&mut self) {
+        while !self.data.len().is_multiple_of(4) {
+            self.data.push(0);
+        }
+    }
+
+    fn update_length(&self, root: &mut Root) {
+        root.buffers[self.index.value()].byte_length = USize64(self.data.len() as u64);
     }
 
-    let inverse_bind_matrices_offset = buffer_data.len();
-    let mut inverse_bind_matrices_count = 0;
-    for content in shared_content {
-        if let Content::SkinnedModelBone(bone) = content {
-            buffer_data.extend_from_slice(bytemuck::cast_slice(&[bone
-                .inverse_bind_pose_transform
-                .transpose()]));
-            inverse_bind_matrices_count += 1;
+    /// replaces the vertex and index offsets with the ones of `model`
+    fn append_meshes(&mut self, model: &TransformedModel) {
+        self.pad();
+        self.vertex_offsets.clear();
+        self.index_offsets.clear();
+
+        for mesh in &model.meshes {
+            self.vertex_offsets.push(self.data.len());
+            self.data.extend_from_slice(&mesh.vertex_buffer.data);
+
+            self.index_offsets.push(self.data.len());
+            let reversed_indices = reverse_winding(&mesh.index_buffer);
+            self.data.extend_from_slice(&reversed_indices.data);
         }
     }
-    let inverse_bind_matrices = OffsetCount {
-        offset: inverse_bind_matrices_offset,
-        count: inverse_bind_matrices_count,
-    };
 
-    while buffer_data.len() % 4 != 0 {
-        buffer_data.push(0);
+    /// replaces the inverse bind matrices with the bones in `shared_content`
+    fn append_inverse_bind_matrices(&mut self, shared_content: &[Content]) {
+        self.pad();
+        let offset = self.data.len();
+        let mut count = 0;
+        for content in shared_content {
+            if let Content::SkinnedModelBone(bone) = content {
+                self.data.extend_from_slice(bytemuck::cast_slice(&[bone
+                    .inverse_bind_pose_transform
+                    .transpose()]));
+                count += 1;
+            }
+        }
+        self.inverse_bind_matrices = OffsetCount { offset, count };
     }
 
-    let mut animation_timestamp_offsets = HashMap::new();
-    let mut animation_translation_offsets = HashMap::new();
-    let mut animation_orientation_offsets = HashMap::new();
-    let mut animation_scale_offsets = HashMap::new();
-    for content in shared_content {
-        if let Content::SkinnedModelAnimationClip(anim) = content {
-            let mut target_timestamp_offsets = HashMap::new();
-            for (target_node_name, keyframes) in &anim.channels {
-                target_timestamp_offsets.insert(
-                    target_node_name.clone(),
-                    OffsetCount {
-                        offset: buffer_data.len(),
-                        count: keyframes.len(),
-                    },
-                );
-                for keyframe in keyframes {
-                    buffer_data.extend_from_slice(keyframe.time.to_le_bytes().as_slice());
+    /// replaces the animation offsets with the clips in `shared_content`
+    fn append_animations(&mut self, shared_content: &[Content]) {
+        self.pad();
+        self.animation_timestamp_offsets.clear();
+        self.animation_translation_offsets.clear();
+        self.animation_orientation_offsets.clear();
+        self.animation_scale_offsets.clear();
+
+        for content in shared_content {
+            if let Content::SkinnedModelAnimationClip(anim) = content {
+                let mut target_timestamp_offsets = HashMap::new();
+                for (target_node_name, keyframes) in &anim.channels {
+                    target_timestamp_offsets.insert(
+                        target_node_name.clone(),
+                        OffsetCount {
+                            offset: self.data.len(),
+                            count: keyframes.len(),
+                        },
+                    );
+                    for keyframe in keyframes {
+                        self.data
+                            .extend_from_slice(keyframe.time.to_le_bytes().as_slice());
+                    }
                 }
-            }
-            assert!(!animation_timestamp_offsets.contains_key(&anim.name));
-            animation_timestamp_offsets.insert(anim.name.clone(), target_timestamp_offsets);
-
-            let mut target_translation_offsets = HashMap::new();
-            for (target_node_name, keyframes) in &anim.channels {
-                target_translation_offsets.insert(
-                    target_node_name.clone(),
-                    OffsetCount {
-                        offset: buffer_data.len(),
-                        count: keyframes.len(),
-                    },
-                );
-                for keyframe in keyframes {
-                    buffer_data
-                        .extend_from_slice(bytemuck::cast_slice(&[keyframe.pose.translation]));
+                assert!(!self.animation_timestamp_offsets.contains_key(&anim.name));
+                self.animation_timestamp_offsets
+                    .insert(anim.name.clone(), target_timestamp_offsets);
+
+                let mut target_translation_offsets = HashMap::new();
+                for (target_node_name, keyframes) in &anim.channels {
+                    target_translation_offsets.insert(
+                        target_node_name.clone(),
+                        OffsetCount {
+                            offset: self.data.len(),
+                            count: keyframes.len(),
+                        },
+                    );
+                    for keyframe in keyframes {
+                        self.data
+                            .extend_from_slice(bytemuck::cast_slice(&[keyframe.pose.translation]));
+                    }
                 }
-            }
-            assert!(!animation_translation_offsets.contains_key(&anim.name));
-            animation_translation_offsets.insert(anim.name.clone(), target_translation_offsets);
-
-            let mut target_orientation_offsets = HashMap::new();
-            for (target_node_name, keyframes) in &anim.channels {
-                target_orientation_offsets.insert(
-                    target_node_name.clone(),
-                    OffsetCount {
-                        offset: buffer_data.len(),
-                        count: keyframes.len(),
-                    },
-                );
-                for keyframe in keyframes {
-                    buffer_data
-                        .extend_from_slice(bytemuck::cast_slice(&[keyframe.pose.orientation]));
+                assert!(!self.animation_translation_offsets.contains_key(&anim.name));
+                self.animation_translation_offsets
+                    .insert(anim.name.clone(), target_translation_offsets);
+
+                let mut target_orientation_offsets = HashMap::new();
+                for (target_node_name, keyframes) in &anim.channels {
+                    target_orientation_offsets.insert(
+                        target_node_name.clone(),
+                        OffsetCount {
+                            offset: self.data.len(),
+                            count: keyframes.len(),
+                        },
+                    );
+                    for keyframe in keyframes {
+                        self.data
+                            .extend_from_slice(bytemuck::cast_slice(&[keyframe.pose.orientation]));
+                    }
                 }
-            }
-            assert!(!animation_orientation_offsets.contains_key(&anim.name));
-            animation_orientation_offsets.insert(anim.name.clone(), target_orientation_offsets);
-
-            let mut target_scale_offsets = HashMap::new();
-            for (target_node_name, keyframes) in &anim.channels {
-                target_scale_offsets.insert(
-                    target_node_name.clone(),
-                    OffsetCount {
-                        offset: buffer_data.len(),
-                        count: keyframes.len(),
-                    },
-                );
-                for keyframe in keyframes {
-                    buffer_data.extend_from_slice(bytemuck::cast_slice(&[keyframe.pose.scale]));
+                assert!(!self.animation_orientation_offsets.contains_key(&anim.name));
+                self.animation_orientation_offsets
+                    .insert(anim.name.clone(), target_orientation_offsets);
+
+                let mut target_scale_offsets = HashMap::new();
+                for (target_node_name, keyframes) in &anim.channels {
+                    target_scale_offsets.insert(
+                        target_node_name.clone(),
+                        OffsetCount {
+                            offset: self.data.len(),
+                            count: keyframes.len(),
+                        },
+                    );
+                    for keyframe in keyframes {
+                        self.data
+                            .extend_from_slice(bytemuck::cast_slice(&[keyframe.pose.scale]));
+                    }
                 }
+                assert!(!self.animation_scale_offsets.contains_key(&anim.name));
+                self.animation_scale_offsets
+                    .insert(anim.name.clone(), target_scale_offsets);
             }
-            assert!(!animation_scale_offsets.contains_key(&anim.name));
-            animation_scale_offsets.insert(anim.name.clone(), target_scale_offsets);
         }
     }
-
-    let buffer_index = root.push(Buffer {
-        byte_length: USize64(buffer_data.len() as u64),
-        name: None,
-        uri: None,
-        extensions: Default::default(),
-        extras: Default::default(),
-    });
-
-    FullBuffer {
-        index: buffer_index,
-        data: buffer_data,
-        vertex_offsets,
-        index_offsets,
-        inverse_bind_matrices,
-        animation_timestamp_offsets,
-        animation_translation_offsets,
-        animation_orientation_offsets,
-        animation_scale_offsets,
-    }
 }
 
 fn build_mesh_parts(
@@ -270,7 +296,7 @@ fn build_mesh_parts(
     model: &TransformedModel,
     mesh: &Mesh,
     mesh_idx: usize,
-    materials: &[Option<Index<Material>>],
+    materials: &[Option<EffectMaterial>],
     bones: Option<&[Index<Node>]>,
 ) -> (Index<Node>, Vec<Index<Node>>) {
     let part_node_indices: Vec<Index<Node>> = mesh
@@ -302,7 +328,7 @@ fn build_mesh_part(
     mesh: &Mesh,
     mesh_idx: usize,
     part: &MeshPart,
-    materials: &[Option<Index<Material>>],
+    materials: &[Option<EffectMaterial>],
 ) -> Index<Node> {
     // vertices
     let vertex_decl = &model.vertex_decls[part.vertex_decl_index as usize];
@@ -325,7 +351,7 @@ fn build_mesh_part(
     let (pos_min, pos_max) = calculate_bounds(
         &mesh.vertex_buffer.data
             [vertex_buffer_local_offset..vertex_buffer_local_offset + vertex_buffer_length],
-        &vertex_decl,
+        vertex_decl,
     );
     let vertex_accessors =
         vertex_decl.accessors(vertex_view, part.vertex_count as u64, pos_min, pos_max);
@@ -375,6 +401,7 @@ fn build_mesh_part(
     } else {
         None
     };
+    let variants = material.and_then(|material| material.variants(root));
 
     let primitive = Primitive {
         attributes: {
@@ -385,27 +412,27 @@ fn build_mesh_part(
             map
         },
         indices: Some(index_accessor),
-        material,
+        material: material.map(|material| material.material),
         targets: None,
         mode: Checked::Valid(gltf::mesh::Mode::Triangles),
-        extensions: Default::default(),
+        extensions: variants.map(|variants| json::extensions::mesh::Primitive {
+            khr_materials_variants: Some(variants),
+        }),
         extras: Default::default(),
     };
 
     let mesh = root.push(json::Mesh {
         primitives: vec![primitive],
         weights: None,
-        name: None,
+        name: Some(mesh.name.clone()),
         extensions: Default::default(),
         extras: Default::default(),
     });
 
-    let node = root.push(Node {
+    root.push(Node {
         mesh: Some(mesh),
         ..Default::default()
-    });
-
-    node
+    })
 }
 
 fn calculate_bounds(vertices: &[u8], decl: &TransformedVertexDeclaration) -> (Vec3, Vec3) {
@@ -414,7 +441,7 @@ fn calculate_bounds(vertices: &[u8], decl: &TransformedVertexDeclaration) -> (Ve
         .iter()
         .find(|el| el.semantic == Semantic::Positions)
         .unwrap()
-        .offset as usize;
+        .offset;
 
     let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
     let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
@@ -433,32 +460,34 @@ fn calculate_bounds(vertices: &[u8], decl: &TransformedVertexDeclaration) -> (Ve
     (min, max)
 }
 
-fn reverse_winding(indices: &IndexBuffer) -> IndexBuffer {
+/// swaps the last two indices of every triangle, xna front faces are clockwise while gltf front
+/// faces are counter clockwise. applying it twice gives back the original order
+pub(crate) fn reverse_winding(indices: &IndexBuffer) -> IndexBuffer {
     let mut data = Vec::with_capacity(indices.data.len());
 
     if indices.is_16_bit {
-        assert!(indices.data.len() % 2 == 0);
+        assert!(indices.data.len().is_multiple_of(2));
         let indices_u16: Vec<u16> = indices
             .data
             .chunks_exact(2)
             .map(|i| u16::from_le_bytes([i[0], i[1]]))
             .collect();
 
-        assert!(indices_u16.len() % 3 == 0);
+        assert!(indices_u16.len().is_multiple_of(3));
         for triangle in indices_u16.chunks_exact(3) {
             data.extend_from_slice(&triangle[0].to_le_bytes());
             data.extend_from_slice(&triangle[2].to_le_bytes());
             data.extend_from_slice(&triangle[1].to_le_bytes());
         }
     } else {
-        assert!(indices.data.len() % 4 == 0);
+        assert!(indices.data.len().is_multiple_of(4));
         let indices_u32: Vec<u32> = indices
             .data
             .chunks_exact(4)
             .map(|i| u32::from_le_bytes([i[0], i[1], i[2], i[3]]))
             .collect();
 
-        assert!(indices_u32.len() % 3 == 0);
+        assert!(indices_u32.len().is_multiple_of(3));
         for triangle in indices_u32.chunks_exact(3) {
             data.extend_from_slice(&triangle[0].to_le_bytes());
             data.extend_from_slice(&triangle[2].to_le_bytes());
@@ -19,19 +19,24 @@ use crate::content::{
 };
 
 use super::{
-    FullBuffer, build_buffer, build_glb_bytes, build_materials, build_mesh_parts,
+    FullBuffer, build_buffer, build_glb_bytes, build_mesh_parts,
+    material::{TextureLoader, build_materials},
     transformed_model::TransformedModel,
 };
 
 impl SkinnedModel {
-    pub fn to_glb(&self, shared_content: &[Content]) -> anyhow::Result<Vec<u8>> {
+    pub fn to_glb(
+        &self,
+        shared_content: &[Content],
+        textures: &TextureLoader,
+    ) -> anyhow::Result<Vec<u8>> {
         let mut root = Root::default();
 
-        let transformed_model = TransformedModel::from(&self.model);
+        let transformed_model = TransformedModel::try_from(&self.model)?;
 
-        let buffer = build_buffer(&mut root, &transformed_model, shared_content);
+        let mut buffer = build_buffer(&mut root, &transformed_model, shared_content);
 
-        let materials = build_materials(&mut root, shared_content);
+        let materials = build_materials(&mut root, &mut buffer, shared_content, textures);
 
         let mut mesh_node_indices = Vec::new();
         let mut mesh_part_node_indices = Vec::new();
@@ -49,13 +54,14 @@ impl SkinnedModel {
             mesh_part_node_indices.extend_from_slice(&part_node_indices);
         }
 
-        let (skin, root_skin_bone_node) = build_skin(&mut root, &buffer, self, shared_content)?;
+        let (root_skin_bone_node, joint_nodes) = build_skeleton(&mut root, self, shared_content)?;
+        let skin = build_skin(&mut root, &buffer, root_skin_bone_node, joint_nodes.clone());
 
         for index in &mesh_part_node_indices {
             root.nodes[index.value()].skin = Some(skin);
         }
 
-        build_animations(&mut root, &buffer, self, shared_content)?;
+        build_animations(&mut root, &buffer, self, shared_content, &joint_nodes)?;
 
         let mut scene_nodes = mesh_node_indices.clone();
         scene_nodes.push(root_skin_bone_node);
@@ -75,12 +81,12 @@ impl SkinnedModel {
     }
 }
 
-fn build_skin(
+/// builds the bone nodes of the model, returns the root bone and every bone in depth first order
+pub(super) fn build_skeleton(
     root: &mut Root,
-    buffer: &FullBuffer,
     model: &SkinnedModel,
     shared_content: &[Content],
-) -> anyhow::Result<(Index<Skin>, Index<Node>)> {
+) -> anyhow::Result<(Index<Node>, Vec<Index<Node>>)> {
     let root_bone = model
         .shared_bone_refs
         .iter()
@@ -152,6 +158,16 @@ fn build_skin(
         &mut joint_nodes,
     )?;
 
+    Ok((root_bone_node, joint_nodes))
+}
+
+/// pushes a skin that uses the inverse bind matrices last appended to the buffer
+pub(super) fn build_skin(
+    root: &mut Root,
+    buffer: &FullBuffer,
+    skeleton: Index<Node>,
+    joint_nodes: Vec<Index<Node>>,
+) -> Index<Skin> {
     let inverse_bind_view = root.push(View {
         buffer: buffer.index,
         byte_length: USize64(
@@ -180,23 +196,23 @@ fn build_skin(
         extras: Default::default(),
     });
 
-    let skin = root.push(Skin {
-        skeleton: Some(root_bone_node),
+    root.push(Skin {
+        skeleton: Some(skeleton),
         joints: joint_nodes,
         inverse_bind_matrices: Some(inverse_bind_accessor),
         name: None,
         extensions: Default::default(),
         extras: Default::default(),
-    });
-
-    Ok((skin, root_bone_node))
+    })
 }
 
-fn build_animations(
+/// animates the bones in `joint_nodes` with the clips last appended to the buffer
+pub(super) fn build_animations(
     root: &mut Root,
     buffer: &FullBuffer,
     model: &SkinnedModel,
     shared_content: &[Content],
+    joint_nodes: &[Index<Node>],
 ) -> anyhow::Result<()> {
     for anim_ref in &model.shared_animation_refs {
         let Content::SkinnedModelAnimationClip(anim) = &shared_content[anim_ref - 1] else {
@@ -355,13 +371,15 @@ fn build_animations(
                 },
             );
 
-            let (target_node_index, _) = root
-                .nodes
+            let target_node = *joint_nodes
                 .iter()
-                .enumerate()
-                .find(|(_, node)| node.name.as_ref() == Some(target_node_name))
-                .unwrap();
-            let target_node = Index::<Node>::new(target_node_index as u32);
+                .find(|index| root.nodes[index.value()].name.as_ref() == Some(target_node_name))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "animation {} targets unknown bone {target_node_name}",
+                        anim.name
+                    )
+                })?;
 
             channels.push(Channel {
                 sampler: translation_sampler,
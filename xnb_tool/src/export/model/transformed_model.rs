@@ -1,3 +1,4 @@
+use byteorder::LittleEndian;
 use glam::{Vec3, Vec4};
 use gltf::{
     Semantic,
@@ -11,6 +12,7 @@ use gltf::{
 
 use crate::content::model::{
     Bone, BoneHierarchy, ElementFormat, ElementUsage, Mesh, Model, VertexBuffer, VertexDeclaration,
+    VertexElement,
 };
 
 /// model transformed into a format more suitable for gltf export
@@ -23,31 +25,39 @@ pub struct TransformedModel {
     pub tag: u8,
 }
 
-impl From<&Model> for TransformedModel {
-    fn from(model: &Model) -> Self {
-        let (meshes, vertex_decls) = transform_meshes(&model.meshes, &model.vertex_decls);
-        TransformedModel {
+impl TryFrom<&Model> for TransformedModel {
+    type Error = anyhow::Error;
+
+    fn try_from(model: &Model) -> anyhow::Result<Self> {
+        let (meshes, vertex_decls) = transform_meshes(&model.meshes, &model.vertex_decls)?;
+        Ok(TransformedModel {
             vertex_decls,
             meshes,
             bones: model.bones.clone(),
             bones_hierarchy: model.bones_hierarchy.clone(),
             root_bone_ref: model.root_bone_ref,
             tag: model.tag,
-        }
+        })
     }
 }
 
 pub struct TransformedVertexDeclaration {
     pub elements: Vec<TransformedVertexElement>,
+    /// whether the elements were moved away from their original offsets
+    pub repacked: bool,
 }
 
 impl TransformedVertexDeclaration {
     pub fn stride(&self) -> usize {
         let mut end = 0;
         for el in &self.elements {
-            end = usize::max(end, el.offset as usize + el.size());
+            end = usize::max(end, el.offset + el.size());
+        }
+        if self.repacked {
+            end.next_multiple_of(4)
+        } else {
+            end
         }
-        end
     }
 
     pub fn accessors(
@@ -64,71 +74,75 @@ impl TransformedVertexDeclaration {
     }
 }
 
-impl From<&VertexDeclaration> for TransformedVertexDeclaration {
-    fn from(decl: &VertexDeclaration) -> Self {
-        let elements = decl
-            .elements
-            .iter()
-            .filter_map(|el| {
-                let semantic = match el.usage {
-                    ElementUsage::Position => Some(Semantic::Positions),
-                    ElementUsage::Normal => Some(Semantic::Normals),
-                    ElementUsage::Tangent => Some(Semantic::Tangents),
-                    ElementUsage::Color => Some(Semantic::Colors(el.usage_index as u32)),
-                    ElementUsage::TextureCoordinate => {
-                        Some(Semantic::TexCoords(el.usage_index as u32))
-                    }
-                    ElementUsage::BlendWeight => Some(Semantic::Weights(el.usage_index as u32)),
-                    ElementUsage::BlendIndices => Some(Semantic::Joints(el.usage_index as u32)),
-                    ElementUsage::Binormal => None,
-                    v => todo!("semantic for element usage: {v:?}"),
-                };
+impl TryFrom<&VertexDeclaration> for TransformedVertexDeclaration {
+    type Error = anyhow::Error;
 
-                let Some(semantic) = semantic else {
-                    return None;
-                };
+    fn try_from(decl: &VertexDeclaration) -> anyhow::Result<Self> {
+        let mut elements = Vec::with_capacity(decl.elements.len());
+        for el in &decl.elements {
+            let semantic = match el.usage {
+                ElementUsage::Position => Some(Semantic::Positions),
+                ElementUsage::Normal => Some(Semantic::Normals),
+                ElementUsage::Tangent => Some(Semantic::Tangents),
+                ElementUsage::Color => Some(Semantic::Colors(el.usage_index as u32)),
+                ElementUsage::TextureCoordinate => Some(Semantic::TexCoords(el.usage_index as u32)),
+                ElementUsage::BlendWeight => Some(Semantic::Weights(el.usage_index as u32)),
+                ElementUsage::BlendIndices => Some(Semantic::Joints(el.usage_index as u32)),
+                ElementUsage::Binormal => None,
+                v => anyhow::bail!("no gltf semantic for element usage {v:?}"),
+            };
 
-                let element_type = if semantic == Semantic::Tangents {
-                    Type::Vec4
-                } else {
-                    match el.format {
-                        ElementFormat::Single => Type::Scalar,
-                        ElementFormat::Vector2 => Type::Vec2,
-                        ElementFormat::Vector3 => Type::Vec3,
-                        ElementFormat::Vector4 => Type::Vec4,
-                        ElementFormat::Color => Type::Vec4,
-                        ElementFormat::Byte4 => Type::Vec4,
-                        v => unimplemented!("type for element format: {v:?}"),
-                    }
-                };
+            let Some(semantic) = semantic else {
+                continue;
+            };
 
-                let component_type = match el.format {
-                    ElementFormat::Single => ComponentType::F32,
-                    ElementFormat::Vector2 => ComponentType::F32,
-                    ElementFormat::Vector3 => ComponentType::F32,
-                    ElementFormat::Vector4 => ComponentType::F32,
-                    ElementFormat::Color => ComponentType::U8,
-                    ElementFormat::Byte4 => ComponentType::U8,
-                    v => unimplemented!("component type for element format: {v:?}"),
-                };
+            let element_type = match semantic {
+                Semantic::Positions | Semantic::Normals => Type::Vec3,
+                Semantic::Tangents => Type::Vec4,
+                _ => match el.format.components() {
+                    1 => Type::Scalar,
+                    2 => Type::Vec2,
+                    3 => Type::Vec3,
+                    _ => Type::Vec4,
+                },
+            };
 
-                let offset = el.offset as usize;
+            // formats gltf cannot read directly are expanded to floats, except for joints which
+            // have to stay integers
+            let component_type = match el.format {
+                ElementFormat::Color | ElementFormat::Byte4 => ComponentType::U8,
+                _ if is_gltf_native(el.format) => ComponentType::F32,
+                _ if matches!(semantic, Semantic::Joints(_)) => ComponentType::U16,
+                _ => ComponentType::F32,
+            };
 
-                let normalized = match el.format {
-                    ElementFormat::Color => true,
-                    _ => false,
-                };
+            let offset = el.offset as usize;
+
+            let normalized = matches!(el.format, ElementFormat::Color);
+
+            elements.push(TransformedVertexElement {
+                semantic,
+                element_type,
+                component_type,
+                offset,
+                normalized,
+                source_format: el.format,
+                source_offset: offset,
+            });
+        }
+
+        // tangents grow to four components, packed formats are expanded and some elements
+        // change their number of components, so the elements need a new layout
+        let repacked = elements.iter().any(|el| el.needs_conversion());
+        if repacked {
+            let mut offset = 0;
+            for el in &mut elements {
+                el.offset = offset;
+                offset += el.size().next_multiple_of(4);
+            }
+        }
 
-                Some(TransformedVertexElement {
-                    semantic,
-                    element_type,
-                    component_type,
-                    offset,
-                    normalized,
-                })
-            })
-            .collect();
-        TransformedVertexDeclaration { elements }
+        Ok(TransformedVertexDeclaration { elements, repacked })
     }
 }
 
@@ -138,6 +152,8 @@ pub struct TransformedVertexElement {
     pub component_type: ComponentType,
     pub offset: usize,
     pub normalized: bool,
+    pub source_format: ElementFormat,
+    pub source_offset: usize,
 }
 
 impl TransformedVertexElement {
@@ -145,6 +161,15 @@ impl TransformedVertexElement {
         self.element_type.multiplicity() * self.component_type.size()
     }
 
+    /// whether the element has to be rewritten rather than copied as is
+    fn needs_conversion(&self) -> bool {
+        self.semantic == Semantic::Tangents
+            // packed as argb, so the bytes are in bgra order
+            || self.source_format == ElementFormat::Color
+            || !is_gltf_native(self.source_format)
+            || self.element_type.multiplicity() != self.source_format.components()
+    }
+
     pub fn accessor(
         &self,
         view: Index<View>,
@@ -184,32 +209,40 @@ impl TransformedVertexElement {
 fn transform_meshes(
     meshes: &[Mesh],
     vertex_decls: &[VertexDeclaration],
-) -> (Vec<Mesh>, Vec<TransformedVertexDeclaration>) {
-    let transformed_decls: Vec<TransformedVertexDeclaration> =
-        vertex_decls.iter().map(|decl| decl.into()).collect();
+) -> anyhow::Result<(Vec<Mesh>, Vec<TransformedVertexDeclaration>)> {
+    let transformed_decls = vertex_decls
+        .iter()
+        .map(TransformedVertexDeclaration::try_from)
+        .collect::<anyhow::Result<Vec<_>>>()?;
 
     let transformed_meshes = meshes
         .iter()
-        .map(|mesh| transform_mesh(mesh, vertex_decls))
+        .map(|mesh| transform_mesh(mesh, vertex_decls, &transformed_decls))
         .collect();
 
-    (transformed_meshes, transformed_decls)
+    Ok((transformed_meshes, transformed_decls))
 }
 
-fn transform_mesh(mesh: &Mesh, vertex_decls: &[VertexDeclaration]) -> Mesh {
+fn transform_mesh(
+    mesh: &Mesh,
+    vertex_decls: &[VertexDeclaration],
+    transformed_decls: &[TransformedVertexDeclaration],
+) -> Mesh {
     let mut transformed_vertex_buffer = Vec::with_capacity(mesh.vertex_buffer.data.len());
     for part in &mesh.parts {
         let decl = &vertex_decls[part.vertex_decl_index as usize];
+        let transformed_decl = &transformed_decls[part.vertex_decl_index as usize];
         let stride = decl.stride();
         let base = part.base_vertex as usize;
         let count = part.vertex_count as usize;
         let part_data = &mesh.vertex_buffer.data[base * stride..(base + count) * stride];
-        if decl
-            .elements
-            .iter()
-            .any(|el| el.usage == ElementUsage::Tangent)
-        {
-            transform_vertex_data_tangent(part_data, decl, &mut transformed_vertex_buffer);
+        if transformed_decl.repacked {
+            transform_vertex_data(
+                part_data,
+                decl,
+                transformed_decl,
+                &mut transformed_vertex_buffer,
+            );
         } else {
             transformed_vertex_buffer.extend_from_slice(part_data);
         }
@@ -219,6 +252,7 @@ fn transform_mesh(mesh: &Mesh, vertex_decls: &[VertexDeclaration]) -> Mesh {
         parent_bone_ref: mesh.parent_bone_ref,
         bounds: mesh.bounds.clone(),
         vertex_buffer: VertexBuffer {
+            declaration: None,
             data: transformed_vertex_buffer,
         },
         index_buffer: mesh.index_buffer.clone(),
@@ -227,66 +261,85 @@ fn transform_mesh(mesh: &Mesh, vertex_decls: &[VertexDeclaration]) -> Mesh {
     }
 }
 
-fn transform_vertex_data_tangent(data: &[u8], decl: &VertexDeclaration, dest: &mut Vec<u8>) {
-    let normal_offset = decl
-        .elements
-        .iter()
-        .find_map(|el| {
-            if el.usage == ElementUsage::Normal {
-                Some(el.offset)
-            } else {
-                None
-            }
-        })
-        .unwrap() as usize;
-    let tangent_offset = decl
-        .elements
-        .iter()
-        .find_map(|el| {
-            if el.usage == ElementUsage::Tangent {
-                Some(el.offset)
-            } else {
-                None
+/// rewrites every vertex into the layout of the transformed declaration
+fn transform_vertex_data(
+    data: &[u8],
+    decl: &VertexDeclaration,
+    transformed_decl: &TransformedVertexDeclaration,
+    dest: &mut Vec<u8>,
+) {
+    let find_element = |usage| decl.elements.iter().find(|el| el.usage == usage);
+    let normal = find_element(ElementUsage::Normal);
+    let binormal = find_element(ElementUsage::Binormal);
+
+    let decode = |vertex: &[u8], el: &VertexElement| {
+        let offset = el.offset as usize;
+        el.format
+            .decode::<LittleEndian>(&vertex[offset..offset + el.format.size()])
+    };
+
+    let stride = transformed_decl.stride();
+    for vertex in data.chunks_exact(decl.stride()) {
+        let start = dest.len();
+        dest.resize(start + stride, 0);
+        let dest = &mut dest[start..];
+
+        for el in &transformed_decl.elements {
+            let source = &vertex[el.source_offset..el.source_offset + el.source_format.size()];
+            let target = &mut dest[el.offset..el.offset + el.size()];
+            if !el.needs_conversion() {
+                target.copy_from_slice(source);
+                continue;
             }
-        })
-        .unwrap() as usize;
-    let binormal_offset = decl
-        .elements
-        .iter()
-        .find_map(|el| {
-            if el.usage == ElementUsage::Binormal {
-                Some(el.offset)
-            } else {
-                None
+
+            let mut value = el.source_format.decode::<LittleEndian>(source);
+            if el.semantic == Semantic::Tangents {
+                let tangent = value.truncate();
+                let w = match (normal, binormal) {
+                    (Some(normal), Some(binormal)) => {
+                        let normal = decode(vertex, normal).truncate();
+                        let binormal = decode(vertex, binormal).truncate();
+                        if normal.cross(tangent).dot(binormal) < 0.0 {
+                            -1.0
+                        } else {
+                            1.0
+                        }
+                    }
+                    _ => 1.0,
+                };
+                value = Vec4::new(tangent.x, tangent.y, tangent.z, w);
             }
-        })
-        .unwrap() as usize;
-
-    for vertex in data.chunks(decl.stride()) {
-        let vec3_size = std::mem::size_of::<Vec3>();
-        let normal_bytes = &vertex[normal_offset..normal_offset + vec3_size];
-        let tangent_bytes = &vertex[tangent_offset..tangent_offset + vec3_size];
-        let binormal_bytes = &vertex[binormal_offset..binormal_offset + vec3_size];
-        let normal = Vec3::from_slice(bytemuck::cast_slice(normal_bytes));
-        let tangent = Vec3::from_slice(bytemuck::cast_slice(tangent_bytes));
-        let binormal = Vec3::from_slice(bytemuck::cast_slice(binormal_bytes));
-        let w = if normal.cross(tangent).dot(binormal) < 0.0 {
-            -1.0
-        } else {
-            1.0
-        };
-        let tangent = Vec4::new(tangent.x, tangent.y, tangent.z, w);
 
-        for el in &decl.elements {
-            match el.usage {
-                ElementUsage::Tangent => dest.extend_from_slice(bytemuck::cast_slice(&[tangent])),
-                ElementUsage::Binormal => {}
-                _ => {
-                    let offset = el.offset as usize;
-                    let bytes = &vertex[offset..offset + el.format.size()];
-                    dest.extend_from_slice(bytes);
+            let count = el.element_type.multiplicity();
+            match el.component_type {
+                ComponentType::F32 => {
+                    target.copy_from_slice(bytemuck::cast_slice(&value.to_array()[..count]))
                 }
+                ComponentType::U16 => {
+                    for (i, bytes) in target.chunks_exact_mut(2).enumerate() {
+                        bytes.copy_from_slice(&(value[i] as u16).to_le_bytes());
+                    }
+                }
+                ComponentType::U8 => {
+                    for (i, byte) in target.iter_mut().enumerate() {
+                        *byte = (value[i] * 255.0).round() as u8;
+                    }
+                }
+                _ => unreachable!("elements are only converted to floats, shorts or bytes"),
             }
         }
     }
 }
+
+/// whether gltf can read the element format without any conversion
+fn is_gltf_native(format: ElementFormat) -> bool {
+    matches!(
+        format,
+        ElementFormat::Single
+            | ElementFormat::Vector2
+            | ElementFormat::Vector3
+            | ElementFormat::Vector4
+            | ElementFormat::Color
+            | ElementFormat::Byte4
+    )
+}
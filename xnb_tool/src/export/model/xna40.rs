@@ -0,0 +1,136 @@
+use anyhow::{Context, anyhow};
+
+use crate::content::{
+    Content,
+    model::{IndexBuffer, Mesh, MeshPart, Model, VertexBuffer, Xna40Model},
+};
+
+use super::material::TextureLoader;
+
+impl Xna40Model {
+    pub fn to_glb(
+        &self,
+        shared_content: &[Content],
+        textures: &TextureLoader,
+    ) -> anyhow::Result<Vec<u8>> {
+        let model = self
+            .to_model(shared_content)
+            .context("failed to resolve shared mesh buffers")?;
+        model.to_glb(shared_content, textures)
+    }
+
+    /// rebuilds the XNA 3.1 layout by copying the vertices and indices of every part out of
+    /// the shared buffers they reference, so the regular model export can be reused
+    fn to_model(&self, shared_content: &[Content]) -> anyhow::Result<Model> {
+        fn shared(shared_content: &[Content], idx: i32) -> anyhow::Result<&Content> {
+            if idx <= 0 {
+                anyhow::bail!("missing shared content reference");
+            }
+            shared_content
+                .get(idx as usize - 1)
+                .ok_or_else(|| anyhow!("shared content index out of range: {idx}"))
+        }
+
+        let mut vertex_decls = Vec::new();
+        let mut meshes = Vec::with_capacity(self.meshes.len());
+        for mesh in &self.meshes {
+            let mut is_16_bit = true;
+            for part in &mesh.parts {
+                let Content::IndexBuffer(index_buffer) =
+                    shared(shared_content, part.shared_content_index_buffer_idx)?
+                else {
+                    anyhow::bail!("expected index buffer");
+                };
+                is_16_bit &= index_buffer.is_16_bit;
+            }
+            let index_size = if is_16_bit { 2 } else { 4 };
+
+            let mut vertices = Vec::new();
+            let mut indices = Vec::new();
+            let mut parts = Vec::with_capacity(mesh.parts.len());
+            for part in &mesh.parts {
+                let Content::VertexBuffer(vertex_buffer) =
+                    shared(shared_content, part.shared_content_vertex_buffer_idx)?
+                else {
+                    anyhow::bail!("expected vertex buffer");
+                };
+                let declaration = vertex_buffer
+                    .declaration
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("vertex buffer has no declaration"))?;
+                let stride = declaration.stride();
+                if !vertices.len().is_multiple_of(stride) {
+                    anyhow::bail!("mesh parts with different vertex strides are not supported");
+                }
+                let base_vertex = vertices.len() / stride;
+                let start = part.vertex_offset as usize * stride;
+                let end = start + part.vertex_count as usize * stride;
+                let part_vertices = vertex_buffer
+                    .data
+                    .get(start..end)
+                    .ok_or_else(|| anyhow!("mesh part vertices out of range"))?;
+                vertices.extend_from_slice(part_vertices);
+
+                let Content::IndexBuffer(index_buffer) =
+                    shared(shared_content, part.shared_content_index_buffer_idx)?
+                else {
+                    anyhow::bail!("expected index buffer");
+                };
+                let part_index_size = if index_buffer.is_16_bit { 2 } else { 4 };
+                let start = part.start_index as usize * part_index_size;
+                let end = start + part.primitive_count as usize * 3 * part_index_size;
+                let part_indices = index_buffer
+                    .data
+                    .get(start..end)
+                    .ok_or_else(|| anyhow!("mesh part indices out of range"))?;
+                let start_index = indices.len() / index_size;
+                if index_buffer.is_16_bit == is_16_bit {
+                    indices.extend_from_slice(part_indices);
+                } else {
+                    // widen to match the other parts of the mesh
+                    for index in part_indices.chunks_exact(2) {
+                        let index = u16::from_le_bytes([index[0], index[1]]) as u32;
+                        indices.extend_from_slice(&index.to_le_bytes());
+                    }
+                }
+
+                vertex_decls.push(declaration.clone());
+                parts.push(MeshPart {
+                    stream_offset: 0,
+                    base_vertex: base_vertex as u32,
+                    vertex_count: part.vertex_count,
+                    start_index: start_index as u32,
+                    primitive_count: part.primitive_count,
+                    vertex_decl_index: vertex_decls.len() as u32 - 1,
+                    tag: part.tag,
+                    shared_content_material_idx: part.shared_content_material_idx,
+                });
+            }
+
+            meshes.push(Mesh {
+                name: mesh.name.clone(),
+                parent_bone_ref: mesh.parent_bone_ref,
+                bounds: mesh.bounds.clone(),
+                vertex_buffer: VertexBuffer {
+                    declaration: None,
+                    data: vertices,
+                },
+                index_buffer: IndexBuffer {
+                    is_16_bit,
+                    data: indices,
+                },
+                parts,
+                tag: mesh.tag,
+            });
+        }
+
+        Ok(Model {
+            bones: self.bones.clone(),
+            bones_hierarchy: self.bones_hierarchy.clone(),
+            vertex_decls,
+            meshes,
+            root_bone_ref: self.root_bone_ref,
+            tag: self.tag,
+        })
+    }
+}
@@ -0,0 +1,40 @@
+use crate::content::sound_effect::{SoundEffect, WaveFormatEx};
+
+use super::wav::{decode_ms_adpcm, encode_pcm_wav};
+
+impl SoundEffect {
+    /// decodes pcm and ms adpcm sound effects to a pcm wav file, the loop is kept in a `smpl`
+    /// chunk
+    pub fn to_wav(&self) -> anyhow::Result<Vec<u8>> {
+        let format = &self.format;
+        let loop_region = (self.loop_length > 0)
+            .then_some((self.loop_start.max(0) as u32, self.loop_length as u32));
+        match format.format_tag {
+            WaveFormatEx::PCM => Ok(encode_pcm_wav(
+                format.channels,
+                format.sample_rate,
+                format.bits_per_sample,
+                &self.data,
+                loop_region,
+            )),
+            WaveFormatEx::ADPCM => {
+                let mut samples =
+                    decode_ms_adpcm(&self.data, format.channels, format.block_align as usize)?;
+                // the last block is padded with silence
+                if self.duration > 0 {
+                    let frames = self.duration as u64 * format.sample_rate as u64 / 1000;
+                    samples.truncate(frames as usize * format.channels as usize);
+                }
+                let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+                Ok(encode_pcm_wav(
+                    format.channels,
+                    format.sample_rate,
+                    16,
+                    &data,
+                    loop_region,
+                ))
+            }
+            tag => anyhow::bail!("unsupported wave format {tag:#x}"),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt::Write;
+
+use crate::content::sprite_font::SpriteFont;
+
+impl SpriteFont {
+    /// describes the glyphs in the text format of BMFont, with the texture as the only page.
+    /// sprite fonts do not store a baseline, it is taken from the bottom of `A` when the font has
+    /// one and from the line spacing otherwise
+    pub fn to_fnt(&self, face: &str, page: &str) -> String {
+        let base = self
+            .characters
+            .iter()
+            .position(|c| *c == 'A')
+            .map(|i| self.cropping[i].y + self.glyphs[i].height)
+            .unwrap_or(self.line_spacing);
+
+        let mut fnt = String::new();
+        writeln!(
+            fnt,
+            "info face=\"{}\" size={} bold=0 italic=0 charset=\"\" unicode=1 stretchH=100 smooth=1 aa=1 padding=0,0,0,0 spacing=0,0",
+            face.replace('"', "'"),
+            self.line_spacing
+        )
+        .unwrap();
+        writeln!(
+            fnt,
+            "common lineHeight={} base={base} scaleW={} scaleH={} pages=1 packed=0",
+            self.line_spacing, self.texture.width, self.texture.height
+        )
+        .unwrap();
+        writeln!(fnt, "page id=0 file=\"{page}\"").unwrap();
+        writeln!(fnt, "chars count={}", self.characters.len()).unwrap();
+
+        for (i, c) in self.characters.iter().enumerate() {
+            let glyph = self.glyphs[i];
+            let cropping = self.cropping[i];
+            // xna draws a glyph at its left bearing plus the cropping offset, then moves on by
+            // the whole width of the glyph and the spacing of the font
+            let kerning = self.kerning[i];
+            let xoffset = kerning.x.round() as i32 + cropping.x;
+            let xadvance = (kerning.x + kerning.y + kerning.z + self.spacing).round() as i32;
+            writeln!(
+                fnt,
+                "char id={} x={} y={} width={} height={} xoffset={xoffset} yoffset={} xadvance={xadvance} page=0 chnl=15",
+                *c as u32, glyph.x, glyph.y, glyph.width, glyph.height, cropping.y
+            )
+            .unwrap();
+        }
+
+        fnt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use crate::content::sprite_font::Rectangle;
+    use crate::content::texture::{SurfaceFormat, Texture2D};
+
+    use super::*;
+
+    #[test]
+    fn fnt_from_font() {
+        let rect = |x, y, width, height| Rectangle {
+            x,
+            y,
+            width,
+            height,
+        };
+        let font = SpriteFont {
+            texture: Texture2D {
+                format: SurfaceFormat::Color,
+                width: 16,
+                height: 8,
+                mips: Vec::new(),
+            },
+            glyphs: vec![rect(0, 0, 5, 7), rect(6, 0, 4, 5)],
+            cropping: vec![rect(1, 2, 7, 12), rect(0, 4, 6, 12)],
+            characters: vec!['A', 'é'],
+            line_spacing: 12,
+            spacing: 1.0,
+            kerning: vec![Vec3::new(1.0, 5.0, 0.5), Vec3::new(-0.4, 4.0, 1.0)],
+            default_character: None,
+        };
+
+        let fnt = font.to_fnt("Test \"Font\"", "test_0.png");
+        let expected = "\
+info face=\"Test 'Font'\" size=12 bold=0 italic=0 charset=\"\" unicode=1 stretchH=100 smooth=1 aa=1 padding=0,0,0,0 spacing=0,0
+common lineHeight=12 base=9 scaleW=16 scaleH=8 pages=1 packed=0
+page id=0 file=\"test_0.png\"
+chars count=2
+char id=65 x=0 y=0 width=5 height=7 xoffset=2 yoffset=2 xadvance=8 page=0 chnl=15
+char id=233 x=6 y=0 width=4 height=5 xoffset=0 yoffset=4 xadvance=6 page=0 chnl=15
+";
+        assert_eq!(fnt, expected);
+    }
+}
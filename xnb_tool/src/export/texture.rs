@@ -1,39 +1,454 @@
+use anyhow::Context;
 use bcndecode::{BcnDecoderFormat, BcnEncoding};
+use half::f16;
 use image::{ExtendedColorType, ImageEncoder, codecs::png::PngEncoder};
 
-use crate::content::texture::Texture2D;
+use crate::content::texture::{SurfaceFormat, Texture2D};
 
 impl Texture2D {
-    pub fn to_png(&self) -> anyhow::Result<Vec<u8>> {
-        let decompressed = if self.format == 0x1C {
-            bcndecode::decode(
-                &self.mips[0],
-                self.width as usize,
-                self.height as usize,
-                BcnEncoding::Bc1,
-                BcnDecoderFormat::RGBA,
-            )?
-        } else if self.format == 0x20 {
-            bcndecode::decode(
-                &self.mips[0],
-                self.width as usize,
-                self.height as usize,
-                BcnEncoding::Bc3,
-                BcnDecoderFormat::RGBA,
-            )?
-        } else {
-            anyhow::bail!("unknown texture format: {}", self.format);
+    /// width and height of the given mip level
+    pub fn mip_size(&self, level: usize) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    /// decodes the given mip level into rgba8 pixels
+    pub fn to_rgba(&self, level: usize) -> anyhow::Result<Vec<u8>> {
+        let Some(data) = self.mips.get(level) else {
+            anyhow::bail!("texture has no mip level {level}");
         };
+        let (width, height) = self.mip_size(level);
+        decode(self.format, data, width as usize, height as usize)
+    }
+
+    pub fn to_png(&self) -> anyhow::Result<Vec<u8>> {
+        self.mip_to_png(0)
+    }
+
+    /// encodes a single mip level as png
+    pub fn mip_to_png(&self, level: usize) -> anyhow::Result<Vec<u8>> {
+        let decompressed = self.to_rgba(level)?;
+        let (width, height) = self.mip_size(level);
+        encode_png(&decompressed, width, height)
+    }
+
+    /// encodes every mip level as one png, with the levels stacked from top to bottom and
+    /// aligned to the left
+    pub fn mips_to_png_strip(&self) -> anyhow::Result<Vec<u8>> {
+        if self.mips.is_empty() {
+            anyhow::bail!("texture has no mip levels to export");
+        }
+        let height = (0..self.mips.len())
+            .map(|level| self.mip_size(level).1)
+            .sum::<u32>();
+        let row_size = self.width as usize * 4;
+        let mut strip = vec![0; row_size * height as usize];
+
+        let mut y = 0;
+        for level in 0..self.mips.len() {
+            let decompressed = self
+                .to_rgba(level)
+                .with_context(|| format!("failed to decode mip level {level}"))?;
+            let (mip_width, _) = self.mip_size(level);
+            for row in decompressed.chunks_exact(mip_width as usize * 4) {
+                let start = y * row_size;
+                strip[start..start + row.len()].copy_from_slice(row);
+                y += 1;
+            }
+        }
+
+        encode_png(&strip, self.width, height)
+    }
+}
+
+/// which mip levels are exported next to level 0
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MipExport {
+    /// only export level 0
+    #[default]
+    None,
+    /// export every level as its own png
+    Separate,
+    /// export every level stacked into a single png
+    Strip,
+}
+
+fn encode_png(pixels: &[u8], width: u32, height: u32) -> anyhow::Result<Vec<u8>> {
+    let mut png = Vec::new();
+    let encoder = PngEncoder::new(&mut png);
+    encoder.write_image(pixels, width, height, ExtendedColorType::Rgba8)?;
+    Ok(png)
+}
+
+fn decode(
+    format: SurfaceFormat,
+    data: &[u8],
+    width: usize,
+    height: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let encoding = match format {
+        SurfaceFormat::Dxt1 => Some(BcnEncoding::Bc1),
+        SurfaceFormat::Dxt2 | SurfaceFormat::Dxt3 => Some(BcnEncoding::Bc2),
+        SurfaceFormat::Dxt4 | SurfaceFormat::Dxt5 => Some(BcnEncoding::Bc3),
+        _ => None,
+    };
+    if let Some(encoding) = encoding {
+        let mut pixels = bcndecode::decode(data, width, height, encoding, BcnDecoderFormat::RGBA)?;
+        if matches!(format, SurfaceFormat::Dxt2 | SurfaceFormat::Dxt4) {
+            unpremultiply(&mut pixels);
+        }
+        return Ok(pixels);
+    }
+
+    let pixel_count = width * height;
+    let is_video = matches!(
+        format,
+        SurfaceFormat::VideoYuYv
+            | SurfaceFormat::VideoUyVy
+            | SurfaceFormat::VideoGrGb
+            | SurfaceFormat::VideoRgBg
+    );
+    // video rows are padded to an even width so every pixel pair is complete
+    let pitch = if is_video {
+        width.next_multiple_of(2) * format.block_bytes()
+    } else {
+        width * format.block_bytes()
+    };
+    let expected = pitch * height;
+    if data.len() < expected {
+        anyhow::bail!(
+            "{format:?} texture of {width}x{height} needs {expected} bytes but only has {}",
+            data.len()
+        );
+    }
+
+    let mut pixels = Vec::with_capacity(pixel_count * 4);
+    match format {
+        // two pixels share their chroma in every four bytes
+        SurfaceFormat::VideoYuYv
+        | SurfaceFormat::VideoUyVy
+        | SurfaceFormat::VideoGrGb
+        | SurfaceFormat::VideoRgBg => {
+            for row in data[..expected].chunks_exact(pitch) {
+                for (x, pair) in row.chunks_exact(4).enumerate() {
+                    let pair = decode_pair(format, pair);
+                    pixels.extend_from_slice(&pair[0]);
+                    if x * 2 + 1 < width {
+                        pixels.extend_from_slice(&pair[1]);
+                    }
+                }
+            }
+        }
+        _ => {
+            for texel in data[..expected].chunks_exact(format.block_bytes()) {
+                pixels.extend_from_slice(&decode_texel(format, texel)?);
+            }
+        }
+    }
+    Ok(pixels)
+}
+
+fn decode_texel(format: SurfaceFormat, texel: &[u8]) -> anyhow::Result<[u8; 4]> {
+    let u16_at = |i: usize| u16::from_le_bytes([texel[i * 2], texel[i * 2 + 1]]);
+    let u32_at = |i: usize| u32::from_le_bytes(texel[i * 4..i * 4 + 4].try_into().unwrap());
+    let f32_at = |i: usize| f32::from_bits(u32_at(i));
+    let f16_at = |i: usize| f16::from_bits(u16_at(i)).to_f32();
+    let bits = |value: u32, shift: u32, count: u32| (value >> shift) & ((1 << count) - 1);
+
+    let rgba = match format {
+        SurfaceFormat::Color => [texel[2], texel[1], texel[0], texel[3]],
+        SurfaceFormat::Bgr32 => [texel[2], texel[1], texel[0], 255],
+        SurfaceFormat::Rgba32 => [texel[0], texel[1], texel[2], texel[3]],
+        SurfaceFormat::Rgb32 => [texel[0], texel[1], texel[2], 255],
+        SurfaceFormat::Bgr24 => [texel[2], texel[1], texel[0], 255],
+        SurfaceFormat::Bgra1010102 => {
+            let v = u32_at(0);
+            [
+                unorm(bits(v, 20, 10), 10),
+                unorm(bits(v, 10, 10), 10),
+                unorm(bits(v, 0, 10), 10),
+                unorm(bits(v, 30, 2), 2),
+            ]
+        }
+        SurfaceFormat::Rgba1010102 => {
+            let v = u32_at(0);
+            [
+                unorm(bits(v, 0, 10), 10),
+                unorm(bits(v, 10, 10), 10),
+                unorm(bits(v, 20, 10), 10),
+                unorm(bits(v, 30, 2), 2),
+            ]
+        }
+        SurfaceFormat::Rg32 => [
+            unorm(u16_at(0) as u32, 16),
+            unorm(u16_at(1) as u32, 16),
+            0,
+            255,
+        ],
+        SurfaceFormat::Rgba64 => [
+            unorm(u16_at(0) as u32, 16),
+            unorm(u16_at(1) as u32, 16),
+            unorm(u16_at(2) as u32, 16),
+            unorm(u16_at(3) as u32, 16),
+        ],
+        SurfaceFormat::Bgr565 => {
+            let v = u16_at(0) as u32;
+            [
+                unorm(bits(v, 11, 5), 5),
+                unorm(bits(v, 5, 6), 6),
+                unorm(bits(v, 0, 5), 5),
+                255,
+            ]
+        }
+        SurfaceFormat::Bgra5551 | SurfaceFormat::Bgr555 => {
+            let v = u16_at(0) as u32;
+            let a = match format {
+                SurfaceFormat::Bgra5551 => unorm(bits(v, 15, 1), 1),
+                _ => 255,
+            };
+            [
+                unorm(bits(v, 10, 5), 5),
+                unorm(bits(v, 5, 5), 5),
+                unorm(bits(v, 0, 5), 5),
+                a,
+            ]
+        }
+        SurfaceFormat::Bgra4444 | SurfaceFormat::Bgr444 => {
+            let v = u16_at(0) as u32;
+            let a = match format {
+                SurfaceFormat::Bgra4444 => unorm(bits(v, 12, 4), 4),
+                _ => 255,
+            };
+            [
+                unorm(bits(v, 8, 4), 4),
+                unorm(bits(v, 4, 4), 4),
+                unorm(bits(v, 0, 4), 4),
+                a,
+            ]
+        }
+        SurfaceFormat::Bgra2338 => {
+            let v = u16_at(0) as u32;
+            [
+                unorm(bits(v, 5, 3), 3),
+                unorm(bits(v, 2, 3), 3),
+                unorm(bits(v, 0, 2), 2),
+                bits(v, 8, 8) as u8,
+            ]
+        }
+        SurfaceFormat::Bgr233 => {
+            let v = texel[0] as u32;
+            [
+                unorm(bits(v, 5, 3), 3),
+                unorm(bits(v, 2, 3), 3),
+                unorm(bits(v, 0, 2), 2),
+                255,
+            ]
+        }
+        SurfaceFormat::Alpha8 => [0, 0, 0, texel[0]],
+        SurfaceFormat::NormalizedByte2 => [
+            snorm(texel[0] as i8 as i32, 8),
+            snorm(texel[1] as i8 as i32, 8),
+            0,
+            255,
+        ],
+        SurfaceFormat::NormalizedByte2Computed => {
+            let u = (texel[0] as i8 as f32 / 127.0).max(-1.0);
+            let v = (texel[1] as i8 as f32 / 127.0).max(-1.0);
+            let w = (1.0 - u * u - v * v).max(0.0).sqrt();
+            [snormf(u), snormf(v), snormf(w), 255]
+        }
+        SurfaceFormat::NormalizedByte4 => [
+            snorm(texel[0] as i8 as i32, 8),
+            snorm(texel[1] as i8 as i32, 8),
+            snorm(texel[2] as i8 as i32, 8),
+            snorm(texel[3] as i8 as i32, 8),
+        ],
+        SurfaceFormat::NormalizedShort2 => [
+            snorm(u16_at(0) as i16 as i32, 16),
+            snorm(u16_at(1) as i16 as i32, 16),
+            0,
+            255,
+        ],
+        SurfaceFormat::NormalizedShort4 => [
+            snorm(u16_at(0) as i16 as i32, 16),
+            snorm(u16_at(1) as i16 as i32, 16),
+            snorm(u16_at(2) as i16 as i32, 16),
+            snorm(u16_at(3) as i16 as i32, 16),
+        ],
+        SurfaceFormat::Single => [float(f32_at(0)), 0, 0, 255],
+        SurfaceFormat::Vector2 => [float(f32_at(0)), float(f32_at(1)), 0, 255],
+        SurfaceFormat::Vector4 => [
+            float(f32_at(0)),
+            float(f32_at(1)),
+            float(f32_at(2)),
+            float(f32_at(3)),
+        ],
+        SurfaceFormat::HalfSingle => [float(f16_at(0)), 0, 0, 255],
+        SurfaceFormat::HalfVector2 => [float(f16_at(0)), float(f16_at(1)), 0, 255],
+        SurfaceFormat::HalfVector4 => [
+            float(f16_at(0)),
+            float(f16_at(1)),
+            float(f16_at(2)),
+            float(f16_at(3)),
+        ],
+        SurfaceFormat::Luminance8 => [texel[0], texel[0], texel[0], 255],
+        SurfaceFormat::Luminance16 => {
+            let l = unorm(u16_at(0) as u32, 16);
+            [l, l, l, 255]
+        }
+        SurfaceFormat::LuminanceAlpha8 => {
+            let v = texel[0] as u32;
+            let l = unorm(bits(v, 0, 4), 4);
+            [l, l, l, unorm(bits(v, 4, 4), 4)]
+        }
+        SurfaceFormat::LuminanceAlpha16 => [texel[0], texel[0], texel[0], texel[1]],
+        SurfaceFormat::NormalizedLuminance16 => {
+            let v = u16_at(0) as u32;
+            [
+                snorm(sign_extend(bits(v, 0, 5), 5), 5),
+                snorm(sign_extend(bits(v, 5, 5), 5), 5),
+                unorm(bits(v, 10, 6), 6),
+                255,
+            ]
+        }
+        SurfaceFormat::NormalizedLuminance32 => [
+            snorm(texel[0] as i8 as i32, 8),
+            snorm(texel[1] as i8 as i32, 8),
+            texel[2],
+            255,
+        ],
+        SurfaceFormat::NormalizedAlpha1010102 => {
+            let v = u32_at(0);
+            [
+                snorm(sign_extend(bits(v, 0, 10), 10), 10),
+                snorm(sign_extend(bits(v, 10, 10), 10), 10),
+                snorm(sign_extend(bits(v, 20, 10), 10), 10),
+                unorm(bits(v, 30, 2), 2),
+            ]
+        }
+        // depth formats are shown as grayscale
+        SurfaceFormat::Depth24Stencil8
+        | SurfaceFormat::Depth24Stencil4
+        | SurfaceFormat::Depth24 => {
+            let d = unorm(bits(u32_at(0), 8, 24), 24);
+            [d, d, d, 255]
+        }
+        SurfaceFormat::Depth32 => {
+            let d = unorm(u32_at(0), 32);
+            [d, d, d, 255]
+        }
+        SurfaceFormat::Depth16 => {
+            let d = unorm(u16_at(0) as u32, 16);
+            [d, d, d, 255]
+        }
+        SurfaceFormat::Depth15Stencil1 => {
+            let d = unorm(bits(u16_at(0) as u32, 1, 15), 15);
+            [d, d, d, 255]
+        }
+        SurfaceFormat::Palette8 | SurfaceFormat::PaletteAlpha16 => {
+            anyhow::bail!("{format:?} textures are stored without their palette")
+        }
+        SurfaceFormat::Multi2Bgra32 | SurfaceFormat::Depth24Stencil8Single => {
+            anyhow::bail!("{format:?} textures cannot be decoded")
+        }
+        SurfaceFormat::Dxt1
+        | SurfaceFormat::Dxt2
+        | SurfaceFormat::Dxt3
+        | SurfaceFormat::Dxt4
+        | SurfaceFormat::Dxt5
+        | SurfaceFormat::VideoYuYv
+        | SurfaceFormat::VideoUyVy
+        | SurfaceFormat::VideoGrGb
+        | SurfaceFormat::VideoRgBg => unreachable!("{format:?} is not stored per texel"),
+    };
+    Ok(rgba)
+}
+
+/// decodes four bytes holding two horizontally adjacent pixels
+fn decode_pair(format: SurfaceFormat, pair: &[u8]) -> [[u8; 4]; 2] {
+    match format {
+        SurfaceFormat::VideoYuYv | SurfaceFormat::VideoUyVy => {
+            let (y0, u, y1, v) = match format {
+                SurfaceFormat::VideoYuYv => (pair[0], pair[1], pair[2], pair[3]),
+                _ => (pair[1], pair[0], pair[3], pair[2]),
+            };
+            [yuv_to_rgba(y0, u, v), yuv_to_rgba(y1, u, v)]
+        }
+        SurfaceFormat::VideoGrGb => [
+            [pair[1], pair[0], pair[3], 255],
+            [pair[1], pair[2], pair[3], 255],
+        ],
+        _ => [
+            [pair[0], pair[1], pair[2], 255],
+            [pair[0], pair[3], pair[2], 255],
+        ],
+    }
+}
+
+/// bt.601 conversion from video range yuv
+fn yuv_to_rgba(y: u8, u: u8, v: u8) -> [u8; 4] {
+    let y = (y as f32 - 16.0) * 1.164;
+    let u = u as f32 - 128.0;
+    let v = v as f32 - 128.0;
+    let channel = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+    [
+        channel(y + 1.596 * v),
+        channel(y - 0.392 * u - 0.813 * v),
+        channel(y + 2.017 * u),
+        255,
+    ]
+}
+
+fn unpremultiply(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let a = pixel[3] as u32;
+        if a == 0 {
+            continue;
+        }
+        for channel in &mut pixel[..3] {
+            *channel = (*channel as u32 * 255 / a).min(255) as u8;
+        }
+    }
+}
+
+/// scales an unsigned value with the given number of bits to 0..=255
+fn unorm(value: u32, bits: u32) -> u8 {
+    let max = ((1u64 << bits) - 1) as f64;
+    (value as f64 * 255.0 / max).round() as u8
+}
+
+/// maps a signed value with the given number of bits from -1..=1 to 0..=255
+fn snorm(value: i32, bits: u32) -> u8 {
+    let max = ((1 << (bits - 1)) - 1) as f32;
+    snormf((value as f32 / max).max(-1.0))
+}
+
+fn snormf(value: f32) -> u8 {
+    ((value * 0.5 + 0.5) * 255.0).round() as u8
+}
+
+fn float(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn sign_extend(value: u32, bits: u32) -> i32 {
+    let shift = 32 - bits;
+    ((value << shift) as i32) >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let mut png = Vec::new();
-        let encoder = PngEncoder::new(&mut png);
-        encoder.write_image(
-            &decompressed,
-            self.width,
-            self.height,
-            ExtendedColorType::Rgba8,
-        )?;
+    #[test]
+    fn odd_width_video_rows_are_padded() {
+        // 3x2 RGBG texture, each row padded to two pixel pairs
+        let row = [10, 20, 30, 40, 50, 60, 70, 80];
+        let data = [row, row].concat();
+        let pixels = decode(SurfaceFormat::VideoRgBg, &data, 3, 2).unwrap();
+        let expected_row = [10, 20, 30, 255, 10, 40, 30, 255, 50, 60, 70, 255];
+        assert_eq!(pixels, [expected_row, expected_row].concat());
 
-        Ok(png)
+        let pixels = decode(SurfaceFormat::VideoYuYv, &[235, 128, 16, 128], 1, 1).unwrap();
+        assert_eq!(pixels, [255, 255, 255, 255]);
     }
 }
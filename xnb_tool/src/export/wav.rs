@@ -0,0 +1,275 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+
+const WAVE_FORMAT_PCM: u16 = 1;
+
+/// wraps interleaved little-endian pcm samples in a wav file, the start and length of a loop in
+/// samples are stored in a `smpl` chunk. loops are cut off at the end of the data and left out
+/// when they start past it
+pub fn encode_pcm_wav(
+    channels: u16,
+    sample_rate: u32,
+    bits_per_sample: u16,
+    data: &[u8],
+    loop_region: Option<(u32, u32)>,
+) -> Vec<u8> {
+    let block_align = channels * bits_per_sample.div_ceil(8);
+    let mut fmt = Vec::with_capacity(16);
+    fmt.write_u16::<LittleEndian>(WAVE_FORMAT_PCM).unwrap();
+    fmt.write_u16::<LittleEndian>(channels).unwrap();
+    fmt.write_u32::<LittleEndian>(sample_rate).unwrap();
+    fmt.write_u32::<LittleEndian>(sample_rate * block_align as u32)
+        .unwrap();
+    fmt.write_u16::<LittleEndian>(block_align).unwrap();
+    fmt.write_u16::<LittleEndian>(bits_per_sample).unwrap();
+
+    let mut wav = Vec::with_capacity(44 + data.len());
+    wav.extend_from_slice(b"RIFF");
+    wav.write_u32::<LittleEndian>(0).unwrap();
+    wav.extend_from_slice(b"WAVE");
+    write_chunk(&mut wav, b"fmt ", &fmt);
+    write_chunk(&mut wav, b"data", data);
+    let frames = (data.len() / block_align.max(1) as usize) as u64;
+    if let Some((start, length)) = loop_region.filter(|(_, length)| *length > 0)
+        && (start as u64) < frames
+    {
+        // the end is inclusive
+        let end = (start as u64 + length as u64).min(frames) - 1;
+        write_chunk(
+            &mut wav,
+            b"smpl",
+            &sampler_chunk(sample_rate, start, end as u32),
+        );
+    }
+    let riff_size = (wav.len() - 8) as u32;
+    wav[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    wav
+}
+
+fn sampler_chunk(sample_rate: u32, start: u32, end: u32) -> Vec<u8> {
+    let mut smpl = Vec::with_capacity(60);
+    let fields = [
+        0,                                  // manufacturer
+        0,                                  // product
+        1_000_000_000 / sample_rate.max(1), // sample period in nanoseconds
+        60,                                 // midi unity note, middle c
+        0,                                  // midi pitch fraction
+        0,                                  // smpte format
+        0,                                  // smpte offset
+        1,                                  // loop count
+        0,                                  // sampler data size
+        0,                                  // loop cue point id
+        0,                                  // loop type, forward
+        start,
+        end,
+        0, // fraction
+        0, // play count, infinite
+    ];
+    for field in fields {
+        smpl.write_u32::<LittleEndian>(field).unwrap();
+    }
+    smpl
+}
+
+fn write_chunk(wav: &mut Vec<u8>, id: &[u8; 4], data: &[u8]) {
+    wav.extend_from_slice(id);
+    wav.write_u32::<LittleEndian>(data.len() as u32).unwrap();
+    wav.extend_from_slice(data);
+    // chunks are aligned to two bytes
+    if !data.len().is_multiple_of(2) {
+        wav.push(0);
+    }
+}
+
+const ADPCM_COEFFICIENTS: [(i32, i32); 7] = [
+    (256, 0),
+    (512, -256),
+    (0, 0),
+    (192, 64),
+    (240, 0),
+    (460, -208),
+    (392, -232),
+];
+
+const ADPCM_ADAPTATION: [i32; 16] = [
+    230, 230, 230, 230, 307, 409, 512, 614, 768, 614, 512, 409, 307, 230, 230, 230,
+];
+
+/// decodes microsoft adpcm into interleaved 16-bit samples, the last block may be shorter than
+/// `block_align`
+pub fn decode_ms_adpcm(data: &[u8], channels: u16, block_align: usize) -> anyhow::Result<Vec<i16>> {
+    let channels = channels as usize;
+    if channels == 0 || channels > 2 {
+        anyhow::bail!("ms adpcm supports 1 or 2 channels, found {channels}");
+    }
+    let header_size = 7 * channels;
+    if block_align <= header_size {
+        anyhow::bail!("ms adpcm block align {block_align} is smaller than the block header");
+    }
+
+    let mut samples = Vec::new();
+    for block in data.chunks(block_align) {
+        if block.len() < header_size {
+            break;
+        }
+
+        let mut coefficients = [(0, 0); 2];
+        let mut deltas = [0; 2];
+        let mut sample1 = [0; 2];
+        let mut sample2 = [0; 2];
+        let read_i16 =
+            |offset: usize| i16::from_le_bytes([block[offset], block[offset + 1]]) as i32;
+        for ch in 0..channels {
+            let predictor = block[ch] as usize;
+            coefficients[ch] = *ADPCM_COEFFICIENTS
+                .get(predictor)
+                .ok_or_else(|| anyhow::anyhow!("invalid ms adpcm predictor {predictor}"))?;
+            deltas[ch] = read_i16(channels + ch * 2);
+            sample1[ch] = read_i16(channels * 3 + ch * 2);
+            sample2[ch] = read_i16(channels * 5 + ch * 2);
+        }
+
+        // the header holds the first two samples, oldest last
+        samples.extend(sample2[..channels].iter().map(|s| *s as i16));
+        samples.extend(sample1[..channels].iter().map(|s| *s as i16));
+
+        // mono blocks hold one sample per nibble, stereo blocks alternate between the channels
+        let nibbles = block[header_size..]
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0xf]);
+        for (i, nibble) in nibbles.enumerate() {
+            let ch = i % channels;
+            let (coefficient1, coefficient2) = coefficients[ch];
+            let predicted = (sample1[ch] * coefficient1 + sample2[ch] * coefficient2) >> 8;
+            let signed = if nibble & 8 != 0 {
+                nibble as i32 - 16
+            } else {
+                nibble as i32
+            };
+            let sample = (predicted + signed * deltas[ch]).clamp(i16::MIN as i32, i16::MAX as i32);
+            sample2[ch] = sample1[ch];
+            sample1[ch] = sample;
+            deltas[ch] = ((ADPCM_ADAPTATION[nibble as usize] * deltas[ch]) >> 8).max(16);
+            samples.push(sample as i16);
+        }
+    }
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// start and inclusive end of the loop in the `smpl` chunk, if there is one
+    fn sampler_loop(wav: &[u8]) -> Option<(u32, u32)> {
+        let mut chunks = &wav[12..];
+        while chunks.len() >= 8 {
+            let size = u32::from_le_bytes(chunks[4..8].try_into().unwrap()) as usize;
+            if &chunks[..4] == b"smpl" {
+                let field = |i: usize| {
+                    u32::from_le_bytes(chunks[8 + i * 4..12 + i * 4].try_into().unwrap())
+                };
+                return Some((field(11), field(12)));
+            }
+            chunks = &chunks[8 + size.next_multiple_of(2)..];
+        }
+        None
+    }
+
+    #[test]
+    fn loops_are_kept_inside_the_data() {
+        // 100 mono 16-bit samples
+        let data = vec![0; 200];
+        let wav = |loop_region| encode_pcm_wav(1, 44100, 16, &data, Some(loop_region));
+        assert_eq!(sampler_loop(&wav((10, 20))), Some((10, 29)));
+        assert_eq!(sampler_loop(&wav((90, 20))), Some((90, 99)));
+        assert_eq!(sampler_loop(&wav((u32::MAX - 1, 10))), None);
+        assert_eq!(sampler_loop(&wav((100, 1))), None);
+        assert_eq!(sampler_loop(&wav((0, 0))), None);
+    }
+
+    /// encodes interleaved samples with the first predictor, returns the data along with the
+    /// samples a decoder should produce from it
+    fn encode_ms_adpcm(
+        samples: &[i16],
+        channels: usize,
+        block_align: usize,
+    ) -> (Vec<u8>, Vec<i16>) {
+        let frames_per_block = (block_align - 7 * channels) * 2 / channels + 2;
+        let mut data = Vec::new();
+        let mut expected = Vec::new();
+        for block in samples.chunks(frames_per_block * channels) {
+            let mut deltas = vec![16; channels];
+            let mut sample1: Vec<i32> = block[channels..channels * 2]
+                .iter()
+                .map(|s| *s as i32)
+                .collect();
+            let mut sample2: Vec<i32> = block[..channels].iter().map(|s| *s as i32).collect();
+            data.extend(std::iter::repeat_n(0, channels));
+            for values in [&deltas, &sample1, &sample2] {
+                for value in values {
+                    data.extend_from_slice(&(*value as i16).to_le_bytes());
+                }
+            }
+
+            expected.extend_from_slice(&block[..channels * 2]);
+            let mut nibbles = Vec::new();
+            for (i, sample) in block[channels * 2..].iter().enumerate() {
+                let ch = i % channels;
+                let predicted = sample1[ch];
+                let step = (*sample as i32 - predicted) as f32 / deltas[ch] as f32;
+                let signed = (step.round() as i32).clamp(-8, 7);
+                let decoded = (predicted + signed * deltas[ch]).clamp(-32768, 32767);
+                sample2[ch] = sample1[ch];
+                sample1[ch] = decoded;
+                let nibble = (signed & 0xf) as usize;
+                deltas[ch] = ((ADPCM_ADAPTATION[nibble] * deltas[ch]) >> 8).max(16);
+                nibbles.push(nibble as u8);
+                expected.push(decoded as i16);
+            }
+            data.extend(nibbles.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+        }
+        (data, expected)
+    }
+
+    #[test]
+    fn ms_adpcm_round_trip() {
+        for channels in [1, 2] {
+            let block_align = 64 * channels;
+            // a few full blocks and a shorter last block
+            let frames = 500;
+            let samples: Vec<i16> = (0..frames * channels)
+                .map(|i| {
+                    let t = (i / channels) as f32 / 22050.0;
+                    let frequency = if i % channels == 0 { 220.0 } else { 330.0 };
+                    ((t * frequency * std::f32::consts::TAU).sin() * 8000.0) as i16
+                })
+                .collect();
+            let (encoded, expected) = encode_ms_adpcm(&samples, channels, block_align);
+            let decoded = decode_ms_adpcm(&encoded, channels as u16, block_align).unwrap();
+            assert!(
+                decoded == expected,
+                "{channels} channels decoded differently"
+            );
+            // the step size starts small in every block, so single samples can be far off while
+            // the signal as a whole is followed closely
+            let error = samples
+                .iter()
+                .zip(&decoded)
+                .map(|(a, b)| a.abs_diff(*b) as usize)
+                .sum::<usize>()
+                / samples.len();
+            assert!(
+                error <= 100,
+                "{channels} channels have a mean error of {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn ms_adpcm_rejects_bad_input() {
+        assert!(decode_ms_adpcm(&[0; 64], 3, 64).is_err());
+        assert!(decode_ms_adpcm(&[0; 64], 1, 7).is_err());
+        // predictor 7 does not exist
+        assert!(decode_ms_adpcm(&[7; 64], 1, 64).is_err());
+    }
+}
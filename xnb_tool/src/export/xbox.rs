@@ -0,0 +1,297 @@
+use anyhow::Context;
+
+use crate::{
+    content::{
+        Content,
+        level_model::{AnimatedLevelPart, LevelGeometry, LevelModel},
+        model::{IndexBuffer, Model, VertexDeclaration},
+        texture::{SurfaceFormat, Texture2D},
+    },
+    xnb::{Platform, XnbContent},
+};
+
+impl XnbContent {
+    /// converts xbox 360 vertex buffers, index buffers and textures to the little-endian,
+    /// linear layout used on windows so they can be exported, does nothing for other platforms
+    pub fn convert_from_xbox360(&mut self) -> anyhow::Result<()> {
+        if self.platform != Platform::Xbox360 {
+            return Ok(());
+        }
+
+        convert_content(&mut self.primary_content).context("failed to convert primary content")?;
+        for (i, content) in self.shared_content.iter_mut().enumerate() {
+            convert_content(content)
+                .with_context(|| format!("failed to convert shared content {i}"))?;
+        }
+
+        self.platform = Platform::Windows;
+        Ok(())
+    }
+}
+
+fn convert_content(content: &mut Content) -> anyhow::Result<()> {
+    match content {
+        Content::Texture2D(texture) => texture.untile_xbox360()?,
+        Content::SpriteFont(font) => font.texture.untile_xbox360()?,
+        Content::Model(model) => model.swap_buffers()?,
+        Content::SkinnedModel(model) => model.model.swap_buffers()?,
+        Content::LevelModel(level) => level.swap_buffers()?,
+        Content::BiTreeModel(model) => {
+            for tree in &mut model.trees {
+                tree.geometry.swap_buffers()?;
+            }
+        }
+        Content::VertexBuffer(buffer) => match &buffer.declaration {
+            Some(decl) => swap_vertices(&mut buffer.data, decl)?,
+            None => eprintln!("WARNING: vertex buffer without a declaration was left big-endian"),
+        },
+        Content::IndexBuffer(buffer) => swap_indices(buffer)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+impl Model {
+    fn swap_buffers(&mut self) -> anyhow::Result<()> {
+        for mesh in &mut self.meshes {
+            // parts usually share vertices, every range must only be swapped once
+            let mut swapped = Vec::new();
+            for part in &mesh.parts {
+                let decl = self
+                    .vertex_decls
+                    .get(part.vertex_decl_index as usize)
+                    .context("mesh part references a missing vertex declaration")?;
+                let stride = decl.stride();
+                let start = part.base_vertex as usize * stride;
+                let end = start + part.vertex_count as usize * stride;
+                if swapped.contains(&(start, end)) {
+                    continue;
+                }
+                let data = mesh
+                    .vertex_buffer
+                    .data
+                    .get_mut(start..end)
+                    .context("mesh part vertices are out of bounds")?;
+                swap_vertices(data, decl)?;
+                swapped.push((start, end));
+            }
+            swap_indices(&mut mesh.index_buffer)?;
+        }
+        Ok(())
+    }
+}
+
+impl LevelModel {
+    fn swap_buffers(&mut self) -> anyhow::Result<()> {
+        for (i, tree) in self.geometry.trees.iter_mut().enumerate() {
+            tree.geometry
+                .swap_buffers()
+                .with_context(|| format!("failed to convert bi tree {i}"))?;
+        }
+        // the raw data is left big-endian so it can be written back
+        let Some(sections) = &mut self.sections else {
+            return Ok(());
+        };
+        for part in &mut sections.animated_parts {
+            part.swap_buffers()?;
+        }
+        for liquid in &mut sections.liquids {
+            liquid.geometry.swap_buffers()?;
+        }
+        for field in &mut sections.force_fields {
+            field.geometry.swap_buffers()?;
+        }
+        Ok(())
+    }
+}
+
+impl AnimatedLevelPart {
+    fn swap_buffers(&mut self) -> anyhow::Result<()> {
+        self.model
+            .swap_buffers()
+            .with_context(|| format!("failed to convert animated part {}", self.name))?;
+        for liquid in &mut self.liquids {
+            liquid.geometry.swap_buffers()?;
+        }
+        for child in &mut self.children {
+            child.swap_buffers()?;
+        }
+        Ok(())
+    }
+}
+
+impl LevelGeometry {
+    fn swap_buffers(&mut self) -> anyhow::Result<()> {
+        swap_vertices(&mut self.vertex_buffer.data, &self.vertex_decl)?;
+        swap_indices(&mut self.index_buffer)
+    }
+}
+
+fn swap_vertices(data: &mut [u8], decl: &VertexDeclaration) -> anyhow::Result<()> {
+    let stride = decl.stride();
+    if stride == 0 || !data.len().is_multiple_of(stride) {
+        anyhow::bail!(
+            "vertex buffer size {} is not a multiple of the vertex stride {stride}",
+            data.len()
+        );
+    }
+    for vertex in data.chunks_exact_mut(stride) {
+        for el in &decl.elements {
+            let start = el.offset as usize;
+            let element = vertex
+                .get_mut(start..start + el.format.size())
+                .context("vertex element is outside of the vertex stride")?;
+            swap_bytes(element, el.format.component_size());
+        }
+    }
+    Ok(())
+}
+
+fn swap_indices(buffer: &mut IndexBuffer) -> anyhow::Result<()> {
+    let size = if buffer.is_16_bit { 2 } else { 4 };
+    if !buffer.data.len().is_multiple_of(size) {
+        anyhow::bail!(
+            "index buffer size {} is not a multiple of {size}",
+            buffer.data.len()
+        );
+    }
+    swap_bytes(&mut buffer.data, size);
+    Ok(())
+}
+
+fn swap_bytes(data: &mut [u8], size: usize) {
+    if size > 1 {
+        for value in data.chunks_exact_mut(size) {
+            value.reverse();
+        }
+    }
+}
+
+/// how a surface format is laid out in xbox 360 texture memory
+struct TexelLayout {
+    /// width and height of a block, 4 for dxt formats and 1 for everything else
+    block_size: u32,
+    /// bytes per block
+    pitch: usize,
+    /// size of the values that are stored big-endian
+    swap_size: usize,
+}
+
+impl TexelLayout {
+    fn new(format: SurfaceFormat) -> anyhow::Result<Self> {
+        let swap_size = match format {
+            SurfaceFormat::Color
+            | SurfaceFormat::Bgr32
+            | SurfaceFormat::Bgra1010102
+            | SurfaceFormat::Rgba32
+            | SurfaceFormat::Rgb32
+            | SurfaceFormat::Rgba1010102
+            | SurfaceFormat::NormalizedByte4
+            | SurfaceFormat::Single
+            | SurfaceFormat::Vector2
+            | SurfaceFormat::Vector4 => 4,
+            SurfaceFormat::Rg32
+            | SurfaceFormat::NormalizedShort2
+            | SurfaceFormat::HalfVector2
+            | SurfaceFormat::Rgba64
+            | SurfaceFormat::NormalizedShort4
+            | SurfaceFormat::HalfVector4
+            | SurfaceFormat::Bgr565
+            | SurfaceFormat::Bgra5551
+            | SurfaceFormat::Bgr555
+            | SurfaceFormat::Bgra4444
+            | SurfaceFormat::Bgr444
+            | SurfaceFormat::Bgra2338
+            | SurfaceFormat::NormalizedByte2
+            | SurfaceFormat::HalfSingle
+            | SurfaceFormat::Luminance16
+            | SurfaceFormat::LuminanceAlpha16
+            | SurfaceFormat::Dxt1
+            | SurfaceFormat::Dxt2
+            | SurfaceFormat::Dxt3
+            | SurfaceFormat::Dxt4
+            | SurfaceFormat::Dxt5 => 2,
+            SurfaceFormat::Alpha8
+            | SurfaceFormat::Bgr233
+            | SurfaceFormat::Luminance8
+            | SurfaceFormat::LuminanceAlpha8 => 1,
+            _ => anyhow::bail!("unsupported xbox 360 texture format: {format:?}"),
+        };
+        Ok(TexelLayout {
+            block_size: format.block_size(),
+            pitch: format.block_bytes(),
+            swap_size,
+        })
+    }
+}
+
+impl Texture2D {
+    fn untile_xbox360(&mut self) -> anyhow::Result<()> {
+        let layout = TexelLayout::new(self.format)?;
+        for (level, mip) in self.mips.iter_mut().enumerate() {
+            let width = (self.width >> level).max(1).div_ceil(layout.block_size);
+            let height = (self.height >> level).max(1).div_ceil(layout.block_size);
+            swap_bytes(mip, layout.swap_size);
+            *mip = untile(mip, width as usize, height as usize, layout.pitch);
+        }
+        Ok(())
+    }
+}
+
+/// rearranges tiled blocks into rows, the tiled data is padded to whole tiles
+fn untile(data: &[u8], width: usize, height: usize, pitch: usize) -> Vec<u8> {
+    let mut linear = vec![0; width * height * pitch];
+    for (offset, block) in data.chunks_exact(pitch).enumerate() {
+        let x = tiled_x(offset, width, pitch);
+        let y = tiled_y(offset, width, pitch);
+        if x < width && y < height {
+            let dest = (y * width + x) * pitch;
+            linear[dest..dest + pitch].copy_from_slice(block);
+        }
+    }
+    linear
+}
+
+/// log2 of the block pitch, which is always 1, 2, 4, 8 or 16 bytes
+fn log_pitch(pitch: usize) -> usize {
+    (pitch >> 2) + ((pitch >> 1) >> (pitch >> 2))
+}
+
+/// byte offset of a block inside its tile, used by both `tiled_x` and `tiled_y`
+fn tile_offset(offset: usize, pitch: usize) -> usize {
+    let offset_byte = offset << log_pitch(pitch);
+    ((offset_byte & !0xFFF) >> 3) + ((offset_byte & 0x700) >> 2) + (offset_byte & 0x3F)
+}
+
+/// column of the block at the given offset into tiled memory, mirrors `XGAddress2DTiledX`
+fn tiled_x(offset: usize, width: usize, pitch: usize) -> usize {
+    let aligned_width = width.next_multiple_of(32);
+    let log_bpp = log_pitch(pitch);
+    let offset_byte = offset << log_bpp;
+    let offset_tile = tile_offset(offset, pitch);
+    let offset_macro = offset_tile >> (7 + log_bpp);
+
+    let macro_x = (offset_macro % (aligned_width >> 5)) << 2;
+    let tile = (((offset_tile >> (5 + log_bpp)) & 2) + (offset_byte >> 6)) & 3;
+    let macro_ = (macro_x + tile) << 3;
+    let micro =
+        ((((offset_tile >> 1) & !0xF) + (offset_tile & 0xF)) & ((pitch << 3) - 1)) >> log_bpp;
+    macro_ + micro
+}
+
+/// row of the block at the given offset into tiled memory, mirrors `XGAddress2DTiledY`
+fn tiled_y(offset: usize, width: usize, pitch: usize) -> usize {
+    let aligned_width = width.next_multiple_of(32);
+    let log_bpp = log_pitch(pitch);
+    let offset_byte = offset << log_bpp;
+    let offset_tile = tile_offset(offset, pitch);
+    let offset_macro = offset_tile >> (7 + log_bpp);
+
+    let macro_y = (offset_macro / (aligned_width >> 5)) << 2;
+    let tile = ((offset_tile >> (6 + log_bpp)) & 1) + ((offset_byte & 0x800) >> 10);
+    let macro_ = (macro_y + tile) << 3;
+    let micro = (((offset_tile & (((pitch << 6) - 1) & !0x1F)) + ((offset_tile & 0xF) << 1))
+        >> (3 + log_bpp))
+        & !1;
+    macro_ + micro + ((offset_tile & 0x10) >> 4)
+}
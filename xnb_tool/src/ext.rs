@@ -1,13 +1,14 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 use glam::{Mat4, Quat, Vec3};
 
 pub trait MyReadBytesExt: ReadBytesExt {
     fn read_bool(&mut self) -> std::io::Result<bool>;
     fn read_7bit_encoded_i32(&mut self) -> std::io::Result<i32>;
     fn read_7bit_length_string(&mut self) -> std::io::Result<String>;
-    fn read_vec3(&mut self) -> std::io::Result<Vec3>;
-    fn read_mat4(&mut self) -> std::io::Result<Mat4>;
-    fn read_quat(&mut self) -> std::io::Result<Quat>;
+    fn read_vec3<E: ByteOrder>(&mut self) -> std::io::Result<Vec3>;
+    fn read_mat4<E: ByteOrder>(&mut self) -> std::io::Result<Mat4>;
+    fn read_quat<E: ByteOrder>(&mut self) -> std::io::Result<Quat>;
+    fn read_utf8_char(&mut self) -> std::io::Result<char>;
 }
 
 impl<R: ReadBytesExt> MyReadBytesExt for R {
@@ -41,33 +42,33 @@ impl<R: ReadBytesExt> MyReadBytesExt for R {
         Ok(s)
     }
 
-    fn read_vec3(&mut self) -> std::io::Result<Vec3> {
-        let x = self.read_f32::<LittleEndian>()?;
-        let y = self.read_f32::<LittleEndian>()?;
-        let z = self.read_f32::<LittleEndian>()?;
+    fn read_vec3<E: ByteOrder>(&mut self) -> std::io::Result<Vec3> {
+        let x = self.read_f32::<E>()?;
+        let y = self.read_f32::<E>()?;
+        let z = self.read_f32::<E>()?;
         Ok(Vec3::new(x, y, z))
     }
 
-    fn read_mat4(&mut self) -> std::io::Result<Mat4> {
-        let m11 = self.read_f32::<LittleEndian>()?;
-        let m12 = self.read_f32::<LittleEndian>()?;
-        let m13 = self.read_f32::<LittleEndian>()?;
-        let m14 = self.read_f32::<LittleEndian>()?;
+    fn read_mat4<E: ByteOrder>(&mut self) -> std::io::Result<Mat4> {
+        let m11 = self.read_f32::<E>()?;
+        let m12 = self.read_f32::<E>()?;
+        let m13 = self.read_f32::<E>()?;
+        let m14 = self.read_f32::<E>()?;
 
-        let m21 = self.read_f32::<LittleEndian>()?;
-        let m22 = self.read_f32::<LittleEndian>()?;
-        let m23 = self.read_f32::<LittleEndian>()?;
-        let m24 = self.read_f32::<LittleEndian>()?;
+        let m21 = self.read_f32::<E>()?;
+        let m22 = self.read_f32::<E>()?;
+        let m23 = self.read_f32::<E>()?;
+        let m24 = self.read_f32::<E>()?;
 
-        let m31 = self.read_f32::<LittleEndian>()?;
-        let m32 = self.read_f32::<LittleEndian>()?;
-        let m33 = self.read_f32::<LittleEndian>()?;
-        let m34 = self.read_f32::<LittleEndian>()?;
+        let m31 = self.read_f32::<E>()?;
+        let m32 = self.read_f32::<E>()?;
+        let m33 = self.read_f32::<E>()?;
+        let m34 = self.read_f32::<E>()?;
 
-        let m41 = self.read_f32::<LittleEndian>()?;
-        let m42 = self.read_f32::<LittleEndian>()?;
-        let m43 = self.read_f32::<LittleEndian>()?;
-        let m44 = self.read_f32::<LittleEndian>()?;
+        let m41 = self.read_f32::<E>()?;
+        let m42 = self.read_f32::<E>()?;
+        let m43 = self.read_f32::<E>()?;
+        let m44 = self.read_f32::<E>()?;
 
         let mat = Mat4::from_cols_array(&[
             m11, m12, m13, m14, m21, m22, m23, m24, m31, m32, m33, m34, m41, m42, m43, m44,
@@ -77,11 +78,104 @@ impl<R: ReadBytesExt> MyReadBytesExt for R {
         Ok(mat)
     }
 
-    fn read_quat(&mut self) -> std::io::Result<Quat> {
-        let x = self.read_f32::<LittleEndian>()?;
-        let y = self.read_f32::<LittleEndian>()?;
-        let z = self.read_f32::<LittleEndian>()?;
-        let w = self.read_f32::<LittleEndian>()?;
+    fn read_quat<E: ByteOrder>(&mut self) -> std::io::Result<Quat> {
+        let x = self.read_f32::<E>()?;
+        let y = self.read_f32::<E>()?;
+        let z = self.read_f32::<E>()?;
+        let w = self.read_f32::<E>()?;
         Ok(Quat::from_xyzw(x, y, z, w))
     }
+
+    /// reads a single utf-8 encoded char, as written by .NET's `BinaryWriter.Write(char)`
+    fn read_utf8_char(&mut self) -> std::io::Result<char> {
+        let first = self.read_u8()?;
+        let len = match first {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 0,
+        };
+        let mut bytes = [first, 0, 0, 0];
+        if len > 1 {
+            self.read_exact(&mut bytes[1..len])?;
+        }
+        std::str::from_utf8(&bytes[..len])
+            .ok()
+            .and_then(|s| s.chars().next())
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("invalid utf-8 char starting with {first:#x}"),
+                )
+            })
+    }
+}
+
+pub trait MyWriteBytesExt: WriteBytesExt {
+    fn write_bool(&mut self, value: bool) -> std::io::Result<()>;
+    fn write_7bit_encoded_i32(&mut self, value: i32) -> std::io::Result<()>;
+    fn write_7bit_length_string(&mut self, value: &str) -> std::io::Result<()>;
+    fn write_vec3<E: ByteOrder>(&mut self, value: Vec3) -> std::io::Result<()>;
+    fn write_mat4<E: ByteOrder>(&mut self, value: Mat4) -> std::io::Result<()>;
+    fn write_quat<E: ByteOrder>(&mut self, value: Quat) -> std::io::Result<()>;
+    fn write_utf8_char(&mut self, value: char) -> std::io::Result<()>;
+}
+
+impl<W: WriteBytesExt> MyWriteBytesExt for W {
+    fn write_bool(&mut self, value: bool) -> std::io::Result<()> {
+        self.write_u8(value as u8)
+    }
+
+    fn write_7bit_encoded_i32(&mut self, value: i32) -> std::io::Result<()> {
+        let mut value = value as u32;
+        while value >= 0x80 {
+            self.write_u8((value as u8) | 0x80)?;
+            value >>= 7;
+        }
+        self.write_u8(value as u8)
+    }
+
+    fn write_7bit_length_string(&mut self, value: &str) -> std::io::Result<()> {
+        // mirrors `read_7bit_length_string`, which maps every byte to a single char
+        let mut bytes = Vec::with_capacity(value.len());
+        for c in value.chars() {
+            let byte = u8::try_from(c as u32).map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("character {c:?} does not fit in a single byte"),
+                )
+            })?;
+            bytes.push(byte);
+        }
+        self.write_7bit_encoded_i32(bytes.len() as i32)?;
+        self.write_all(&bytes)
+    }
+
+    fn write_vec3<E: ByteOrder>(&mut self, value: Vec3) -> std::io::Result<()> {
+        self.write_f32::<E>(value.x)?;
+        self.write_f32::<E>(value.y)?;
+        self.write_f32::<E>(value.z)?;
+        Ok(())
+    }
+
+    fn write_mat4<E: ByteOrder>(&mut self, value: Mat4) -> std::io::Result<()> {
+        for v in value.transpose().to_cols_array() {
+            self.write_f32::<E>(v)?;
+        }
+        Ok(())
+    }
+
+    fn write_quat<E: ByteOrder>(&mut self, value: Quat) -> std::io::Result<()> {
+        self.write_f32::<E>(value.x)?;
+        self.write_f32::<E>(value.y)?;
+        self.write_f32::<E>(value.z)?;
+        self.write_f32::<E>(value.w)?;
+        Ok(())
+    }
+
+    fn write_utf8_char(&mut self, value: char) -> std::io::Result<()> {
+        let mut bytes = [0; 4];
+        self.write_all(value.encode_utf8(&mut bytes).as_bytes())
+    }
 }
@@ -0,0 +1,254 @@
+//! block compression encoders for the dxt formats
+
+use glam::Vec3;
+
+/// rgba8 texels of a 4x4 block in row order
+type Block = [[u8; 4]; 16];
+
+/// compresses rgba8 pixels into dxt1 blocks, alpha is ignored
+pub fn encode_dxt1(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(width.div_ceil(4) * height.div_ceil(4) * 8);
+    for block in blocks(pixels, width, height) {
+        encode_color_block(&block, &mut out);
+    }
+    out
+}
+
+/// compresses rgba8 pixels into dxt3 blocks with explicit 4 bit alpha
+pub fn encode_dxt3(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(width.div_ceil(4) * height.div_ceil(4) * 16);
+    for block in blocks(pixels, width, height) {
+        let mut alpha = 0u64;
+        for (i, texel) in block.iter().enumerate() {
+            let value = (texel[3] as u64 * 15 + 127) / 255;
+            alpha |= value << (i * 4);
+        }
+        out.extend_from_slice(&alpha.to_le_bytes());
+        encode_color_block(&block, &mut out);
+    }
+    out
+}
+
+/// compresses rgba8 pixels into dxt5 blocks with interpolated alpha
+pub fn encode_dxt5(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(width.div_ceil(4) * height.div_ceil(4) * 16);
+    for block in blocks(pixels, width, height) {
+        encode_alpha_block(&block, &mut out);
+        encode_color_block(&block, &mut out);
+    }
+    out
+}
+
+/// splits the image into 4x4 blocks, blocks hanging over the edge repeat the last texel
+fn blocks(pixels: &[u8], width: usize, height: usize) -> impl Iterator<Item = Block> + '_ {
+    let blocks_wide = width.div_ceil(4);
+    let blocks_high = height.div_ceil(4);
+    (0..blocks_high).flat_map(move |by| {
+        (0..blocks_wide).map(move |bx| {
+            let mut block = [[0; 4]; 16];
+            for (i, texel) in block.iter_mut().enumerate() {
+                let x = (bx * 4 + i % 4).min(width - 1);
+                let y = (by * 4 + i / 4).min(height - 1);
+                let offset = (y * width + x) * 4;
+                texel.copy_from_slice(&pixels[offset..offset + 4]);
+            }
+            block
+        })
+    })
+}
+
+/// fits both endpoints to the principal axis of the block colors and picks the closest of the
+/// four palette entries for every texel
+fn encode_color_block(block: &Block, out: &mut Vec<u8>) {
+    let colors = block.map(|texel| Vec3::new(texel[0] as f32, texel[1] as f32, texel[2] as f32));
+    let mean = colors.iter().sum::<Vec3>() / 16.0;
+
+    let axis = principal_axis(&colors, mean);
+    let (mut min, mut max) = (f32::MAX, f32::MIN);
+    for color in &colors {
+        let t = (*color - mean).dot(axis);
+        min = min.min(t);
+        max = max.max(t);
+    }
+
+    let mut color0 = to_565(mean + axis * max);
+    let mut color1 = to_565(mean + axis * min);
+    if color0 < color1 {
+        std::mem::swap(&mut color0, &mut color1);
+    }
+
+    let mut indices = 0u32;
+    if color0 != color1 {
+        let c0 = from_565(color0);
+        let c1 = from_565(color1);
+        let palette = [c0, c1, (c0 * 2.0 + c1) / 3.0, (c0 + c1 * 2.0) / 3.0];
+        for (i, color) in colors.iter().enumerate() {
+            let index = nearest(&palette, |entry| entry.distance_squared(*color));
+            indices |= (index as u32) << (i * 2);
+        }
+    }
+
+    out.extend_from_slice(&color0.to_le_bytes());
+    out.extend_from_slice(&color1.to_le_bytes());
+    out.extend_from_slice(&indices.to_le_bytes());
+}
+
+/// uses the block's alpha range as endpoints with six interpolated values in between
+fn encode_alpha_block(block: &Block, out: &mut Vec<u8>) {
+    let alpha0 = block.iter().map(|texel| texel[3]).max().unwrap();
+    let alpha1 = block.iter().map(|texel| texel[3]).min().unwrap();
+
+    let mut indices = 0u64;
+    if alpha0 != alpha1 {
+        let (a0, a1) = (alpha0 as f32, alpha1 as f32);
+        let mut palette = [a0, a1, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        for i in 1..7 {
+            palette[i + 1] = (a0 * (7 - i) as f32 + a1 * i as f32) / 7.0;
+        }
+        for (i, texel) in block.iter().enumerate() {
+            let alpha = texel[3] as f32;
+            let index = nearest(&palette, |entry| (entry - alpha).abs());
+            indices |= (index as u64) << (i * 3);
+        }
+    }
+
+    out.push(alpha0);
+    out.push(alpha1);
+    out.extend_from_slice(&indices.to_le_bytes()[..6]);
+}
+
+/// direction of the largest spread of the colors, found by power iteration on the covariance
+fn principal_axis(colors: &[Vec3; 16], mean: Vec3) -> Vec3 {
+    let mut covariance = [Vec3::ZERO; 3];
+    for color in colors {
+        let d = *color - mean;
+        covariance[0] += d * d.x;
+        covariance[1] += d * d.y;
+        covariance[2] += d * d.z;
+    }
+
+    let mut axis = Vec3::ONE;
+    for _ in 0..8 {
+        let next = covariance[0] * axis.x + covariance[1] * axis.y + covariance[2] * axis.z;
+        if next.length_squared() < f32::EPSILON {
+            break;
+        }
+        axis = next.normalize();
+    }
+    axis.normalize_or_zero()
+}
+
+fn nearest<T: Copy>(palette: &[T], distance: impl Fn(T) -> f32) -> usize {
+    palette
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(**a).total_cmp(&distance(**b)))
+        .map(|(i, _)| i)
+        .unwrap()
+}
+
+fn to_565(color: Vec3) -> u16 {
+    let color = color.clamp(Vec3::ZERO, Vec3::splat(255.0));
+    let r = (color.x * 31.0 / 255.0).round() as u16;
+    let g = (color.y * 63.0 / 255.0).round() as u16;
+    let b = (color.z * 31.0 / 255.0).round() as u16;
+    (r << 11) | (g << 5) | b
+}
+
+fn from_565(color: u16) -> Vec3 {
+    let r = ((color >> 11) & 0x1f) as f32 * 255.0 / 31.0;
+    let g = ((color >> 5) & 0x3f) as f32 * 255.0 / 63.0;
+    let b = (color & 0x1f) as f32 * 255.0 / 31.0;
+    Vec3::new(r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use bcndecode::{BcnDecoderFormat, BcnEncoding};
+
+    use super::*;
+
+    /// smooth color and alpha gradients, dxt handles these with small errors
+    fn gradient(width: usize, height: usize) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let (r, g) = (x * 12, y * 12);
+                pixels.extend_from_slice(&[r as u8, g as u8, (255 - r) as u8, ((r + g) / 2) as u8]);
+            }
+        }
+        pixels
+    }
+
+    /// decodes the whole blocks and crops them, bcndecode flips images whose size is not a
+    /// multiple of 4
+    fn decode(data: &[u8], width: usize, height: usize, encoding: BcnEncoding) -> Vec<u8> {
+        let padded_width = width.next_multiple_of(4);
+        let padded_height = height.next_multiple_of(4);
+        let padded = bcndecode::decode(
+            data,
+            padded_width,
+            padded_height,
+            encoding,
+            BcnDecoderFormat::RGBA,
+        )
+        .unwrap();
+        padded
+            .chunks_exact(padded_width * 4)
+            .take(height)
+            .flat_map(|row| &row[..width * 4])
+            .copied()
+            .collect()
+    }
+
+    /// largest difference of the given channels between two rgba8 images
+    fn max_error(a: &[u8], b: &[u8], channels: std::ops::Range<usize>) -> u8 {
+        a.chunks_exact(4)
+            .zip(b.chunks_exact(4))
+            .flat_map(|(a, b)| channels.clone().map(move |c| a[c].abs_diff(b[c])))
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn dxt1_round_trip() {
+        for (width, height) in [(4, 4), (16, 16), (6, 5), (1, 1)] {
+            let pixels = gradient(width, height);
+            let encoded = encode_dxt1(&pixels, width, height);
+            assert_eq!(encoded.len(), width.div_ceil(4) * height.div_ceil(4) * 8);
+            let decoded = decode(&encoded, width, height, BcnEncoding::Bc1);
+            let error = max_error(&pixels, &decoded, 0..3);
+            assert!(error <= 24, "{width}x{height} has a color error of {error}");
+            assert!(decoded.chunks_exact(4).all(|pixel| pixel[3] == 255));
+        }
+    }
+
+    #[test]
+    fn dxt1_two_color_blocks_are_exact() {
+        let pixels: Vec<u8> = (0..64)
+            .flat_map(|i| {
+                if (i % 8 + i / 8) % 2 == 0 {
+                    [0, 0, 0, 255]
+                } else {
+                    [255, 255, 255, 255]
+                }
+            })
+            .collect();
+        let encoded = encode_dxt1(&pixels, 8, 8);
+        assert_eq!(decode(&encoded, 8, 8, BcnEncoding::Bc1), pixels);
+    }
+
+    #[test]
+    fn dxt5_round_trip() {
+        for (width, height) in [(4, 4), (16, 16), (6, 5), (1, 1)] {
+            let pixels = gradient(width, height);
+            let encoded = encode_dxt5(&pixels, width, height);
+            assert_eq!(encoded.len(), width.div_ceil(4) * height.div_ceil(4) * 16);
+            let decoded = decode(&encoded, width, height, BcnEncoding::Bc3);
+            let error = max_error(&pixels, &decoded, 0..3);
+            assert!(error <= 24, "{width}x{height} has a color error of {error}");
+            let error = max_error(&pixels, &decoded, 3..4);
+            assert!(error <= 4, "{width}x{height} has an alpha error of {error}");
+        }
+    }
+}
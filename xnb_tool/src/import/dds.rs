@@ -0,0 +1,231 @@
+use std::io::{Cursor, Read};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::{
+    content::texture::{SurfaceFormat, Texture2D},
+    export::dds::{
+        DDPF_ALPHAPIXELS, DDPF_FOURCC, DDS_HEADER_SIZE, DDS_MAGIC, DDSD_MIPMAPCOUNT,
+        DdsPixelFormat, four_cc,
+    },
+};
+
+use super::texture::{MipFilter, can_encode, encode, generate_mips};
+
+/// DXGI formats other tools commonly write for surface formats that have a legacy header
+const DXGI_ALIASES: [(u32, SurfaceFormat); 14] = [
+    (28, SurfaceFormat::Rgba32),   // R8G8B8A8_UNORM
+    (29, SurfaceFormat::Rgba32),   // R8G8B8A8_UNORM_SRGB
+    (65, SurfaceFormat::Alpha8),   // A8_UNORM
+    (71, SurfaceFormat::Dxt1),     // BC1_UNORM
+    (72, SurfaceFormat::Dxt1),     // BC1_UNORM_SRGB
+    (74, SurfaceFormat::Dxt3),     // BC2_UNORM
+    (75, SurfaceFormat::Dxt3),     // BC2_UNORM_SRGB
+    (77, SurfaceFormat::Dxt5),     // BC3_UNORM
+    (78, SurfaceFormat::Dxt5),     // BC3_UNORM_SRGB
+    (85, SurfaceFormat::Bgr565),   // B5G6R5_UNORM
+    (86, SurfaceFormat::Bgra5551), // B5G5R5A1_UNORM
+    (87, SurfaceFormat::Color),    // B8G8R8A8_UNORM
+    (88, SurfaceFormat::Bgr32),    // B8G8R8X8_UNORM
+    (91, SurfaceFormat::Color),    // B8G8R8A8_UNORM_SRGB
+];
+
+impl Texture2D {
+    /// builds a texture from a dds, the stored levels are copied without decoding them. when the
+    /// dds has no mip levels of its own they are generated with `filter` from the top level
+    pub fn from_dds(dds: &[u8], filter: MipFilter) -> anyhow::Result<Self> {
+        let mut reader = Cursor::new(dds);
+        if reader.read_u32::<LittleEndian>()? != DDS_MAGIC {
+            anyhow::bail!("not a dds file");
+        }
+        let header_size = reader.read_u32::<LittleEndian>()?;
+        if header_size != DDS_HEADER_SIZE {
+            anyhow::bail!("unexpected dds header size: {header_size}");
+        }
+        let flags = reader.read_u32::<LittleEndian>()?;
+        let height = reader.read_u32::<LittleEndian>()?;
+        let width = reader.read_u32::<LittleEndian>()?;
+        let _pitch_or_linear_size = reader.read_u32::<LittleEndian>()?;
+        let depth = reader.read_u32::<LittleEndian>()?;
+        let mip_count = reader.read_u32::<LittleEndian>()?;
+        reader.set_position(reader.position() + 11 * 4);
+
+        let _pixel_format_size = reader.read_u32::<LittleEndian>()?;
+        let pf_flags = reader.read_u32::<LittleEndian>()?;
+        let four_cc_value = reader.read_u32::<LittleEndian>()?;
+        let bits = reader.read_u32::<LittleEndian>()?;
+        let mut masks = [0; 4];
+        reader.read_u32_into::<LittleEndian>(&mut masks)?;
+        let _caps = reader.read_u32::<LittleEndian>()?;
+        let caps2 = reader.read_u32::<LittleEndian>()?;
+        reader.set_position(reader.position() + 3 * 4);
+
+        if depth > 1 || caps2 != 0 {
+            anyhow::bail!("only 2d dds textures are supported");
+        }
+
+        let format = if pf_flags & DDPF_FOURCC != 0 && four_cc_value == four_cc(b"DX10") {
+            let dxgi_format = reader.read_u32::<LittleEndian>()?;
+            let _resource_dimension = reader.read_u32::<LittleEndian>()?;
+            let _misc_flags = reader.read_u32::<LittleEndian>()?;
+            let array_size = reader.read_u32::<LittleEndian>()?;
+            let _alpha_mode = reader.read_u32::<LittleEndian>()?;
+            if array_size > 1 {
+                anyhow::bail!("dds texture arrays are not supported");
+            }
+            DXGI_ALIASES
+                .iter()
+                .find(|(dxgi, _)| *dxgi == dxgi_format)
+                .map(|(_, format)| *format)
+                .or_else(|| find_format(DdsPixelFormat::Dxgi(dxgi_format)))
+                .ok_or_else(|| anyhow::anyhow!("unsupported dxgi format: {dxgi_format}"))?
+        } else if pf_flags & DDPF_FOURCC != 0 {
+            find_format(DdsPixelFormat::FourCc(four_cc_value)).ok_or_else(|| {
+                let code = four_cc_value.to_le_bytes();
+                anyhow::anyhow!("unsupported four cc: {}", String::from_utf8_lossy(&code))
+            })?
+        } else {
+            // alpha pixels only says whether the alpha mask is used
+            let parsed = DdsPixelFormat::Masks {
+                flags: pf_flags & !DDPF_ALPHAPIXELS,
+                bits,
+                masks,
+            };
+            find_format(parsed).ok_or_else(|| {
+                anyhow::anyhow!("unsupported dds pixel format: {bits} bits with masks {masks:x?}")
+            })?
+        };
+
+        if width == 0 || height == 0 {
+            anyhow::bail!("dds texture has no pixels");
+        }
+        let mip_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+            mip_count.max(1)
+        } else {
+            1
+        };
+        // every level halves the size down to 1x1
+        let max_mip_count = 32 - width.max(height).leading_zeros();
+        if mip_count > max_mip_count {
+            anyhow::bail!(
+                "dds declares {mip_count} mip levels but a {width}x{height} texture has at most {max_mip_count}"
+            );
+        }
+
+        let mut texture = Texture2D {
+            format,
+            width,
+            height,
+            mips: Vec::with_capacity(mip_count as usize),
+        };
+        let data_size: usize = (0..mip_count as usize)
+            .map(|level| texture.mip_data_size(level))
+            .sum();
+        let remaining = dds.len().saturating_sub(reader.position() as usize);
+        if remaining < data_size {
+            anyhow::bail!(
+                "dds is truncated, {mip_count} mip levels need {data_size} bytes but only {remaining} are left"
+            );
+        }
+        for level in 0..mip_count as usize {
+            let mut mip = vec![0; texture.mip_data_size(level)];
+            reader.read_exact(&mut mip)?;
+            texture.mips.push(mip);
+        }
+
+        if texture.mips.len() == 1 && filter != MipFilter::None {
+            if can_encode(format) {
+                let pixels = texture.to_rgba(0)?;
+                let levels = generate_mips(&pixels, width, height, filter);
+                for (pixels, width, height) in levels.iter().skip(1) {
+                    texture.mips.push(encode(format, pixels, *width, *height)?);
+                }
+            } else {
+                eprintln!(
+                    "WARNING: generating mips for {format:?} is not supported, only the top level is imported"
+                );
+            }
+        }
+
+        Ok(texture)
+    }
+}
+
+/// looks for the surface format that is written with the given pixel format
+fn find_format(pixel_format: DdsPixelFormat) -> Option<SurfaceFormat> {
+    let strip_alpha = |pixel_format| match pixel_format {
+        DdsPixelFormat::Masks { flags, bits, masks } => DdsPixelFormat::Masks {
+            flags: flags & !DDPF_ALPHAPIXELS,
+            bits,
+            masks,
+        },
+        other => other,
+    };
+    (0..=u8::MAX as u32)
+        .filter_map(SurfaceFormat::from_repr)
+        .find(|format| strip_alpha(DdsPixelFormat::new(*format)) == pixel_format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a texture of the given format with a full mip chain of arbitrary bytes
+    fn texture(format: SurfaceFormat, width: u32, height: u32) -> Texture2D {
+        let mut texture = Texture2D {
+            format,
+            width,
+            height,
+            mips: Vec::new(),
+        };
+        let mip_count = 32 - width.max(height).leading_zeros();
+        for level in 0..mip_count as usize {
+            let size = texture.mip_data_size(level);
+            texture
+                .mips
+                .push((0..size).map(|i| (i * 31 + level * 7) as u8).collect());
+        }
+        texture
+    }
+
+    #[test]
+    fn round_trip() {
+        let formats = (0..=u8::MAX as u32).filter_map(SurfaceFormat::from_repr);
+        for format in formats {
+            for (width, height) in [(16, 8), (4, 4), (1, 1)] {
+                let texture = texture(format, width, height);
+                let dds = texture.to_dds().unwrap();
+                let imported = Texture2D::from_dds(&dds, MipFilter::None)
+                    .unwrap_or_else(|e| panic!("{format:?} {width}x{height}: {e:#}"));
+                assert_eq!(imported.format, format);
+                assert_eq!((imported.width, imported.height), (width, height));
+                assert!(imported.mips == texture.mips, "{format:?} {width}x{height}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_bad_mip_counts() {
+        let mut dds = texture(SurfaceFormat::Color, 8, 8).to_dds().unwrap();
+        // a level more than 8x8 can have
+        dds[28..32].copy_from_slice(&5u32.to_le_bytes());
+        assert!(Texture2D::from_dds(&dds, MipFilter::None).is_err());
+
+        let dds = texture(SurfaceFormat::Color, 8, 8).to_dds().unwrap();
+        assert!(Texture2D::from_dds(&dds[..dds.len() - 1], MipFilter::None).is_err());
+    }
+
+    #[test]
+    fn generates_missing_mips() {
+        let mut dxt1 = texture(SurfaceFormat::Dxt1, 8, 8);
+        dxt1.mips.truncate(1);
+        let imported = Texture2D::from_dds(&dxt1.to_dds().unwrap(), MipFilter::Box).unwrap();
+        assert_eq!(imported.mips.len(), 4);
+
+        // mips can't be encoded for this format, the top level is kept on its own
+        let mut bgr565 = texture(SurfaceFormat::Bgr565, 8, 8);
+        bgr565.mips.truncate(1);
+        let imported = Texture2D::from_dds(&bgr565.to_dds().unwrap(), MipFilter::Box).unwrap();
+        assert_eq!(imported.mips.len(), 1);
+    }
+}
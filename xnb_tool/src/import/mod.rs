@@ -0,0 +1,4 @@
+mod bcn;
+mod dds;
+pub mod model;
+pub mod texture;
@@ -0,0 +1,937 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{Context, anyhow};
+use byteorder::LittleEndian;
+use glam::{Mat4, Quat, Vec3, Vec4};
+use gltf::{
+    Document, Gltf, Node, Primitive, Scene, Skin,
+    animation::{Interpolation, util::ReadOutputs},
+    buffer::Data,
+    image::Source,
+    mesh::Mode,
+};
+use indexmap::IndexMap;
+
+use crate::{
+    content::{
+        Content,
+        color::Color,
+        effect::{BasicEffect, SkinnedModelBasicEffect},
+        model::{
+            Bone, BoneHierarchy, BoundingSphere, ElementFormat, ElementMethod, ElementUsage,
+            IndexBuffer, Mesh, MeshPart, Model, VertexBuffer, VertexDeclaration, VertexElement,
+        },
+        skinned_model::{
+            SkinnedModel, SkinnedModelAnimationClip, SkinnedModelAnimationKeyframe,
+            SkinnedModelBone, SkinnedModelPose,
+        },
+    },
+    export::model::reverse_winding,
+    xnb::XnbContent,
+};
+
+impl XnbContent {
+    /// imports a gltf or glb file as a skinned model if it has a skin, or as a model otherwise.
+    /// effects kept in the material extras on export are used as they are, other materials are
+    /// converted to basic effects
+    pub fn from_gltf(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let Gltf { document, blob } = Gltf::open(path).context("failed to parse gltf")?;
+        let buffers = gltf::import_buffers(&document, path.parent(), blob)
+            .context("failed to load gltf buffers")?;
+
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or_else(|| anyhow!("gltf has no scene"))?;
+
+        let mut skins = document.skins();
+        let skin = skins.next();
+        if skins.next().is_some() {
+            eprintln!("WARNING: only the first skin is imported");
+        }
+
+        let mut importer = Importer {
+            document: &document,
+            buffers: &buffers,
+            skinned: skin.is_some(),
+            shared_content: Vec::new(),
+            materials: HashMap::new(),
+            vertex_decls: Vec::new(),
+        };
+        let model = importer.build_model(&scene)?;
+
+        let primary_content = match skin {
+            Some(skin) => Content::SkinnedModel(importer.build_skinned_model(model, &skin)?),
+            None => Content::Model(model),
+        };
+        Ok(XnbContent::with_shared_content(
+            primary_content,
+            importer.shared_content,
+        ))
+    }
+}
+
+struct Importer<'a> {
+    document: &'a Document,
+    buffers: &'a [Data],
+    skinned: bool,
+    shared_content: Vec<Content>,
+    /// shared content ref of the effect built for each gltf material, `None` is the default
+    /// material used by primitives without one
+    materials: HashMap<Option<usize>, i32>,
+    vertex_decls: Vec<VertexDeclaration>,
+}
+
+/// bones built from the node hierarchy
+#[derive(Default)]
+struct Skeleton<'a> {
+    bones: Vec<Bone>,
+    bones_hierarchy: Vec<BoneHierarchy>,
+    /// nodes with a mesh and the ref of the bone the mesh belongs to
+    mesh_nodes: Vec<(Node<'a>, u32)>,
+}
+
+impl<'a> Skeleton<'a> {
+    /// every node becomes a bone, except for mesh nodes without children or a transform which
+    /// are how the exporter attaches mesh parts to their bone, and the groups holding them
+    fn new(scene: &Scene<'a>) -> Self {
+        let mut skeleton = Skeleton::default();
+        let roots: Vec<Node> = scene.nodes().collect();
+        if let [root] = roots.as_slice()
+            && !is_part_node(root)
+            && !is_part_group(root)
+        {
+            skeleton.add_bone(root, 0);
+            return skeleton;
+        }
+
+        // xna models have a single root bone
+        skeleton.push_bone("root".to_string(), Mat4::IDENTITY, 0);
+        for node in &roots {
+            skeleton.add_node(node, 1);
+        }
+        skeleton
+    }
+
+    fn add_node(&mut self, node: &Node<'a>, parent_ref: u32) {
+        if is_part_node(node) {
+            self.mesh_nodes.push((node.clone(), parent_ref));
+        } else if is_part_group(node) {
+            for child in node.children() {
+                self.mesh_nodes.push((child, parent_ref));
+            }
+        } else {
+            self.add_bone(node, parent_ref);
+        }
+    }
+
+    fn add_bone(&mut self, node: &Node<'a>, parent_ref: u32) {
+        // the exporter stores bone transforms transposed
+        let transform = Mat4::from_cols_array_2d(&node.transform().matrix()).transpose();
+        let bone_ref = self.push_bone(node_name(node), transform, parent_ref);
+        if node.mesh().is_some() {
+            self.mesh_nodes.push((node.clone(), bone_ref));
+        }
+        for child in node.children() {
+            self.add_node(&child, bone_ref);
+        }
+    }
+
+    fn push_bone(&mut self, name: String, transform: Mat4, parent_ref: u32) -> u32 {
+        self.bones.push(Bone { name, transform });
+        self.bones_hierarchy.push(BoneHierarchy {
+            parent_ref,
+            children_refs: Vec::new(),
+        });
+        let bone_ref = self.bones.len() as u32;
+        if parent_ref > 0 {
+            self.bones_hierarchy[parent_ref as usize - 1]
+                .children_refs
+                .push(bone_ref);
+        }
+        bone_ref
+    }
+}
+
+fn is_part_node(node: &Node) -> bool {
+    node.mesh().is_some()
+        && node.children().len() == 0
+        && Mat4::from_cols_array_2d(&node.transform().matrix()) == Mat4::IDENTITY
+}
+
+/// unnamed node that only groups mesh parts, the skinned exporter puts the parts of every mesh
+/// under one
+fn is_part_group(node: &Node) -> bool {
+    node.name().is_none()
+        && node.mesh().is_none()
+        && node.children().len() > 0
+        && node.children().all(|child| is_part_node(&child))
+        && Mat4::from_cols_array_2d(&node.transform().matrix()) == Mat4::IDENTITY
+}
+
+fn node_name(node: &Node) -> String {
+    node.name()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("node{}", node.index()))
+}
+
+impl Importer<'_> {
+    fn build_model(&mut self, scene: &Scene) -> anyhow::Result<Model> {
+        let skeleton = Skeleton::new(scene);
+
+        // the exporter splits every mesh part into its own gltf mesh, parts of the same bone
+        // with the same mesh name are merged back into one mesh
+        let mut groups: IndexMap<(u32, String), Vec<gltf::Mesh>> = IndexMap::new();
+        for (node, bone_ref) in &skeleton.mesh_nodes {
+            let mesh = node.mesh().unwrap();
+            let name = match mesh.name() {
+                Some(name) => name.to_string(),
+                None => skeleton.bones[*bone_ref as usize - 1].name.clone(),
+            };
+            groups.entry((*bone_ref, name)).or_default().push(mesh);
+        }
+
+        let mut meshes = Vec::with_capacity(groups.len());
+        for ((bone_ref, name), group) in groups {
+            let mesh = self
+                .build_mesh(&group, name.clone(), bone_ref)
+                .with_context(|| format!("failed to import mesh {name}"))?;
+            meshes.push(mesh);
+        }
+
+        Ok(Model {
+            bones: skeleton.bones,
+            bones_hierarchy: skeleton.bones_hierarchy,
+            vertex_decls: std::mem::take(&mut self.vertex_decls),
+            meshes,
+            root_bone_ref: 1,
+            tag: 0,
+        })
+    }
+
+    /// every primitive becomes a mesh part, all parts share one vertex and index buffer
+    fn build_mesh(
+        &mut self,
+        group: &[gltf::Mesh],
+        name: String,
+        parent_bone_ref: u32,
+    ) -> anyhow::Result<Mesh> {
+        let mut vertex_data = Vec::new();
+        let mut index_data = Vec::new();
+        let mut parts = Vec::new();
+        let mut min = Vec3::MAX;
+        let mut max = Vec3::MIN;
+        let mut all_positions = Vec::new();
+
+        for primitive in group.iter().flat_map(|mesh| mesh.primitives()) {
+            if primitive.mode() != Mode::Triangles {
+                anyhow::bail!(
+                    "only triangle lists are supported, found {:?}",
+                    primitive.mode()
+                );
+            }
+
+            let (decl, data, positions) = self.read_vertices(&primitive)?;
+            let stride = decl.stride();
+            let vertex_decl_index = match self.vertex_decls.iter().position(|d| *d == decl) {
+                Some(index) => index,
+                None => {
+                    self.vertex_decls.push(decl);
+                    self.vertex_decls.len() - 1
+                }
+            };
+
+            // parts are addressed by base vertex, so they have to start at a multiple of their
+            // stride when the parts of a mesh use different declarations
+            while !vertex_data.len().is_multiple_of(stride) {
+                vertex_data.push(0);
+            }
+            let base_vertex = vertex_data.len() / stride;
+            vertex_data.extend_from_slice(&data);
+
+            let reader = primitive.reader(|buffer| Some(&self.buffers[buffer.index()]));
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..positions.len() as u32).collect(),
+            };
+            if !indices.len().is_multiple_of(3) {
+                anyhow::bail!("index count {} is not a multiple of 3", indices.len());
+            }
+            let start_index = index_data.len();
+            index_data.extend_from_slice(&indices);
+
+            let material = self.material_ref(&primitive)?;
+            parts.push(MeshPart {
+                stream_offset: 0,
+                base_vertex: base_vertex as u32,
+                vertex_count: positions.len() as u32,
+                start_index: start_index as u32,
+                primitive_count: (indices.len() / 3) as u32,
+                vertex_decl_index: vertex_decl_index as u32,
+                tag: 0,
+                shared_content_material_idx: material,
+            });
+
+            for position in &positions {
+                min = min.min(*position);
+                max = max.max(*position);
+            }
+            all_positions.extend(positions);
+        }
+
+        let center = (min + max) / 2.0;
+        let radius = all_positions
+            .iter()
+            .map(|position| position.distance(center))
+            .fold(0.0, f32::max);
+
+        let is_16_bit = index_data.iter().all(|index| *index <= u16::MAX as u32);
+        let data = if is_16_bit {
+            index_data
+                .iter()
+                .flat_map(|index| (*index as u16).to_le_bytes())
+                .collect()
+        } else {
+            index_data
+                .iter()
+                .flat_map(|index| index.to_le_bytes())
+                .collect()
+        };
+        // undo the winding change made on export
+        let index_buffer = reverse_winding(&IndexBuffer { is_16_bit, data });
+
+        Ok(Mesh {
+            name,
+            parent_bone_ref,
+            bounds: BoundingSphere { center, radius },
+            vertex_buffer: VertexBuffer {
+                declaration: None,
+                data: vertex_data,
+            },
+            index_buffer,
+            parts,
+            tag: 0,
+        })
+    }
+
+    /// reads the attributes of a primitive into a vertex declaration, the vertex data in that
+    /// layout and the positions
+    fn read_vertices(
+        &self,
+        primitive: &Primitive,
+    ) -> anyhow::Result<(VertexDeclaration, Vec<u8>, Vec<Vec3>)> {
+        let reader = primitive.reader(|buffer| Some(&self.buffers[buffer.index()]));
+
+        let positions: Vec<Vec3> = reader
+            .read_positions()
+            .ok_or_else(|| anyhow!("primitive has no positions"))?
+            .map(Vec3::from)
+            .collect();
+        let mut attributes = vec![Attribute {
+            usage: ElementUsage::Position,
+            usage_index: 0,
+            format: ElementFormat::Vector3,
+            values: positions.iter().map(|p| p.extend(0.0)).collect(),
+        }];
+
+        let normals: Option<Vec<Vec3>> = reader
+            .read_normals()
+            .map(|normals| normals.map(Vec3::from).collect());
+        if let Some(normals) = &normals {
+            attributes.push(Attribute {
+                usage: ElementUsage::Normal,
+                usage_index: 0,
+                format: ElementFormat::Vector3,
+                values: normals.iter().map(|n| n.extend(0.0)).collect(),
+            });
+        }
+
+        let mut set = 0;
+        while let Some(tex_coords) = reader.read_tex_coords(set) {
+            attributes.push(Attribute {
+                usage: ElementUsage::TextureCoordinate,
+                usage_index: set as u8,
+                format: ElementFormat::Vector2,
+                values: tex_coords
+                    .into_f32()
+                    .map(|[u, v]| Vec4::new(u, v, 0.0, 0.0))
+                    .collect(),
+            });
+            set += 1;
+        }
+
+        let mut set = 0;
+        while let Some(colors) = reader.read_colors(set) {
+            attributes.push(Attribute {
+                usage: ElementUsage::Color,
+                usage_index: set as u8,
+                format: ElementFormat::Color,
+                values: colors.into_rgba_f32().map(Vec4::from).collect(),
+            });
+            set += 1;
+        }
+
+        if let Some(tangents) = reader.read_tangents() {
+            let tangents: Vec<Vec4> = tangents.map(Vec4::from).collect();
+            // xna stores the binormal instead of the handedness
+            if let Some(normals) = &normals {
+                attributes.push(Attribute {
+                    usage: ElementUsage::Binormal,
+                    usage_index: 0,
+                    format: ElementFormat::Vector3,
+                    values: normals
+                        .iter()
+                        .zip(&tangents)
+                        .map(|(n, t)| (n.cross(t.truncate()) * t.w).extend(0.0))
+                        .collect(),
+                });
+            }
+            attributes.push(Attribute {
+                usage: ElementUsage::Tangent,
+                usage_index: 0,
+                format: ElementFormat::Vector3,
+                values: tangents.iter().map(|t| t.truncate().extend(0.0)).collect(),
+            });
+        }
+
+        if let Some(joints) = reader.read_joints(0) {
+            let joints: Vec<[u16; 4]> = joints.into_u16().collect();
+            if let Some(joint) = joints.iter().flatten().find(|joint| **joint > 255) {
+                anyhow::bail!("joint index {joint} does not fit in a byte");
+            }
+            attributes.push(Attribute {
+                usage: ElementUsage::BlendIndices,
+                usage_index: 0,
+                format: ElementFormat::Byte4,
+                values: joints
+                    .iter()
+                    .map(|joint| Vec4::from_array(joint.map(f32::from)))
+                    .collect(),
+            });
+        }
+        if let Some(weights) = reader.read_weights(0) {
+            attributes.push(Attribute {
+                usage: ElementUsage::BlendWeight,
+                usage_index: 0,
+                format: ElementFormat::Vector4,
+                values: weights.into_f32().map(Vec4::from).collect(),
+            });
+        }
+        if reader.read_joints(1).is_some() {
+            eprintln!("WARNING: only the first 4 joint influences of a vertex are imported");
+        }
+
+        let mut elements = Vec::with_capacity(attributes.len());
+        let mut offset = 0;
+        for attribute in &attributes {
+            if attribute.values.len() != positions.len() {
+                anyhow::bail!(
+                    "expected {} values for {:?} but found {}",
+                    positions.len(),
+                    attribute.usage,
+                    attribute.values.len()
+                );
+            }
+            elements.push(VertexElement {
+                stream: 0,
+                offset: offset as u16,
+                format: attribute.format,
+                method: ElementMethod::Default,
+                usage: attribute.usage,
+                usage_index: attribute.usage_index,
+            });
+            offset += attribute.format.size();
+        }
+        let decl = VertexDeclaration {
+            elements,
+            vertex_stride: None,
+        };
+
+        let stride = decl.stride();
+        let mut data = vec![0; positions.len() * stride];
+        for (i, vertex) in data.chunks_exact_mut(stride).enumerate() {
+            for (el, attribute) in decl.elements.iter().zip(&attributes) {
+                let offset = el.offset as usize;
+                el.format.encode::<LittleEndian>(
+                    attribute.values[i],
+                    &mut vertex[offset..offset + el.format.size()],
+                );
+            }
+        }
+
+        Ok((decl, data, positions))
+    }
+
+    /// shared content ref of the effect for the material of the primitive, effects are added to
+    /// the shared content the first time a material is used
+    fn material_ref(&mut self, primitive: &Primitive) -> anyhow::Result<i32> {
+        let material = primitive.material();
+        if let Some(material_ref) = self.materials.get(&material.index()) {
+            return Ok(*material_ref);
+        }
+
+        let effect = match material
+            .extras()
+            .as_ref()
+            .and_then(|extras| serde_json::from_str::<Content>(extras.get()).ok())
+        {
+            Some(effect) if is_effect(&effect) => effect,
+            _ => self.convert_material(&material),
+        };
+        self.shared_content.push(effect);
+        let material_ref = self.shared_content.len() as i32;
+        self.materials.insert(material.index(), material_ref);
+        Ok(material_ref)
+    }
+
+    /// builds an effect from the pbr properties of a material
+    fn convert_material(&self, material: &gltf::Material) -> Content {
+        let pbr = material.pbr_metallic_roughness();
+        let [r, g, b, alpha] = pbr.base_color_factor();
+        let diffuse_color = Color { r, g, b };
+        let diffuse = pbr
+            .base_color_texture()
+            .map(|info| texture_reference(&info.texture()));
+        let normal = material
+            .normal_texture()
+            .map(|normal| texture_reference(&normal.texture()));
+        let [er, eg, eb] = material.emissive_factor();
+        let specular_power = specular_power(pbr.roughness_factor());
+
+        if self.skinned {
+            Content::SkinnedModelBasicEffect(SkinnedModelBasicEffect {
+                method: 0,
+                emissive_amount: er.max(eg).max(eb),
+                diffuse_color,
+                specular_amount: 0.0,
+                specular_power,
+                alpha,
+                use_soft_light_blend: false,
+                map_0_diffuse: diffuse,
+                map_1_diffuse: None,
+                map_0_damage: None,
+                map_1_damage: None,
+                material_map: None,
+                normal_map: normal,
+            })
+        } else {
+            Content::BasicEffect(BasicEffect {
+                texture: diffuse.unwrap_or_default(),
+                diffuse_color,
+                emissive_color: Color {
+                    r: er,
+                    g: eg,
+                    b: eb,
+                },
+                specular_color: Color {
+                    r: 0.0,
+                    g: 0.0,
+                    b: 0.0,
+                },
+                specular_power,
+                alpha,
+                vertex_color_enabled: false,
+            })
+        }
+    }
+
+    fn build_skinned_model(&mut self, model: Model, skin: &Skin) -> anyhow::Result<SkinnedModel> {
+        let joints: Vec<Node> = skin.joints().collect();
+        let reader = skin.reader(|buffer| Some(&self.buffers[buffer.index()]));
+        let inverse_bind_matrices: Vec<Mat4> = match reader.read_inverse_bind_matrices() {
+            // the exporter stores inverse bind matrices transposed
+            Some(matrices) => matrices
+                .map(|m| Mat4::from_cols_array_2d(&m).transpose())
+                .collect(),
+            None => vec![Mat4::IDENTITY; joints.len()],
+        };
+        if inverse_bind_matrices.len() != joints.len() {
+            anyhow::bail!(
+                "skin has {} joints but {} inverse bind matrices",
+                joints.len(),
+                inverse_bind_matrices.len()
+            );
+        }
+
+        let parents: HashMap<usize, usize> = self
+            .document
+            .nodes()
+            .flat_map(|node| {
+                node.children()
+                    .map(move |child| (child.index(), node.index()))
+            })
+            .collect();
+        let first_bone_ref = self.shared_content.len() + 1;
+        let joint_ref = |node: usize| {
+            joints
+                .iter()
+                .position(|joint| joint.index() == node)
+                .map(|i| first_bone_ref + i)
+        };
+
+        for (i, joint) in joints.iter().enumerate() {
+            let (translation, rotation, scale) = joint.transform().decomposed();
+            self.shared_content
+                .push(Content::SkinnedModelBone(SkinnedModelBone {
+                    index: i as u16,
+                    name: node_name(joint),
+                    translation: translation.into(),
+                    orientation: Quat::from_array(rotation),
+                    scale: scale.into(),
+                    inverse_bind_pose_transform: inverse_bind_matrices[i],
+                    shared_parent_ref: parents
+                        .get(&joint.index())
+                        .and_then(|parent| joint_ref(*parent))
+                        .unwrap_or(0),
+                    shared_child_refs: joint
+                        .children()
+                        .filter_map(|child| joint_ref(child.index()))
+                        .collect(),
+                }));
+        }
+        let shared_bone_refs = (first_bone_ref..first_bone_ref + joints.len()).collect();
+
+        let mut shared_animation_refs = Vec::new();
+        for animation in self.document.animations() {
+            let clip = self
+                .build_animation_clip(&animation, &joints)
+                .with_context(|| format!("failed to import animation {}", animation.index()))?;
+            self.shared_content
+                .push(Content::SkinnedModelAnimationClip(clip));
+            shared_animation_refs.push(self.shared_content.len());
+        }
+
+        Ok(SkinnedModel {
+            model,
+            shared_bone_refs,
+            shared_animation_refs,
+        })
+    }
+
+    /// xna keyframes hold a full pose, so the separate gltf channels of a joint are sampled at
+    /// the union of their timestamps. properties without a channel keep the joint's rest pose
+    fn build_animation_clip(
+        &self,
+        animation: &gltf::Animation,
+        joints: &[Node],
+    ) -> anyhow::Result<SkinnedModelAnimationClip> {
+        let mut tracks: HashMap<usize, Track> = HashMap::new();
+        for channel in animation.channels() {
+            let target = channel.target().node();
+            if !joints.iter().any(|joint| joint.index() == target.index()) {
+                eprintln!(
+                    "WARNING: skipping animation channel for {}, it is not a joint",
+                    node_name(&target)
+                );
+                continue;
+            }
+
+            let reader = channel.reader(|buffer| Some(&self.buffers[buffer.index()]));
+            let times: Vec<f32> = reader
+                .read_inputs()
+                .ok_or_else(|| anyhow!("animation channel has no timestamps"))?
+                .collect();
+            let interpolation = channel.sampler().interpolation();
+            let track = tracks.entry(target.index()).or_default();
+            match reader
+                .read_outputs()
+                .ok_or_else(|| anyhow!("animation channel has no values"))?
+            {
+                ReadOutputs::Translations(values) => {
+                    track.translation = Some(Curve::new(
+                        times,
+                        values.map(Vec3::from).collect(),
+                        interpolation,
+                    )?);
+                }
+                ReadOutputs::Rotations(values) => {
+                    track.rotation = Some(Curve::new(
+                        times,
+                        values.into_f32().map(Quat::from_array).collect(),
+                        interpolation,
+                    )?);
+                }
+                ReadOutputs::Scales(values) => {
+                    track.scale = Some(Curve::new(
+                        times,
+                        values.map(Vec3::from).collect(),
+                        interpolation,
+                    )?);
+                }
+                ReadOutputs::MorphTargetWeights(..) => {
+                    eprintln!("WARNING: skipping morph target animation channel");
+                }
+            }
+        }
+
+        let mut duration = 0.0f32;
+        let mut channels = IndexMap::new();
+        for joint in joints {
+            let Some(track) = tracks.get(&joint.index()) else {
+                continue;
+            };
+
+            let mut times: Vec<f32> = [
+                track.translation.as_ref().map(|c| &c.times),
+                track.rotation.as_ref().map(|c| &c.times),
+                track.scale.as_ref().map(|c| &c.times),
+            ]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .collect();
+            times.sort_by(f32::total_cmp);
+            times.dedup_by(|a, b| (*a - *b).abs() < 1e-6);
+            if let Some(last) = times.last() {
+                duration = duration.max(*last);
+            }
+
+            let (translation, rotation, scale) = joint.transform().decomposed();
+            let (translation, rotation, scale) = (
+                Vec3::from(translation),
+                Quat::from_array(rotation),
+                Vec3::from(scale),
+            );
+            let keyframes = times
+                .iter()
+                .map(|time| SkinnedModelAnimationKeyframe {
+                    time: *time,
+                    pose: SkinnedModelPose {
+                        translation: track
+                            .translation
+                            .as_ref()
+                            .map_or(translation, |c| c.sample(*time, Vec3::lerp)),
+                        orientation: track
+                            .rotation
+                            .as_ref()
+                            .map_or(rotation, |c| c.sample(*time, Quat::slerp)),
+                        scale: track
+                            .scale
+                            .as_ref()
+                            .map_or(scale, |c| c.sample(*time, Vec3::lerp)),
+                    },
+                })
+                .collect();
+            channels.insert(node_name(joint), keyframes);
+        }
+
+        Ok(SkinnedModelAnimationClip {
+            name: animation
+                .name()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("animation{}", animation.index())),
+            duration,
+            channels,
+        })
+    }
+}
+
+struct Attribute {
+    usage: ElementUsage,
+    usage_index: u8,
+    format: ElementFormat,
+    values: Vec<Vec4>,
+}
+
+#[derive(Default)]
+struct Track {
+    translation: Option<Curve<Vec3>>,
+    rotation: Option<Curve<Quat>>,
+    scale: Option<Curve<Vec3>>,
+}
+
+struct Curve<T> {
+    times: Vec<f32>,
+    values: Vec<T>,
+    step: bool,
+}
+
+impl<T: Copy> Curve<T> {
+    fn new(times: Vec<f32>, values: Vec<T>, interpolation: Interpolation) -> anyhow::Result<Self> {
+        let values_per_key = match interpolation {
+            Interpolation::CubicSpline => 3,
+            Interpolation::Linear | Interpolation::Step => 1,
+        };
+        if times.is_empty() {
+            anyhow::bail!("animation channel has no keyframes");
+        }
+        if values.len() != times.len() * values_per_key {
+            anyhow::bail!(
+                "animation channel has {} timestamps but {} values",
+                times.len(),
+                values.len()
+            );
+        }
+        let values = match interpolation {
+            // every keyframe stores an in tangent, the value and an out tangent, the tangents
+            // are dropped and the curve is sampled linearly
+            Interpolation::CubicSpline => values.into_iter().skip(1).step_by(3).collect(),
+            Interpolation::Linear | Interpolation::Step => values,
+        };
+        Ok(Curve {
+            times,
+            values,
+            step: interpolation == Interpolation::Step,
+        })
+    }
+
+    fn sample(&self, time: f32, lerp: fn(T, T, f32) -> T) -> T {
+        let next = self.times.partition_point(|t| *t <= time);
+        if next == 0 {
+            return self.values[0];
+        }
+        if next >= self.times.len() {
+            return self.values[self.values.len() - 1];
+        }
+        let (start, end) = (self.times[next - 1], self.times[next]);
+        if self.step || end <= start {
+            return self.values[next - 1];
+        }
+        lerp(
+            self.values[next - 1],
+            self.values[next],
+            (time - start) / (end - start),
+        )
+    }
+}
+
+fn is_effect(content: &Content) -> bool {
+    matches!(
+        content,
+        Content::BasicEffect(..)
+            | Content::AdditiveEffect(..)
+            | Content::RenderDeferredEffect(..)
+            | Content::SkinnedModelBasicEffect(..)
+            | Content::SkinnedModelDeferredNormalMappedEffect(..)
+    )
+}
+
+/// xna style reference to the texture, the image name or the file name without its extension
+fn texture_reference(texture: &gltf::Texture) -> String {
+    let image = texture.source();
+    let name = match (image.name(), image.source()) {
+        (Some(name), _) => name.to_string(),
+        (None, Source::Uri { uri, .. }) => Path::new(uri)
+            .with_extension("")
+            .to_string_lossy()
+            .into_owned(),
+        (None, Source::View { .. }) => format!("texture{}", texture.index()),
+    };
+    name.replace('/', "\\")
+}
+
+/// inverse of the roughness conversion done on export
+fn specular_power(roughness: f32) -> f32 {
+    let roughness = roughness.max(0.01);
+    2.0 / (roughness * roughness) - 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::export::model::material::TextureLoader;
+
+    /// a quad of two triangles on the second bone, with positions and texture coordinates
+    fn quad_model() -> Model {
+        let corners = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]];
+        let mut data = Vec::new();
+        for [x, y] in corners {
+            for value in [x, y, 0.5, x, 1.0 - y] {
+                data.extend_from_slice(&f32::to_le_bytes(value));
+            }
+        }
+        let arm = Mat4::from_rotation_translation(
+            Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+            Vec3::new(2.0, 3.0, 4.0),
+        );
+        let model = json!({
+            "bones": [
+                {"name": "root", "transform": Mat4::IDENTITY},
+                {"name": "arm", "transform": arm},
+            ],
+            "bones_hierarchy": [
+                {"parent_ref": 0, "children_refs": [2]},
+                {"parent_ref": 1, "children_refs": []},
+            ],
+            "vertex_decls": [{"elements": [
+                {"stream": 0, "offset": 0, "format": "Vector3", "method": "Default",
+                    "usage": "Position", "usage_index": 0},
+                {"stream": 0, "offset": 12, "format": "Vector2", "method": "Default",
+                    "usage": "TextureCoordinate", "usage_index": 0},
+            ]}],
+            "meshes": [{
+                "name": "quad", "parent_bone_ref": 2,
+                "bounds": {"center": [0.5, 0.5, 0.5], "radius": 1.0},
+                "vertex_buffer": {"data": data},
+                "index_buffer": {"is_16_bit": true, "data": [0, 0, 1, 0, 2, 0, 2, 0, 1, 0, 3, 0]},
+                "parts": [{"stream_offset": 0, "base_vertex": 0, "vertex_count": 4,
+                    "start_index": 0, "primitive_count": 2, "vertex_decl_index": 0, "tag": 0,
+                    "shared_content_material_idx": 1}],
+                "tag": 0,
+            }],
+            "root_bone_ref": 1,
+            "tag": 0,
+        });
+        serde_json::from_value(model).unwrap()
+    }
+
+    #[test]
+    fn exported_glb_imports_back() {
+        let model = quad_model();
+        let effect: Content = serde_json::from_value(json!({"BasicEffect": {
+            "texture": "", "diffuse_color": {"r": 1.0, "g": 1.0, "b": 1.0},
+            "emissive_color": {"r": 0.0, "g": 0.0, "b": 0.0},
+            "specular_color": {"r": 0.0, "g": 0.0, "b": 0.0},
+            "specular_power": 16.0, "alpha": 1.0, "vertex_color_enabled": false,
+        }}))
+        .unwrap();
+        let glb = model.to_glb(&[effect], &TextureLoader::default()).unwrap();
+        let path = std::env::temp_dir().join(format!("xnb_tool_import_{}.glb", std::process::id()));
+        std::fs::write(&path, glb).unwrap();
+        let imported = XnbContent::from_gltf(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let Content::Model(imported) = imported.unwrap().primary_content else {
+            panic!("expected a model");
+        };
+        for (bone, original) in imported.bones.iter().zip(&model.bones) {
+            assert_eq!(bone.name, original.name);
+            assert!(bone.transform.abs_diff_eq(original.transform, 1e-6));
+        }
+        assert_eq!(imported.bones.len(), model.bones.len());
+        assert_eq!(
+            serde_json::to_value(&imported.bones_hierarchy).unwrap(),
+            serde_json::to_value(&model.bones_hierarchy).unwrap()
+        );
+
+        let (mesh, original) = (&imported.meshes[0], &model.meshes[0]);
+        assert_eq!(imported.meshes.len(), 1);
+        assert_eq!(mesh.parent_bone_ref, 2);
+        // the winding is flipped on export and back on import
+        assert_eq!(mesh.index_buffer.data, original.index_buffer.data);
+        let decl = &imported.vertex_decls[mesh.parts[0].vertex_decl_index as usize];
+        assert_eq!(decl.elements, model.vertex_decls[0].elements);
+        assert_eq!(mesh.vertex_buffer.data, original.vertex_buffer.data);
+    }
+
+    #[test]
+    fn curves_reject_bad_channels() {
+        let curve = |times: usize, values: usize, interpolation| {
+            Curve::new(vec![0.0; times], vec![0.0f32; values], interpolation)
+        };
+        assert!(curve(0, 0, Interpolation::Linear).is_err());
+        assert!(curve(2, 1, Interpolation::Linear).is_err());
+        assert!(curve(2, 2, Interpolation::CubicSpline).is_err());
+        assert!(curve(2, 2, Interpolation::Step).is_ok());
+
+        // only the values between the tangents are kept
+        let values = vec![0.0, 1.0, 0.0, 0.0, 2.0, 0.0];
+        let curve = Curve::new(vec![0.0, 1.0], values, Interpolation::CubicSpline).unwrap();
+        assert_eq!(curve.values, [1.0, 2.0]);
+        assert_eq!(curve.sample(0.5, |a, b, t| a + (b - a) * t), 1.5);
+    }
+}
@@ -0,0 +1,259 @@
+use std::f32::consts::PI;
+
+use image::ImageFormat;
+
+use crate::content::texture::{SurfaceFormat, Texture2D};
+
+use super::bcn;
+
+/// filter used to generate mip levels
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MipFilter {
+    /// only keep the top level
+    None,
+    /// average every 2x2 block of the previous level
+    #[default]
+    Box,
+    /// kaiser windowed sinc, sharper than box at the cost of slight ringing
+    Kaiser,
+}
+
+impl Texture2D {
+    /// builds a texture from a png, see [`Texture2D::from_rgba`]
+    pub fn from_png(png: &[u8], filter: MipFilter) -> anyhow::Result<Self> {
+        let image = image::load_from_memory_with_format(png, ImageFormat::Png)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        Self::from_rgba(image.as_raw(), width, height, filter)
+    }
+
+    /// builds a texture from rgba8 pixels, fully opaque images are compressed as dxt1 and
+    /// everything else as dxt5. dxt needs dimensions that are multiples of 4, other sizes are
+    /// stored uncompressed as `Color`
+    pub fn from_rgba(
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        filter: MipFilter,
+    ) -> anyhow::Result<Self> {
+        if width == 0 || height == 0 {
+            anyhow::bail!("texture has no pixels");
+        }
+        if pixels.len() != width as usize * height as usize * 4 {
+            anyhow::bail!(
+                "expected {} bytes for a {width}x{height} image but got {}",
+                width as usize * height as usize * 4,
+                pixels.len()
+            );
+        }
+
+        let format = if !width.is_multiple_of(4) || !height.is_multiple_of(4) {
+            SurfaceFormat::Color
+        } else if pixels.chunks_exact(4).all(|pixel| pixel[3] == 255) {
+            SurfaceFormat::Dxt1
+        } else {
+            SurfaceFormat::Dxt5
+        };
+
+        let levels = generate_mips(pixels, width, height, filter);
+        let mips = levels
+            .iter()
+            .map(|(pixels, width, height)| encode(format, pixels, *width, *height))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Texture2D {
+            format,
+            width,
+            height,
+            mips,
+        })
+    }
+}
+
+/// whether [`encode`] can write the given surface format
+pub(crate) fn can_encode(format: SurfaceFormat) -> bool {
+    matches!(
+        format,
+        SurfaceFormat::Dxt1
+            | SurfaceFormat::Dxt3
+            | SurfaceFormat::Dxt5
+            | SurfaceFormat::Color
+            | SurfaceFormat::Rgba32
+    )
+}
+
+/// encodes rgba8 pixels into the given surface format
+pub(crate) fn encode(
+    format: SurfaceFormat,
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+) -> anyhow::Result<Vec<u8>> {
+    let (width, height) = (width as usize, height as usize);
+    let data = match format {
+        SurfaceFormat::Dxt1 => bcn::encode_dxt1(pixels, width, height),
+        SurfaceFormat::Dxt3 => bcn::encode_dxt3(pixels, width, height),
+        SurfaceFormat::Dxt5 => bcn::encode_dxt5(pixels, width, height),
+        SurfaceFormat::Color => pixels
+            .chunks_exact(4)
+            .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+            .collect(),
+        SurfaceFormat::Rgba32 => pixels.to_vec(),
+        _ => anyhow::bail!("encoding {format:?} textures is not supported"),
+    };
+    Ok(data)
+}
+
+/// returns the top level followed by every generated level down to 1x1
+pub(crate) fn generate_mips(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    filter: MipFilter,
+) -> Vec<(Vec<u8>, u32, u32)> {
+    let mut levels = vec![(pixels.to_vec(), width, height)];
+    if filter == MipFilter::None {
+        return levels;
+    }
+
+    let mut current: Vec<f32> = pixels.iter().map(|v| *v as f32 / 255.0).collect();
+    let (mut width, mut height) = (width as usize, height as usize);
+    while width > 1 || height > 1 {
+        let next_width = (width / 2).max(1);
+        let next_height = (height / 2).max(1);
+        current = match filter {
+            MipFilter::Box => downsample_box(&current, width, height, next_width, next_height),
+            _ => {
+                // filter the rows, then the columns by filtering the rows of the transpose
+                let rows = resample_rows_kaiser(&current, width, height, next_width);
+                let transposed = transpose(&rows, next_width, height);
+                let columns = resample_rows_kaiser(&transposed, height, next_width, next_height);
+                transpose(&columns, next_height, next_width)
+            }
+        };
+        width = next_width;
+        height = next_height;
+
+        let pixels = current
+            .iter()
+            .map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .collect();
+        levels.push((pixels, width as u32, height as u32));
+    }
+    levels
+}
+
+/// averages the 2x2 texels under every destination texel, clamping at the edges
+fn downsample_box(
+    pixels: &[f32],
+    width: usize,
+    height: usize,
+    next_width: usize,
+    next_height: usize,
+) -> Vec<f32> {
+    let mut out = vec![0.0; next_width * next_height * 4];
+    for y in 0..next_height {
+        for x in 0..next_width {
+            let dest = (y * next_width + x) * 4;
+            for (sx, sy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let sx = (x * 2 + sx).min(width - 1);
+                let sy = (y * 2 + sy).min(height - 1);
+                let src = (sy * width + sx) * 4;
+                for c in 0..4 {
+                    out[dest + c] += pixels[src + c] / 4.0;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// resamples every row from `width` to `next_width` texels
+fn resample_rows_kaiser(
+    pixels: &[f32],
+    width: usize,
+    height: usize,
+    next_width: usize,
+) -> Vec<f32> {
+    const WIDTH: f32 = 3.0;
+    const ALPHA: f32 = 4.0;
+
+    let scale = width as f32 / next_width as f32;
+    let radius = WIDTH * scale;
+
+    // the weights are the same for every row
+    let weights: Vec<Vec<(usize, f32)>> = (0..next_width)
+        .map(|x| {
+            let center = (x as f32 + 0.5) * scale;
+            let start = (center - radius).floor() as isize;
+            let end = (center + radius).ceil() as isize;
+            let mut weights: Vec<(usize, f32)> = (start..=end)
+                .map(|sx| {
+                    let distance = (sx as f32 + 0.5 - center) / scale;
+                    let sx = sx.clamp(0, width as isize - 1) as usize;
+                    (sx, kaiser(distance, WIDTH, ALPHA))
+                })
+                .filter(|(_, weight)| *weight != 0.0)
+                .collect();
+            let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+            for (_, weight) in &mut weights {
+                *weight /= total;
+            }
+            weights
+        })
+        .collect();
+
+    let mut out = vec![0.0; next_width * height * 4];
+    for y in 0..height {
+        for (x, weights) in weights.iter().enumerate() {
+            let dest = (y * next_width + x) * 4;
+            for (sx, weight) in weights {
+                let src = (y * width + sx) * 4;
+                for c in 0..4 {
+                    out[dest + c] += pixels[src + c] * weight;
+                }
+            }
+        }
+    }
+    out
+}
+
+fn transpose(pixels: &[f32], width: usize, height: usize) -> Vec<f32> {
+    let mut out = vec![0.0; pixels.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let src = (y * width + x) * 4;
+            let dest = (x * height + y) * 4;
+            out[dest..dest + 4].copy_from_slice(&pixels[src..src + 4]);
+        }
+    }
+    out
+}
+
+fn kaiser(x: f32, width: f32, alpha: f32) -> f32 {
+    let x = x.abs();
+    if x >= width {
+        return 0.0;
+    }
+    let sinc = if x < 1e-6 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    };
+    let t = x / width;
+    sinc * bessel_i0(alpha * (1.0 - t * t).sqrt()) / bessel_i0(alpha)
+}
+
+/// modified bessel function of the first kind, order zero
+fn bessel_i0(x: f32) -> f32 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half = x / 2.0;
+    for k in 1..32 {
+        term *= (half / k as f32) * (half / k as f32);
+        sum += term;
+        if term < sum * 1e-8 {
+            break;
+        }
+    }
+    sum
+}
@@ -1,5 +1,8 @@
 mod ext;
+mod lzx;
 
 pub mod content;
 pub mod export;
+pub mod import;
+pub mod xact;
 pub mod xnb;
@@ -0,0 +1,523 @@
+//! LZX encoder producing the framed chunks found in compressed xnb files.
+//! every frame is emitted as a single verbatim block, matches never cross frame boundaries
+//! and never use the repeated offset slots, which keeps the encoder simple while still
+//! being readable by the XNA decompressor.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::{Cursor, Read, Seek, Write};
+
+use anyhow::Context;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use lzxd::Lzxd;
+
+/// uncompressed size of every frame except the last one
+pub const FRAME_SIZE: usize = 0x8000;
+
+const WINDOW_SIZE: usize = 0x10000;
+const NUM_CHARS: usize = 256;
+const POSITION_SLOTS: usize = 32;
+const MAIN_TREE_ELEMENTS: usize = NUM_CHARS + POSITION_SLOTS * 8;
+const LENGTH_TREE_ELEMENTS: usize = 249;
+const PRETREE_ELEMENTS: usize = 20;
+const MAX_PATH_LENGTH: u8 = 16;
+const MAX_PRETREE_PATH_LENGTH: u8 = 15;
+
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 257;
+const NUM_PRIMARY_LENGTHS: usize = 7;
+const MAX_OFFSET: usize = WINDOW_SIZE - 3;
+
+const HASH_BITS: u32 = 15;
+const MAX_CHAIN: usize = 64;
+const NO_POSITION: u32 = u32::MAX;
+
+const BLOCK_VERBATIM: u32 = 1;
+
+const BASE_POSITION: [u32; POSITION_SLOTS] = [
+    0, 1, 2, 3, 4, 6, 8, 12, 16, 24, 32, 48, 64, 96, 128, 192, 256, 384, 512, 768, 1024, 1536,
+    2048, 3072, 4096, 6144, 8192, 12288, 16384, 24576, 32768, 49152,
+];
+
+const FOOTER_BITS: [u8; POSITION_SLOTS] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13, 14, 14,
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    Literal(u8),
+    Match { length: usize, offset: usize },
+}
+
+pub struct LzxEncoder<'a> {
+    data: &'a [u8],
+    position: usize,
+    head: Vec<u32>,
+    prev: Vec<u32>,
+    main_lengths: [u8; MAIN_TREE_ELEMENTS],
+    length_lengths: [u8; LENGTH_TREE_ELEMENTS],
+    first_chunk: bool,
+}
+
+impl<'a> LzxEncoder<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        LzxEncoder {
+            data,
+            position: 0,
+            head: vec![NO_POSITION; 1 << HASH_BITS],
+            prev: vec![NO_POSITION; data.len()],
+            main_lengths: [0; MAIN_TREE_ELEMENTS],
+            length_lengths: [0; LENGTH_TREE_ELEMENTS],
+            first_chunk: true,
+        }
+    }
+
+    /// compresses the next frame, returning its uncompressed size and the compressed chunk
+    pub fn compress_next(&mut self) -> Option<(usize, Vec<u8>)> {
+        if self.position >= self.data.len() {
+            return None;
+        }
+
+        let start = self.position;
+        let end = usize::min(start + FRAME_SIZE, self.data.len());
+        let tokens = self.tokenize(start, end);
+        self.position = end;
+
+        let mut main_freqs = [0u32; MAIN_TREE_ELEMENTS];
+        let mut length_freqs = [0u32; LENGTH_TREE_ELEMENTS];
+        for token in &tokens {
+            match *token {
+                Token::Literal(value) => main_freqs[value as usize] += 1,
+                Token::Match { length, offset } => {
+                    let (symbol, length_symbol, _, _) = match_symbols(length, offset);
+                    main_freqs[symbol] += 1;
+                    if let Some(length_symbol) = length_symbol {
+                        length_freqs[length_symbol] += 1;
+                    }
+                }
+            }
+        }
+
+        let main_lengths = build_path_lengths(&main_freqs, MAX_PATH_LENGTH);
+        let length_lengths = build_path_lengths(&length_freqs, MAX_PATH_LENGTH);
+        let main_codes = canonical_codes(&main_lengths);
+        let length_codes = canonical_codes(&length_lengths);
+
+        let mut writer = BitWriter::default();
+        if self.first_chunk {
+            // no e8 translation
+            writer.write_bits(0, 1);
+            self.first_chunk = false;
+        }
+
+        let size = (end - start) as u32;
+        writer.write_bits(BLOCK_VERBATIM, 3);
+        writer.write_bits(size >> 8, 16);
+        writer.write_bits(size & 0xFF, 8);
+
+        write_lengths(
+            &mut writer,
+            &mut self.main_lengths[..NUM_CHARS],
+            &main_lengths[..NUM_CHARS],
+        );
+        write_lengths(
+            &mut writer,
+            &mut self.main_lengths[NUM_CHARS..],
+            &main_lengths[NUM_CHARS..],
+        );
+        write_lengths(&mut writer, &mut self.length_lengths, &length_lengths);
+
+        for token in &tokens {
+            match *token {
+                Token::Literal(value) => {
+                    let value = value as usize;
+                    writer.write_bits(main_codes[value] as u32, main_lengths[value] as u32);
+                }
+                Token::Match { length, offset } => {
+                    let (symbol, length_symbol, footer, footer_bits) =
+                        match_symbols(length, offset);
+                    writer.write_bits(main_codes[symbol] as u32, main_lengths[symbol] as u32);
+                    if let Some(length_symbol) = length_symbol {
+                        writer.write_bits(
+                            length_codes[length_symbol] as u32,
+                            length_lengths[length_symbol] as u32,
+                        );
+                    }
+                    writer.write_bits(footer, footer_bits as u32);
+                }
+            }
+        }
+
+        Some((end - start, writer.finish()))
+    }
+
+    fn tokenize(&mut self, start: usize, end: usize) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut i = start;
+        while i < end {
+            let found = self.find_match(i, end);
+            self.insert(i);
+
+            let Some((length, offset)) = found else {
+                tokens.push(Token::Literal(self.data[i]));
+                i += 1;
+                continue;
+            };
+
+            // lazy evaluation, prefer a longer match starting at the next byte
+            if i + 1 < end
+                && let Some((next_length, _)) = self.find_match(i + 1, end)
+                && next_length > length
+            {
+                tokens.push(Token::Literal(self.data[i]));
+                i += 1;
+                continue;
+            }
+
+            tokens.push(Token::Match { length, offset });
+            for j in i + 1..i + length {
+                self.insert(j);
+            }
+            i += length;
+        }
+        tokens
+    }
+
+    fn hash(&self, position: usize) -> Option<usize> {
+        let bytes = self.data.get(position..position + MIN_MATCH)?;
+        let hash = (bytes[0] as u32) << 10 ^ (bytes[1] as u32) << 5 ^ bytes[2] as u32;
+        Some((hash & ((1 << HASH_BITS) - 1)) as usize)
+    }
+
+    fn insert(&mut self, position: usize) {
+        if let Some(hash) = self.hash(position) {
+            self.prev[position] = self.head[hash];
+            self.head[hash] = position as u32;
+        }
+    }
+
+    fn find_match(&self, position: usize, end: usize) -> Option<(usize, usize)> {
+        let hash = self.hash(position)?;
+        let max_length = usize::min(MAX_MATCH, end - position);
+        if max_length < MIN_MATCH {
+            return None;
+        }
+
+        let mut best: Option<(usize, usize)> = None;
+        let mut candidate = self.head[hash];
+        for _ in 0..MAX_CHAIN {
+            if candidate == NO_POSITION {
+                break;
+            }
+            let candidate_position = candidate as usize;
+            let offset = position - candidate_position;
+            if offset > MAX_OFFSET {
+                break;
+            }
+
+            let length = self.data[candidate_position..]
+                .iter()
+                .zip(&self.data[position..position + max_length])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if length > best.map_or(0, |(l, _)| l) {
+                best = Some((length, offset));
+                if length == max_length {
+                    break;
+                }
+            }
+
+            candidate = self.prev[candidate_position];
+        }
+
+        // short matches far away are more expensive than the literals they replace
+        best.filter(|&(length, offset)| length >= MIN_MATCH && (length > 3 || offset <= 4096))
+    }
+}
+
+/// compresses data into the frames stored in xnb files. every frame is prefixed with its
+/// compressed size, frames that are not `FRAME_SIZE` long also store their uncompressed size
+/// after a 0xFF marker
+pub fn compress(raw: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(raw.len() / 2);
+
+    let mut encoder = LzxEncoder::new(raw);
+    while let Some((frame_size, block)) = encoder.compress_next() {
+        let block_size: u16 = block
+            .len()
+            .try_into()
+            .context("compressed frame exceeds maximum block size")?;
+
+        // a block size starting with 0xFF would be mistaken for the extended frame prefix
+        if frame_size == FRAME_SIZE && block_size < 0xFF00 {
+            data.write_u16::<BigEndian>(block_size)?;
+        } else {
+            data.write_u8(0xFF)?;
+            data.write_u16::<BigEndian>(frame_size as u16)?;
+            data.write_u16::<BigEndian>(block_size)?;
+        }
+        data.write_all(&block)?;
+    }
+
+    Ok(data)
+}
+
+/// decompresses the frames written by [`compress`] or by XNA
+pub fn decompress(data: &[u8], uncompressed_size: usize) -> anyhow::Result<Vec<u8>> {
+    let mut data = Cursor::new(data);
+    let mut decompressed = Vec::with_capacity(uncompressed_size);
+
+    let mut lzxd = Lzxd::new(lzxd::WindowSize::KB64);
+
+    while (data.position() as usize) < data.get_ref().len() {
+        let frame_size;
+        let block_size;
+        if data.read_u8()? == 0xFF {
+            frame_size = data.read_u16::<BigEndian>()?;
+            block_size = data.read_u16::<BigEndian>()?;
+        } else {
+            data.seek_relative(-1)?;
+            block_size = data.read_u16::<BigEndian>()?;
+            frame_size = 0x8000;
+        }
+
+        if block_size == 0 || frame_size == 0 {
+            break;
+        }
+
+        let mut block = vec![0; block_size as usize];
+        data.read_exact(&mut block)?;
+        let frame = lzxd.decompress_next(&block, frame_size as usize)?;
+        decompressed.extend_from_slice(frame);
+    }
+
+    Ok(decompressed)
+}
+
+/// returns the main tree symbol, optional length tree symbol and the verbatim offset bits
+fn match_symbols(length: usize, offset: usize) -> (usize, Option<usize>, u32, u8) {
+    let formatted_offset = (offset + 2) as u32;
+    let slot = BASE_POSITION
+        .iter()
+        .rposition(|&base| base <= formatted_offset)
+        .unwrap();
+
+    let length_header = usize::min(length - 2, NUM_PRIMARY_LENGTHS);
+    let length_symbol =
+        (length_header == NUM_PRIMARY_LENGTHS).then(|| length - 2 - NUM_PRIMARY_LENGTHS);
+
+    let symbol = NUM_CHARS + (slot << 3) + length_header;
+    let footer = formatted_offset - BASE_POSITION[slot];
+    (symbol, length_symbol, footer, FOOTER_BITS[slot])
+}
+
+/// encodes a range of tree path lengths as deltas against the previous tree using a pretree
+fn write_lengths(writer: &mut BitWriter, previous: &mut [u8], lengths: &[u8]) {
+    // (code, extra bits, extra value)
+    let mut codes = Vec::new();
+    let mut i = 0;
+    while i < lengths.len() {
+        let zeros = lengths[i..]
+            .iter()
+            .take(51)
+            .take_while(|&&length| length == 0)
+            .count();
+        if zeros >= 20 {
+            codes.push((18, 5, zeros as u32 - 20));
+            i += zeros;
+        } else if zeros >= 4 {
+            codes.push((17, 4, zeros as u32 - 4));
+            i += zeros;
+        } else {
+            let delta = (previous[i] + 17 - lengths[i]) % 17;
+            codes.push((delta as usize, 0, 0));
+            i += 1;
+        }
+    }
+
+    let mut freqs = [0u32; PRETREE_ELEMENTS];
+    for &(code, _, _) in &codes {
+        freqs[code] += 1;
+    }
+    let pretree_lengths = build_path_lengths(&freqs, MAX_PRETREE_PATH_LENGTH);
+    let pretree_codes = canonical_codes(&pretree_lengths);
+
+    for &length in &pretree_lengths {
+        writer.write_bits(length as u32, 4);
+    }
+    for &(code, extra_bits, extra) in &codes {
+        writer.write_bits(pretree_codes[code] as u32, pretree_lengths[code] as u32);
+        writer.write_bits(extra, extra_bits);
+    }
+
+    previous.copy_from_slice(lengths);
+}
+
+/// builds length limited huffman path lengths, the decoder only accepts complete trees so
+/// there are always at least two symbols present
+fn build_path_lengths(freqs: &[u32], max_length: u8) -> Vec<u8> {
+    let mut freqs = freqs.to_vec();
+    let used = freqs.iter().filter(|&&f| f > 0).count();
+    for f in freqs
+        .iter_mut()
+        .filter(|f| **f == 0)
+        .take(2usize.saturating_sub(used))
+    {
+        *f = 1;
+    }
+
+    loop {
+        let lengths = huffman_path_lengths(&freqs);
+        if lengths.iter().all(|&length| length <= max_length) {
+            return lengths;
+        }
+        // flatten the distribution until the tree is shallow enough
+        for f in freqs.iter_mut().filter(|f| **f > 0) {
+            *f = f.div_ceil(2);
+        }
+    }
+}
+
+fn huffman_path_lengths(freqs: &[u32]) -> Vec<u8> {
+    let mut parents = vec![usize::MAX; freqs.len()];
+    let mut heap = BinaryHeap::new();
+    for (i, &f) in freqs.iter().enumerate() {
+        if f > 0 {
+            heap.push(Reverse((f as u64, i)));
+        }
+    }
+
+    while heap.len() > 1 {
+        let Reverse((a_freq, a)) = heap.pop().unwrap();
+        let Reverse((b_freq, b)) = heap.pop().unwrap();
+        let node = parents.len();
+        parents.push(usize::MAX);
+        parents[a] = node;
+        parents[b] = node;
+        heap.push(Reverse((a_freq + b_freq, node)));
+    }
+
+    let mut lengths = vec![0u8; freqs.len()];
+    for (i, length) in lengths.iter_mut().enumerate() {
+        if freqs[i] == 0 {
+            continue;
+        }
+        let mut node = i;
+        while parents[node] != usize::MAX {
+            node = parents[node];
+            *length = length.saturating_add(1);
+        }
+    }
+    lengths
+}
+
+fn canonical_codes(lengths: &[u8]) -> Vec<u16> {
+    let mut codes = vec![0u16; lengths.len()];
+    let mut code = 0u32;
+    for bit in 1..=MAX_PATH_LENGTH {
+        for (i, &length) in lengths.iter().enumerate() {
+            if length == bit {
+                codes[i] = code as u16;
+                code += 1;
+            }
+        }
+        code <<= 1;
+    }
+    codes
+}
+
+/// writes bits msb first into little endian 16 bit words
+#[derive(Default)]
+struct BitWriter {
+    output: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write_bits(&mut self, value: u32, bits: u32) {
+        debug_assert!(bits <= 16);
+        if bits == 0 {
+            return;
+        }
+        self.buffer = (self.buffer << bits) | (value & ((1 << bits) - 1));
+        self.count += bits;
+        if self.count >= 16 {
+            self.count -= 16;
+            let word = (self.buffer >> self.count) as u16;
+            self.output.extend_from_slice(&word.to_le_bytes());
+            self.buffer &= (1 << self.count) - 1;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.write_bits(0, 16 - self.count);
+        }
+        self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// text, runs and noise so that literals, short and long matches all show up
+    fn mixed_data(len: usize) -> Vec<u8> {
+        let text = b"the quick brown fox jumps over the lazy dog. ";
+        let mut state = 0x2545f491u32;
+        let mut data = Vec::with_capacity(len);
+        while data.len() < len {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            match state % 3 {
+                0 => data.extend_from_slice(text),
+                1 => data.extend(std::iter::repeat_n(
+                    state as u8,
+                    (state >> 8) as usize % 300,
+                )),
+                _ => data.extend((0..(state >> 16) % 200).map(|i| (state >> (i % 24)) as u8)),
+            }
+        }
+        data.truncate(len);
+        data
+    }
+
+    #[test]
+    fn round_trip() {
+        let sizes = [
+            0,
+            1,
+            2,
+            3,
+            5,
+            FRAME_SIZE - 1,
+            FRAME_SIZE,
+            FRAME_SIZE + 1,
+            0x10011,
+            200 * 1024,
+        ];
+        for size in sizes {
+            let raw = mixed_data(size);
+            let compressed = compress(&raw).unwrap();
+            let decompressed = decompress(&compressed, raw.len()).unwrap();
+            assert!(decompressed == raw, "round trip of {size} bytes failed");
+        }
+    }
+
+    #[test]
+    fn partial_frames_use_extended_prefix() {
+        let compressed = compress(&mixed_data(5)).unwrap();
+        assert_eq!(compressed[0], 0xFF);
+        assert_eq!(u16::from_be_bytes([compressed[1], compressed[2]]), 5);
+
+        // a full frame followed by a single byte
+        let compressed = compress(&mixed_data(FRAME_SIZE + 1)).unwrap();
+        let first_block = u16::from_be_bytes([compressed[0], compressed[1]]) as usize;
+        let second = &compressed[2 + first_block..];
+        assert_eq!(second[0], 0xFF);
+        assert_eq!(u16::from_be_bytes([second[1], second[2]]), 1);
+    }
+}
@@ -1,10 +1,21 @@
-use std::{ffi::OsStr, fs::File, io::BufReader, path::Path};
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{BufReader, BufWriter, Cursor, Write},
+    path::{Path, PathBuf},
+};
 
 use anyhow::Context;
 use args::{Args, Subcommands};
 use clap::Parser;
 use walkdir::WalkDir;
-use xnb_tool::xnb::{ExtractOptions, Xnb};
+use xnb_tool::content::error::ContentError;
+use xnb_tool::content::{Content, KIND_NAMES, texture::Texture2D};
+use xnb_tool::export::model::character::ContentLoader;
+use xnb_tool::export::model::material::TextureLoader;
+use xnb_tool::xact::wave_bank::{Codec, WaveBank, WaveBankEntry};
+use xnb_tool::xact::{SoundLibrary, referenced_sounds};
+use xnb_tool::xnb::{ExtractOptions, ImportOptions, Mismatch, RepackOptions, Xnb, XnbContent};
 
 mod args;
 
@@ -19,16 +30,73 @@ fn main() -> anyhow::Result<()> {
             dump_raw,
             msgpack,
             compression_level,
+            mips,
+            dds,
         } => {
             let options = ExtractOptions {
                 overwrite,
                 dump_raw,
                 msgpack,
                 compression_level,
+                mips,
+                dds,
             };
             extract(&input, &output, &options)
                 .with_context(|| format!("failed to extract {input}"))?;
         }
+        Subcommands::Repack {
+            input,
+            output,
+            overwrite,
+            compress,
+        } => {
+            let options = RepackOptions {
+                overwrite,
+                compress,
+            };
+            repack(&input, &output, &options)
+                .with_context(|| format!("failed to repack {input}"))?;
+        }
+        Subcommands::Import {
+            input,
+            output,
+            overwrite,
+            compress,
+            mips,
+        } => {
+            let options = ImportOptions {
+                overwrite,
+                compress,
+                mips,
+            };
+            import(&input, &output, &options)
+                .with_context(|| format!("failed to import {input}"))?;
+        }
+        Subcommands::Character {
+            input,
+            root,
+            output,
+            model,
+            overwrite,
+        } => {
+            character(&input, &root, &output, model, overwrite)
+                .with_context(|| format!("failed to assemble {input}"))?;
+        }
+        Subcommands::WaveBank {
+            input,
+            output,
+            overwrite,
+        } => {
+            wave_bank(&input, output.as_deref(), overwrite)
+                .with_context(|| format!("failed to extract {input}"))?;
+        }
+        Subcommands::Cues { input, sound_banks } => {
+            cues(&input, &sound_banks)
+                .with_context(|| format!("failed to check cues of {input}"))?;
+        }
+        Subcommands::Verify { input } => {
+            verify(&input).with_context(|| format!("failed to verify {input}"))?;
+        }
     }
 
     Ok(())
@@ -55,7 +123,10 @@ fn extract(
     } else if input_path.is_dir() {
         extract_directory(input_path, output_path, options)?;
     } else {
-        todo!();
+        anyhow::bail!(
+            "input path {} is neither a file nor a directory",
+            input_path.display()
+        );
     }
 
     Ok(())
@@ -94,7 +165,13 @@ fn extract_directory(
         eprintln!("\nextracting entry: {}", relative_path.display());
 
         if let Err(e) = extract_file(entry.path(), output_path.join(relative_path), options) {
-            failures.push(relative_path.display().to_string());
+            // content errors say which reader failed and where, which is worth repeating
+            match e.downcast_ref::<ContentError>() {
+                Some(content_error) => {
+                    failures.push(format!("{}: {content_error}", relative_path.display()))
+                }
+                None => failures.push(relative_path.display().to_string()),
+            }
             eprintln!("failed to extract entry: {e}");
             for (i, cause) in e.chain().enumerate() {
                 eprintln!("  {i}: {cause}");
@@ -120,10 +197,628 @@ fn extract_file(
     output_file_path: impl AsRef<Path>,
     options: &ExtractOptions,
 ) -> anyhow::Result<()> {
+    let input_file_path = input_file_path.as_ref();
     let file = File::open(input_file_path).context("failed to open file")?;
     let mut reader = BufReader::new(file);
     let xnb = Xnb::parse(&mut reader).context("failed to parse xnb header")?;
-    xnb.extract(output_file_path, options)
+    // effects reference textures relative to the xnb they are stored in
+    let textures = TextureLoader::new(input_file_path.parent().unwrap_or(Path::new(".")));
+    xnb.extract(output_file_path, &textures, options)
         .context("failed to extract xnb")?;
     Ok(())
 }
+
+fn repack(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    options: &RepackOptions,
+) -> anyhow::Result<()> {
+    let input_path = input_path.as_ref();
+    let output_path = output_path.as_ref();
+
+    if !input_path.try_exists()? {
+        anyhow::bail!("input path {} does not exist", input_path.display());
+    }
+
+    if input_path.is_file() {
+        let Some(xnb_path) = xnb_path(input_path) else {
+            anyhow::bail!("not an extracted content file: {}", input_path.display());
+        };
+        repack_file(
+            input_path,
+            output_path.join(xnb_path.file_name().unwrap()),
+            options,
+        )?;
+    } else if input_path.is_dir() {
+        repack_directory(input_path, output_path, options)?;
+    } else {
+        anyhow::bail!(
+            "input path {} is neither a file nor a directory",
+            input_path.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn repack_directory(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    options: &RepackOptions,
+) -> anyhow::Result<()> {
+    let input_path = input_path.as_ref();
+    let output_path = output_path.as_ref();
+
+    let mut successes = 0;
+    let mut failures = Vec::new();
+
+    for entry in WalkDir::new(input_path) {
+        let entry = match entry {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("failed to read entry: {e}");
+                continue;
+            }
+        };
+
+        if entry.path().is_dir() {
+            continue;
+        }
+
+        let Some(xnb_path) = xnb_path(entry.path()) else {
+            eprintln!("\nskipping non content file: {}", entry.path().display());
+            continue;
+        };
+
+        let relative_path = entry.path().strip_prefix(input_path)?;
+        let relative_xnb_path = xnb_path.strip_prefix(input_path)?;
+        eprintln!("\nrepacking entry: {}", relative_path.display());
+
+        if let Err(e) = repack_file(entry.path(), output_path.join(relative_xnb_path), options) {
+            failures.push(relative_path.display().to_string());
+            eprintln!("failed to repack entry: {e}");
+            for (i, cause) in e.chain().enumerate() {
+                eprintln!("  {i}: {cause}");
+            }
+        } else {
+            successes += 1;
+        }
+    }
+
+    println!("\nrepacked {successes} files");
+    if !failures.is_empty() {
+        println!("failed to repack {} files:", failures.len());
+        for f in &failures {
+            println!("  {f}");
+        }
+    }
+
+    Ok(())
+}
+
+fn repack_file(
+    input_file_path: impl AsRef<Path>,
+    output_file_path: impl AsRef<Path>,
+    options: &RepackOptions,
+) -> anyhow::Result<()> {
+    let output_file_path = output_file_path.as_ref();
+
+    let content = XnbContent::load(input_file_path).context("failed to load content")?;
+    let xnb = Xnb::from_content(&content, options.compress).context("failed to build xnb")?;
+
+    let directory = output_file_path.parent().unwrap();
+    if !directory.try_exists()? {
+        std::fs::create_dir_all(directory)
+            .with_context(|| format!("failed to create directory {}", directory.display()))?;
+    }
+
+    if output_file_path.try_exists()? && !options.overwrite {
+        anyhow::bail!("{} already exists", output_file_path.display());
+    }
+
+    let file = File::create(output_file_path).context("failed to create xnb file")?;
+    let mut writer = BufWriter::new(file);
+    xnb.write(&mut writer).context("failed to write xnb")?;
+    writer.flush()?;
+
+    eprintln!("saved to {}", output_file_path.display());
+    Ok(())
+}
+
+/// maps an extracted content file such as `foo.item.json` back to `foo.xnb`
+fn xnb_path(content_file_path: &Path) -> Option<PathBuf> {
+    let extension = content_file_path.extension()?;
+    if extension != "json" && extension != "msgpack" {
+        return None;
+    }
+    strip_kind_suffix(content_file_path)
+}
+
+/// maps an imported file back to the xnb it was extracted from, `foo.texture2d.png` and `foo.png`
+/// both become `foo.xnb`, other dotted names such as `foo.v2.png` keep their stem
+fn imported_xnb_path(input_file_path: &Path) -> PathBuf {
+    strip_kind_suffix(input_file_path).unwrap_or_else(|| input_file_path.with_extension("xnb"))
+}
+
+/// replaces the extension and the kind suffix extract adds before it with `xnb`, returns `None`
+/// when there is no kind suffix
+fn strip_kind_suffix(path: &Path) -> Option<PathBuf> {
+    let stem = path.with_extension("");
+    let kind = stem.extension()?.to_str()?;
+    if !KIND_NAMES.contains(&kind) {
+        return None;
+    }
+    Some(stem.with_extension("xnb"))
+}
+
+fn import(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    options: &ImportOptions,
+) -> anyhow::Result<()> {
+    let input_path = input_path.as_ref();
+    let output_path = output_path.as_ref();
+
+    if !input_path.try_exists()? {
+        anyhow::bail!("input path {} does not exist", input_path.display());
+    }
+
+    if input_path.is_file() {
+        if !is_importable(input_path) {
+            anyhow::bail!("not a png, dds, gltf or glb file: {}", input_path.display());
+        }
+        import_file(
+            input_path,
+            imported_xnb_path(&output_path.join(input_path.file_name().unwrap())),
+            options,
+        )?;
+    } else if input_path.is_dir() {
+        import_directory(input_path, output_path, options)?;
+    } else {
+        anyhow::bail!(
+            "input path {} is neither a file nor a directory",
+            input_path.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn import_directory(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    options: &ImportOptions,
+) -> anyhow::Result<()> {
+    let input_path = input_path.as_ref();
+    let output_path = output_path.as_ref();
+
+    let mut successes = 0;
+    let mut failures = Vec::new();
+
+    for entry in WalkDir::new(input_path) {
+        let entry = match entry {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("failed to read entry: {e}");
+                continue;
+            }
+        };
+
+        if entry.path().is_dir() {
+            continue;
+        }
+
+        if !is_importable(entry.path()) {
+            eprintln!("\nskipping unsupported file: {}", entry.path().display());
+            continue;
+        }
+
+        let relative_path = entry.path().strip_prefix(input_path)?;
+        eprintln!("\nimporting entry: {}", relative_path.display());
+
+        let xnb_path = imported_xnb_path(&output_path.join(relative_path));
+        if let Err(e) = import_file(entry.path(), xnb_path, options) {
+            failures.push(relative_path.display().to_string());
+            eprintln!("failed to import entry: {e}");
+            for (i, cause) in e.chain().enumerate() {
+                eprintln!("  {i}: {cause}");
+            }
+        } else {
+            successes += 1;
+        }
+    }
+
+    println!("\nimported {successes} files");
+    if !failures.is_empty() {
+        println!("failed to import {} files:", failures.len());
+        for f in &failures {
+            println!("  {f}");
+        }
+    }
+
+    Ok(())
+}
+
+fn import_file(
+    input_file_path: impl AsRef<Path>,
+    output_file_path: impl AsRef<Path>,
+    options: &ImportOptions,
+) -> anyhow::Result<()> {
+    let input_file_path = input_file_path.as_ref();
+    let output_file_path = output_file_path.as_ref();
+
+    let extension = input_file_path.extension().and_then(OsStr::to_str);
+    let content = if matches!(extension, Some("gltf" | "glb")) {
+        let content = XnbContent::from_gltf(input_file_path).context("failed to import gltf")?;
+        match &content.primary_content {
+            Content::Model(model) => {
+                eprintln!("{} bones, {} meshes", model.bones.len(), model.meshes.len())
+            }
+            Content::SkinnedModel(model) => eprintln!(
+                "{} bones, {} meshes, {} skinned bones, {} animations",
+                model.model.bones.len(),
+                model.model.meshes.len(),
+                model.shared_bone_refs.len(),
+                model.shared_animation_refs.len()
+            ),
+            _ => {}
+        }
+        content
+    } else {
+        let data = std::fs::read(input_file_path).context("failed to read image")?;
+        let texture = if extension == Some("dds") {
+            Texture2D::from_dds(&data, options.mips).context("failed to import dds")?
+        } else {
+            Texture2D::from_png(&data, options.mips).context("failed to import png")?
+        };
+        eprintln!(
+            "{}x{} {:?} with {} mip levels",
+            texture.width,
+            texture.height,
+            texture.format,
+            texture.mips.len()
+        );
+        XnbContent::new(Content::Texture2D(texture))
+    };
+
+    let xnb = Xnb::from_content(&content, options.compress).context("failed to build xnb")?;
+
+    let directory = output_file_path.parent().unwrap();
+    if !directory.try_exists()? {
+        std::fs::create_dir_all(directory)
+            .with_context(|| format!("failed to create directory {}", directory.display()))?;
+    }
+
+    if output_file_path.try_exists()? && !options.overwrite {
+        anyhow::bail!("{} already exists", output_file_path.display());
+    }
+
+    let file = File::create(output_file_path).context("failed to create xnb file")?;
+    let mut writer = BufWriter::new(file);
+    xnb.write(&mut writer).context("failed to write xnb")?;
+    writer.flush()?;
+
+    eprintln!("saved to {}", output_file_path.display());
+    Ok(())
+}
+
+fn is_importable(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(OsStr::to_str),
+        Some("png" | "dds" | "gltf" | "glb")
+    )
+}
+
+fn character(
+    input_path: impl AsRef<Path>,
+    root_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    model: usize,
+    overwrite: bool,
+) -> anyhow::Result<()> {
+    let input_path = input_path.as_ref();
+    let output_path = output_path.as_ref();
+
+    let content = XnbContent::open(input_path)?;
+    let Content::Character(character) = &content.primary_content else {
+        anyhow::bail!("{} is not a character", input_path.display());
+    };
+    eprintln!(
+        "{}: model {model} of {}, {} equipment attachments",
+        character.name,
+        character.models.len(),
+        character.equipment.len()
+    );
+
+    let loader = ContentLoader::new(root_path.as_ref());
+    let glb = character
+        .to_glb(model, &loader)
+        .context("failed to build glb")?;
+
+    if output_path.try_exists()? && !overwrite {
+        anyhow::bail!("{} already exists", output_path.display());
+    }
+    if let Some(directory) = output_path.parent()
+        && !directory.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(directory)
+            .with_context(|| format!("failed to create directory {}", directory.display()))?;
+    }
+    std::fs::write(output_path, glb).context("failed to write glb")?;
+
+    eprintln!("saved to {}", output_path.display());
+    Ok(())
+}
+
+fn wave_bank(
+    input_path: impl AsRef<Path>,
+    output_path: Option<&str>,
+    overwrite: bool,
+) -> anyhow::Result<()> {
+    let input_path = input_path.as_ref();
+
+    let file = File::open(input_path)?;
+    let bank = WaveBank::read(&mut BufReader::new(file))?;
+    eprintln!(
+        "{}: version {}, {} entries",
+        bank.name,
+        bank.version,
+        bank.entries.len()
+    );
+    for (i, entry) in bank.entries.iter().enumerate() {
+        let format = &entry.format;
+        eprintln!(
+            "{i:4}: {} {} {}ch {}Hz {} samples",
+            entry.name.as_deref().unwrap_or("-"),
+            format.codec.name(),
+            format.channels,
+            format.sample_rate,
+            entry.duration
+        );
+    }
+
+    let Some(output_path) = output_path else {
+        return Ok(());
+    };
+    let output_path = Path::new(output_path);
+    std::fs::create_dir_all(output_path)
+        .with_context(|| format!("failed to create directory {}", output_path.display()))?;
+
+    let mut successes = 0;
+    let mut failures = Vec::new();
+
+    for (i, entry) in bank.entries.iter().enumerate() {
+        if let Err(e) = save_wave_bank_entry(i, entry, output_path, overwrite) {
+            failures.push(match &entry.name {
+                Some(name) => format!("{i} {name}"),
+                None => i.to_string(),
+            });
+            eprintln!("failed to extract entry {i}: {e}");
+            for (i, cause) in e.chain().enumerate() {
+                eprintln!("  {i}: {cause}");
+            }
+        } else {
+            successes += 1;
+        }
+    }
+
+    println!("\nextracted {successes} entries");
+    if !failures.is_empty() {
+        println!("failed to extract {} entries:", failures.len());
+        for f in &failures {
+            println!("  {f}");
+        }
+    }
+
+    Ok(())
+}
+
+fn save_wave_bank_entry(
+    i: usize,
+    entry: &WaveBankEntry,
+    output_path: &Path,
+    overwrite: bool,
+) -> anyhow::Result<()> {
+    let stem = match &entry.name {
+        Some(name) => format!("{i:04}_{name}"),
+        None => format!("{i:04}"),
+    };
+    // xma and xwma would need an external decoder, their data is saved as is
+    let (extension, data) = match entry.format.codec {
+        Codec::Pcm | Codec::Adpcm => (
+            "wav".to_string(),
+            entry.to_wav().context("failed to decode entry")?,
+        ),
+        codec => (format!("{}.raw", codec.name()), entry.data.clone()),
+    };
+    let entry_path = output_path.join(format!("{stem}.{extension}"));
+    if entry_path.try_exists()? && !overwrite {
+        anyhow::bail!("{} already exists", entry_path.display());
+    }
+    std::fs::write(&entry_path, data)
+        .with_context(|| format!("failed to write {}", entry_path.display()))?;
+    match entry.format.codec {
+        Codec::Pcm | Codec::Adpcm => eprintln!("saved to {}", entry_path.display()),
+        codec => eprintln!(
+            "WARNING: unsupported codec {}, saved raw data to {}",
+            codec.name(),
+            entry_path.display()
+        ),
+    }
+    Ok(())
+}
+
+fn cues(input_path: impl AsRef<Path>, sound_banks_path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let input_path = input_path.as_ref();
+
+    let library = SoundLibrary::load(sound_banks_path)?;
+    for (flag, bank) in &library.banks {
+        match flag {
+            Some(flag) => eprintln!("{}: {} cues ({flag:?})", bank.name, bank.cues.len()),
+            None => eprintln!(
+                "WARNING: {}: {} cues, not matched to a bank flag",
+                bank.name,
+                bank.cues.len()
+            ),
+        }
+    }
+
+    let mut referenced = 0;
+    let mut missing = Vec::new();
+    for entry in WalkDir::new(input_path) {
+        let entry = match entry {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("failed to read entry: {e}");
+                continue;
+            }
+        };
+
+        if entry.path().is_dir() || entry.path().extension() != Some(OsStr::new("xnb")) {
+            continue;
+        }
+
+        let relative_path = match entry.path().strip_prefix(input_path)? {
+            p if p.as_os_str().is_empty() => entry.path(),
+            p => p,
+        };
+        let content = match XnbContent::open(entry.path()) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("WARNING: skipping {}: {e}", relative_path.display());
+                continue;
+            }
+        };
+        for (location, sound) in referenced_sounds(&content.primary_content) {
+            referenced += 1;
+            if library.resolve_cue(&sound).is_none() {
+                missing.push(format!(
+                    "{}: {location}: {} ({:?})",
+                    relative_path.display(),
+                    sound.cue,
+                    sound.bank
+                ));
+            }
+        }
+    }
+
+    println!(
+        "\n{} of {referenced} referenced cues are missing",
+        missing.len()
+    );
+    for m in &missing {
+        println!("  {m}");
+    }
+
+    Ok(())
+}
+
+fn verify(input_path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let input_path = input_path.as_ref();
+
+    if !input_path.try_exists()? {
+        anyhow::bail!("input path {} does not exist", input_path.display());
+    }
+
+    let mut successes = 0;
+    let mut mismatches = Vec::new();
+    let mut failures = Vec::new();
+
+    for entry in WalkDir::new(input_path) {
+        let entry = match entry {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("failed to read entry: {e}");
+                continue;
+            }
+        };
+
+        if entry.path().is_dir() {
+            continue;
+        }
+
+        if entry.path().extension() != Some(OsStr::new("xnb")) {
+            continue;
+        }
+
+        let relative_path = match entry.path().strip_prefix(input_path)? {
+            p if p.as_os_str().is_empty() => entry.path(),
+            p => p,
+        };
+
+        match verify_file(entry.path()) {
+            Ok(None) => successes += 1,
+            Ok(Some(mismatch)) => {
+                eprintln!(
+                    "{}: mismatch at offset {:#x}",
+                    relative_path.display(),
+                    mismatch.offset
+                );
+                for (i, step) in mismatch.path.iter().enumerate() {
+                    eprintln!("  {i}: {step}");
+                }
+                mismatches.push((relative_path.display().to_string(), mismatch));
+            }
+            Err(e) => {
+                eprintln!("{}: failed to verify: {e}", relative_path.display());
+                for (i, cause) in e.chain().enumerate() {
+                    eprintln!("  {i}: {cause}");
+                }
+                failures.push(relative_path.display().to_string());
+            }
+        }
+    }
+
+    println!("\nverified {successes} files");
+    if !mismatches.is_empty() {
+        println!("{} files differ after re-serializing:", mismatches.len());
+        for (f, mismatch) in &mismatches {
+            println!("  {f} at offset {:#x}", mismatch.offset);
+        }
+    }
+    if !failures.is_empty() {
+        println!("failed to verify {} files:", failures.len());
+        for f in &failures {
+            println!("  {f}");
+        }
+    }
+
+    Ok(())
+}
+
+fn verify_file(input_file_path: impl AsRef<Path>) -> anyhow::Result<Option<Mismatch>> {
+    let file = File::open(input_file_path).context("failed to open file")?;
+    let mut reader = BufReader::new(file);
+    let xnb = Xnb::parse(&mut reader).context("failed to parse xnb header")?;
+    let raw = xnb.payload()?;
+    let content = XnbContent::parse(
+        &mut Cursor::new(raw.as_ref()),
+        xnb.header().platform,
+        xnb.header().version,
+    )
+    .context("failed to parse xnb content")?;
+    content.verify(&raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_known_kind_suffixes_are_stripped() {
+        let path = |p: &str| PathBuf::from(p);
+        assert_eq!(
+            imported_xnb_path(&path("a/foo.texture2d.png")),
+            path("a/foo.xnb")
+        );
+        assert_eq!(imported_xnb_path(&path("a/foo.png")), path("a/foo.xnb"));
+        assert_eq!(
+            imported_xnb_path(&path("wizard.v2.png")),
+            path("wizard.v2.xnb")
+        );
+        assert_eq!(xnb_path(&path("foo.character.json")), Some(path("foo.xnb")));
+        assert_eq!(xnb_path(&path("foo.model.msgpack")), Some(path("foo.xnb")));
+        assert_eq!(xnb_path(&path("foo.bar.json")), None);
+        assert_eq!(xnb_path(&path("foo.json")), None);
+    }
+}
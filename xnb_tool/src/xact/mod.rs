@@ -0,0 +1,141 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use anyhow::Context;
+use sound_bank::{Cue, SoundBank};
+
+use crate::content::{
+    Content,
+    animation::{AnimationActionKind, AnimationSet},
+    event::{Event, EventConditions},
+    sound::{Bank, Sound},
+};
+
+pub mod sound_bank;
+pub mod wave_bank;
+
+/// the sound banks of a game, keyed by the bank flag that content uses to refer to them
+pub struct SoundLibrary {
+    pub banks: Vec<(Option<Bank>, SoundBank)>,
+}
+
+impl SoundLibrary {
+    /// reads every .xsb in a directory, banks are matched to their flag by name, ignoring case
+    /// and spaces
+    pub fn load(directory: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let directory = directory.as_ref();
+        let mut banks = Vec::new();
+        for entry in std::fs::read_dir(directory)
+            .with_context(|| format!("failed to read directory {}", directory.display()))?
+        {
+            let path = entry?.path();
+            if !path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("xsb"))
+            {
+                continue;
+            }
+            let file =
+                File::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
+            let sound_bank = SoundBank::read(&mut BufReader::new(file))
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let flag = bank_flag(&sound_bank.name).or_else(|| {
+                path.file_stem()
+                    .and_then(|stem| bank_flag(&stem.to_string_lossy()))
+            });
+            banks.push((flag, sound_bank));
+        }
+        // read_dir order is platform dependent
+        banks.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+        Ok(SoundLibrary { banks })
+    }
+
+    /// looks the cue up in the banks flagged by the sound first, then in every other bank since
+    /// the flags are not always accurate
+    pub fn resolve_cue(&self, sound: &Sound) -> Option<(&SoundBank, &Cue)> {
+        let (flagged, others): (Vec<_>, Vec<_>) = self
+            .banks
+            .iter()
+            .partition(|(flag, _)| flag.is_some_and(|flag| sound.bank.intersects(flag)));
+        flagged
+            .into_iter()
+            .chain(others)
+            .find_map(|(_, bank)| bank.cue(&sound.cue).map(|cue| (bank, cue)))
+    }
+}
+
+fn bank_flag(name: &str) -> Option<Bank> {
+    let name: String = name.chars().filter(|c| !c.is_whitespace()).collect();
+    Bank::all()
+        .iter_names()
+        .find(|(flag_name, _)| flag_name.eq_ignore_ascii_case(&name))
+        .map(|(_, flag)| flag)
+}
+
+/// every sound played by a character or item, along with where it is referenced. cues without a
+/// bank, like the gun sound of items, get an empty bank
+pub fn referenced_sounds(content: &Content) -> Vec<(String, Sound)> {
+    let mut sounds = Vec::new();
+    let mut push = |location: String, cue: &str, bank: Bank| {
+        if !cue.is_empty() {
+            let cue = cue.to_string();
+            sounds.push((location, Sound { cue, bank }));
+        }
+    };
+    match content {
+        Content::Character(character) => {
+            for sound in &character.sounds {
+                push("sounds".to_string(), &sound.cue, sound.bank);
+            }
+            let summon = &character.summon_element_sound;
+            push("summon_element_sound".to_string(), &summon.cue, summon.bank);
+            condition_sounds("conditions", &character.conditions, &mut push);
+            animation_sounds(&character.animations, &mut push);
+        }
+        Content::Item(item) => {
+            for sound in &item.sounds {
+                push("sounds".to_string(), &sound.cue, sound.bank);
+            }
+            push(
+                "gun_sound_cue".to_string(),
+                &item.gun_sound_cue,
+                Bank::empty(),
+            );
+            condition_sounds("melee_conditions", &item.melee_conditions, &mut push);
+            condition_sounds("gun_conditions", &item.gun_conditions, &mut push);
+            condition_sounds("ranged_conditions", &item.ranged_conditions, &mut push);
+        }
+        _ => {}
+    }
+    sounds
+}
+
+fn condition_sounds(
+    location: &str,
+    conditions: &[EventConditions],
+    push: &mut impl FnMut(String, &str, Bank),
+) {
+    for (i, condition) in conditions.iter().enumerate() {
+        for event in &condition.events {
+            if let Event::Sound(event) = event {
+                push(format!("{location}[{i}]"), &event.cue, event.banks);
+            }
+        }
+    }
+}
+
+fn animation_sounds(sets: &[AnimationSet], push: &mut impl FnMut(String, &str, Bank)) {
+    for (i, set) in sets.iter().enumerate() {
+        for clip in &set.clips {
+            for action in &clip.actions {
+                if let AnimationActionKind::PlaySound(action) = &action.kind {
+                    let sound = &action.sound;
+                    push(
+                        format!("animations[{i}].{}", clip.key),
+                        &sound.cue,
+                        sound.bank,
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,665 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use anyhow::{Context, anyhow};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+
+const NAME_SIZE: usize = 64;
+
+const SOUND_COMPLEX: u8 = 0x1;
+const SOUND_RPC: u8 = 0xe;
+const SOUND_DSP: u8 = 0x10;
+
+const CUE_SINGLE_SOUND: u8 = 0x4;
+
+/// XACT sound bank (.xsb), maps cue names to the waves they play
+#[derive(Debug)]
+pub struct SoundBank {
+    pub name: String,
+    /// names of the wave banks that the waves of the cues index into
+    pub wave_banks: Vec<String>,
+    pub cues: Vec<Cue>,
+}
+
+#[derive(Debug)]
+pub struct Cue {
+    pub name: String,
+    /// one variation is picked every time the cue is played
+    pub variations: Vec<CueVariation>,
+}
+
+#[derive(Debug)]
+pub struct CueVariation {
+    pub weight_min: f32,
+    pub weight_max: f32,
+    pub sound: XactSound,
+}
+
+#[derive(Debug)]
+pub struct XactSound {
+    pub category: u16,
+    /// in decibels
+    pub volume: f32,
+    /// in semitones
+    pub pitch: f32,
+    pub waves: Vec<WaveReference>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaveReference {
+    /// index into the wave banks of the sound bank
+    pub wave_bank: usize,
+    /// index of the entry in the wave bank
+    pub entry: usize,
+}
+
+impl SoundBank {
+    /// reads a sound bank, banks starting with `KBDS` come from the xbox 360 and are big-endian
+    pub fn read(reader: &mut (impl Read + Seek)) -> anyhow::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        match &magic {
+            b"SDBK" => Self::read_as::<LittleEndian>(reader),
+            b"KBDS" => Self::read_as::<BigEndian>(reader),
+            _ => anyhow::bail!("not a sound bank, found magic {magic:?}"),
+        }
+    }
+
+    fn read_as<E: ByteOrder>(reader: &mut (impl Read + Seek)) -> anyhow::Result<Self> {
+        let _tool_version = reader.read_u16::<E>()?;
+        let _format_version = reader.read_u16::<E>()?;
+        let _crc = reader.read_u16::<E>()?;
+        let _last_modified = reader.read_u64::<E>()?;
+        let _platform = reader.read_u8()?;
+        let simple_cue_count = reader.read_u16::<E>()? as usize;
+        let complex_cue_count = reader.read_u16::<E>()? as usize;
+        reader.read_u16::<E>()?;
+        let _total_cue_count = reader.read_u16::<E>()?;
+        let wave_bank_count = reader.read_u8()? as usize;
+        let _sound_count = reader.read_u16::<E>()?;
+        let _cue_names_length = reader.read_u16::<E>()?;
+        reader.read_u16::<E>()?;
+        let simple_cues_offset = reader.read_u32::<E>()?;
+        let complex_cues_offset = reader.read_u32::<E>()?;
+        let _cue_names_offset = reader.read_u32::<E>()?;
+        reader.read_u32::<E>()?;
+        let _variations_offset = reader.read_u32::<E>()?;
+        let _transitions_offset = reader.read_u32::<E>()?;
+        let wave_bank_names_offset = reader.read_u32::<E>()?;
+        let _cue_hashes_offset = reader.read_u32::<E>()?;
+        let cue_name_index_offset = reader.read_u32::<E>()?;
+        let _sounds_offset = reader.read_u32::<E>()?;
+        let name = read_name(reader, NAME_SIZE)?;
+
+        reader.seek(SeekFrom::Start(wave_bank_names_offset as u64))?;
+        let mut wave_banks = Vec::with_capacity(wave_bank_count);
+        for _ in 0..wave_bank_count {
+            wave_banks.push(read_name(reader, NAME_SIZE)?);
+        }
+
+        // every cue has a name offset followed by an unknown u16
+        let cue_count = simple_cue_count + complex_cue_count;
+        reader.seek(SeekFrom::Start(cue_name_index_offset as u64))?;
+        let mut name_offsets = Vec::with_capacity(cue_count);
+        for _ in 0..cue_count {
+            name_offsets.push(reader.read_u32::<E>()?);
+            reader.read_u16::<E>()?;
+        }
+        let mut names = Vec::with_capacity(cue_count);
+        for offset in name_offsets {
+            reader.seek(SeekFrom::Start(offset as u64))?;
+            names.push(read_null_terminated(reader)?);
+        }
+        let mut names = names.into_iter();
+
+        // simple cues play a single sound
+        let mut sound_offsets = Vec::with_capacity(simple_cue_count);
+        reader.seek(SeekFrom::Start(simple_cues_offset as u64))?;
+        for _ in 0..simple_cue_count {
+            let _flags = reader.read_u8()?;
+            sound_offsets.push(reader.read_u32::<E>()?);
+        }
+        let mut cues = Vec::with_capacity(cue_count);
+        for offset in sound_offsets {
+            let name = names.next().unwrap_or_default();
+            let sound = read_sound_at::<E>(reader, offset)
+                .with_context(|| format!("failed to read sound of cue {name}"))?;
+            cues.push(Cue {
+                name,
+                variations: vec![CueVariation {
+                    weight_min: 0.0,
+                    weight_max: 1.0,
+                    sound,
+                }],
+            });
+        }
+
+        // complex cues play a single sound or pick one from a variation table
+        let mut complex_cues = Vec::with_capacity(complex_cue_count);
+        reader.seek(SeekFrom::Start(complex_cues_offset as u64))?;
+        for _ in 0..complex_cue_count {
+            let flags = reader.read_u8()?;
+            let offset = reader.read_u32::<E>()?;
+            let _transitions_offset = reader.read_u32::<E>()?;
+            let _instance_limit = reader.read_u8()?;
+            let _fade_in = reader.read_u16::<E>()?;
+            let _fade_out = reader.read_u16::<E>()?;
+            let _instance_flags = reader.read_u8()?;
+            complex_cues.push((flags, offset));
+        }
+        for (flags, offset) in complex_cues {
+            let name = names.next().unwrap_or_default();
+            let variations = if flags & CUE_SINGLE_SOUND != 0 {
+                read_sound_at::<E>(reader, offset).map(|sound| {
+                    vec![CueVariation {
+                        weight_min: 0.0,
+                        weight_max: 1.0,
+                        sound,
+                    }]
+                })
+            } else {
+                read_variations::<E>(reader, offset)
+            }
+            .with_context(|| format!("failed to read cue {name}"))?;
+            cues.push(Cue { name, variations });
+        }
+
+        Ok(SoundBank {
+            name,
+            wave_banks,
+            cues,
+        })
+    }
+
+    pub fn cue(&self, name: &str) -> Option<&Cue> {
+        self.cues.iter().find(|cue| cue.name == name)
+    }
+}
+
+impl Cue {
+    /// every wave that any variation of the cue can play
+    pub fn waves(&self) -> impl Iterator<Item = WaveReference> + '_ {
+        self.variations
+            .iter()
+            .flat_map(|variation| variation.sound.waves.iter().copied())
+    }
+}
+
+enum VariationTarget {
+    Wave(WaveReference),
+    Sound(u32),
+}
+
+fn read_variations<E: ByteOrder>(
+    reader: &mut (impl Read + Seek),
+    offset: u32,
+) -> anyhow::Result<Vec<CueVariation>> {
+    reader.seek(SeekFrom::Start(offset as u64))?;
+    let entry_count = reader.read_u16::<E>()?;
+    let kind = (reader.read_u16::<E>()? >> 3) & 0x7;
+    reader.read_u32::<E>()?;
+
+    let mut entries = Vec::with_capacity(entry_count as usize);
+    for _ in 0..entry_count {
+        // variations either point straight at a wave or at a sound
+        let entry = match kind {
+            0 => {
+                let wave = read_wave_reference::<E>(reader)?;
+                let weight_min = reader.read_u8()? as f32 / 255.0;
+                let weight_max = reader.read_u8()? as f32 / 255.0;
+                (VariationTarget::Wave(wave), weight_min, weight_max)
+            }
+            1 => {
+                let sound_offset = reader.read_u32::<E>()?;
+                let weight_min = reader.read_u8()? as f32 / 255.0;
+                let weight_max = reader.read_u8()? as f32 / 255.0;
+                (VariationTarget::Sound(sound_offset), weight_min, weight_max)
+            }
+            3 => {
+                let sound_offset = reader.read_u32::<E>()?;
+                let weight_min = reader.read_f32::<E>()?;
+                let weight_max = reader.read_f32::<E>()?;
+                let _flags = reader.read_u32::<E>()?;
+                (VariationTarget::Sound(sound_offset), weight_min, weight_max)
+            }
+            4 => (
+                VariationTarget::Wave(read_wave_reference::<E>(reader)?),
+                0.0,
+                1.0,
+            ),
+            _ => anyhow::bail!("unknown variation table kind: {kind}"),
+        };
+        entries.push(entry);
+    }
+
+    let mut variations = Vec::with_capacity(entries.len());
+    for (target, weight_min, weight_max) in entries {
+        let sound = match target {
+            VariationTarget::Sound(offset) => read_sound_at::<E>(reader, offset)?,
+            VariationTarget::Wave(wave) => XactSound {
+                category: 0,
+                volume: 0.0,
+                pitch: 0.0,
+                waves: vec![wave],
+            },
+        };
+        variations.push(CueVariation {
+            weight_min,
+            weight_max,
+            sound,
+        });
+    }
+    Ok(variations)
+}
+
+fn read_sound_at<E: ByteOrder>(
+    reader: &mut (impl Read + Seek),
+    offset: u32,
+) -> anyhow::Result<XactSound> {
+    reader.seek(SeekFrom::Start(offset as u64))?;
+    let flags = reader.read_u8()?;
+    let category = reader.read_u16::<E>()?;
+    let volume = read_decibels(reader)?;
+    let pitch = reader.read_i16::<E>()? as f32 / 100.0;
+    let _priority = reader.read_u8()?;
+    let _entry_length = reader.read_u16::<E>()?;
+
+    let mut waves = Vec::new();
+    let track_count = if flags & SOUND_COMPLEX != 0 {
+        reader.read_u8()?
+    } else {
+        waves.push(read_wave_reference::<E>(reader)?);
+        0
+    };
+
+    // both blocks start with their length, which includes the length itself
+    for block in [SOUND_RPC, SOUND_DSP] {
+        if flags & block != 0 {
+            let start = reader.stream_position()?;
+            let length = reader.read_u16::<E>()?;
+            reader.seek(SeekFrom::Start(start + length as u64))?;
+        }
+    }
+
+    let mut event_offsets = Vec::with_capacity(track_count as usize);
+    for _ in 0..track_count {
+        let _volume = reader.read_u8()?;
+        event_offsets.push(reader.read_u32::<E>()?);
+        let _filter = reader.read_u32::<E>()?;
+    }
+    for (i, offset) in event_offsets.into_iter().enumerate() {
+        reader.seek(SeekFrom::Start(offset as u64))?;
+        read_track_events::<E>(reader, &mut waves)
+            .with_context(|| format!("failed to read events of track {i}"))?;
+    }
+
+    Ok(XactSound {
+        category,
+        volume,
+        pitch,
+        waves,
+    })
+}
+
+/// collects the waves played by the events of a track
+fn read_track_events<E: ByteOrder>(
+    reader: &mut impl Read,
+    waves: &mut Vec<WaveReference>,
+) -> anyhow::Result<()> {
+    let event_count = reader.read_u8()?;
+    for _ in 0..event_count {
+        let info = reader.read_u32::<E>()?;
+        let _random_offset = reader.read_u16::<E>()?;
+        let _separator = reader.read_u8()?;
+        let kind = info & 0x1f;
+        match kind {
+            // stop
+            0 => skip(reader, 1)?,
+            // play wave
+            1 => {
+                let _flags = reader.read_u8()?;
+                waves.push(read_wave_reference::<E>(reader)?);
+                // loop count, pan angle and arc
+                skip(reader, 5)?;
+            }
+            // play wave with track variation, with effect variation, or with both
+            3 | 4 | 6 => {
+                let _flags = reader.read_u8()?;
+                if kind == 4 {
+                    waves.push(read_wave_reference::<E>(reader)?);
+                }
+                skip(reader, 5)?;
+                if kind != 3 {
+                    // pitch, volume and filter ranges and the variation flags
+                    skip(reader, 24)?;
+                }
+                if kind != 4 {
+                    let track_count = reader.read_u16::<E>()?;
+                    let _variation = reader.read_u16::<E>()?;
+                    skip(reader, 4)?;
+                    for _ in 0..track_count {
+                        waves.push(read_wave_reference::<E>(reader)?);
+                        // min and max weight
+                        skip(reader, 2)?;
+                    }
+                }
+            }
+            // pitch, volume and their repeating variants
+            7 | 8 | 16 | 17 => {
+                let flags = reader.read_u8()?;
+                // ramps and equations are the same size, repeating equations also store their
+                // repeat count and frequency
+                skip(reader, 14)?;
+                if flags & 0x1 == 0 && (kind == 16 || kind == 17) {
+                    skip(reader, 4)?;
+                }
+            }
+            // marker and repeating marker
+            9 | 18 => skip(reader, 8)?,
+            _ => anyhow::bail!("unknown event kind: {kind}"),
+        }
+    }
+    Ok(())
+}
+
+fn read_wave_reference<E: ByteOrder>(reader: &mut impl Read) -> anyhow::Result<WaveReference> {
+    let entry = reader.read_u16::<E>()? as usize;
+    let wave_bank = reader.read_u8()? as usize;
+    Ok(WaveReference { wave_bank, entry })
+}
+
+/// xact stores volumes as a byte on a curve from -96 to +6 decibels
+fn read_decibels(reader: &mut impl Read) -> anyhow::Result<f32> {
+    const A: f64 = -96.0;
+    const B: f64 = 0.432254984608615;
+    const C: f64 = 80.1748600297963;
+    const D: f64 = 67.7385212334047;
+    let value = reader.read_u8()? as f64;
+    Ok(((A - D) / (1.0 + (value / C).powf(B)) + D) as f32)
+}
+
+fn skip(reader: &mut impl Read, count: u64) -> anyhow::Result<()> {
+    let skipped = std::io::copy(&mut reader.take(count), &mut std::io::sink())?;
+    if skipped != count {
+        return Err(anyhow!("unexpected end of file"));
+    }
+    Ok(())
+}
+
+/// reads a fixed size name padded with zeros
+fn read_name(reader: &mut impl Read, size: usize) -> anyhow::Result<String> {
+    let mut bytes = vec![0; size];
+    reader.read_exact(&mut bytes)?;
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(size);
+    Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
+
+fn read_null_terminated(reader: &mut impl Read) -> anyhow::Result<String> {
+    let mut bytes = Vec::new();
+    loop {
+        match reader.read_u8()? {
+            0 => break,
+            byte => bytes.push(byte),
+        }
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::marker::PhantomData;
+
+    use byteorder::WriteBytesExt;
+
+    use super::*;
+
+    const HEADER_SIZE: usize = 138;
+
+    /// sound bank under construction, blobs are appended after the header and the header is
+    /// written last once every offset is known
+    struct Builder<E> {
+        bytes: Vec<u8>,
+        order: PhantomData<E>,
+    }
+
+    impl<E: ByteOrder> Builder<E> {
+        fn new() -> Self {
+            Builder {
+                bytes: vec![0; HEADER_SIZE],
+                order: PhantomData,
+            }
+        }
+
+        fn offset(&self) -> u32 {
+            self.bytes.len() as u32
+        }
+
+        fn place(&mut self, blob: &[u8]) -> u32 {
+            let offset = self.offset();
+            self.bytes.extend_from_slice(blob);
+            offset
+        }
+
+        fn u8(&mut self, value: u8) -> &mut Self {
+            self.bytes.push(value);
+            self
+        }
+
+        fn u16(&mut self, value: u16) -> &mut Self {
+            self.bytes.write_u16::<E>(value).unwrap();
+            self
+        }
+
+        fn u32(&mut self, value: u32) -> &mut Self {
+            self.bytes.write_u32::<E>(value).unwrap();
+            self
+        }
+
+        /// a sound playing a single wave
+        fn simple_sound(&mut self, entry: u16, wave_bank: u8) -> u32 {
+            let offset = self.offset();
+            self.u8(0).u16(3).u8(0xb4).u16(-200i16 as u16).u8(0).u16(12);
+            self.u16(entry).u8(wave_bank);
+            offset
+        }
+
+        /// a complex sound, every track is a list of events
+        fn complex_sound(&mut self, tracks: &[Vec<u8>]) -> u32 {
+            let event_offsets: Vec<u32> = tracks.iter().map(|track| self.place(track)).collect();
+            let offset = self.offset();
+            self.u8(SOUND_COMPLEX).u16(1).u8(0xb4).u16(0).u8(0).u16(0);
+            self.u8(tracks.len() as u8);
+            for event_offset in event_offsets {
+                self.u8(0xb4).u32(event_offset).u32(0);
+            }
+            offset
+        }
+
+        fn finish(
+            mut self,
+            magic: &[u8; 4],
+            wave_banks: &[&str],
+            simple_cues: &[(&str, u32)],
+            complex_cues: &[(&str, u8, u32)],
+        ) -> Vec<u8> {
+            let wave_bank_names_offset = self.offset();
+            for name in wave_banks {
+                self.bytes.extend_from_slice(&padded_name(name));
+            }
+            let simple_cues_offset = self.offset();
+            for (_, sound) in simple_cues {
+                self.u8(0).u32(*sound);
+            }
+            let complex_cues_offset = self.offset();
+            for (_, flags, offset) in complex_cues {
+                self.u8(*flags)
+                    .u32(*offset)
+                    .u32(u32::MAX)
+                    .u8(0)
+                    .u16(0)
+                    .u16(0)
+                    .u8(0);
+            }
+            let names = simple_cues
+                .iter()
+                .map(|(name, _)| *name)
+                .chain(complex_cues.iter().map(|(name, _, _)| *name));
+            let name_offsets: Vec<u32> = names
+                .map(|name| self.place(&[name.as_bytes(), &[0]].concat()))
+                .collect();
+            let cue_name_index_offset = self.offset();
+            for offset in &name_offsets {
+                self.u32(*offset).u16(0);
+            }
+
+            let mut header = magic.to_vec();
+            for value in [46, 43, 0] {
+                header.write_u16::<E>(value).unwrap();
+            }
+            header.write_u64::<E>(0).unwrap();
+            header.push(1);
+            for value in [simple_cues.len(), complex_cues.len(), 0, name_offsets.len()] {
+                header.write_u16::<E>(value as u16).unwrap();
+            }
+            header.push(wave_banks.len() as u8);
+            for _ in 0..3 {
+                header.write_u16::<E>(0).unwrap();
+            }
+            let offsets = [
+                simple_cues_offset,
+                complex_cues_offset,
+                0,
+                0,
+                0,
+                0,
+                wave_bank_names_offset,
+                0,
+                cue_name_index_offset,
+                0,
+            ];
+            for offset in offsets {
+                header.write_u32::<E>(offset).unwrap();
+            }
+            header.extend_from_slice(&padded_name("Test Sounds"));
+            self.bytes[..HEADER_SIZE].copy_from_slice(&header);
+            self.bytes
+        }
+    }
+
+    fn padded_name(name: &str) -> Vec<u8> {
+        let mut bytes = name.as_bytes().to_vec();
+        bytes.resize(NAME_SIZE, 0);
+        bytes
+    }
+
+    /// events of a track, each one is a kind and its body
+    fn events<E: ByteOrder>(events: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = vec![events.len() as u8];
+        for (kind, body) in events {
+            bytes.write_u32::<E>(kind | 10 << 5).unwrap();
+            bytes.write_u16::<E>(0).unwrap();
+            bytes.push(0xff);
+            bytes.extend_from_slice(body);
+        }
+        bytes
+    }
+
+    fn sound_bank<E: ByteOrder>(magic: &[u8; 4]) -> Vec<u8> {
+        let mut bank = Builder::<E>::new();
+        let hurt = bank.simple_sound(3, 0);
+
+        let play = |entry: u16, wave_bank: u8| {
+            let mut body = vec![0];
+            body.write_u16::<E>(entry).unwrap();
+            body.extend_from_slice(&[wave_bank, 0, 0, 0, 0, 0]);
+            (1, body)
+        };
+        let volume_ramp = (8, [vec![1], vec![0; 14]].concat());
+        let marker = (9, vec![0; 8]);
+        let mut track_variation = vec![0; 6];
+        for value in [2, 0, 0, 0] {
+            track_variation.write_u16::<E>(value).unwrap();
+        }
+        for (entry, wave_bank) in [(7, 1), (8, 1)] {
+            track_variation.write_u16::<E>(entry).unwrap();
+            track_variation.extend_from_slice(&[wave_bank, 0, 255]);
+        }
+        let big = bank.complex_sound(&[
+            events::<E>(&[volume_ramp, play(2, 0), marker]),
+            events::<E>(&[(3, track_variation)]),
+        ]);
+
+        // variation table of waves with byte weights
+        let summon = bank.offset();
+        bank.u16(2).u16(0).u32(0);
+        bank.u16(0).u8(0).u8(0).u8(128);
+        bank.u16(5).u8(1).u8(128).u8(255);
+
+        // variation table of sounds
+        let thud_sounds = [bank.simple_sound(0, 0), bank.simple_sound(1, 1)];
+        let thud = bank.offset();
+        bank.u16(2).u16(1 << 3).u32(0);
+        for sound in thud_sounds {
+            bank.u32(sound).u8(0).u8(255);
+        }
+
+        bank.finish(
+            magic,
+            &["Spells", "Shared"],
+            &[("wizard_hurt", hurt)],
+            &[
+                ("spell_big", CUE_SINGLE_SOUND, big),
+                ("spell_summon", 0, summon),
+                ("death_thud", 0, thud),
+            ],
+        )
+    }
+
+    fn wave(wave_bank: usize, entry: usize) -> WaveReference {
+        WaveReference { wave_bank, entry }
+    }
+
+    #[test]
+    fn sound_bank_from_bytes() {
+        for bank in [
+            sound_bank::<LittleEndian>(b"SDBK"),
+            sound_bank::<BigEndian>(b"KBDS"),
+        ] {
+            let bank = SoundBank::read(&mut Cursor::new(bank)).unwrap();
+            assert_eq!(bank.name, "Test Sounds");
+            assert_eq!(bank.wave_banks, ["Spells", "Shared"]);
+            let names: Vec<_> = bank.cues.iter().map(|cue| cue.name.as_str()).collect();
+            assert_eq!(
+                names,
+                ["wizard_hurt", "spell_big", "spell_summon", "death_thud"]
+            );
+
+            let hurt = bank.cue("wizard_hurt").unwrap();
+            assert_eq!(hurt.waves().collect::<Vec<_>>(), [wave(0, 3)]);
+            let sound = &hurt.variations[0].sound;
+            assert_eq!(sound.category, 3);
+            assert_eq!(sound.pitch, -2.0);
+
+            let big = bank.cue("spell_big").unwrap();
+            assert_eq!(
+                big.waves().collect::<Vec<_>>(),
+                [wave(0, 2), wave(1, 7), wave(1, 8)]
+            );
+
+            let summon = bank.cue("spell_summon").unwrap();
+            assert_eq!(summon.waves().collect::<Vec<_>>(), [wave(0, 0), wave(1, 5)]);
+            assert_eq!(summon.variations[1].weight_max, 1.0);
+
+            let thud = bank.cue("death_thud").unwrap();
+            assert_eq!(thud.waves().collect::<Vec<_>>(), [wave(0, 0), wave(1, 1)]);
+            assert_eq!(thud.variations[0].sound.category, 3);
+        }
+    }
+
+    #[test]
+    fn sound_bank_magic_is_checked() {
+        let mut bank = sound_bank::<LittleEndian>(b"SDBK");
+        bank[..4].copy_from_slice(b"WBND");
+        assert!(SoundBank::read(&mut Cursor::new(bank)).is_err());
+    }
+}
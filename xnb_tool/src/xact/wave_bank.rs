@@ -0,0 +1,473 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use anyhow::{Context, anyhow};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+
+use crate::export::wav::{decode_ms_adpcm, encode_pcm_wav};
+
+const FLAG_STREAMING: u32 = 0x1;
+const FLAG_ENTRY_NAMES: u32 = 0x10000;
+const FLAG_COMPACT: u32 = 0x20000;
+
+/// size of the name stored in the bank data and of every entry name
+const NAME_SIZE: usize = 64;
+
+/// ms adpcm block sizes are stored with this offset subtracted
+const ADPCM_BLOCK_ALIGN_OFFSET: u16 = 22;
+
+/// XACT wave bank (.xwb), holds the audio that the cues of a sound bank play
+#[derive(Debug)]
+pub struct WaveBank {
+    pub name: String,
+    pub version: u32,
+    pub streaming: bool,
+    pub entries: Vec<WaveBankEntry>,
+}
+
+#[derive(Debug)]
+pub struct WaveBankEntry {
+    /// only stored when the bank was built with entry names
+    pub name: Option<String>,
+    pub format: WaveFormat,
+    /// length in samples
+    pub duration: u32,
+    /// start and length of the loop in samples
+    pub loop_region: (u32, u32),
+    /// audio data, 16-bit pcm is always little-endian
+    pub data: Vec<u8>,
+}
+
+#[repr(u32)]
+#[derive(strum::FromRepr, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Pcm = 0,
+    Xma,
+    Adpcm,
+    Wma,
+}
+
+impl Codec {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Codec::Pcm => "pcm",
+            Codec::Xma => "xma",
+            Codec::Adpcm => "adpcm",
+            Codec::Wma => "xwma",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WaveFormat {
+    pub codec: Codec,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+}
+
+impl WaveFormat {
+    /// unpacks the format that xact stores in a single u32
+    fn unpack(value: u32, version: u32) -> anyhow::Result<Self> {
+        // the first version has a one bit codec
+        let (codec, channels, sample_rate, align, bits) = if version == 1 {
+            (
+                value & 0x1,
+                (value >> 1) & 0x7,
+                (value >> 4) & 0x3ffff,
+                (value >> 22) & 0xff,
+                (value >> 30) & 0x1,
+            )
+        } else {
+            (
+                value & 0x3,
+                (value >> 2) & 0x7,
+                (value >> 5) & 0x3ffff,
+                (value >> 23) & 0xff,
+                (value >> 31) & 0x1,
+            )
+        };
+        let codec = Codec::from_repr(codec).ok_or_else(|| anyhow!("unknown codec: {codec}"))?;
+        let channels = channels as u16;
+        let (block_align, bits_per_sample) = match codec {
+            Codec::Pcm => {
+                let bits_per_sample = if bits == 1 { 16 } else { 8 };
+                (channels * bits_per_sample / 8, bits_per_sample)
+            }
+            Codec::Adpcm => ((align as u16 + ADPCM_BLOCK_ALIGN_OFFSET) * channels, 4),
+            Codec::Xma | Codec::Wma => (align as u16, if bits == 1 { 16 } else { 8 }),
+        };
+        Ok(WaveFormat {
+            codec,
+            channels,
+            sample_rate,
+            block_align,
+            bits_per_sample,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    offset: u32,
+    length: u32,
+}
+
+impl WaveBank {
+    /// reads a wave bank, banks starting with `DNBW` come from the xbox 360 and are big-endian
+    pub fn read(reader: &mut (impl Read + Seek)) -> anyhow::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        match &magic {
+            b"WBND" => Self::read_as::<LittleEndian>(reader),
+            b"DNBW" => Self::read_as::<BigEndian>(reader),
+            _ => anyhow::bail!("not a wave bank, found magic {magic:?}"),
+        }
+    }
+
+    fn read_as<E: ByteOrder>(reader: &mut (impl Read + Seek)) -> anyhow::Result<Self> {
+        let version = reader.read_u32::<E>()?;
+        if version >= 42 {
+            let _header_version = reader.read_u32::<E>()?;
+        }
+        // the seek table segment was added after version 3
+        let num_segments = if version <= 3 { 4 } else { 5 };
+        let mut segments = Vec::with_capacity(num_segments);
+        for _ in 0..num_segments {
+            let offset = reader.read_u32::<E>()?;
+            let length = reader.read_u32::<E>()?;
+            segments.push(Segment { offset, length });
+        }
+        let wave_data = segments[num_segments - 1];
+        let names_segment = if version >= 42 {
+            segments[3]
+        } else {
+            segments[2]
+        };
+
+        reader.seek(SeekFrom::Start(segments[0].offset as u64))?;
+        let flags = reader.read_u32::<E>()?;
+        let entry_count = reader.read_u32::<E>()? as usize;
+        let name_size = if version == 2 || version == 3 {
+            16
+        } else {
+            NAME_SIZE
+        };
+        let name = read_name(reader, name_size)?;
+        let (metadata_size, name_element_size, alignment) = if version == 1 {
+            (20, 0, 0)
+        } else {
+            let metadata_size = reader.read_u32::<E>()?;
+            let name_element_size = reader.read_u32::<E>()?;
+            let alignment = reader.read_u32::<E>()?;
+            (metadata_size, name_element_size, alignment)
+        };
+        let compact_format = if flags & FLAG_COMPACT != 0 {
+            Some(reader.read_u32::<E>()?)
+        } else {
+            None
+        };
+
+        let metadata_offset = if version == 1 {
+            reader.stream_position()?
+        } else {
+            segments[1].offset as u64
+        };
+        reader.seek(SeekFrom::Start(metadata_offset))?;
+
+        // entries are read first, compact entries only know where they end once the next one
+        // has been read
+        let mut regions = Vec::with_capacity(entry_count);
+        for _ in 0..entry_count {
+            let region = match compact_format {
+                Some(format) => {
+                    let value = reader.read_u32::<E>()?;
+                    let offset = (value & 0x1fffff) * alignment;
+                    (0, format, offset, 0, (0, 0))
+                }
+                None => {
+                    let mut fields = [0; 6];
+                    let count = if version == 1 {
+                        5
+                    } else {
+                        (metadata_size as usize / 4).min(6)
+                    };
+                    for field in &mut fields[..count] {
+                        *field = reader.read_u32::<E>()?;
+                    }
+                    let skip = metadata_size as i64 - count as i64 * 4;
+                    if skip > 0 {
+                        reader.seek(SeekFrom::Current(skip))?;
+                    }
+                    if version == 1 {
+                        let [format, offset, length, loop_offset, loop_length, _] = fields;
+                        (0, format, offset, length, (loop_offset, loop_length))
+                    } else {
+                        let [
+                            flags_and_duration,
+                            format,
+                            offset,
+                            length,
+                            loop_offset,
+                            loop_length,
+                        ] = fields;
+                        (
+                            flags_and_duration >> 4,
+                            format,
+                            offset,
+                            length,
+                            (loop_offset, loop_length),
+                        )
+                    }
+                }
+            };
+            regions.push(region);
+        }
+        if compact_format.is_some() {
+            for i in 0..regions.len() {
+                let end = regions
+                    .get(i + 1)
+                    .map(|next| next.2)
+                    .unwrap_or(wave_data.length);
+                regions[i].3 = end.saturating_sub(regions[i].2);
+            }
+        }
+
+        let names = if flags & FLAG_ENTRY_NAMES != 0 && name_element_size > 0 {
+            reader.seek(SeekFrom::Start(names_segment.offset as u64))?;
+            let mut names = Vec::with_capacity(entry_count);
+            for _ in 0..entry_count {
+                names.push(read_name(reader, name_element_size as usize)?);
+            }
+            names
+        } else {
+            Vec::new()
+        };
+
+        let mut entries = Vec::with_capacity(entry_count);
+        for (i, (duration, format, offset, length, loop_region)) in regions.into_iter().enumerate()
+        {
+            let format = WaveFormat::unpack(format, version)
+                .with_context(|| format!("failed to read format of entry {i}"))?;
+            reader.seek(SeekFrom::Start(wave_data.offset as u64 + offset as u64))?;
+            let mut data = vec![0; length as usize];
+            reader
+                .read_exact(&mut data)
+                .with_context(|| format!("failed to read data of entry {i}"))?;
+            if format.codec == Codec::Pcm && format.bits_per_sample == 16 {
+                for sample in data.chunks_exact_mut(2) {
+                    let value = E::read_u16(sample);
+                    LittleEndian::write_u16(sample, value);
+                }
+            }
+            entries.push(WaveBankEntry {
+                name: names.get(i).cloned(),
+                format,
+                duration,
+                loop_region,
+                data,
+            });
+        }
+
+        Ok(WaveBank {
+            name,
+            version,
+            streaming: flags & FLAG_STREAMING != 0,
+            entries,
+        })
+    }
+}
+
+impl WaveBankEntry {
+    /// decodes pcm and ms adpcm entries to a pcm wav file, other codecs are not supported
+    pub fn to_wav(&self) -> anyhow::Result<Vec<u8>> {
+        let format = &self.format;
+        match format.codec {
+            Codec::Pcm => Ok(encode_pcm_wav(
+                format.channels,
+                format.sample_rate,
+                format.bits_per_sample,
+                &self.data,
+                Some(self.loop_region),
+            )),
+            Codec::Adpcm => {
+                let mut samples =
+                    decode_ms_adpcm(&self.data, format.channels, format.block_align as usize)?;
+                // the last block is padded with silence
+                if self.duration > 0 {
+                    samples.truncate(self.duration as usize * format.channels as usize);
+                }
+                let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+                Ok(encode_pcm_wav(
+                    format.channels,
+                    format.sample_rate,
+                    16,
+                    &data,
+                    Some(self.loop_region),
+                ))
+            }
+            codec => anyhow::bail!("unsupported codec {}", codec.name()),
+        }
+    }
+}
+
+/// reads a fixed size name padded with zeros
+fn read_name(reader: &mut impl Read, size: usize) -> anyhow::Result<String> {
+    let mut bytes = vec![0; size];
+    reader.read_exact(&mut bytes)?;
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(size);
+    Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use byteorder::WriteBytesExt;
+
+    use super::*;
+
+    const HEADER_SIZE: u32 = 12 + 5 * 8;
+
+    fn pack_format(codec: u32, channels: u32, sample_rate: u32, align: u32, bits: u32) -> u32 {
+        codec | channels << 2 | sample_rate << 5 | align << 23 | bits << 31
+    }
+
+    fn padded_name(name: &str) -> Vec<u8> {
+        let mut bytes = name.as_bytes().to_vec();
+        bytes.resize(NAME_SIZE, 0);
+        bytes
+    }
+
+    /// bank data of a version 46 bank with entry names and an alignment of 4 bytes
+    fn bank_data<E: ByteOrder>(flags: u32, entry_count: u32) -> Vec<u8> {
+        let mut bank_data = Vec::new();
+        bank_data.write_u32::<E>(flags | FLAG_ENTRY_NAMES).unwrap();
+        bank_data.write_u32::<E>(entry_count).unwrap();
+        bank_data.extend_from_slice(&padded_name("Test Bank"));
+        for value in [24, NAME_SIZE as u32, 4] {
+            bank_data.write_u32::<E>(value).unwrap();
+        }
+        bank_data
+    }
+
+    /// lays out the segments of a version 46 bank after the header
+    fn layout<E: ByteOrder>(magic: &[u8; 4], segments: [Vec<u8>; 4]) -> Vec<u8> {
+        let [bank_data, metadata, names, wave_data] = segments;
+        let bank_offset = HEADER_SIZE;
+        let metadata_offset = bank_offset + bank_data.len() as u32;
+        let names_offset = metadata_offset + metadata.len() as u32;
+        let data_offset = names_offset + names.len() as u32;
+        let segments = [
+            (bank_offset, bank_data.len()),
+            (metadata_offset, metadata.len()),
+            (names_offset, 0),
+            (names_offset, names.len()),
+            (data_offset, wave_data.len()),
+        ];
+
+        let mut bank = magic.to_vec();
+        bank.write_u32::<E>(46).unwrap();
+        bank.write_u32::<E>(44).unwrap();
+        for (offset, length) in segments {
+            bank.write_u32::<E>(offset).unwrap();
+            bank.write_u32::<E>(length as u32).unwrap();
+        }
+        [bank, bank_data, metadata, names, wave_data].concat()
+    }
+
+    /// bank with a named 16-bit pcm entry and a looping xwma entry
+    fn wave_bank<E: ByteOrder>(magic: &[u8; 4]) -> Vec<u8> {
+        let mut wave_data: Vec<u8> = [1i16, -2, 300, -32768]
+            .iter()
+            .flat_map(|sample| {
+                let mut bytes = [0; 2];
+                E::write_i16(&mut bytes, *sample);
+                bytes
+            })
+            .collect();
+        wave_data.extend_from_slice(&[9; 6]);
+
+        let mut metadata = Vec::new();
+        let entries = [
+            (4, pack_format(0, 1, 22050, 2, 1), 0, 8, (0, 0)),
+            (100, pack_format(3, 2, 44100, 5, 1), 8, 6, (10, 20)),
+        ];
+        for (duration, format, offset, length, (loop_offset, loop_length)) in entries {
+            for value in [
+                duration << 4,
+                format,
+                offset,
+                length,
+                loop_offset,
+                loop_length,
+            ] {
+                metadata.write_u32::<E>(value).unwrap();
+            }
+        }
+        let names = [padded_name("tone"), padded_name("music")].concat();
+        layout::<E>(magic, [bank_data::<E>(0, 2), metadata, names, wave_data])
+    }
+
+    #[test]
+    fn wave_bank_from_bytes() {
+        for bank in [
+            wave_bank::<LittleEndian>(b"WBND"),
+            wave_bank::<BigEndian>(b"DNBW"),
+        ] {
+            let bank = WaveBank::read(&mut Cursor::new(bank)).unwrap();
+            assert_eq!(bank.name, "Test Bank");
+            assert_eq!(bank.version, 46);
+            assert!(!bank.streaming);
+            assert_eq!(bank.entries.len(), 2);
+
+            let pcm = &bank.entries[0];
+            assert_eq!(pcm.name.as_deref(), Some("tone"));
+            assert_eq!(pcm.format.codec, Codec::Pcm);
+            assert_eq!(pcm.format.channels, 1);
+            assert_eq!(pcm.format.sample_rate, 22050);
+            assert_eq!(pcm.format.bits_per_sample, 16);
+            assert_eq!(pcm.format.block_align, 2);
+            assert_eq!(pcm.duration, 4);
+            // pcm is always little-endian once read
+            let samples: Vec<u8> = [1i16, -2, 300, -32768]
+                .iter()
+                .flat_map(|sample| sample.to_le_bytes())
+                .collect();
+            assert_eq!(pcm.data, samples);
+
+            let wma = &bank.entries[1];
+            assert_eq!(wma.name.as_deref(), Some("music"));
+            assert_eq!(wma.format.codec, Codec::Wma);
+            assert_eq!(wma.format.channels, 2);
+            assert_eq!(wma.format.block_align, 5);
+            assert_eq!(wma.loop_region, (10, 20));
+            assert_eq!(wma.data, [9; 6]);
+        }
+    }
+
+    #[test]
+    fn compact_entries_end_at_the_next_entry() {
+        // every entry shares the format after the bank data, offsets are in units of the
+        // alignment
+        let mut bank_data = bank_data::<LittleEndian>(FLAG_COMPACT, 2);
+        bank_data.extend_from_slice(&pack_format(0, 1, 8000, 1, 0).to_le_bytes());
+        let metadata = [0u32, 2].map(u32::to_le_bytes).concat();
+        let names = [padded_name("a"), padded_name("b")].concat();
+        let wave_data = [[1; 8].as_slice(), &[2; 6]].concat();
+        let bank = layout::<LittleEndian>(b"WBND", [bank_data, metadata, names, wave_data]);
+
+        let bank = WaveBank::read(&mut Cursor::new(bank)).unwrap();
+        assert_eq!(bank.entries.len(), 2);
+        assert_eq!(bank.entries[0].format.sample_rate, 8000);
+        assert_eq!(bank.entries[0].format.bits_per_sample, 8);
+        assert_eq!(bank.entries[0].data, [1; 8]);
+        assert_eq!(bank.entries[1].name.as_deref(), Some("b"));
+        assert_eq!(bank.entries[1].data, [2; 6]);
+    }
+
+    #[test]
+    fn wave_bank_magic_is_checked() {
+        assert!(WaveBank::read(&mut Cursor::new(b"RIFF\0\0\0\0".to_vec())).is_err());
+    }
+}
@@ -1,26 +1,35 @@
 use std::{
+    borrow::Cow,
+    ffi::OsStr,
     fs::File,
-    io::{Cursor, Read, Seek, Write},
+    io::{BufReader, Cursor, Read, Seek, Write},
     path::Path,
 };
 
 use anyhow::Context;
-use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
-use flate2::{Compression, write::ZlibEncoder};
-use lzxd::Lzxd;
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
+use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
 use serde::{Deserialize, Serialize};
 
-use crate::{content::Content, ext::MyReadBytesExt};
+use crate::{
+    content::Content,
+    export::{model::material::TextureLoader, texture::MipExport},
+    ext::{MyReadBytesExt, MyWriteBytesExt},
+    import::texture::MipFilter,
+    lzx,
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Platform {
+    #[default]
     Windows,
     WindowsPhone,
     Xbox360,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Version {
+    #[default]
     XNA31,
     XNA40,
 }
@@ -35,12 +44,53 @@ pub struct Header {
     pub uncompressed_size: u32,
 }
 
+/// size of the header of an uncompressed xnb file, the compressed header has an extra u32
+const HEADER_SIZE: usize = 10;
+
+/// assembly the XNA 3.1 content readers live in
+const XNA31_FRAMEWORK_ASSEMBLY: &str =
+    "Microsoft.Xna.Framework, Version=3.1.0.0, Culture=neutral, PublicKeyToken=6d5c3888ef60e27d";
+
+/// assembly of the .NET types that XNA 3.1 generic readers can hold, such as `System.Char`
+const MSCORLIB_ASSEMBLY: &str =
+    "mscorlib, Version=2.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089";
+
 pub struct Xnb {
     header: Header,
     data: Vec<u8>,
 }
 
 impl Xnb {
+    /// builds an xnb from parsed content, optionally LZX compressed
+    pub fn from_content(content: &XnbContent, compress: bool) -> anyhow::Result<Self> {
+        let mut raw = Vec::new();
+        content
+            .write(&mut raw)
+            .context("failed to serialize xnb content")?;
+
+        let (data, header_size, uncompressed_size) = if compress {
+            let data = lzx::compress(&raw).context("failed to compress xnb content")?;
+            (data, HEADER_SIZE + 4, raw.len())
+        } else {
+            (raw, HEADER_SIZE, 0)
+        };
+
+        let header = Header {
+            platform: content.platform,
+            version: content.version,
+            hi_def: content.hi_def,
+            compressed: compress,
+            compressed_size: (header_size + data.len())
+                .try_into()
+                .context("content exceeds xnb size limit")?,
+            uncompressed_size: uncompressed_size
+                .try_into()
+                .context("content exceeds xnb size limit")?,
+        };
+
+        Ok(Xnb { header, data })
+    }
+
     pub fn header(&self) -> &Header {
         &self.header
     }
@@ -69,10 +119,6 @@ impl Xnb {
             v => anyhow::bail!("unknown version: {v}"),
         };
 
-        if version != Version::XNA31 {
-            anyhow::bail!("unsupported XNA version: {version:?}, only 3.1 is supported");
-        }
-
         let flags = reader.read_u8()?;
         let hi_def = flags & 0x01 != 0;
         let compressed = flags & 0x80 != 0;
@@ -101,9 +147,46 @@ impl Xnb {
         Ok(xnb)
     }
 
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_all(b"XNB")?;
+
+        let platform = match self.header.platform {
+            Platform::Windows => b'w',
+            Platform::WindowsPhone => b'm',
+            Platform::Xbox360 => b'x',
+        };
+        writer.write_u8(platform)?;
+
+        let version = match self.header.version {
+            Version::XNA31 => 4,
+            Version::XNA40 => 5,
+        };
+        writer.write_u8(version)?;
+
+        let mut flags = 0;
+        if self.header.hi_def {
+            flags |= 0x01;
+        }
+        if self.header.compressed {
+            flags |= 0x80;
+        }
+        writer.write_u8(flags)?;
+
+        writer.write_u32::<LittleEndian>(self.header.compressed_size)?;
+        if self.header.compressed {
+            writer.write_u32::<LittleEndian>(self.header.uncompressed_size)?;
+        }
+
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+
+    /// saves the content to `file_path` along with png, glb, fnt and wav exports, `textures` finds the
+    /// textures that exported models reference
     pub fn extract(
         &self,
         file_path: impl AsRef<Path>,
+        textures: &TextureLoader,
         options: &ExtractOptions,
     ) -> anyhow::Result<()> {
         let directory = file_path.as_ref().parent().unwrap();
@@ -112,13 +195,7 @@ impl Xnb {
                 .with_context(|| format!("failed to create directory {}", directory.display()))?;
         }
 
-        let raw = if self.header.compressed {
-            self.decompress()
-                .context("failed to decompress xnb content")?
-        } else {
-            // TODO: find a way to avoid the clone, maybe COW?
-            self.data.clone()
-        };
+        let raw = self.payload()?;
 
         if options.dump_raw {
             let file_path = file_path.as_ref().with_extension("raw");
@@ -132,33 +209,13 @@ impl Xnb {
         }
 
         let mut reader = Cursor::new(&raw);
-        let content = XnbContent::parse(&mut reader)?;
+        let mut content =
+            XnbContent::parse(&mut reader, self.header.platform, self.header.version)?;
+        content.hi_def = self.header.hi_def;
 
-        let extension = match content.primary_content {
-            Content::Null => {
-                eprintln!("WARNING: null content");
-                return Ok(());
-            }
-            Content::String(..) => "string",
-            Content::ExternalReference(..) => "externalreference",
-            Content::Item(..) => "item",
-            Content::Character(..) => "character",
-            Content::Texture2D(..) => "texture2d",
-            Content::Model(..) => "model",
-            Content::SkinnedModel(..) => "skinnedmodel",
-            Content::SkinnedModelBone(..) => "skinnedmodelbone",
-            Content::SkinnedModelAnimationClip(..) => "skinnedmodelanimationclip",
-            Content::VertexDeclaration(..) => "vertexdecl",
-            Content::VertexBuffer(..) => "vertexbuffer",
-            Content::IndexBuffer(..) => "indexbuffer",
-            Content::Effect(..) => "effect",
-            Content::BasicEffect(..) => "basiceffect",
-            Content::AdditiveEffect(..) => "additiveeffect",
-            Content::RenderDeferredEffect(..) => "renderdeferredeffect",
-            Content::SkinnedModelBasicEffect(..) => "skinnedmodelbasiceffect",
-            Content::SkinnedModelDeferredNormalMappedEffect(..) => {
-                "skinnedmodeldeferrednormalmappedeffect"
-            }
+        let Some(extension) = content.primary_content.kind_name() else {
+            eprintln!("WARNING: null content");
+            return Ok(());
         };
 
         let extension = if options.msgpack {
@@ -196,19 +253,76 @@ impl Xnb {
 
         eprintln!("saved to {}", file_path.display());
 
+        // the saved content keeps the original layout so it can be repacked
+        content
+            .convert_from_xbox360()
+            .context("failed to convert xbox 360 content")?;
+
         match &content.primary_content {
             Content::Texture2D(texture) => {
-                let file_path = file_path.with_extension("png");
-                let exists = file_path.try_exists()?;
+                let png_path = file_path.with_extension("png");
+                let exists = png_path.try_exists()?;
                 if exists && !options.overwrite {
-                    anyhow::bail!("{} already exists", file_path.display());
+                    anyhow::bail!("{} already exists", png_path.display());
                 }
-                let mut file = File::create(&file_path).context("failed to create png file")?;
+                let mut file = File::create(&png_path).context("failed to create png file")?;
 
                 let png = texture.to_png().context("failed to encode png")?;
                 file.write_all(&png)?;
 
-                eprintln!("saved to {}", file_path.display());
+                eprintln!("saved to {}", png_path.display());
+
+                if options.dds {
+                    let dds_path = file_path.with_extension("dds");
+                    let exists = dds_path.try_exists()?;
+                    if exists && !options.overwrite {
+                        anyhow::bail!("{} already exists", dds_path.display());
+                    }
+                    let mut file = File::create(&dds_path).context("failed to create dds file")?;
+
+                    let dds = texture.to_dds().context("failed to encode dds")?;
+                    file.write_all(&dds)?;
+
+                    eprintln!("saved to {}", dds_path.display());
+                }
+
+                match options.mips {
+                    MipExport::None => {}
+                    MipExport::Separate => {
+                        for level in 1..texture.mips.len() {
+                            let png_path = file_path.with_extension(format!("mip{level}.png"));
+                            let exists = png_path.try_exists()?;
+                            if exists && !options.overwrite {
+                                anyhow::bail!("{} already exists", png_path.display());
+                            }
+                            let mut file =
+                                File::create(&png_path).context("failed to create png file")?;
+
+                            let png = texture
+                                .mip_to_png(level)
+                                .with_context(|| format!("failed to encode mip level {level}"))?;
+                            file.write_all(&png)?;
+
+                            eprintln!("saved to {}", png_path.display());
+                        }
+                    }
+                    MipExport::Strip => {
+                        let png_path = file_path.with_extension("mips.png");
+                        let exists = png_path.try_exists()?;
+                        if exists && !options.overwrite {
+                            anyhow::bail!("{} already exists", png_path.display());
+                        }
+                        let mut file =
+                            File::create(&png_path).context("failed to create png file")?;
+
+                        let png = texture
+                            .mips_to_png_strip()
+                            .context("failed to encode mip strip")?;
+                        file.write_all(&png)?;
+
+                        eprintln!("saved to {}", png_path.display());
+                    }
+                }
             }
             Content::Model(model) => {
                 let file_path = file_path.with_extension("glb");
@@ -219,13 +333,13 @@ impl Xnb {
                 let mut file = File::create(&file_path).context("failed to create glb file")?;
 
                 let glb = model
-                    .to_glb(&content.shared_content)
+                    .to_glb(&content.shared_content, textures)
                     .context("failed to build glb")?;
                 file.write_all(&glb)?;
 
                 eprintln!("saved to {}", file_path.display());
             }
-            Content::SkinnedModel(model) => {
+            Content::Xna40Model(model) => {
                 let file_path = file_path.with_extension("glb");
                 let exists = file_path.try_exists()?;
                 if exists && !options.overwrite {
@@ -234,47 +348,123 @@ impl Xnb {
                 let mut file = File::create(&file_path).context("failed to create glb file")?;
 
                 let glb = model
-                    .to_glb(&content.shared_content)
+                    .to_glb(&content.shared_content, textures)
                     .context("failed to build glb")?;
                 file.write_all(&glb)?;
 
                 eprintln!("saved to {}", file_path.display());
             }
-            _ => {}
-        }
+            Content::SpriteFont(font) => {
+                let png_path = file_path.with_extension("png");
+                let exists = png_path.try_exists()?;
+                if exists && !options.overwrite {
+                    anyhow::bail!("{} already exists", png_path.display());
+                }
+                let mut file = File::create(&png_path).context("failed to create png file")?;
 
-        Ok(())
-    }
+                let png = font.texture.to_png().context("failed to encode png")?;
+                file.write_all(&png)?;
 
-    pub fn decompress(&self) -> anyhow::Result<Vec<u8>> {
-        let mut data = Cursor::new(&self.data);
-        let mut decompressed = Vec::with_capacity(self.header.uncompressed_size as usize);
+                eprintln!("saved to {}", png_path.display());
 
-        let mut lzxd = Lzxd::new(lzxd::WindowSize::KB64);
+                let fnt_path = file_path.with_extension("fnt");
+                let exists = fnt_path.try_exists()?;
+                if exists && !options.overwrite {
+                    anyhow::bail!("{} already exists", fnt_path.display());
+                }
+                let mut file = File::create(&fnt_path).context("failed to create fnt file")?;
+
+                // the face is the name of the xnb, the page is the png saved above
+                let face = file_path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| name.split('.').next())
+                    .unwrap_or_default();
+                let page = png_path.file_name().unwrap().to_string_lossy();
+                file.write_all(font.to_fnt(face, &page).as_bytes())?;
+
+                eprintln!("saved to {}", fnt_path.display());
+            }
+            Content::SoundEffect(sound_effect) => {
+                let wav = match sound_effect.to_wav() {
+                    Ok(wav) => wav,
+                    // the json above already holds everything needed to repack
+                    Err(e) => {
+                        eprintln!("WARNING: failed to export wav: {e}");
+                        return Ok(());
+                    }
+                };
+
+                let wav_path = file_path.with_extension("wav");
+                let exists = wav_path.try_exists()?;
+                if exists && !options.overwrite {
+                    anyhow::bail!("{} already exists", wav_path.display());
+                }
+                let mut file = File::create(&wav_path).context("failed to create wav file")?;
+                file.write_all(&wav)?;
 
-        while (data.position() as usize) < data.get_ref().len() {
-            let frame_size;
-            let block_size;
-            if data.read_u8()? == 0xFF {
-                frame_size = data.read_u16::<BigEndian>()?;
-                block_size = data.read_u16::<BigEndian>()?;
-            } else {
-                data.seek_relative(-1)?;
-                block_size = data.read_u16::<BigEndian>()?;
-                frame_size = 0x8000;
+                eprintln!("saved to {}", wav_path.display());
             }
+            Content::LevelModel(level) => {
+                let file_path = file_path.with_extension("glb");
+                let exists = file_path.try_exists()?;
+                if exists && !options.overwrite {
+                    anyhow::bail!("{} already exists", file_path.display());
+                }
+                let mut file = File::create(&file_path).context("failed to create glb file")?;
+
+                let glb = level
+                    .to_glb(&content.shared_content, textures)
+                    .context("failed to build glb")?;
+                file.write_all(&glb)?;
 
-            if block_size == 0 || frame_size == 0 {
-                break;
+                eprintln!("saved to {}", file_path.display());
             }
+            Content::SkinnedModel(model) => {
+                let file_path = file_path.with_extension("glb");
+                let exists = file_path.try_exists()?;
+                if exists && !options.overwrite {
+                    anyhow::bail!("{} already exists", file_path.display());
+                }
+                let mut file = File::create(&file_path).context("failed to create glb file")?;
 
-            let mut block = vec![0; block_size as usize];
-            data.read_exact(&mut block)?;
-            let frame = lzxd.decompress_next(&block, frame_size as usize)?;
-            decompressed.extend_from_slice(&frame);
+                let glb = model
+                    .to_glb(&content.shared_content, textures)
+                    .context("failed to build glb")?;
+                file.write_all(&glb)?;
+
+                eprintln!("saved to {}", file_path.display());
+            }
+            _ => {}
         }
 
-        Ok(decompressed)
+        Ok(())
+    }
+
+    /// parses the content of the xnb
+    pub fn content(&self) -> anyhow::Result<XnbContent> {
+        let raw = self.payload()?;
+        let mut reader = Cursor::new(raw.as_ref());
+        let mut content =
+            XnbContent::parse(&mut reader, self.header.platform, self.header.version)?;
+        content.hi_def = self.header.hi_def;
+        Ok(content)
+    }
+
+    /// the uncompressed content, decompressing it if needed
+    pub fn payload(&self) -> anyhow::Result<Cow<'_, [u8]>> {
+        if self.header.compressed {
+            let raw = self
+                .decompress()
+                .context("failed to decompress xnb content")?;
+            Ok(Cow::Owned(raw))
+        } else {
+            Ok(Cow::Borrowed(&self.data))
+        }
+    }
+
+    pub fn decompress(&self) -> anyhow::Result<Vec<u8>> {
+        lzx::decompress(&self.data, self.header.uncompressed_size as usize)
     }
 }
 
@@ -283,6 +473,17 @@ pub struct XnbContent {
     pub readers: Vec<TypeReader>,
     pub primary_content: Content,
     pub shared_content: Vec<Content>,
+    #[serde(default)]
+    pub version: Version,
+    #[serde(default)]
+    pub platform: Platform,
+    /// whether the content targets the XNA 4.0 HiDef profile instead of Reach
+    #[serde(default)]
+    pub hi_def: bool,
+    /// shared content kept in the raw bytes of a level whose end could not be found, it is
+    /// still counted when writing
+    #[serde(default)]
+    pub unparsed_shared_content: usize,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -292,29 +493,100 @@ pub struct TypeReader {
 }
 
 impl XnbContent {
-    pub fn parse(reader: &mut impl Read) -> anyhow::Result<Self> {
+    /// wraps a single piece of content for XNA 3.1 on windows, with a type reader for it and no
+    /// shared content
+    pub fn new(primary_content: Content) -> Self {
+        Self::with_shared_content(primary_content, Vec::new())
+    }
+
+    /// wraps content for XNA 3.1 on windows, with a type reader for everything the primary and
+    /// shared content need
+    pub fn with_shared_content(primary_content: Content, shared_content: Vec<Content>) -> Self {
+        let mut readers: Vec<TypeReader> = Vec::new();
+        let names = std::iter::once(&primary_content)
+            .chain(&shared_content)
+            .flat_map(Content::reader_names);
+        for name in names {
+            if readers
+                .iter()
+                .any(|reader| reader.name.split(",").next().unwrap() == name)
+            {
+                continue;
+            }
+            let name = if let Some((_, element)) = name.split_once("[[") {
+                // generic readers also name the assembly of their element type
+                let element_assembly = if element.starts_with("System.") {
+                    MSCORLIB_ASSEMBLY
+                } else {
+                    XNA31_FRAMEWORK_ASSEMBLY
+                };
+                format!("{name}, {element_assembly}]], {XNA31_FRAMEWORK_ASSEMBLY}")
+            } else if name.starts_with("Microsoft.Xna.Framework") {
+                format!("{name}, {XNA31_FRAMEWORK_ASSEMBLY}")
+            } else {
+                // the other readers live in an assembly named after their root namespace
+                let assembly = name.split('.').next().unwrap();
+                format!("{name}, {assembly}")
+            };
+            readers.push(TypeReader { name, version: 0 });
+        }
+        XnbContent {
+            readers,
+            primary_content,
+            shared_content,
+            version: Version::XNA31,
+            platform: Platform::Windows,
+            hi_def: false,
+            unparsed_shared_content: 0,
+        }
+    }
+
+    pub fn parse(
+        reader: &mut (impl Read + Seek),
+        platform: Platform,
+        version: Version,
+    ) -> anyhow::Result<Self> {
+        // xbox 360 content is written in the console's native big-endian byte order
+        match platform {
+            Platform::Xbox360 => Self::parse_as::<BigEndian>(reader, platform, version),
+            Platform::Windows | Platform::WindowsPhone => {
+                Self::parse_as::<LittleEndian>(reader, platform, version)
+            }
+        }
+    }
+
+    fn parse_as<E: ByteOrder>(
+        reader: &mut (impl Read + Seek),
+        platform: Platform,
+        version: Version,
+    ) -> anyhow::Result<Self> {
         let reader_count = reader.read_7bit_encoded_i32()?;
         let mut readers = Vec::with_capacity(reader_count as usize);
         for _ in 0..reader_count {
             let name = reader.read_7bit_length_string()?;
-            let version = reader.read_i32::<LittleEndian>()?;
+            let version = reader.read_i32::<E>()?;
             let reader = TypeReader { name, version };
             readers.push(reader);
         }
 
         let shared_content_count = reader.read_7bit_encoded_i32()?;
 
-        let primary_content = Content::read(reader, &readers)?;
+        let primary_content = Content::read::<E>(reader, &readers, version)?;
 
+        // a level that could not be parsed to its end already holds the shared content
+        let unparsed_shared_content = match &primary_content {
+            Content::LevelModel(level) if level.sections.is_none() => shared_content_count as usize,
+            _ => 0,
+        };
         let mut shared_content = Vec::with_capacity(shared_content_count as usize);
-        for _ in 0..shared_content_count {
-            let content = Content::read(reader, &readers)?;
+        for _ in unparsed_shared_content..shared_content_count as usize {
+            let content = Content::read::<E>(reader, &readers, version)?;
             shared_content.push(content);
         }
 
         let mut rem = Vec::new();
         reader.read_to_end(&mut rem)?;
-        if rem.len() != 0 {
+        if !rem.is_empty() {
             eprintln!("WARNING: {} bytes left in XNB", rem.len());
             // dbg!(&rem);
         }
@@ -323,14 +595,209 @@ impl XnbContent {
             readers,
             shared_content,
             primary_content,
+            version,
+            platform,
+            hi_def: false,
+            unparsed_shared_content,
+        };
+        Ok(content)
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        match self.platform {
+            Platform::Xbox360 => self.write_as::<BigEndian>(writer),
+            Platform::Windows | Platform::WindowsPhone => self.write_as::<LittleEndian>(writer),
+        }
+    }
+
+    fn write_as<E: ByteOrder>(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        writer.write_7bit_encoded_i32(self.readers.len() as i32)?;
+        for reader in &self.readers {
+            writer.write_7bit_length_string(&reader.name)?;
+            writer.write_i32::<E>(reader.version)?;
+        }
+
+        let shared_content_count = self.shared_content.len() + self.unparsed_shared_content;
+        writer.write_7bit_encoded_i32(shared_content_count as i32)?;
+
+        self.primary_content
+            .write::<E>(writer, &self.readers, self.version)
+            .context("failed to write primary content")?;
+
+        for (i, content) in self.shared_content.iter().enumerate() {
+            content
+                .write::<E>(writer, &self.readers, self.version)
+                .with_context(|| format!("failed to write shared content {i}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// re-serializes the content and compares it against the raw payload it was parsed from,
+    /// returning the first mismatch if the output is not byte-exact
+    pub fn verify(&self, raw: &[u8]) -> anyhow::Result<Option<Mismatch>> {
+        let mut writer = DiffWriter {
+            expected: raw,
+            position: 0,
+            mismatch: None,
         };
+
+        if let Err(e) = self.write(&mut writer) {
+            let Some(offset) = writer.mismatch else {
+                return Err(e);
+            };
+            // every context up to the io error describes where the write was at
+            let mut path: Vec<String> = e.chain().map(|cause| cause.to_string()).collect();
+            path.pop();
+            return Ok(Some(Mismatch { offset, path }));
+        }
+
+        if writer.position != raw.len() {
+            return Ok(Some(Mismatch {
+                offset: writer.position,
+                path: vec![format!(
+                    "{} trailing bytes were not written",
+                    raw.len() - writer.position
+                )],
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// loads content saved by [`Xnb::extract`] as json or msgpack, optionally zlib compressed
+    pub fn load(file_path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let file_path = file_path.as_ref();
+        let mut data = std::fs::read(file_path).context("failed to read file")?;
+
+        // zlib streams always start with 0x78, which is neither '{' nor a msgpack array
+        if data.first() == Some(&0x78) {
+            let mut decompressed = Vec::new();
+            ZlibDecoder::new(data.as_slice())
+                .read_to_end(&mut decompressed)
+                .context("failed to decompress content")?;
+            data = decompressed;
+        }
+
+        let content = if file_path.extension() == Some(OsStr::new("msgpack")) {
+            rmp_serde::from_slice(&data).context("failed to deserialize msgpack content")?
+        } else {
+            serde_json::from_slice(&data).context("failed to deserialize json content")?
+        };
+        Ok(content)
+    }
+
+    /// loads an xnb or content saved by [`Xnb::extract`] and converts it to the pc layout
+    pub fn open(file_path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let file_path = file_path.as_ref();
+        let mut content = if file_path.extension() == Some(OsStr::new("xnb")) {
+            let file = File::open(file_path).context("failed to open file")?;
+            let xnb =
+                Xnb::parse(&mut BufReader::new(file)).context("failed to parse xnb header")?;
+            xnb.content()?
+        } else {
+            XnbContent::load(file_path)?
+        };
+        content
+            .convert_from_xbox360()
+            .context("failed to convert xbox 360 content")?;
         Ok(content)
     }
 }
 
+#[derive(Debug)]
+pub struct Mismatch {
+    /// offset into the decompressed payload
+    pub offset: usize,
+    /// the writers that were active when the mismatch happened, outermost first
+    pub path: Vec<String>,
+}
+
+/// writer that compares everything written against the expected bytes and fails on the
+/// first difference
+struct DiffWriter<'a> {
+    expected: &'a [u8],
+    position: usize,
+    mismatch: Option<usize>,
+}
+
+impl Write for DiffWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let expected = &self.expected[self.position..];
+        let matching = buf.iter().zip(expected).take_while(|(a, b)| a == b).count();
+        if matching != buf.len() {
+            let offset = self.position + matching;
+            self.mismatch = Some(offset);
+            return Err(std::io::Error::other(format!(
+                "output differs from original at offset {offset:#x}"
+            )));
+        }
+        self.position += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 pub struct ExtractOptions {
     pub overwrite: bool,
     pub dump_raw: bool,
     pub msgpack: bool,
     pub compression_level: u8,
+    pub mips: MipExport,
+    pub dds: bool,
+}
+
+pub struct RepackOptions {
+    pub overwrite: bool,
+    pub compress: bool,
+}
+
+pub struct ImportOptions {
+    pub overwrite: bool,
+    pub compress: bool,
+    pub mips: MipFilter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(content: &XnbContent) -> Vec<u8> {
+        let mut raw = Vec::new();
+        content.write(&mut raw).unwrap();
+        raw
+    }
+
+    #[test]
+    fn verify_reports_the_first_mismatch() {
+        let content = XnbContent::with_shared_content(
+            Content::String("primary".to_string()),
+            vec![Content::String("shared".to_string())],
+        );
+        let raw = written(&content);
+        assert!(content.verify(&raw).unwrap().is_none());
+
+        let mut corrupted = raw.clone();
+        let offset = raw.len() - 2;
+        corrupted[offset] ^= 0xff;
+        let mismatch = content.verify(&corrupted).unwrap().unwrap();
+        assert_eq!(mismatch.offset, offset);
+        assert_eq!(mismatch.path, ["failed to write shared content 0"]);
+
+        let mut corrupted = raw.clone();
+        let offset = raw.len() - 10;
+        corrupted[offset] ^= 0xff;
+        let mismatch = content.verify(&corrupted).unwrap().unwrap();
+        assert_eq!(mismatch.offset, offset);
+        assert_eq!(mismatch.path, ["failed to write primary content"]);
+
+        let mut longer = raw.clone();
+        longer.push(0);
+        let mismatch = content.verify(&longer).unwrap().unwrap();
+        assert_eq!(mismatch.offset, raw.len());
+        assert_eq!(mismatch.path, ["1 trailing bytes were not written"]);
+    }
 }